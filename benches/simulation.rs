@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_pacman::bench_sim;
+use rust_pacman::board::{BlockType, Board};
+use rust_pacman::config::Config;
+use rust_pacman::game::pathfind::first_step;
+use rust_pacman::position::Tile;
+use rust_pacman::theme::Theme;
+use rust_pacman::{BOARD_HEIGHT, BOARD_WIDTH};
+
+/// The bot playing on a fixed seed, restarting whenever it runs out of lives
+fn bot_ticks(c: &mut Criterion) {
+    let config = Config::new();
+    let theme = Theme::new();
+    c.bench_function("bot 1000 ticks", |b| {
+        b.iter(|| bench_sim::measure(black_box(1000), &config, &theme))
+    });
+}
+
+/// A* from Pac-Man's start to the far corner of the classic maze
+fn pathfind(c: &mut Criterion) {
+    let mut map = vec![BlockType::Nothing; BOARD_WIDTH * BOARD_HEIGHT];
+    Board::new().copy_board(&mut map);
+    let (from, to) = (Tile { x: 13, y: 26 }, Tile { x: 1, y: 4 });
+    c.bench_function("pathfind across the maze", |b| {
+        b.iter(|| first_step(black_box(&map), from, to))
+    });
+}
+
+criterion_group!(benches, bot_ticks, pathfind);
+criterion_main!(benches);
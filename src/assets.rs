@@ -0,0 +1,405 @@
+use crate::error::PacmanError;
+use crate::renderer::{Renderer, SpriteCmd};
+use crate::shapes;
+use crate::texture::GameTexture;
+use crate::theme::Theme;
+use crate::WHITE;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, TextureCreator, WindowCanvas};
+use sdl2::surface::Surface;
+use sdl2::video::WindowContext;
+use std::path::Path;
+
+#[cfg(feature = "ttf")]
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+/// Widest row of sprite sheets before the atlas starts a new one
+const ATLAS_WIDTH: u32 = 512;
+
+/// Board artwork, loaded on its own rather than packed into the atlas
+pub const MAP_IMAGE: &str = "Map24.png";
+
+#[cfg(feature = "ttf")]
+pub const FONT_SIZE: u16 = 24;
+
+/// Stands in for a missing image; loud enough that nobody mistakes it for artwork
+const PLACEHOLDER_COLOR: Color = Color::RGB(255, 0, 255);
+
+/// Sprite sheets packed into the atlas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sprite {
+    PacMan,
+    PacManDeath,
+    GhostBody,
+    GhostEyes,
+    Pellet,
+    Energizer,
+    Door,
+    Lives,
+    Fruit,
+}
+
+impl Sprite {
+    pub const ALL: [Sprite; 9] = [
+        Sprite::PacMan,
+        Sprite::PacManDeath,
+        Sprite::GhostBody,
+        Sprite::GhostEyes,
+        Sprite::Pellet,
+        Sprite::Energizer,
+        Sprite::Door,
+        Sprite::Lives,
+        Sprite::Fruit,
+    ];
+
+    /// Image the sheet is read from, looked up through the theme
+    pub fn file(self) -> &'static str {
+        match self {
+            Sprite::PacMan => "PacMan32.png",
+            Sprite::PacManDeath => "GameOver32.png",
+            Sprite::GhostBody => "GhostBody32.png",
+            Sprite::GhostEyes => "GhostEyes32.png",
+            Sprite::Pellet => "Pellet24.png",
+            Sprite::Energizer => "Energizer24.png",
+            Sprite::Door => "Door.png",
+            Sprite::Lives => "Lives32.png",
+            Sprite::Fruit => "Fruit32.png",
+        }
+    }
+
+    /// Size of the stock sheet, given to a placeholder when the image is missing
+    fn stock_size(self) -> (u32, u32) {
+        match self {
+            Sprite::PacMan => (96, 32),
+            Sprite::PacManDeath => (320, 32),
+            Sprite::GhostBody => (192, 32),
+            Sprite::GhostEyes => (160, 32),
+            Sprite::Pellet | Sprite::Energizer => (24, 24),
+            Sprite::Door => (47, 6),
+            Sprite::Lives => (32, 32),
+            Sprite::Fruit => (256, 32),
+        }
+    }
+
+    fn index(self) -> usize {
+        Sprite::ALL
+            .iter()
+            .position(|&sprite| sprite == self)
+            .unwrap_or(0)
+    }
+}
+
+/// Every sprite sheet in one texture, loaded once and shared by the board and entities.
+///
+/// Callers keep addressing frames in sheet coordinates; `frame` moves them into the atlas.
+///
+/// Every image file is read through `load_image`, so a missing one is reported the same way
+/// wherever it is used.
+pub struct AssetManager<'a> {
+    atlas: GameTexture<'a>,
+    clips: [Rect; Sprite::ALL.len()],
+    placeholders: bool,
+    shapes_only: bool,
+    /// Size of the sprites drawn from here on, as a multiple of their own
+    scale: u32,
+}
+
+impl Default for AssetManager<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> AssetManager<'a> {
+    /// An empty atlas; nothing is drawn until `load` has run
+    pub fn new() -> Self {
+        AssetManager {
+            atlas: GameTexture::new(),
+            clips: [Rect::new(0, 0, 1, 1); Sprite::ALL.len()],
+            placeholders: false,
+            shapes_only: false,
+            scale: 1,
+        }
+    }
+
+    /// Let missing images be replaced instead of stopping the game, for development
+    pub fn set_placeholders(&mut self, enabled: bool) {
+        self.placeholders = enabled;
+    }
+
+    /// Draw every sprite from filled shapes and read no image files at all
+    pub fn set_shapes_only(&mut self, enabled: bool) {
+        self.shapes_only = enabled;
+    }
+
+    /// Whether `set_alpha` shows; shapes are always drawn solid
+    pub fn can_fade(&self) -> bool {
+        !self.shapes_only
+    }
+
+    /// Opacity for the sprites drawn from here on, 255 being solid
+    pub fn set_alpha(&mut self, alpha: u8) -> Result<(), PacmanError> {
+        self.atlas.set_alpha(alpha)
+    }
+
+    /// Draw sprites `scale` times their size from here on, grown about their centres; shapes
+    /// always keep their own size
+    pub fn set_scale(&mut self, scale: u32) {
+        self.scale = scale.max(1);
+    }
+
+    /// Where a frame drawn at `x`, `y` starts once grown to the current scale
+    fn scaled_origin(&self, frame: Rect, x: i32, y: i32) -> (i32, i32) {
+        let growth = self.scale as i32 - 1;
+        (
+            x - frame.width() as i32 * growth / 2,
+            y - frame.height() as i32 * growth / 2,
+        )
+    }
+
+    /// Read an image through the theme.
+    ///
+    /// When it cannot be read and placeholders are allowed, the problem is printed and `None`
+    /// returned so the caller can draw something else.
+    pub fn load_image(
+        &self,
+        theme: &Theme,
+        file: &str,
+    ) -> Result<Option<Surface<'static>>, PacmanError> {
+        if self.shapes_only {
+            return Ok(None);
+        }
+        let path = theme.asset_path(file);
+        let error = match sdl2::image::LoadSurface::from_file(Path::new(&path)) {
+            Ok(surface) => return Ok(Some(surface)),
+            Err(_) if !Path::new(&path).is_file() => format!(
+                "Missing image {}, expected at {}",
+                file,
+                theme.search_paths(file).join(" or ")
+            ),
+            Err(e) => format!("Unable to load image {}: {}", path, e),
+        };
+
+        if self.placeholders {
+            println!("{}; drawing a placeholder", error);
+            Ok(None)
+        } else {
+            Err(PacmanError::AssetLoad(error))
+        }
+    }
+
+    /// Read every sheet the theme provides and pack them into a single texture
+    pub fn load(
+        &mut self,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        theme: &Theme,
+    ) -> Result<(), PacmanError> {
+        let mut sheets = Vec::new();
+        for sprite in Sprite::ALL {
+            let mut sheet = match self.load_image(theme, sprite.file())? {
+                Some(sheet) => sheet,
+                None => placeholder(sprite.stock_size())?,
+            };
+            // Copy alpha across as it is rather than blending it onto the empty atlas
+            sheet
+                .set_blend_mode(BlendMode::None)
+                .map_err(PacmanError::AssetLoad)?;
+            sheets.push(sheet);
+        }
+
+        let sizes: Vec<(u32, u32)> = sheets
+            .iter()
+            .map(|sheet| (sheet.width(), sheet.height()))
+            .collect();
+        let (clips, width, height) = pack(&sizes);
+        let mut atlas = Surface::new(width, height, PixelFormatEnum::RGBA8888)
+            .map_err(PacmanError::AssetLoad)?;
+        for (sheet, clip) in sheets.iter().zip(&clips) {
+            sheet
+                .blit(None, &mut atlas, *clip)
+                .map_err(PacmanError::AssetLoad)?;
+        }
+
+        self.atlas.load_from_surface(texture_creator, &atlas)?;
+        self.clips.copy_from_slice(&clips);
+        Ok(())
+    }
+
+    /// Where a whole sheet sits in the atlas
+    pub fn clip(&self, sprite: Sprite) -> Rect {
+        self.clips[sprite.index()]
+    }
+
+    /// Size of a sheet as loaded
+    pub fn size(&self, sprite: Sprite) -> (u32, u32) {
+        let clip = self.clip(sprite);
+        (clip.width(), clip.height())
+    }
+
+    /// Move `frame`, given relative to the sheet, into atlas coordinates
+    pub fn frame(&self, sprite: Sprite, frame: Rect) -> Rect {
+        let sheet = self.clip(sprite);
+        Rect::new(
+            sheet.x() + frame.x(),
+            sheet.y() + frame.y(),
+            frame.width(),
+            frame.height(),
+        )
+    }
+
+    /// Draw whole sheets at the given places as a single batch from the atlas
+    pub fn draw_sheets(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        renderer: &mut Renderer,
+        sheets: &[(Sprite, i32, i32)],
+    ) -> Result<(), PacmanError> {
+        if self.shapes_only {
+            for &(sprite, x, y) in sheets {
+                let (width, height) = self.size(sprite);
+                let whole = Rect::new(0, 0, width, height);
+                shapes::draw(canvas, sprite, whole, x, y, 0, WHITE)?;
+            }
+            return Ok(());
+        }
+
+        self.clear_tint()?;
+        let commands: Vec<SpriteCmd> = sheets
+            .iter()
+            .map(|&(sprite, x, y)| SpriteCmd::new(&self.atlas, x, y, Some(self.clip(sprite))))
+            .collect();
+        renderer.draw_batch(canvas, &commands)
+    }
+
+    /// Undo the last `render` tint so batched sprites come out in their own colours
+    pub fn clear_tint(&mut self) -> Result<(), PacmanError> {
+        self.atlas.set_color(WHITE.r, WHITE.g, WHITE.b)
+    }
+
+    /// Draw one frame of a sheet, tinted by `color` (`WHITE` leaves it as drawn)
+    pub fn render(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        sprite: Sprite,
+        frame: Rect,
+        x: i32,
+        y: i32,
+        color: Color,
+    ) -> Result<(), PacmanError> {
+        if self.shapes_only {
+            return shapes::draw(canvas, sprite, frame, x, y, 0, color);
+        }
+        self.atlas.set_color(color.r, color.g, color.b)?;
+        let clip = self.frame(sprite, frame);
+        let (x, y) = self.scaled_origin(frame, x, y);
+        self.atlas
+            .render_with_facing(canvas, x, y, 0, Some(clip), self.scale)
+    }
+
+    /// Draw one untinted frame of a sheet turned to `facing`
+    pub fn render_with_facing(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        sprite: Sprite,
+        frame: Rect,
+        x: i32,
+        y: i32,
+        facing: u8,
+    ) -> Result<(), PacmanError> {
+        if self.shapes_only {
+            return shapes::draw(canvas, sprite, frame, x, y, facing, WHITE);
+        }
+        self.clear_tint()?;
+        let clip = self.frame(sprite, frame);
+        let (x, y) = self.scaled_origin(frame, x, y);
+        self.atlas
+            .render_with_facing(canvas, x, y, facing, Some(clip), self.scale)
+    }
+}
+
+/// Open the theme's font, saying where it was expected when it is missing
+#[cfg(feature = "ttf")]
+pub fn load_font<'ttf>(
+    ttf_context: &'ttf Sdl2TtfContext,
+    theme: &Theme,
+) -> Result<Font<'ttf, 'static>, PacmanError> {
+    let path = theme.font_path();
+    if !Path::new(&path).is_file() {
+        return Err(PacmanError::AssetLoad(format!(
+            "Missing font {}, expected at {}",
+            theme.manifest.font,
+            theme.search_paths(&theme.manifest.font).join(" or ")
+        )));
+    }
+    ttf_context
+        .load_font(&path, FONT_SIZE)
+        .map_err(|e| PacmanError::AssetLoad(format!("Unable to load font {}: {}", path, e)))
+}
+
+/// A solid block the size of the sheet it replaces
+fn placeholder((width, height): (u32, u32)) -> Result<Surface<'static>, PacmanError> {
+    let mut surface =
+        Surface::new(width, height, PixelFormatEnum::RGBA8888).map_err(PacmanError::AssetLoad)?;
+    surface
+        .fill_rect(None, PLACEHOLDER_COLOR)
+        .map_err(PacmanError::AssetLoad)?;
+    Ok(surface)
+}
+
+/// Lay sheets out left to right in rows no wider than `ATLAS_WIDTH`.
+///
+/// Returns each sheet's place in the atlas and the atlas width and height.
+fn pack(sizes: &[(u32, u32)]) -> (Vec<Rect>, u32, u32) {
+    let mut clips = Vec::with_capacity(sizes.len());
+    let (mut x, mut y, mut row_height, mut width) = (0, 0, 0, 0);
+    for &(sheet_width, sheet_height) in sizes {
+        if x > 0 && x + sheet_width > ATLAS_WIDTH {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+        clips.push(Rect::new(x as i32, y as i32, sheet_width, sheet_height));
+        x += sheet_width;
+        width = width.max(x);
+        row_height = row_height.max(sheet_height);
+    }
+    (clips, width.max(1), (y + row_height).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_keeps_sheets_apart() {
+        let sizes = [(96, 32), (320, 32), (192, 32), (24, 24), (47, 6)];
+        let (clips, width, height) = pack(&sizes);
+
+        assert_eq!(clips[0], Rect::new(0, 0, 96, 32));
+        assert_eq!(clips[1], Rect::new(96, 0, 320, 32));
+        assert_eq!(clips[2], Rect::new(0, 32, 192, 32));
+        assert_eq!((width, height), (416, 64));
+        for (index, clip) in clips.iter().enumerate() {
+            assert!(clip.right() as u32 <= width && clip.bottom() as u32 <= height);
+            let apart = |other: &Rect| {
+                other.right() <= clip.x()
+                    || other.bottom() <= clip.y()
+                    || clip.right() <= other.x()
+                    || clip.bottom() <= other.y()
+            };
+            assert!(clips[..index].iter().all(apart));
+        }
+    }
+
+    #[test]
+    fn test_frames_move_into_the_atlas() {
+        let mut assets = AssetManager::new();
+        assets.clips[Sprite::GhostEyes.index()] = Rect::new(100, 64, 160, 32);
+        assert_eq!(
+            assets.frame(Sprite::GhostEyes, Rect::new(32, 0, 32, 32)),
+            Rect::new(132, 64, 32, 32)
+        );
+        assert_eq!(assets.size(Sprite::GhostEyes), (160, 32));
+    }
+}
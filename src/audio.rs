@@ -0,0 +1,246 @@
+use crate::error::PacmanError;
+use crate::game::state::GameState;
+use crate::theme::Theme;
+use sdl2::mixer::{Channel, Chunk, Music, Sdl2MixerContext, MAX_VOLUME};
+use std::path::Path;
+
+/// Music volume while nothing else is playing, and while a jingle has it ducked
+const MUSIC_VOLUME: i32 = MAX_VOLUME / 2;
+const DUCKED_VOLUME: i32 = MAX_VOLUME / 8;
+const CHUNK_SIZE: i32 = 1024;
+/// How far a sound at the maze's edge leans to that side; the far ear still hears some of it
+const PAN_STRENGTH: f32 = 0.7;
+
+/// A looping music track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Track {
+    /// Behind the game over, coin and milestone screens
+    Menu,
+    /// Behind a run in progress
+    Game,
+}
+
+impl Track {
+    const ALL: [Track; 2] = [Track::Menu, Track::Game];
+
+    fn file(self) -> &'static str {
+        match self {
+            Track::Menu => "music_menu.ogg",
+            Track::Game => "music_game.ogg",
+        }
+    }
+
+    /// The track for a screen; the menu theme plays wherever no maze is being played
+    pub fn for_state(state: &GameState) -> Self {
+        match state {
+            GameState::GameOver | GameState::InsertCoin | GameState::Milestone => Track::Menu,
+            _ => Track::Game,
+        }
+    }
+}
+
+/// A one-shot sound the game asks for; the music ducks while it plays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundCue {
+    Death,
+    GhostEaten,
+    FruitEaten,
+    /// A pellet or energizer was eaten
+    Waka,
+    /// A cheat code was entered on the menu
+    Unlock,
+}
+
+impl SoundCue {
+    const ALL: [SoundCue; 5] = [
+        SoundCue::Death,
+        SoundCue::GhostEaten,
+        SoundCue::FruitEaten,
+        SoundCue::Waka,
+        SoundCue::Unlock,
+    ];
+
+    fn file(self) -> &'static str {
+        match self {
+            SoundCue::Death => "death.wav",
+            SoundCue::GhostEaten => "ghost_eaten.wav",
+            SoundCue::FruitEaten => "fruit_eaten.wav",
+            SoundCue::Waka => "waka.wav",
+            SoundCue::Unlock => "unlock.wav",
+        }
+    }
+}
+
+/// A cue and where it came from, left to right
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sound {
+    pub cue: SoundCue,
+    /// -1.0 is the maze's left edge, 0.0 its centre and 1.0 its right edge
+    pub pan: f32,
+}
+
+impl Sound {
+    pub fn centered(cue: SoundCue) -> Self {
+        Sound { cue, pan: 0.0 }
+    }
+
+    /// A cue from board x position `x` on a maze `width` board pixels wide
+    pub fn at(cue: SoundCue, x: i32, width: u32) -> Self {
+        let half = width as f32 / 2.0;
+        let pan = ((x as f32 - half) / half).clamp(-1.0, 1.0);
+        Sound { cue, pan }
+    }
+}
+
+/// Music and sound effects through SDL_mixer.
+///
+/// Tracks and effects are read from the theme's folders when present; any that are missing
+/// are simply not played, so the game runs the same with or without audio files.
+pub struct Audio {
+    _mixer: Sdl2MixerContext,
+    tracks: Vec<(Track, Music<'static>)>,
+    effects: Vec<(SoundCue, Chunk)>,
+    playing: Option<Track>,
+    volume: i32,
+}
+
+impl Audio {
+    pub fn open(sdl_context: &sdl2::Sdl, theme: &Theme) -> Result<Self, PacmanError> {
+        sdl_context.audio().map_err(PacmanError::SdlInit)?;
+        let mixer = sdl2::mixer::init(sdl2::mixer::InitFlag::OGG).map_err(PacmanError::SdlInit)?;
+        sdl2::mixer::open_audio(
+            sdl2::mixer::DEFAULT_FREQUENCY,
+            sdl2::mixer::DEFAULT_FORMAT,
+            sdl2::mixer::DEFAULT_CHANNELS,
+            CHUNK_SIZE,
+        )
+        .map_err(PacmanError::SdlInit)?;
+
+        let mut tracks = Vec::new();
+        for track in Track::ALL {
+            if let Some(path) = find(theme, track.file()) {
+                let music = Music::from_file(&path).map_err(|e| load_error(&path, e))?;
+                tracks.push((track, music));
+            }
+        }
+        let mut effects = Vec::new();
+        for cue in SoundCue::ALL {
+            if let Some(path) = find(theme, cue.file()) {
+                let chunk = Chunk::from_file(&path).map_err(|e| load_error(&path, e))?;
+                effects.push((cue, chunk));
+            }
+        }
+        println!(
+            "Audio: {} music tracks, {} sound effects",
+            tracks.len(),
+            effects.len()
+        );
+
+        Music::set_volume(MUSIC_VOLUME);
+        Ok(Audio {
+            _mixer: mixer,
+            tracks,
+            effects,
+            playing: None,
+            volume: MUSIC_VOLUME,
+        })
+    }
+
+    /// Loop `track`, or stop the music for `None`; asking for the track already playing
+    /// carries on without restarting it
+    pub fn play_music(&mut self, track: Option<Track>) {
+        if track == self.playing {
+            return;
+        }
+        self.playing = track;
+        Music::halt();
+        let music = self
+            .tracks
+            .iter()
+            .find(|(candidate, _)| Some(*candidate) == track);
+        if let Some((_, music)) = music {
+            if let Err(e) = music.play(-1) {
+                println!("Could not play music: {}", e);
+            }
+        }
+    }
+
+    pub fn play(&self, sound: Sound) {
+        let effect = self.effects.iter().find(|(cue, _)| *cue == sound.cue);
+        if let Some((_, chunk)) = effect {
+            let (left, right) = channel_volumes(sound.pan);
+            let played = Channel::all()
+                .play(chunk, 0)
+                .and_then(|channel| channel.set_panning(left, right));
+            if let Err(e) = played {
+                println!("Could not play sound: {}", e);
+            }
+        }
+    }
+
+    /// Duck the music under any sound effect still playing, once a frame
+    pub fn update(&mut self) {
+        let volume = music_volume(Channel::all().is_playing());
+        if volume != self.volume {
+            Music::set_volume(volume);
+            self.volume = volume;
+        }
+    }
+}
+
+fn music_volume(effect_playing: bool) -> i32 {
+    if effect_playing {
+        DUCKED_VOLUME
+    } else {
+        MUSIC_VOLUME
+    }
+}
+
+/// Left and right channel volumes for `pan`; the side it leans to stays at full volume
+fn channel_volumes(pan: f32) -> (u8, u8) {
+    let volume = |lean: f32| (255.0 * (1.0 - PAN_STRENGTH * lean.max(0.0))) as u8;
+    (volume(pan), volume(-pan))
+}
+
+/// The first of the theme's places for `file` that exists
+fn find(theme: &Theme, file: &str) -> Option<String> {
+    theme
+        .search_paths(file)
+        .into_iter()
+        .find(|path| Path::new(path).exists())
+}
+
+fn load_error(path: &str, e: String) -> PacmanError {
+    PacmanError::AssetLoad(format!("Could not load {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_theme_plays_off_the_maze() {
+        assert_eq!(Track::for_state(&GameState::GameOver), Track::Menu);
+        assert_eq!(Track::for_state(&GameState::InsertCoin), Track::Menu);
+        assert_eq!(Track::for_state(&GameState::Playing), Track::Game);
+        assert_eq!(Track::for_state(&GameState::Paused), Track::Game);
+    }
+
+    #[test]
+    fn test_effects_duck_the_music() {
+        assert!(music_volume(true) < music_volume(false));
+    }
+
+    #[test]
+    fn test_sounds_pan_toward_their_side_of_the_maze() {
+        assert_eq!(Sound::at(SoundCue::GhostEaten, 336, 672).pan, 0.0);
+        assert_eq!(Sound::at(SoundCue::FruitEaten, 0, 672).pan, -1.0);
+        assert_eq!(Sound::at(SoundCue::FruitEaten, 900, 672).pan, 1.0);
+
+        assert_eq!(channel_volumes(0.0), (255, 255));
+        let (left, right) = channel_volumes(-1.0);
+        assert_eq!(left, 255);
+        assert!(right > 0 && right < 255);
+        assert_eq!(channel_volumes(1.0), (right, left));
+    }
+}
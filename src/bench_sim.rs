@@ -0,0 +1,106 @@
+use crate::config::Config;
+use crate::game::perf::SimSystem;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::theme::Theme;
+use std::time::{Duration, Instant};
+
+/// Seed used when none is given, so runs before and after a change play the same games
+const BENCH_SEED: u64 = 1;
+
+/// How fast the simulation ran, overall and in each timed system
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub ticks: u32,
+    /// Games played, counting the one still running at the end
+    pub games: u32,
+    pub elapsed: Duration,
+    pub systems: Vec<(SimSystem, Duration)>,
+}
+
+impl BenchReport {
+    pub fn ticks_per_second(&self) -> f64 {
+        self.ticks as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "Simulated {} ticks ({} games) in {:.3} s: {:.0} ticks/s\n",
+            self.ticks,
+            self.games,
+            self.elapsed.as_secs_f64(),
+            self.ticks_per_second()
+        );
+        for (system, total) in &self.systems {
+            let percent =
+                100.0 * total.as_secs_f64() / self.elapsed.as_secs_f64().max(f64::EPSILON);
+            text.push_str(&format!(
+                "  {:<10} {:>10.3} ms {:>6.1}%\n",
+                system.name(),
+                total.as_secs_f64() * 1000.0,
+                percent
+            ));
+        }
+        text
+    }
+}
+
+/// Run `ticks` headless updates with the bot playing and print how long they took
+pub fn run(ticks: u32, config: &Config, theme: &Theme) -> Result<(), Box<dyn std::error::Error>> {
+    let report = measure(ticks, config, theme);
+    print!("{}", report.text());
+    Ok(())
+}
+
+/// Let the bot play for `ticks` updates, starting a new game whenever one ends
+pub fn measure(ticks: u32, config: &Config, theme: &Theme) -> BenchReport {
+    let mut config = config.clone();
+    config.telemetry = false;
+    config.seed.get_or_insert(BENCH_SEED);
+
+    let mut game = Game::headless(&config, theme);
+    game.set_autoplay(true);
+    game.enable_system_timings();
+
+    let mut games = 1;
+    let started = Instant::now();
+    for _ in 0..ticks {
+        if game.get_game_state() == GameState::GameOver {
+            game.reset(&config);
+            games += 1;
+        }
+        game.update();
+    }
+    let elapsed = started.elapsed();
+
+    let timings = game.get_system_timings();
+    BenchReport {
+        ticks,
+        games,
+        elapsed,
+        systems: SimSystem::ALL
+            .iter()
+            .map(|&system| (system, timings.total(system)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_times_every_system() {
+        let report = measure(600, &Config::new(), &Theme::new());
+        assert_eq!(report.ticks, 600);
+        assert_eq!(report.systems.len(), SimSystem::ALL.len());
+        let timed: Duration = report.systems.iter().map(|(_, total)| *total).sum();
+        assert!(timed > Duration::ZERO && timed <= report.elapsed);
+
+        let text = report.text();
+        assert!(text.starts_with("Simulated 600 ticks (1 games)"));
+        for system in SimSystem::ALL {
+            assert!(text.contains(system.name()));
+        }
+    }
+}
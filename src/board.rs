@@ -1,12 +1,34 @@
+use crate::assets::{AssetManager, Sprite, MAP_IMAGE};
+use crate::error::PacmanError;
+use crate::font::GameFont;
+use crate::hud::Hud;
+use crate::locale::Locale;
+use crate::renderer::{Renderer, SpriteCmd};
 use crate::texture::GameTexture;
-use crate::{
-    BLOCK_SIZE_24, BLOCK_SIZE_32, BLUE, BOARD_HEIGHT, BOARD_WIDTH, WHITE, WINDOW_HEIGHT,
-    WINDOW_WIDTH,
-};
+use crate::theme::Theme;
+use crate::wall_tiles::{self, Corner, Side, WallPiece};
+use crate::{BLOCK_SIZE_24, BLUE, BOARD_HEIGHT, BOARD_WIDTH, WHITE};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
 use sdl2::render::{TextureCreator, WindowCanvas};
-use sdl2::ttf::{Font, Sdl2TtfContext};
 use sdl2::video::WindowContext;
 
+/// Gap between the bottom of the door sprite and the bottom of its tile row
+const DOOR_WALL_OFFSET: i32 = 3;
+/// Width of each end of the door still visible while it is open
+const DOOR_OPEN_STUB: u32 = 6;
+/// Thickness of the lines drawn around walls on mazes without artwork
+const WALL_OUTLINE: u32 = 3;
+/// Radius of the rounded outside corners of those walls
+const WALL_CORNER_RADIUS: i32 = 8;
+/// Gap between a teleporter's ring and the edge of its tile
+const TELEPORT_INSET: u32 = 4;
+/// Row just below the ghost house, where the arcade fruit sits
+const FRUIT_ROW: usize = 20;
+/// Top and bottom rows of the maze's outer wall; a `|` in both rows of a column joins them
+/// through a vertical tunnel
+pub const TUNNEL_ROWS: (usize, usize) = (3, 33);
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlockType {
     Wall,
@@ -14,6 +36,39 @@ pub enum BlockType {
     Pellet,
     Energizer,
     Nothing,
+    /// One of a pair of tiles joined to each other, written `A` to `Z` with the pair's letter
+    Teleport(u8),
+}
+
+impl BlockType {
+    /// Map a `CHAR_BOARD` character; entity start markers and vertical tunnels (`|`) count
+    /// as empty floor
+    pub fn from_char(c: char) -> Self {
+        match c {
+            '#' => BlockType::Wall,
+            '=' => BlockType::Door,
+            '.' => BlockType::Pellet,
+            'o' => BlockType::Energizer,
+            'A'..='Z' => BlockType::Teleport(c as u8 - b'A'),
+            _ => BlockType::Nothing,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            BlockType::Wall => '#',
+            BlockType::Door => '=',
+            BlockType::Pellet => '.',
+            BlockType::Energizer => 'o',
+            BlockType::Nothing => ' ',
+            BlockType::Teleport(pair) => (b'A' + pair) as char,
+        }
+    }
+
+    /// Pellets and energizers, which all have to be eaten to clear a level
+    pub fn is_food(self) -> bool {
+        matches!(self, BlockType::Pellet | BlockType::Energizer)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -22,10 +77,43 @@ pub enum Direction {
     Up,
     Left,
     Down,
-    #[allow(dead_code)]
     Nowhere,
 }
 
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Right => Direction::Left,
+            Direction::Up => Direction::Down,
+            Direction::Left => Direction::Right,
+            Direction::Down => Direction::Up,
+            Direction::Nowhere => Direction::Nowhere,
+        }
+    }
+
+    /// Single-letter form used in save files
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Right => 'R',
+            Direction::Up => 'U',
+            Direction::Left => 'L',
+            Direction::Down => 'D',
+            Direction::Nowhere => 'N',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'R' => Some(Direction::Right),
+            'U' => Some(Direction::Up),
+            'L' => Some(Direction::Left),
+            'D' => Some(Direction::Down),
+            'N' => Some(Direction::Nowhere),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EntityType {
     PacMan,
@@ -33,22 +121,30 @@ pub enum EntityType {
     Inky,
     Pinky,
     Clyde,
-    #[allow(dead_code)]
     None,
 }
 
+/// Lives a new game starts with
+pub const STARTING_LIVES: i8 = 4;
+
 pub struct Board<'a> {
     numeric_board: [BlockType; BOARD_HEIGHT * BOARD_WIDTH],
+    /// Index into `MAZES` of the layout being played
+    maze: usize,
+    /// A maze loaded from a file, played on every level in place of `MAZES`
+    custom_maze: Option<String>,
+    maze_color: Color,
+    /// Row, first and last column of the ghost house door
+    door_span: Option<(usize, usize, usize)>,
     score: u32,
     lives: i8,
-    #[allow(dead_code)]
+    /// The extra life for reaching the score threshold has been handed out
     is_extra: bool,
+    /// Pellets and energizers left in the maze being played, kept up as they are eaten
+    food_left: usize,
+    locale: Locale,
 
     map_texture: GameTexture<'a>,
-    pellet_texture: GameTexture<'a>,
-    energizer_texture: GameTexture<'a>,
-    door_texture: GameTexture<'a>,
-    lives_texture: GameTexture<'a>,
 
     score_word_texture: GameTexture<'a>,
     score_texture: GameTexture<'a>,
@@ -56,6 +152,12 @@ pub struct Board<'a> {
     high_score_texture: GameTexture<'a>,
 }
 
+impl Default for Board<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Board<'a> {
     pub const CHAR_BOARD: &'static str = concat!(
         "                            ",
@@ -95,79 +197,220 @@ impl<'a> Board<'a> {
         "                            ",
         "                            "
     );
-    pub fn new(
-        texture_creator: &'a TextureCreator<WindowContext>,
-        ttf_context: &'a Sdl2TtfContext,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let font = ttf_context.load_font("assets/emulogic.ttf", 24)?;
 
+    /// Second maze of the Ms. Pac-Man rules; the ghost house, tunnels and start tiles match
+    /// `CHAR_BOARD`, only the corridors above and below differ
+    pub const ALT_BOARD: &'static str = concat!(
+        "                            ",
+        "                            ",
+        "                            ",
+        "############################",
+        "#......##..........##......#",
+        "#o####.##.########.##.####o#",
+        "#.####.##.########.##.####.#",
+        "#..........................#",
+        "#.####.##.########.##.####.#",
+        "#.####.##.########.##.####.#",
+        "#.####.##.########.##.####.#",
+        "#......##....##....##......#",
+        "######.##### ## #####.######",
+        "     #.##### ## #####.#     ",
+        "     #.##    1     ##.#     ",
+        "     #.## ###==### ##.#     ",
+        "######.## #      # ##.######",
+        "      .   #2 3 4 #   .      ",
+        "######.## #      # ##.######",
+        "     #.## ######## ##.#     ",
+        "     #.##          ##.#     ",
+        "     #.## ######## ##.#     ",
+        "######.## ######## ##.######",
+        "#..........................#",
+        "#.####.###.######.###.####.#",
+        "#.####.###.######.###.####.#",
+        "#o..##.......0 .......##..o#",
+        "###.##.####.####.####.##.###",
+        "###.##.####.####.####.##.###",
+        "#......##....##....##......#",
+        "#.####.##.##.##.##.##.####.#",
+        "#.####.##.##.##.##.##.####.#",
+        "#..........................#",
+        "############################",
+        "                            ",
+        "                            "
+    );
+    const MAZES: [&'static str; 2] = [Self::CHAR_BOARD, Self::ALT_BOARD];
+    /// Built-in layouts `set_maze` can switch between
+    pub const MAZE_COUNT: usize = Self::MAZES.len();
+
+    /// Board state without textures; call `load_textures` before drawing
+    pub fn new() -> Self {
         let mut board = Board {
             numeric_board: [BlockType::Nothing; BOARD_HEIGHT * BOARD_WIDTH],
+            maze: 0,
+            custom_maze: None,
+            maze_color: BLUE,
+            door_span: None,
             score: 0,
-            lives: 4,
+            lives: STARTING_LIVES,
             is_extra: false,
+            food_left: 0,
+            locale: Locale::new(),
             map_texture: GameTexture::new(),
-            pellet_texture: GameTexture::new(),
-            energizer_texture: GameTexture::new(),
-            door_texture: GameTexture::new(),
-            lives_texture: GameTexture::new(),
             score_word_texture: GameTexture::new(),
             score_texture: GameTexture::new(),
             high_score_word_texture: GameTexture::new(),
             high_score_texture: GameTexture::new(),
         };
 
-        board
-            .map_texture
-            .load_from_file(texture_creator, "assets/Map24.png")?;
-        board
-            .pellet_texture
-            .load_from_file(texture_creator, "assets/Pellet24.png")?;
-        board
-            .energizer_texture
-            .load_from_file(texture_creator, "assets/Energizer24.png")?;
-        board
-            .door_texture
-            .load_from_file(texture_creator, "assets/Door.png")?;
-        board
-            .lives_texture
-            .load_from_file(texture_creator, "assets/Lives32.png")?;
+        board.convert_sketch();
 
         board
-            .score_word_texture
-            .load_from_rendered_text(texture_creator, "Score", &font, WHITE)?;
-        board.high_score_word_texture.load_from_rendered_text(
+    }
+
+    /// Without the map image the first maze is outlined from its tiles like the others
+    pub fn load_textures(
+        &mut self,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        assets: &AssetManager<'a>,
+        font: &GameFont,
+        theme: &Theme,
+    ) -> Result<(), PacmanError> {
+        match assets.load_image(theme, MAP_IMAGE)? {
+            Some(map) => self.map_texture.load_from_surface(texture_creator, &map)?,
+            None => self.map_texture.reset(),
+        }
+
+        self.score_word_texture
+            .load_from_rendered_text(texture_creator, "Score", font, WHITE)?;
+        self.high_score_word_texture.load_from_rendered_text(
             texture_creator,
             "High Score",
-            &font,
+            font,
             WHITE,
         )?;
 
-        board.map_texture.set_color(BLUE.r, BLUE.g, BLUE.b)?;
+        self.map_texture.set_color(BLUE.r, BLUE.g, BLUE.b)?;
 
-        board.convert_sketch();
-        board.set_score(texture_creator, &font)?;
-        board.set_high_score(texture_creator, &font)?;
+        self.set_score(texture_creator, font)?;
+        self.set_high_score(texture_creator, font)?;
 
-        Ok(board)
+        Ok(())
+    }
+
+    fn sketch(&self) -> &str {
+        match self.custom_maze {
+            Some(ref sketch) => sketch,
+            None => Self::MAZES[self.maze],
+        }
+    }
+
+    /// Switch to another maze layout; `copy_board` hands out the new layout afterwards.
+    ///
+    /// Ignored once a custom maze is loaded.
+    pub fn set_maze(&mut self, maze: usize) {
+        let maze = maze % Self::MAZES.len();
+        if maze != self.maze && self.custom_maze.is_none() {
+            self.maze = maze;
+            self.convert_sketch();
+        }
+    }
+
+    /// Play `sketch`, as read by `maze_file::load`, on every level from now on
+    pub fn set_custom_maze(&mut self, sketch: String) {
+        self.custom_maze = Some(sketch);
+        self.convert_sketch();
+    }
+
+    /// Go back to the built-in mazes after a custom one
+    pub fn clear_custom_maze(&mut self) {
+        if self.custom_maze.take().is_some() {
+            self.convert_sketch();
+        }
+    }
+
+    /// Where a still fruit appears, centered below the ghost house like Pac-Man's start
+    pub fn fruit_position(&self) -> crate::position::Position {
+        let start = self.reset_position(EntityType::PacMan);
+        crate::position::Position::new(start.get_x(), (FRUIT_ROW as u32 * BLOCK_SIZE_24) as i16)
+    }
+
+    /// Rows with an open tunnel at the left edge of the maze
+    pub fn tunnel_rows(&self) -> Vec<usize> {
+        (1..BOARD_HEIGHT - 1)
+            .filter(|&row| {
+                let edge = |row: usize| self.numeric_board[row * BOARD_WIDTH];
+                edge(row) != BlockType::Wall
+                    && edge(row - 1) == BlockType::Wall
+                    && edge(row + 1) == BlockType::Wall
+            })
+            .collect()
+    }
+
+    /// Whether any column of `map` opens through the top wall into a vertical tunnel
+    pub fn wraps_vertically(map: &[BlockType]) -> bool {
+        let top = TUNNEL_ROWS.0 * BOARD_WIDTH;
+        map[top..top + BOARD_WIDTH]
+            .iter()
+            .any(|&block| block != BlockType::Wall)
     }
 
     fn convert_sketch(&mut self) {
-        let chars: Vec<char> = Self::CHAR_BOARD.chars().collect();
+        let chars: Vec<char> = self.sketch().chars().collect();
         for i in 0..BOARD_HEIGHT * BOARD_WIDTH {
             if i < chars.len() {
-                self.numeric_board[i] = match chars[i] {
-                    '#' => BlockType::Wall,
-                    '=' => BlockType::Door,
-                    '.' => BlockType::Pellet,
-                    'o' => BlockType::Energizer,
-                    _ => BlockType::Nothing,
-                };
+                self.numeric_board[i] = BlockType::from_char(chars[i]);
             }
         }
+        self.door_span = Self::find_door(&self.numeric_board);
+    }
+
+    /// The run of door tiles on the first row that has any
+    fn find_door(map: &[BlockType]) -> Option<(usize, usize, usize)> {
+        let first = map.iter().position(|&block| block == BlockType::Door)?;
+        let (row, column) = (first / BOARD_WIDTH, first % BOARD_WIDTH);
+        let length = map[first..(row + 1) * BOARD_WIDTH]
+            .iter()
+            .take_while(|&&block| block == BlockType::Door)
+            .count();
+        Some((row, column, column + length - 1))
+    }
+
+    /// Where a door sprite of `size` goes: centered across the door tiles, on the house wall
+    fn door_position(&self, size: (u32, u32)) -> Option<(i32, i32)> {
+        let (row, first, last) = self.door_span?;
+        let span = ((last - first + 1) as u32 * BLOCK_SIZE_24) as i32;
+        let x = (first as u32 * BLOCK_SIZE_24) as i32 + (span - size.0 as i32 + 1) / 2;
+        let y = ((row + 1) as u32 * BLOCK_SIZE_24) as i32 - size.1 as i32 - DOOR_WALL_OFFSET;
+        Some((x, y))
+    }
+
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
+    pub fn get_locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// Where the scores, lives and messages go around this board
+    pub fn hud(&self) -> Hud {
+        Hud::for_board(BOARD_WIDTH, BOARD_HEIGHT)
+    }
+
+    /// Index of the maze layout in play; a custom maze counts as the first
+    pub fn get_maze(&self) -> usize {
+        if self.custom_maze.is_some() {
+            0
+        } else {
+            self.maze
+        }
+    }
+
+    pub fn set_maze_color(&mut self, color: Color) -> Result<(), PacmanError> {
+        self.maze_color = color;
+        self.map_texture.set_color(color.r, color.g, color.b)
     }
 
-    #[allow(dead_code)]
     pub fn get_block_type(&self, x: usize, y: usize) -> BlockType {
         if x >= BOARD_WIDTH || y >= BOARD_HEIGHT {
             return BlockType::Wall;
@@ -179,9 +422,21 @@ impl<'a> Board<'a> {
         actual_map.copy_from_slice(&self.numeric_board);
     }
 
-    pub fn reset_position(&self, entity_type: EntityType) -> crate::position::Position {
-        let chars: Vec<char> = Self::CHAR_BOARD.chars().collect();
+    /// Count the food in `actual_map`, whenever a maze is put in play
+    pub fn count_food(&mut self, actual_map: &[BlockType]) {
+        self.food_left = actual_map.iter().filter(|block| block.is_food()).count();
+    }
+
+    /// One pellet or energizer was eaten
+    pub fn food_eaten(&mut self) {
+        self.food_left = self.food_left.saturating_sub(1);
+    }
 
+    pub fn get_food_left(&self) -> usize {
+        self.food_left
+    }
+
+    pub fn reset_position(&self, entity_type: EntityType) -> crate::position::Position {
         let target_char = match entity_type {
             EntityType::PacMan => '0',
             EntityType::Blinky => '1',
@@ -191,35 +446,66 @@ impl<'a> Board<'a> {
             EntityType::None => return crate::position::Position::new(0, 0),
         };
 
-        for (i, &ch) in chars.iter().enumerate() {
-            if ch == target_char {
-                let x = (i % BOARD_WIDTH) as u32 * BLOCK_SIZE_24 + BLOCK_SIZE_24 / 2;
-                let y = (i / BOARD_WIDTH) as u32 * BLOCK_SIZE_24;
-                return crate::position::Position::new(x as i16, y as i16);
-            }
-        }
+        self.marker_position(target_char)
+            .unwrap_or(crate::position::Position::new(0, 0))
+    }
 
-        crate::position::Position::new(0, 0)
+    /// Where a start marker such as '0' or an extra ghost's '5' sits, if the maze has it
+    pub fn marker_position(&self, marker: char) -> Option<crate::position::Position> {
+        let i = self.sketch().chars().position(|ch| ch == marker)?;
+        let x = (i % BOARD_WIDTH) as u32 * BLOCK_SIZE_24 + BLOCK_SIZE_24 / 2;
+        let y = (i / BOARD_WIDTH) as u32 * BLOCK_SIZE_24;
+        Some(crate::position::Position::new(x as i16, y as i16))
     }
 
     pub fn draw(
         &mut self,
         canvas: &mut WindowCanvas,
+        renderer: &mut Renderer,
+        assets: &mut AssetManager,
         actual_map: &[BlockType],
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.score_word_texture.render(canvas, 0, 0, None)?;
-        self.score_texture
-            .render(canvas, 0, BLOCK_SIZE_32 as i32, None)?;
-        self.high_score_word_texture.render(canvas, 336, 0, None)?;
-        self.high_score_texture
-            .render(canvas, 336, BLOCK_SIZE_32 as i32, None)?;
-
-        self.map_texture.render(canvas, 0, 0, None)?;
+        door_open: bool,
+    ) -> Result<(), PacmanError> {
+        let hud = self.hud();
+        let at = |texture, (x, y): (i32, i32)| SpriteCmd::new(texture, x, y, None);
+        let scores = [
+            at(&self.score_word_texture, hud.score_label()),
+            at(&self.score_texture, hud.score()),
+            at(&self.high_score_word_texture, hud.high_score_label()),
+            at(&self.high_score_texture, hud.high_score()),
+        ];
+        renderer.draw_batch(canvas, &scores)?;
+
+        // Only the first maze has artwork; the others are outlined from their tiles
+        if self.maze == 0 && self.custom_maze.is_none() && self.map_texture.is_loaded() {
+            self.map_texture.render(canvas, 0, 0, None)?;
+        } else {
+            self.draw_walls(canvas)?;
+        }
 
-        let door_x = (WINDOW_WIDTH / 2) as i32 - 23;
-        let door_y = (WINDOW_HEIGHT / 2) as i32 - 57;
-        self.door_texture.render(canvas, door_x, door_y, None)?;
+        let (width, height) = assets.size(Sprite::Door);
+        if let Some((door_x, door_y)) = self.door_position((width, height)) {
+            if door_open {
+                // Slid back into the walls, leaving only the ends showing
+                let left = Rect::new(0, 0, DOOR_OPEN_STUB, height);
+                let right = Rect::new((width - DOOR_OPEN_STUB) as i32, 0, DOOR_OPEN_STUB, height);
+                assets.render(canvas, Sprite::Door, left, door_x, door_y, WHITE)?;
+                assets.render(
+                    canvas,
+                    Sprite::Door,
+                    right,
+                    door_x + right.x(),
+                    door_y,
+                    WHITE,
+                )?;
+            } else {
+                let whole = Rect::new(0, 0, width, height);
+                assets.render(canvas, Sprite::Door, whole, door_x, door_y, WHITE)?;
+            }
+        }
 
+        // Food and lives come from the atlas, so each layer is a single texture bind
+        let mut food = Vec::new();
         for y in 0..BOARD_HEIGHT {
             for x in 0..BOARD_WIDTH {
                 let index = y * BOARD_WIDTH + x;
@@ -229,31 +515,87 @@ impl<'a> Board<'a> {
                 let render_y = (y as u32 * BLOCK_SIZE_24) as i32;
 
                 match block_type {
-                    BlockType::Pellet => {
-                        self.pellet_texture
-                            .render(canvas, render_x, render_y, None)?;
-                    }
-                    BlockType::Energizer => {
-                        self.energizer_texture
-                            .render(canvas, render_x, render_y, None)?;
-                    }
+                    BlockType::Pellet => food.push((Sprite::Pellet, render_x, render_y)),
+                    BlockType::Energizer => food.push((Sprite::Energizer, render_x, render_y)),
                     _ => {}
                 }
             }
         }
+        assets.draw_sheets(canvas, renderer, &food)?;
+        self.draw_teleporters(canvas, actual_map)?;
 
-        for i in 1..=self.lives {
-            if i > 0 {
-                let lives_x = (i as u32 * BLOCK_SIZE_32) as i32;
-                let lives_y = (26 * BLOCK_SIZE_32 - BLOCK_SIZE_32 / 4) as i32;
-                self.lives_texture.render(canvas, lives_x, lives_y, None)?;
-            }
+        let mut lives = Vec::new();
+        for i in 0..self.lives {
+            let (x, y) = hud.life(i as u8);
+            lives.push((Sprite::Lives, x, y));
         }
+        assets.draw_sheets(canvas, renderer, &lives)?;
 
         Ok(())
     }
 
-    #[allow(dead_code)]
+    /// Ring each teleporter pad in the maze color
+    fn draw_teleporters(
+        &self,
+        canvas: &mut WindowCanvas,
+        actual_map: &[BlockType],
+    ) -> Result<(), PacmanError> {
+        let inset = TELEPORT_INSET as i32;
+        let size = BLOCK_SIZE_24 - 2 * TELEPORT_INSET;
+        canvas.set_draw_color(self.maze_color);
+        for (index, _) in actual_map
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| matches!(block, BlockType::Teleport(_)))
+        {
+            let x = ((index % BOARD_WIDTH) as u32 * BLOCK_SIZE_24) as i32;
+            let y = ((index / BOARD_WIDTH) as u32 * BLOCK_SIZE_24) as i32;
+            canvas
+                .draw_rect(Rect::new(x + inset, y + inset, size, size))
+                .map_err(PacmanError::Render)?;
+            canvas
+                .draw_rect(Rect::new(x + inset + 1, y + inset + 1, size - 2, size - 2))
+                .map_err(PacmanError::Render)?;
+        }
+        Ok(())
+    }
+
+    /// Outline the walls from the tile grid, in the maze color.
+    ///
+    /// Each wall tile picks its pieces from its neighbours, so any maze comes out joined up.
+    fn draw_walls(&self, canvas: &mut WindowCanvas) -> Result<(), PacmanError> {
+        let solid = |x: i32, y: i32| {
+            x < 0
+                || y < 0
+                || x >= BOARD_WIDTH as i32
+                || y >= BOARD_HEIGHT as i32
+                || matches!(
+                    self.numeric_board[y as usize * BOARD_WIDTH + x as usize],
+                    BlockType::Wall | BlockType::Door
+                )
+        };
+        let size = BLOCK_SIZE_24 as i32;
+
+        let mut rects = Vec::new();
+        let mut points = Vec::new();
+        for y in 0..BOARD_HEIGHT as i32 {
+            for x in 0..BOARD_WIDTH as i32 {
+                if self.numeric_board[y as usize * BOARD_WIDTH + x as usize] != BlockType::Wall {
+                    continue;
+                }
+                for piece in wall_tiles::pieces(solid, x, y) {
+                    wall_piece_shape(piece, x * size, y * size, &mut rects, &mut points);
+                }
+            }
+        }
+
+        canvas.set_draw_color(self.maze_color);
+        canvas.fill_rects(&rects).map_err(PacmanError::Render)?;
+        canvas
+            .draw_points(points.as_slice())
+            .map_err(PacmanError::Render)
+    }
+
     pub fn get_score(&self) -> u32 {
         self.score
     }
@@ -262,20 +604,16 @@ impl<'a> Board<'a> {
         self.lives
     }
 
-    pub fn score_increase(&mut self, points: u16) {
-        match points {
-            0 => self.score += 10,
-            1 => self.score += 50,
-            _ => self.score += points as u32,
-        }
+    pub fn score_increase(&mut self, points: u32) {
+        self.score = self.score.saturating_add(points);
     }
 
     pub fn set_score(
         &mut self,
         texture_creator: &'a TextureCreator<WindowContext>,
-        font: &Font,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let score_text = format!("{}", self.score);
+        font: &GameFont,
+    ) -> Result<(), PacmanError> {
+        let score_text = self.locale.format_number(self.score);
         self.score_texture
             .load_from_rendered_text(texture_creator, &score_text, font, WHITE)?;
         Ok(())
@@ -284,10 +622,10 @@ impl<'a> Board<'a> {
     pub fn set_high_score(
         &mut self,
         texture_creator: &'a TextureCreator<WindowContext>,
-        font: &Font,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        font: &GameFont,
+    ) -> Result<(), PacmanError> {
         let high_score = self.score.max(0);
-        let high_score_text = format!("{}", high_score);
+        let high_score_text = self.locale.format_number(high_score);
         self.high_score_texture.load_from_rendered_text(
             texture_creator,
             &high_score_text,
@@ -297,13 +635,103 @@ impl<'a> Board<'a> {
         Ok(())
     }
 
+    /// Overwrite score and lives, e.g. when loading a snapshot.
+    ///
+    /// A score already past `extra_life` is taken to have earned its life.
+    pub fn restore(&mut self, score: u32, lives: i8, extra_life: u32) {
+        self.score = score;
+        self.lives = lives;
+        self.is_extra = extra_life > 0 && score >= extra_life;
+    }
+
+    /// Hand out the extra life once the score reaches `threshold`; true when it was just earned
+    pub fn award_extra_life(&mut self, threshold: u32) -> bool {
+        if self.is_extra || threshold == 0 || self.score < threshold {
+            return false;
+        }
+        self.is_extra = true;
+        self.lives += 1;
+        true
+    }
+
+    /// Set the score without touching the extra life, e.g. from the debug console
+    pub fn overwrite_score(&mut self, score: u32) {
+        self.score = score;
+    }
+
+    pub fn set_lives(&mut self, lives: i8) {
+        self.lives = lives;
+    }
+
     pub fn decrease_lives(&mut self) {
         if self.lives > 0 {
             self.lives -= 1;
         }
     }
+}
+
+/// The stock maze as a flat map, for tests that need one without a `Board`
+#[cfg(test)]
+pub fn stock_map() -> Vec<BlockType> {
+    Board::CHAR_BOARD
+        .chars()
+        .map(BlockType::from_char)
+        .collect()
+}
 
-    pub fn score_increase_by_value(&mut self, value: u16) {
-        self.score += value as u32;
+/// Add the rectangles and arc pixels that draw `piece` in the tile at `left`, `top`
+fn wall_piece_shape(
+    piece: WallPiece,
+    left: i32,
+    top: i32,
+    rects: &mut Vec<Rect>,
+    points: &mut Vec<Point>,
+) {
+    let size = BLOCK_SIZE_24 as i32;
+    let thickness = WALL_OUTLINE as i32;
+    let radius = WALL_CORNER_RADIUS;
+    let (right, bottom) = (left + size, top + size);
+    let inset = |rounded: bool| if rounded { radius } else { 0 };
+
+    match piece {
+        WallPiece::Edge(side, first, second) => {
+            let (start, end) = (inset(first), size - inset(second));
+            let length = (end - start) as u32;
+            rects.push(match side {
+                Side::Top => Rect::new(left + start, top, length, WALL_OUTLINE),
+                Side::Bottom => Rect::new(left + start, bottom - thickness, length, WALL_OUTLINE),
+                Side::Left => Rect::new(left, top + start, WALL_OUTLINE, length),
+                Side::Right => Rect::new(right - thickness, top + start, WALL_OUTLINE, length),
+            });
+        }
+        WallPiece::OuterCorner(corner) => {
+            // A quarter ring whose outside runs into the two edges
+            let (center_x, center_y, sign_x, sign_y) = match corner {
+                Corner::TopLeft => (left + radius, top + radius, -1, -1),
+                Corner::TopRight => (right - radius - 1, top + radius, 1, -1),
+                Corner::BottomLeft => (left + radius, bottom - radius - 1, -1, 1),
+                Corner::BottomRight => (right - radius - 1, bottom - radius - 1, 1, 1),
+            };
+            for dy in 0..=radius {
+                for dx in 0..=radius {
+                    let distance = dx * dx + dy * dy;
+                    let inner = radius - thickness;
+                    if distance <= radius * radius && distance > inner * inner {
+                        points.push(Point::new(center_x + sign_x * dx, center_y + sign_y * dy));
+                    }
+                }
+            }
+        }
+        WallPiece::InnerCorner(corner) => {
+            let x = match corner {
+                Corner::TopLeft | Corner::BottomLeft => left,
+                Corner::TopRight | Corner::BottomRight => right - thickness,
+            };
+            let y = match corner {
+                Corner::TopLeft | Corner::TopRight => top,
+                Corner::BottomLeft | Corner::BottomRight => bottom - thickness,
+            };
+            rects.push(Rect::new(x, y, WALL_OUTLINE, WALL_OUTLINE));
+        }
     }
 }
@@ -0,0 +1,1176 @@
+use crate::board::Board;
+use crate::error::PacmanError;
+use crate::game::ghost_manager::{ExtraGhost, MAX_EXTRA_GHOSTS};
+use crate::game::levels::Difficulty;
+use crate::game::personality::GhostPersonality;
+use crate::game::replay::fnv1a;
+use crate::game::rng::GameRng;
+use crate::game::rules_mode::RulesMode;
+use crate::game::scoring::ScoringRules;
+use crate::layout::{Layout, MAX_SCALE, TILE_SIZES};
+use crate::maze_file;
+use crate::maze_gen;
+use crate::pacing::{FRAME_RATES, MIN_GAME_SPEED};
+use crate::palette::Palette;
+use crate::theme::MazeColors;
+use crate::{BOARD_WIDTH, TARGET_FPS};
+
+/// What the binary should do once started
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Play,
+    /// Render two saved snapshots offscreen and write an image of the changed pixels
+    SnapshotDiff {
+        before: String,
+        after: String,
+        output: String,
+    },
+    /// Play many bot-controlled games headlessly and report how far they got
+    Simulate(SimulateOptions),
+    /// Play in the terminal instead of a window, e.g. over SSH
+    Tui,
+    /// Time this many headless bot-played updates and report the rate and per-system costs
+    BenchSim(u32),
+}
+
+/// Which side of an online versus game this instance plays
+#[derive(Debug, Clone, PartialEq)]
+pub enum Netplay {
+    /// Wait for the other player on this port and play Pac-Man
+    Host(u16),
+    /// Connect to a host at `address:port` and play Blinky
+    Join(String),
+}
+
+/// Practice options: start on any level and maze, optionally without losing lives
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Practice {
+    pub level: u16,
+    /// Index of the built-in maze played on every level; the usual rotation when absent
+    pub maze: Option<usize>,
+    pub infinite_lives: bool,
+}
+
+impl Default for Practice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Practice {
+    pub fn new() -> Self {
+        Practice {
+            level: 1,
+            maze: None,
+            infinite_lives: false,
+        }
+    }
+}
+
+/// How the simulate command prints its report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// Options for the simulate command
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulateOptions {
+    /// Games played per starting level
+    pub games: u32,
+    /// Starting levels to compare, each one a difficulty profile
+    pub levels: Vec<u16>,
+    /// Worker threads; 0 means one per available core
+    pub threads: usize,
+    pub format: ReportFormat,
+    /// Where to write the report, since games log progress to stdout
+    pub output: String,
+}
+
+impl Default for SimulateOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulateOptions {
+    pub fn new() -> Self {
+        SimulateOptions {
+            games: 100,
+            levels: vec![1],
+            threads: 0,
+            format: ReportFormat::Csv,
+            output: "simulation.csv".to_string(),
+        }
+    }
+
+    fn set(&mut self, flag: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let invalid = || format!("Invalid value for {}: {}", flag, value);
+        match flag {
+            "--games" => {
+                self.games = value.parse().map_err(|_| invalid())?;
+                if self.games == 0 {
+                    return Err(invalid().into());
+                }
+            }
+            "--levels" => {
+                self.levels = value
+                    .split(',')
+                    .map(|level| level.trim().parse().ok().filter(|&level: &u16| level > 0))
+                    .collect::<Option<Vec<u16>>>()
+                    .ok_or_else(invalid)?;
+            }
+            "--threads" => self.threads = value.parse().map_err(|_| invalid())?,
+            "--format" => {
+                self.format = match value {
+                    "csv" => ReportFormat::Csv,
+                    "json" => ReportFormat::Json,
+                    _ => return Err(invalid().into()),
+                };
+                if self.output == "simulation.csv" || self.output == "simulation.json" {
+                    self.output = format!("simulation.{}", value);
+                }
+            }
+            "--output" => self.output = value.to_string(),
+            _ => return Err(format!("Unknown argument: {}", flag).into()),
+        }
+        Ok(())
+    }
+}
+
+/// Startup options read from the command line
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub command: Command,
+    pub palette: Palette,
+    pub ghost_patterns: bool,
+    /// Show how many dots of the maze have been eaten
+    pub dots_hud: bool,
+    /// Crumbs, sparkles and bursts when things are eaten
+    pub particles: bool,
+    /// Shake on a death and flash on an energizer
+    pub screen_effects: bool,
+    /// Fading afterimages behind frightened ghosts
+    pub ghost_trails: bool,
+    /// Background music; sound effects play either way
+    pub music: bool,
+    /// Flash a note in the corner for sounds that matter, for players who cannot hear them
+    pub sound_indicators: bool,
+    pub theme: Option<String>,
+    /// Whether the maze changes color with each level or layout
+    pub maze_colors: MazeColors,
+    /// Draw missing images as solid blocks instead of refusing to start
+    pub placeholder_assets: bool,
+    /// Draw the maze, food and characters from plain shapes and read no images; the font
+    /// is still needed for text unless `bitmap_font` is set
+    pub no_assets: bool,
+    /// Draw text with the built-in arcade font instead of the theme's TrueType font, as
+    /// builds without the `ttf` feature always do
+    pub bitmap_font: bool,
+    /// Tile size and pixel scaling of the window; only changes how the game is drawn
+    pub layout: Layout,
+    /// Wait for the display's refresh before showing each frame
+    pub vsync: bool,
+    /// Frames drawn per second; the game itself always updates at `TARGET_FPS`
+    pub frame_rate: u32,
+    /// Percent of full speed the whole game runs at, stretching each update in real time
+    pub game_speed: u32,
+    pub telemetry: bool,
+    /// Locale tag overriding `LC_ALL`/`LANG`, e.g. `de_DE`
+    pub locale: Option<String>,
+    /// Pause when the window loses focus, so switching away does not cost a life
+    pub pause_on_focus_loss: bool,
+    /// Steer by swiping or with an on-screen d-pad, on touch screens or by dragging the mouse
+    pub touch: bool,
+    /// Click a tile to send Pac-Man there, turning at each junction on the way
+    pub mouse_steer: bool,
+    /// Print state changes as `announce:` lines for screen readers and other tools
+    pub announce: bool,
+    /// Speech command run with each announcement as its last argument, e.g. `espeak`
+    pub announce_command: Option<String>,
+    /// Name stored in recorded replays
+    pub player: String,
+    /// Fixed RNG seed; a fresh one is drawn when absent
+    pub seed: Option<u64>,
+    /// Replay file to play back instead of taking keyboard input
+    pub replay: Option<String>,
+    /// Enable the freeze, frame-advance, slow-motion and quick-save keys
+    pub debug: bool,
+    /// Continue the game saved when the window was last closed
+    pub resume: bool,
+    /// A second player drives Blinky with IJKL or a gamepad
+    pub coop: bool,
+    /// Play versus against another instance over the network
+    pub netplay: Option<Netplay>,
+    /// Log every random draw into the replay so the run can be audited
+    pub ranked: bool,
+    /// Cabinet mode: C inserts a coin and Enter spends a credit to start or continue
+    pub coins: bool,
+    /// Start with the built-in bot playing, as a CPU demo or for soak tests
+    pub bot: bool,
+    /// Seconds without a key press before the bot takes over Pac-Man
+    pub idle_takeover: Option<u32>,
+    /// Points for pellets, ghosts and fruit, from `--rules FILE`
+    pub scoring_rules: ScoringRules,
+    /// Arcade or Ms. Pac-Man mazes, fruit and ghost behavior
+    pub rules_mode: RulesMode,
+    /// Starting lives, ghost speed, energizer length and scatter/chase schedule
+    pub difficulty: Difficulty,
+    /// Keep the arcade's targeting bugs, such as Pinky aiming up and to the left, and its
+    /// short stop for every dot Pac-Man eats
+    pub arcade_quirks: bool,
+    /// Ticks a queued turn waits for an opening before it is dropped; forever when absent
+    pub input_buffer: Option<u32>,
+    /// Ghost targeting reaches and scatter corners, from `--personality FILE`
+    pub ghost_personality: GhostPersonality,
+    /// Ghosts from `--extra-ghost`, joining the usual four in the order given
+    pub extra_ghosts: Vec<ExtraGhost>,
+    /// Practice run from `--practice`, `--practice-maze` and `--infinite-lives`; such runs
+    /// show the pellets left and are never recorded
+    pub practice: Option<Practice>,
+    /// Endless run from `--survival`: pellets come back in waves, ghosts keep joining and the
+    /// score grows with time survived; such runs are never recorded
+    pub survival: bool,
+    /// Challenge from `--ghost-rush`: no scatter waves, faster ghosts that all aim straight at
+    /// Pac-Man, and double points for pellets; such runs are never recorded
+    pub ghost_rush: bool,
+    /// Tiles around Pac-Man that stay lit with `--flashlight N`; the rest of the maze is dark
+    pub flashlight: Option<u16>,
+    /// Board sketch from `--maze FILE`, or generated from the seed by `--maze random`,
+    /// played instead of the built-in mazes
+    pub custom_maze: Option<String>,
+    /// File the custom maze was read from, watched for edits in debug builds
+    pub maze_path: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Config {
+            command: Command::Play,
+            palette: Palette::Standard,
+            ghost_patterns: false,
+            dots_hud: false,
+            particles: true,
+            screen_effects: true,
+            ghost_trails: true,
+            music: true,
+            sound_indicators: false,
+            theme: None,
+            maze_colors: MazeColors::Fixed,
+            placeholder_assets: false,
+            no_assets: false,
+            bitmap_font: !cfg!(feature = "ttf"),
+            layout: Layout::new(),
+            vsync: true,
+            frame_rate: TARGET_FPS,
+            game_speed: 100,
+            telemetry: false,
+            locale: None,
+            pause_on_focus_loss: true,
+            touch: false,
+            mouse_steer: false,
+            announce: false,
+            announce_command: None,
+            player: "PLAYER".to_string(),
+            seed: None,
+            replay: None,
+            debug: false,
+            resume: false,
+            coop: false,
+            netplay: None,
+            ranked: false,
+            coins: false,
+            bot: false,
+            idle_takeover: None,
+            scoring_rules: ScoringRules::new(),
+            rules_mode: RulesMode::Arcade,
+            difficulty: Difficulty::Normal,
+            arcade_quirks: false,
+            input_buffer: None,
+            ghost_personality: GhostPersonality::new(),
+            extra_ghosts: Vec::new(),
+            practice: None,
+            survival: false,
+            ghost_rush: false,
+            flashlight: None,
+            custom_maze: None,
+            maze_path: None,
+        }
+    }
+
+    /// Options that shape the simulation, so replays can tell whether they still apply.
+    ///
+    /// Cosmetic flags are left out; they may differ between recording and playback.
+    pub fn fingerprint(&self) -> String {
+        let mut fingerprint = format!("theme={}", self.theme.as_deref().unwrap_or(""));
+        if self.coop {
+            fingerprint.push_str(";coop");
+        }
+        if self.arcade_quirks {
+            fingerprint.push_str(";quirks");
+        }
+        if self.survival {
+            fingerprint.push_str(";survival");
+        }
+        if self.ghost_rush {
+            fingerprint.push_str(";rush");
+        }
+        if self.difficulty != Difficulty::Normal {
+            fingerprint.push_str(&format!(";difficulty={}", self.difficulty.name()));
+        }
+        if let Some(ticks) = self.input_buffer {
+            fingerprint.push_str(&format!(";buffer={}", ticks));
+        }
+        if self.ghost_personality != GhostPersonality::new() {
+            fingerprint.push_str(&format!(
+                ";personality={}",
+                self.ghost_personality.fingerprint()
+            ));
+        }
+        for extra in &self.extra_ghosts {
+            fingerprint.push_str(&format!(";ghost={}", extra.fingerprint()));
+        }
+        if self.rules_mode != RulesMode::Arcade {
+            fingerprint.push_str(&format!(";mode={}", self.rules_mode.name()));
+        }
+        if let Some(ref sketch) = self.custom_maze {
+            fingerprint.push_str(&format!(";maze={:x}", fnv1a(sketch.as_bytes())));
+        }
+        if self.scoring_rules != ScoringRules::new() {
+            fingerprint.push_str(&format!(";rules={}", self.scoring_rules.fingerprint()));
+        }
+        fingerprint
+    }
+
+    /// Parse options, skipping the program name
+    pub fn from_args<I>(args: I) -> Result<Self, PacmanError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Config::from_args_over(args, Config::new())
+    }
+
+    /// Apply the command line on top of `base`, such as the defaults with saved settings
+    pub fn from_args_over<I>(args: I, base: Config) -> Result<Self, PacmanError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Config::parse_args(args, base).map_err(|e| PacmanError::ConfigParse(e.to_string()))
+    }
+
+    fn parse_args<I>(args: I, base: Config) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut config = base;
+        let mut args = args.into_iter().skip(1);
+        let mut random_maze = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--palette" => {
+                    let name = args.next().ok_or("--palette expects a value")?;
+                    config.palette = Palette::from_name(&name)
+                        .ok_or_else(|| format!("Unknown palette: {}", name))?;
+                }
+                "--ghost-patterns" => config.ghost_patterns = true,
+                "--dots-hud" => config.dots_hud = true,
+                "--no-particles" => config.particles = false,
+                "--no-screen-effects" => config.screen_effects = false,
+                "--no-ghost-trails" => config.ghost_trails = false,
+                "--no-music" => config.music = false,
+                "--sound-indicators" => config.sound_indicators = true,
+                "--theme" => {
+                    config.theme = Some(args.next().ok_or("--theme expects a directory name")?);
+                }
+                "--maze-colors" => {
+                    let name = args
+                        .next()
+                        .ok_or("--maze-colors expects fixed, level or maze")?;
+                    config.maze_colors = MazeColors::from_name(&name)
+                        .ok_or_else(|| format!("Unknown maze colors: {}", name))?;
+                }
+                "--placeholder-assets" => config.placeholder_assets = true,
+                "--no-assets" => config.no_assets = true,
+                "--bitmap-font" => config.bitmap_font = true,
+                "--scale" => {
+                    let value = args.next().ok_or("--scale expects a number")?;
+                    config.layout.scale = value
+                        .parse()
+                        .ok()
+                        .filter(|scale| (1..=MAX_SCALE).contains(scale))
+                        .ok_or_else(|| format!("Scale must be 1 to {}: {}", MAX_SCALE, value))?;
+                }
+                "--no-vsync" => config.vsync = false,
+                "--fps" => {
+                    let value = args.next().ok_or("--fps expects a frame rate")?;
+                    config.frame_rate = value
+                        .parse()
+                        .ok()
+                        .filter(|fps| (FRAME_RATES.0..=FRAME_RATES.1).contains(fps))
+                        .ok_or_else(|| {
+                            format!(
+                                "Frame rate must be {} to {}: {}",
+                                FRAME_RATES.0, FRAME_RATES.1, value
+                            )
+                        })?;
+                }
+                "--game-speed" => {
+                    let value = args.next().ok_or("--game-speed expects a percentage")?;
+                    config.game_speed = value
+                        .parse()
+                        .ok()
+                        .filter(|speed| (MIN_GAME_SPEED..=100).contains(speed))
+                        .ok_or_else(|| {
+                            format!("Game speed must be {} to 100: {}", MIN_GAME_SPEED, value)
+                        })?;
+                }
+                "--tile-size" => {
+                    let value = args.next().ok_or("--tile-size expects a size in pixels")?;
+                    config.layout.tile_size = value
+                        .parse()
+                        .ok()
+                        .filter(|size| (TILE_SIZES.0..=TILE_SIZES.1).contains(size))
+                        .ok_or_else(|| {
+                            format!(
+                                "Tile size must be {} to {} pixels: {}",
+                                TILE_SIZES.0, TILE_SIZES.1, value
+                            )
+                        })?;
+                }
+                "--telemetry" => config.telemetry = true,
+                "--debug" => config.debug = true,
+                "--resume" => config.resume = true,
+                "--coop" => config.coop = true,
+                "--ranked" => config.ranked = true,
+                "--bot" => config.bot = true,
+                "--coins" => config.coins = true,
+                "--arcade-quirks" => config.arcade_quirks = true,
+                "--seed" => {
+                    let value = args.next().ok_or("--seed expects a number")?;
+                    config.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid seed: {}", value))?,
+                    );
+                }
+                "--input-buffer" => {
+                    let value = args.next().ok_or("--input-buffer expects ticks")?;
+                    match value.parse() {
+                        Ok(ticks) if ticks > 0 => config.input_buffer = Some(ticks),
+                        _ => return Err(format!("Invalid input buffer: {}", value).into()),
+                    }
+                }
+                "--extra-ghost" => {
+                    let spec = args
+                        .next()
+                        .ok_or("--extra-ghost expects KIND[:RRGGBB[:COL,ROW]]")?;
+                    if config.extra_ghosts.len() == MAX_EXTRA_GHOSTS {
+                        return Err(format!(
+                            "At most {} extra ghosts are allowed",
+                            MAX_EXTRA_GHOSTS
+                        )
+                        .into());
+                    }
+                    config.extra_ghosts.push(ExtraGhost::parse(&spec)?);
+                }
+                "--idle-takeover" => {
+                    let value = args.next().ok_or("--idle-takeover expects seconds")?;
+                    match value.parse() {
+                        Ok(seconds) if seconds > 0 => config.idle_takeover = Some(seconds),
+                        _ => return Err(format!("Invalid idle takeover delay: {}", value).into()),
+                    }
+                }
+                "--mode" => {
+                    let name = args.next().ok_or("--mode expects arcade or ms")?;
+                    config.rules_mode = RulesMode::from_name(&name)
+                        .ok_or_else(|| format!("Unknown rules mode: {}", name))?;
+                }
+                "--difficulty" => {
+                    let name = args
+                        .next()
+                        .ok_or("--difficulty expects easy, normal, hard or kids")?;
+                    config.difficulty = Difficulty::from_name(&name)
+                        .ok_or_else(|| format!("Unknown difficulty: {}", name))?;
+                }
+                "--practice" => {
+                    let value = args.next().ok_or("--practice expects a level")?;
+                    let level = value
+                        .parse()
+                        .ok()
+                        .filter(|&level: &u16| level > 0)
+                        .ok_or_else(|| format!("Invalid level: {}", value))?;
+                    config.practice.get_or_insert_with(Practice::new).level = level;
+                }
+                "--practice-maze" => {
+                    let value = args.next().ok_or("--practice-maze expects a maze number")?;
+                    let maze = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|maze| (1..=Board::MAZE_COUNT).contains(maze))
+                        .ok_or_else(|| {
+                            format!("Maze number must be 1 to {}: {}", Board::MAZE_COUNT, value)
+                        })?;
+                    config.practice.get_or_insert_with(Practice::new).maze = Some(maze - 1);
+                }
+                "--survival" => config.survival = true,
+                "--ghost-rush" => config.ghost_rush = true,
+                "--flashlight" => {
+                    let value = args
+                        .next()
+                        .ok_or("--flashlight expects a radius in tiles")?;
+                    let radius = value
+                        .parse::<u16>()
+                        .ok()
+                        .filter(|radius| (1..=BOARD_WIDTH as u16).contains(radius))
+                        .ok_or_else(|| {
+                            format!("Flashlight radius must be 1 to {}: {}", BOARD_WIDTH, value)
+                        })?;
+                    config.flashlight = Some(radius);
+                }
+                "--infinite-lives" => {
+                    config
+                        .practice
+                        .get_or_insert_with(Practice::new)
+                        .infinite_lives = true;
+                }
+                "--maze" => {
+                    let path = args.next().ok_or("--maze expects a file or random")?;
+                    if path == "random" {
+                        random_maze = true;
+                    } else {
+                        config.custom_maze = Some(maze_file::load(&path)?);
+                        config.maze_path = Some(path);
+                    }
+                }
+                "--arcade-plus" => config.scoring_rules.add_arcade_plus(),
+                "--rules" => {
+                    let path = args.next().ok_or("--rules expects a file")?;
+                    config.scoring_rules = ScoringRules::load(&path)?;
+                }
+                "--personality" => {
+                    let path = args.next().ok_or("--personality expects a file")?;
+                    config.ghost_personality = GhostPersonality::load(&path)?;
+                }
+                "--replay" => {
+                    config.replay = Some(args.next().ok_or("--replay expects a file")?);
+                }
+                "--host" => {
+                    let value = args.next().ok_or("--host expects a port")?;
+                    let port = value
+                        .parse()
+                        .map_err(|_| format!("Invalid port: {}", value))?;
+                    config.netplay = Some(Netplay::Host(port));
+                }
+                "--join" => {
+                    let address = args.next().ok_or("--join expects an address:port")?;
+                    config.netplay = Some(Netplay::Join(address));
+                }
+                "--player" => {
+                    config.player = args.next().ok_or("--player expects a name")?;
+                }
+                "--locale" => {
+                    config.locale =
+                        Some(args.next().ok_or("--locale expects a tag such as en_US")?);
+                }
+                "--no-focus-pause" => config.pause_on_focus_loss = false,
+                "--touch" => config.touch = true,
+                "--mouse-steer" => config.mouse_steer = true,
+                "--announce" => config.announce = true,
+                "--announce-command" => {
+                    config.announce = true;
+                    config.announce_command =
+                        Some(args.next().ok_or("--announce-command expects a program")?);
+                }
+                "snapshot-diff" => {
+                    let usage = "usage: snapshot-diff <before> <after> <output.bmp>";
+                    config.command = Command::SnapshotDiff {
+                        before: args.next().ok_or(usage)?,
+                        after: args.next().ok_or(usage)?,
+                        output: args.next().ok_or(usage)?,
+                    };
+                }
+                "simulate" => config.command = Command::Simulate(SimulateOptions::new()),
+                "--tui" => config.command = Command::Tui,
+                "--bench-sim" => {
+                    let value = args.next().ok_or("--bench-sim expects a number of ticks")?;
+                    match value.parse() {
+                        Ok(ticks) if ticks > 0 => config.command = Command::BenchSim(ticks),
+                        _ => return Err(format!("Invalid tick count: {}", value).into()),
+                    }
+                }
+                "--games" | "--levels" | "--threads" | "--format" | "--output" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("{} expects a value", arg))?;
+                    match config.command {
+                        Command::Simulate(ref mut options) => options.set(&arg, &value)?,
+                        _ => {
+                            return Err(
+                                format!("{} only applies to the simulate command", arg).into()
+                            )
+                        }
+                    }
+                }
+                _ => return Err(format!("Unknown argument: {}", arg).into()),
+            }
+        }
+
+        if random_maze {
+            // Draw the seed now so the same one both builds the maze and plays the game
+            let seed = *config
+                .seed
+                .get_or_insert_with(|| GameRng::from_entropy().get_seed());
+            config.custom_maze = Some(maze_gen::generate(seed));
+            config.maze_path = None;
+        }
+        if config.touch && config.mouse_steer {
+            return Err("--touch and --mouse-steer both use the mouse; pick one".into());
+        }
+        if config.ranked && !config.difficulty.counts_for_high_scores() {
+            return Err("Kids mode runs cannot be ranked".into());
+        }
+        if config.ranked && config.debug {
+            return Err("--ranked cannot be combined with --debug".into());
+        }
+        if config.game_speed < 100 && (config.ranked || config.netplay.is_some()) {
+            return Err("A slowed game cannot be ranked or played versus".into());
+        }
+        if config.practice.is_some()
+            && (config.ranked
+                || config.netplay.is_some()
+                || config.replay.is_some()
+                || config.resume)
+        {
+            return Err(
+                "Practice cannot be combined with --ranked, --host, --join, --replay or --resume"
+                    .into(),
+            );
+        }
+        if config.survival
+            && (config.practice.is_some()
+                || config.ranked
+                || config.netplay.is_some()
+                || config.replay.is_some()
+                || config.resume)
+        {
+            return Err(
+                "Survival runs cannot be practiced, ranked, replayed, resumed or played versus"
+                    .into(),
+            );
+        }
+        if config.ghost_rush && (config.ranked || config.replay.is_some() || config.resume) {
+            return Err(
+                "--ghost-rush cannot be combined with --ranked, --replay or --resume".into(),
+            );
+        }
+        if config.netplay.is_some() {
+            if config.replay.is_some() || config.resume {
+                return Err(
+                    "--host and --join cannot be combined with --replay or --resume".into(),
+                );
+            }
+            // The guest plays Blinky through the co-op controls
+            config.coop = true;
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("rust_pacman")
+            .chain(list.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::from_args(args(&[])).unwrap();
+        assert_eq!(config.palette, Palette::Standard);
+        assert!(!config.ghost_patterns);
+        assert_eq!(config.command, Command::Play);
+        assert_eq!(config.theme, None);
+        assert!(!config.telemetry);
+    }
+
+    #[test]
+    fn test_palette_and_patterns() {
+        let config =
+            Config::from_args(args(&["--palette", "colorblind", "--ghost-patterns"])).unwrap();
+        assert_eq!(config.palette, Palette::Colorblind);
+        assert!(config.ghost_patterns);
+        assert!(!config.dots_hud);
+    }
+
+    #[test]
+    fn test_no_particles_flag() {
+        assert!(Config::new().particles);
+        let config = Config::from_args(args(&["--no-particles"])).unwrap();
+        assert!(!config.particles);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+
+        let config = Config::from_args(args(&["--no-screen-effects"])).unwrap();
+        assert!(!config.screen_effects);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+
+        let config = Config::from_args(args(&["--no-ghost-trails"])).unwrap();
+        assert!(!config.ghost_trails);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_sound_flags() {
+        assert!(Config::new().music);
+        let config = Config::from_args(args(&["--no-music"])).unwrap();
+        assert!(!config.music);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+
+        let config = Config::from_args(args(&["--sound-indicators"])).unwrap();
+        assert!(config.sound_indicators);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_dots_hud_flag() {
+        let config = Config::from_args(args(&["--dots-hud"])).unwrap();
+        assert!(config.dots_hud);
+    }
+
+    #[test]
+    fn test_telemetry_flag() {
+        let config = Config::from_args(args(&["--telemetry"])).unwrap();
+        assert!(config.telemetry);
+    }
+
+    #[test]
+    fn test_maze_colors_option() {
+        let config = Config::from_args(args(&["--maze-colors", "level"])).unwrap();
+        assert_eq!(config.maze_colors, MazeColors::PerLevel);
+        assert_eq!(Config::new().maze_colors, MazeColors::Fixed);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--maze-colors", "rainbow"])).is_err());
+    }
+
+    #[test]
+    fn test_layout_options() {
+        let config = Config::from_args(args(&["--scale", "2", "--tile-size", "16"])).unwrap();
+        assert_eq!(
+            config.layout,
+            Layout {
+                tile_size: 16,
+                scale: 2
+            }
+        );
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--scale", "0"])).is_err());
+        assert!(Config::from_args(args(&["--scale", "9"])).is_err());
+        assert!(Config::from_args(args(&["--tile-size", "4"])).is_err());
+    }
+
+    #[test]
+    fn test_frame_rate_flags() {
+        let config = Config::from_args(args(&["--no-vsync", "--fps", "144"])).unwrap();
+        assert!(!config.vsync);
+        assert_eq!(config.frame_rate, 144);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--fps", "10"])).is_err());
+        assert!(Config::from_args(args(&["--fps"])).is_err());
+    }
+
+    #[test]
+    fn test_game_speed_flag() {
+        assert_eq!(Config::new().game_speed, 100);
+        let config = Config::from_args(args(&["--game-speed", "75"])).unwrap();
+        assert_eq!(config.game_speed, 75);
+        // The same updates run, only further apart
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+
+        assert!(Config::from_args(args(&["--game-speed", "40"])).is_err());
+        assert!(Config::from_args(args(&["--game-speed", "120"])).is_err());
+        assert!(Config::from_args(args(&["--game-speed", "50", "--ranked"])).is_err());
+    }
+
+    #[test]
+    fn test_placeholder_assets_flag() {
+        let config = Config::from_args(args(&["--placeholder-assets"])).unwrap();
+        assert!(config.placeholder_assets);
+        assert!(!Config::new().placeholder_assets);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+
+        let config = Config::from_args(args(&["--no-assets"])).unwrap();
+        assert!(config.no_assets);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+
+        let config = Config::from_args(args(&["--bitmap-font"])).unwrap();
+        assert!(config.bitmap_font);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_cosmetic_options() {
+        let plain = Config::from_args(args(&[])).unwrap();
+        let styled =
+            Config::from_args(args(&["--palette", "colorblind", "--player", "Ada"])).unwrap();
+        let themed = Config::from_args(args(&["--theme", "neon"])).unwrap();
+        assert_eq!(plain.fingerprint(), styled.fingerprint());
+        assert_ne!(plain.fingerprint(), themed.fingerprint());
+    }
+
+    #[test]
+    fn test_coop_changes_fingerprint() {
+        let solo = Config::from_args(args(&[])).unwrap();
+        let coop = Config::from_args(args(&["--coop"])).unwrap();
+        assert!(!solo.coop);
+        assert!(coop.coop);
+        assert_ne!(solo.fingerprint(), coop.fingerprint());
+    }
+
+    #[test]
+    fn test_netplay_flags() {
+        let host = Config::from_args(args(&["--host", "7777"])).unwrap();
+        assert_eq!(host.netplay, Some(Netplay::Host(7777)));
+        assert!(host.coop);
+
+        let guest = Config::from_args(args(&["--join", "10.0.0.2:7777"])).unwrap();
+        assert_eq!(
+            guest.netplay,
+            Some(Netplay::Join("10.0.0.2:7777".to_string()))
+        );
+        assert_eq!(host.fingerprint(), guest.fingerprint());
+
+        assert!(Config::from_args(args(&["--host", "port"])).is_err());
+        assert!(Config::from_args(args(&["--host", "7777", "--resume"])).is_err());
+    }
+
+    #[test]
+    fn test_idle_takeover_flag() {
+        let config = Config::from_args(args(&["--idle-takeover", "30"])).unwrap();
+        assert_eq!(config.idle_takeover, Some(30));
+        assert_eq!(Config::new().idle_takeover, None);
+        assert!(Config::from_args(args(&["--idle-takeover", "0"])).is_err());
+        assert!(Config::from_args(args(&["--idle-takeover"])).is_err());
+    }
+
+    #[test]
+    fn test_scoring_rules_change_fingerprint() {
+        let mut config = Config::new();
+        let arcade = config.fingerprint();
+        config.scoring_rules.pellet = 1;
+        assert_ne!(config.fingerprint(), arcade);
+        assert!(Config::from_args(args(&["--rules", "no_such_rules.txt"])).is_err());
+
+        let plus = Config::from_args(args(&["--arcade-plus"])).unwrap();
+        assert!(plus.scoring_rules.life_bonus > 0);
+        assert_ne!(plus.fingerprint(), arcade);
+    }
+
+    #[test]
+    fn test_ghost_personality_changes_fingerprint() {
+        let mut config = Config::new();
+        let arcade = config.fingerprint();
+        config.ghost_personality.clyde_shyness = 4;
+        assert_ne!(config.fingerprint(), arcade);
+        assert!(Config::from_args(args(&["--personality", "no_such_file.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_rules_mode_flag() {
+        let config = Config::from_args(args(&["--mode", "ms"])).unwrap();
+        assert_eq!(config.rules_mode, RulesMode::MsPacman);
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--mode", "jr"])).is_err());
+    }
+
+    #[test]
+    fn test_custom_maze_changes_fingerprint() {
+        let mut config = Config::new();
+        config.custom_maze = Some(crate::board::Board::ALT_BOARD.to_string());
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--maze", "no_such_maze.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_random_maze_follows_the_seed() {
+        let config = Config::from_args(args(&["--maze", "random", "--seed", "7"])).unwrap();
+        assert_eq!(config.custom_maze, Some(crate::maze_gen::generate(7)));
+
+        let unseeded = Config::from_args(args(&["--maze", "random"])).unwrap();
+        let seed = unseeded.seed.expect("a random maze fixes the seed");
+        assert_eq!(unseeded.custom_maze, Some(crate::maze_gen::generate(seed)));
+    }
+
+    #[test]
+    fn test_arcade_quirks_flag() {
+        let config = Config::from_args(args(&["--arcade-quirks"])).unwrap();
+        assert!(config.arcade_quirks);
+        assert!(!Config::new().arcade_quirks);
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_input_buffer_flag() {
+        let config = Config::from_args(args(&["--input-buffer", "8"])).unwrap();
+        assert_eq!(config.input_buffer, Some(8));
+        assert_eq!(Config::new().input_buffer, None);
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--input-buffer", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_extra_ghost_flag() {
+        let config = Config::from_args(args(&[
+            "--extra-ghost",
+            "blinky",
+            "--extra-ghost",
+            "clyde:ffb8ae:1,4",
+        ]))
+        .unwrap();
+        assert_eq!(config.extra_ghosts.len(), 2);
+        assert_eq!(config.extra_ghosts[1].corner, Some((1, 4)));
+        assert!(Config::new().extra_ghosts.is_empty());
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+
+        assert!(Config::from_args(args(&["--extra-ghost", "sue"])).is_err());
+        assert!(Config::from_args(args(&["--extra-ghost", "inky:red"])).is_err());
+        let six: Vec<&str> = ["--extra-ghost", "pinky"].repeat(6);
+        assert!(Config::from_args(args(&six)).is_err());
+    }
+
+    #[test]
+    fn test_bot_flag() {
+        assert!(Config::from_args(args(&["--bot"])).unwrap().bot);
+        assert!(!Config::from_args(args(&[])).unwrap().bot);
+    }
+
+    #[test]
+    fn test_difficulty_option() {
+        let config = Config::from_args(args(&["--difficulty", "hard"])).unwrap();
+        assert_eq!(config.difficulty, Difficulty::Hard);
+        assert_eq!(Config::new().difficulty, Difficulty::Normal);
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--difficulty", "insane"])).is_err());
+        assert!(Config::from_args(args(&["--difficulty", "kids", "--ranked"])).is_err());
+        assert!(Config::from_args(args(&["--difficulty"])).is_err());
+    }
+
+    #[test]
+    fn test_practice_options() {
+        let config = Config::from_args(args(&["--practice", "7", "--practice-maze", "2"])).unwrap();
+        assert_eq!(
+            config.practice,
+            Some(Practice {
+                level: 7,
+                maze: Some(1),
+                infinite_lives: false,
+            })
+        );
+        assert_eq!(Config::new().practice, None);
+
+        let config = Config::from_args(args(&["--infinite-lives"])).unwrap();
+        assert_eq!(config.practice.map(|practice| practice.level), Some(1));
+        assert!(config.practice.unwrap().infinite_lives);
+
+        assert!(Config::from_args(args(&["--practice", "0"])).is_err());
+        assert!(Config::from_args(args(&["--practice-maze", "3"])).is_err());
+        assert!(Config::from_args(args(&["--practice", "2", "--ranked"])).is_err());
+    }
+
+    #[test]
+    fn test_survival_flag() {
+        let config = Config::from_args(args(&["--survival"])).unwrap();
+        assert!(config.survival);
+        assert!(!Config::new().survival);
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--survival", "--practice", "3"])).is_err());
+        assert!(Config::from_args(args(&["--survival", "--resume"])).is_err());
+    }
+
+    #[test]
+    fn test_ghost_rush_flag() {
+        let config = Config::from_args(args(&["--ghost-rush"])).unwrap();
+        assert!(config.ghost_rush);
+        assert!(!Config::new().ghost_rush);
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+        // The doubled pellets are the rush's own, not a change to the rules
+        assert_eq!(config.scoring_rules, ScoringRules::new());
+        assert!(Config::from_args(args(&["--ghost-rush", "--ranked"])).is_err());
+        assert!(Config::from_args(args(&["--ghost-rush", "--survival"])).is_ok());
+    }
+
+    #[test]
+    fn test_flashlight_flag() {
+        let config = Config::from_args(args(&["--flashlight", "4"])).unwrap();
+        assert_eq!(config.flashlight, Some(4));
+        assert_eq!(Config::new().flashlight, None);
+        // Only what is seen changes, so replays still apply
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--flashlight", "0"])).is_err());
+        assert!(Config::from_args(args(&["--flashlight"])).is_err());
+    }
+
+    #[test]
+    fn test_coins_flag() {
+        let config = Config::from_args(args(&["--coins"])).unwrap();
+        assert!(config.coins);
+        assert!(!Config::new().coins);
+        // Credits only decide when games start, not how they play
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_ranked_flag() {
+        let config = Config::from_args(args(&["--ranked"])).unwrap();
+        assert!(config.ranked);
+        assert!(!Config::from_args(args(&[])).unwrap().ranked);
+        assert!(Config::from_args(args(&["--ranked", "--debug"])).is_err());
+    }
+
+    #[test]
+    fn test_debug_and_resume_flags() {
+        let config = Config::from_args(args(&[])).unwrap();
+        assert!(!config.debug);
+        assert!(!config.resume);
+        let config = Config::from_args(args(&["--debug", "--resume"])).unwrap();
+        assert!(config.debug);
+        assert!(config.resume);
+    }
+
+    #[test]
+    fn test_seed() {
+        let config = Config::from_args(args(&["--seed", "42"])).unwrap();
+        assert_eq!(config.seed, Some(42));
+    }
+
+    #[test]
+    fn test_replay_file() {
+        let config = Config::from_args(args(&["--replay", "best_replay.txt"])).unwrap();
+        assert_eq!(config.replay.as_deref(), Some("best_replay.txt"));
+        assert!(Config::from_args(args(&["--replay"])).is_err());
+    }
+
+    #[test]
+    fn test_locale_override() {
+        let config = Config::from_args(args(&["--locale", "de_DE"])).unwrap();
+        assert_eq!(config.locale.as_deref(), Some("de_DE"));
+    }
+
+    #[test]
+    fn test_no_focus_pause_flag() {
+        assert!(Config::new().pause_on_focus_loss);
+        let config = Config::from_args(args(&["--no-focus-pause"])).unwrap();
+        assert!(!config.pause_on_focus_loss);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_touch_flag() {
+        assert!(!Config::new().touch);
+        let config = Config::from_args(args(&["--touch"])).unwrap();
+        assert!(config.touch);
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+    }
+
+    #[test]
+    fn test_mouse_steer_flag() {
+        assert!(!Config::new().mouse_steer);
+        let config = Config::from_args(args(&["--mouse-steer"])).unwrap();
+        assert!(config.mouse_steer);
+        assert!(Config::from_args(args(&["--mouse-steer", "--touch"])).is_err());
+    }
+
+    #[test]
+    fn test_announce_flags() {
+        let config = Config::from_args(args(&["--announce-command", "espeak -s 160"])).unwrap();
+        assert!(config.announce);
+        assert_eq!(config.announce_command.as_deref(), Some("espeak -s 160"));
+        assert_eq!(config.fingerprint(), Config::new().fingerprint());
+        assert!(Config::from_args(args(&["--announce-command"])).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_diff_command() {
+        let config =
+            Config::from_args(args(&["snapshot-diff", "a.txt", "b.txt", "diff.bmp"])).unwrap();
+        assert_eq!(
+            config.command,
+            Command::SnapshotDiff {
+                before: "a.txt".to_string(),
+                after: "b.txt".to_string(),
+                output: "diff.bmp".to_string(),
+            }
+        );
+        assert!(Config::from_args(args(&["snapshot-diff", "a.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_simulate_command() {
+        let config = Config::from_args(args(&["simulate"])).unwrap();
+        assert_eq!(config.command, Command::Simulate(SimulateOptions::new()));
+
+        let config = Config::from_args(args(&[
+            "simulate", "--games", "20", "--levels", "1,5,13", "--format", "json",
+        ]))
+        .unwrap();
+        match config.command {
+            Command::Simulate(options) => {
+                assert_eq!(options.games, 20);
+                assert_eq!(options.levels, vec![1, 5, 13]);
+                assert_eq!(options.format, ReportFormat::Json);
+                assert_eq!(options.output, "simulation.json");
+            }
+            other => panic!("Expected simulate, got {:?}", other),
+        }
+
+        assert!(Config::from_args(args(&["--games", "5"])).is_err());
+        assert!(Config::from_args(args(&["simulate", "--levels", "1,0"])).is_err());
+        assert!(Config::from_args(args(&["simulate", "--format", "xml"])).is_err());
+        assert!(Config::from_args(args(&["simulate", "--games", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_tui_flag() {
+        let config = Config::from_args(args(&["--tui", "--seed", "4"])).unwrap();
+        assert_eq!(config.command, Command::Tui);
+        assert_eq!(config.seed, Some(4));
+    }
+
+    #[test]
+    fn test_bench_sim_flag() {
+        let config = Config::from_args(args(&["--bench-sim", "100000"])).unwrap();
+        assert_eq!(config.command, Command::BenchSim(100_000));
+        assert!(Config::from_args(args(&["--bench-sim"])).is_err());
+        assert!(Config::from_args(args(&["--bench-sim", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(Config::from_args(args(&["--palette"])).is_err());
+        assert!(Config::from_args(args(&["--palette", "sepia"])).is_err());
+        assert!(Config::from_args(args(&["--bogus"])).is_err());
+        assert!(Config::from_args(args(&["--theme"])).is_err());
+        assert!(Config::from_args(args(&["--locale"])).is_err());
+        assert!(Config::from_args(args(&["--player"])).is_err());
+        assert!(Config::from_args(args(&["--seed", "abc"])).is_err());
+    }
+
+    #[test]
+    fn test_errors_are_config_parse_errors() {
+        match Config::from_args(args(&["--bogus"])) {
+            Err(PacmanError::ConfigParse(message)) => assert!(message.contains("--bogus")),
+            other => panic!("expected a ConfigParse error, got {:?}", other.err()),
+        }
+    }
+}
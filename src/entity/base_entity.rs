@@ -1,6 +1,9 @@
-use crate::board::{BlockType, Direction, EntityType};
+use crate::board::{BlockType, Direction, EntityType, TUNNEL_ROWS};
 use crate::position::Position;
-use crate::{BLOCK_SIZE_24, BOARD_WIDTH, WINDOW_WIDTH};
+use crate::{BLOCK_SIZE_24, BOARD_WIDTH, SCREEN_WIDTH};
+
+/// Pixels from one end of a vertical tunnel to the other
+pub const VERTICAL_WRAP: i16 = ((TUNNEL_ROWS.1 - TUNNEL_ROWS.0 + 1) as u32 * BLOCK_SIZE_24) as i16;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Facing {
@@ -29,7 +32,6 @@ impl Facing {
 
 pub trait Entity {
     fn new(identity: EntityType) -> Self;
-    #[allow(dead_code)]
     fn get_identity(&self) -> EntityType;
     fn get_speed(&self) -> u8;
     fn get_direction(&self) -> Direction;
@@ -59,12 +61,13 @@ pub trait Entity {
 
 pub struct BaseEntity {
     pub position: Position,
-    #[allow(dead_code)]
     pub identity: EntityType,
     pub speed: u8,
     pub direction: Direction,
     pub facing: Facing,
     pub life_statement: bool,
+    /// Teleporters are ignored until this runs out, so the far end does not send it back
+    pub teleport_cooldown_ms: u32,
 }
 
 impl Entity for BaseEntity {
@@ -76,6 +79,7 @@ impl Entity for BaseEntity {
             direction: Direction::Right,
             facing: Facing::Right,
             life_statement: true,
+            teleport_cooldown_ms: 0,
         }
     }
 
@@ -198,11 +202,20 @@ impl Entity for BaseEntity {
     }
 
     fn check_wrap(&mut self) {
-        if self.get_x() > (WINDOW_WIDTH + BLOCK_SIZE_24) as i16 {
+        if self.get_x() > (SCREEN_WIDTH + BLOCK_SIZE_24) as i16 {
             self.mod_x(-(BLOCK_SIZE_24 as i16));
         }
         if self.get_x() < -(BLOCK_SIZE_24 as i16) {
-            self.mod_x((WINDOW_WIDTH + BLOCK_SIZE_24) as i16);
+            self.mod_x((SCREEN_WIDTH + BLOCK_SIZE_24) as i16);
+        }
+
+        // Vertical tunnels swap ends halfway out of the maze, before reaching the HUD rows
+        let top = (TUNNEL_ROWS.0 as u32 * BLOCK_SIZE_24 - BLOCK_SIZE_24 / 2) as i16;
+        if self.get_y() < top {
+            self.mod_y(self.get_y() + VERTICAL_WRAP);
+        }
+        if self.get_y() > top + VERTICAL_WRAP {
+            self.mod_y(self.get_y() - VERTICAL_WRAP);
         }
     }
 
@@ -1,16 +1,15 @@
 use crate::board::{Direction, EntityType};
 use crate::entity::{Entity, Ghost, GhostBehavior, GhostType};
 use crate::position::Position;
+use crate::theme::Theme;
 use crate::{BLOCK_SIZE_24, RED};
 
-pub struct Blinky<'a> {
-    ghost: Ghost<'a>,
+pub struct Blinky {
+    ghost: Ghost,
 }
 
-impl<'a> Blinky<'a> {
-    pub fn new(
-        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+impl Blinky {
+    pub fn new(theme: &Theme) -> Self {
         let scatter_target = Position::new(
             (25 * BLOCK_SIZE_24 + BLOCK_SIZE_24 / 2) as i16,
             (BLOCK_SIZE_24 / 2) as i16,
@@ -25,23 +24,15 @@ impl<'a> Blinky<'a> {
             EntityType::Blinky,
             scatter_target,
             home_position,
-            texture_creator,
-        )?;
+            theme,
+        );
 
         ghost.entity.set_facing(Direction::Up);
-        Ok(Blinky { ghost })
-    }
-
-    pub fn get_ghost_mut(&mut self) -> &mut Ghost<'a> {
-        &mut self.ghost
-    }
-
-    pub fn get_ghost(&self) -> &Ghost<'a> {
-        &self.ghost
+        Blinky { ghost }
     }
 }
 
-impl<'a> GhostBehavior<'a> for Blinky<'a> {
+impl GhostBehavior for Blinky {
     fn get_ghost_type(&self) -> GhostType {
         GhostType::Blinky
     }
@@ -50,6 +41,10 @@ impl<'a> GhostBehavior<'a> for Blinky<'a> {
         self.ghost.scatter_target
     }
 
+    fn start_facing(&self) -> Direction {
+        Direction::Left
+    }
+
     fn calculate_target(
         &mut self,
         pacman_pos: Position,
@@ -59,22 +54,15 @@ impl<'a> GhostBehavior<'a> for Blinky<'a> {
         self.ghost.target = pacman_pos;
     }
 
-    #[allow(dead_code)]
     fn get_can_use_door(&self) -> bool {
-        self.ghost.can_use_door
-    }
-
-    #[allow(dead_code)]
-    fn set_can_use_door(&mut self, can_use_door: bool) {
-        self.ghost.can_use_door = can_use_door;
+        self.ghost.get_mode().can_use_door()
     }
 
-    fn get_ghost_mut(&mut self) -> &mut Ghost<'a> {
+    fn get_ghost_mut(&mut self) -> &mut Ghost {
         &mut self.ghost
     }
 
-    #[allow(dead_code)]
-    fn get_ghost(&self) -> &Ghost<'a> {
+    fn get_ghost(&self) -> &Ghost {
         &self.ghost
     }
 }
@@ -0,0 +1,147 @@
+use crate::assets::{AssetManager, Sprite};
+use crate::board::{BlockType, Direction, EntityType};
+use crate::entity::{BaseEntity, Entity};
+use crate::error::PacmanError;
+use crate::game::rng::GameRng;
+use crate::position::Position;
+use crate::{BLOCK_SIZE_24, BLOCK_SIZE_32, WHITE};
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+/// How long a bonus fruit stays on the board
+const FRUIT_MS: u32 = 9500;
+/// Fruit pictures in the sprite sheet, cherry through key
+const FRUIT_KINDS: usize = 8;
+/// Height offsets of a hop, each shown for `BOUNCE_FRAME_TICKS` updates
+const BOUNCE: [i32; 4] = [0, -3, -5, -3];
+const BOUNCE_FRAME_TICKS: u32 = 6;
+
+/// The bonus fruit, either sitting still or bouncing around the maze
+pub struct Fruit {
+    pub entity: BaseEntity,
+    kind: usize,
+    remaining_ms: u32,
+    wandering: bool,
+    bounce_ticks: u32,
+}
+
+impl Default for Fruit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fruit {
+    /// No fruit on the board
+    pub fn new() -> Self {
+        Fruit {
+            entity: BaseEntity::new(EntityType::None),
+            kind: 0,
+            remaining_ms: 0,
+            wandering: false,
+            bounce_ticks: 0,
+        }
+    }
+
+    /// Put fruit number `kind` down at `position`, where it stays until eaten or gone
+    pub fn place(&mut self, kind: usize, position: Position) {
+        self.show(kind, position, Direction::Nowhere);
+        self.wandering = false;
+    }
+
+    /// Send fruit number `kind` in from a tunnel mouth, wandering the maze from there
+    pub fn enter(&mut self, kind: usize, position: Position, direction: Direction) {
+        self.show(kind, position, direction);
+        self.wandering = true;
+    }
+
+    fn show(&mut self, kind: usize, position: Position, direction: Direction) {
+        self.kind = kind.min(FRUIT_KINDS - 1);
+        self.entity.set_position(position);
+        self.entity.mod_direction(direction);
+        self.remaining_ms = FRUIT_MS;
+        self.bounce_ticks = 0;
+    }
+
+    pub fn remove(&mut self) {
+        self.remaining_ms = 0;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining_ms > 0
+    }
+
+    pub fn update(&mut self, elapsed_ms: u32, actual_map: &[BlockType], rng: &mut GameRng) {
+        if !self.is_active() {
+            return;
+        }
+        self.remaining_ms = self.remaining_ms.saturating_sub(elapsed_ms);
+        if !self.wandering {
+            return;
+        }
+
+        self.bounce_ticks += 1;
+        let block_size = BLOCK_SIZE_24 as i16;
+        let aligned = self.entity.get_x().rem_euclid(block_size) == 0
+            && self.entity.get_y().rem_euclid(block_size) == 0;
+        if aligned {
+            self.choose_direction(actual_map, rng);
+        }
+        self.entity.move_entity(self.entity.get_direction());
+        self.entity.check_wrap();
+    }
+
+    /// Carry on at random at a junction, turning back only at a dead end
+    fn choose_direction(&mut self, actual_map: &[BlockType], rng: &mut GameRng) {
+        let current = self.entity.get_direction();
+        let open: Vec<Direction> = [
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+        ]
+        .into_iter()
+        .filter(|&direction| direction != current.opposite())
+        .filter(|&direction| {
+            let (x, y) = self.entity.get_possible_position(direction);
+            !self.entity.wall_collision(x, y, actual_map, false)
+        })
+        .collect();
+
+        let direction = match open.len() {
+            0 => current.opposite(),
+            1 => open[0],
+            choices => open[rng.next_index(choices, "fruit turn")],
+        };
+        self.entity.mod_direction(direction);
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut WindowCanvas,
+        assets: &mut AssetManager,
+    ) -> Result<(), PacmanError> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let hop = if self.wandering {
+            BOUNCE[(self.bounce_ticks / BOUNCE_FRAME_TICKS) as usize % BOUNCE.len()]
+        } else {
+            0
+        };
+        let clip = Rect::new(
+            self.kind as i32 * BLOCK_SIZE_32 as i32,
+            0,
+            BLOCK_SIZE_32,
+            BLOCK_SIZE_32,
+        );
+        assets.render(
+            canvas,
+            Sprite::Fruit,
+            clip,
+            (self.entity.get_x() - 4) as i32,
+            (self.entity.get_y() - 4) as i32 + hop,
+            WHITE,
+        )
+    }
+}
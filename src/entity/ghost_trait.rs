@@ -1,21 +1,32 @@
 #![allow(dead_code)]
 
+use crate::assets::{AssetManager, Sprite};
+use crate::board::{BlockType, Board, Direction, EntityType};
+use crate::entity::base_entity::VERTICAL_WRAP;
+use crate::entity::pacman::Pacman;
 use crate::entity::Facing;
-use crate::board::{BlockType, Direction, EntityType};
 use crate::entity::{BaseEntity, Entity};
-use crate::entity::pacman::Pacman;
-use crate::position::Position;
-use crate::texture::GameTexture;
-use crate::{BLOCK_SIZE_24, BLOCK_SIZE_32, BLUE, RED, WHITE, WINDOW_WIDTH};
+use crate::error::PacmanError;
+use crate::game::ghost_mode::{GhostEvent, GhostMode};
+use crate::game::pathfind;
+use crate::game::power::{PowerState, WARNING_MS};
+use crate::game::rng::GameRng;
+use crate::game::targeting;
+use crate::game::tile_graph::TileGraph;
+use crate::palette::{GhostPattern, Palette};
+use crate::position::{Position, Tile};
+use crate::theme::Theme;
+use crate::{BLACK, BLOCK_SIZE_24, BLOCK_SIZE_32, RED, SCREEN_WIDTH, TICK_MS, WHITE};
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::WindowCanvas;
+use std::collections::VecDeque;
 
-pub trait GhostBehavior<'a> {
-    #[allow(dead_code)]
+pub trait GhostBehavior {
     fn get_ghost_type(&self) -> GhostType;
-    #[allow(dead_code)]
     fn get_scatter_target(&self) -> Position;
+    /// Which way the ghost faces when a new life starts
+    fn start_facing(&self) -> Direction;
     fn calculate_target(
         &mut self,
         pacman_pos: Position,
@@ -23,98 +34,208 @@ pub trait GhostBehavior<'a> {
         blinky_pos: Option<Position>,
     );
     fn get_can_use_door(&self) -> bool;
-    fn set_can_use_door(&mut self, can_use_door: bool);
-    fn get_ghost_mut(&mut self) -> &mut Ghost<'a>;
-    fn get_ghost(&self) -> &Ghost<'a>;
+    fn get_ghost_mut(&mut self) -> &mut Ghost;
+    fn get_ghost(&self) -> &Ghost;
 
     fn update_pos(
         &mut self,
-        actual_map: &[BlockType],
+        graph: &TileGraph,
         pacman: &Pacman,
         blinky_pos: Option<Position>,
         timed_status: bool,
+        power: &PowerState,
+        rng: &mut GameRng,
     ) {
         let pacman_pos = pacman.get_position();
         let pacman_dir = pacman.get_direction();
+        if self.get_ghost_mut().regenerate() {
+            return;
+        }
 
         let speed = {
             let ghost = self.get_ghost_mut();
-            ghost.update_speed(pacman.is_energized());
-            ghost.update_status(pacman.is_energized(), timed_status);
-            ghost.entity.get_speed()
+            ghost.update_mode(power.is_active(), timed_status);
+            ghost.update_speed();
+            ghost.steps()
         };
 
         for _ in 0..speed {
             let should_calculate = {
                 let ghost = self.get_ghost_mut();
-                ghost.should_calculate_normal_target(pacman.is_energized())
+                ghost.should_calculate_normal_target()
             };
 
             {
                 let ghost = self.get_ghost_mut();
-                ghost.update_facing(pacman.is_energized());
+                ghost.update_facing();
             }
 
             if should_calculate {
-                self.calculate_target(pacman_pos, pacman_dir, blinky_pos);
+                if self.get_ghost().direct_chase {
+                    self.get_ghost_mut().target = pacman_pos;
+                } else {
+                    self.calculate_target(pacman_pos, pacman_dir, blinky_pos);
+                }
             }
 
             {
                 let ghost = self.get_ghost_mut();
-                ghost.calculate_direction(actual_map);
+                // Frightened ghosts wander instead of chasing a target, and so do ghosts told
+                // to scatter at random
+                let reason = match ghost.mode {
+                    GhostMode::Frightened => Some("frightened turn"),
+                    GhostMode::Scatter if ghost.random_scatter => Some("scatter turn"),
+                    _ => None,
+                };
+                if !ghost.steer_eyes(graph.layout()) {
+                    ghost.calculate_direction(graph, reason.map(|reason| (&mut *rng, reason)));
+                }
                 ghost.entity.move_entity(ghost.entity.get_direction());
                 ghost.entity.check_wrap();
+
+                // Eyes that just got home stop there to re-form
+                ghost.update_house_mode(timed_status);
+                if ghost.is_regenerating() {
+                    break;
+                }
             }
         }
     }
+
+    /// Move under a player's control, skipping target calculation entirely.
+    ///
+    /// The ghost turns towards `wanted` as soon as the maze allows it and otherwise keeps
+    /// going until a wall stops it; the door stays shut. Inside the house and on the way
+    /// back as eyes the ghost runs on its usual autopilot.
+    fn update_pos_controlled(
+        &mut self,
+        graph: &TileGraph,
+        pacman: &Pacman,
+        wanted: Direction,
+        timed_status: bool,
+        power: &PowerState,
+        rng: &mut GameRng,
+    ) {
+        let ghost = self.get_ghost();
+        if !ghost.mode.is_alive() || ghost.mode.is_housed() {
+            self.update_pos(graph, pacman, None, timed_status, power, rng);
+            return;
+        }
+
+        let actual_map = graph.layout();
+        let ghost = self.get_ghost_mut();
+        ghost.update_mode(power.is_active(), timed_status);
+        ghost.update_speed();
+
+        for _ in 0..ghost.steps() {
+            if wanted != Direction::Nowhere {
+                let (x, y) = ghost.entity.get_possible_position(wanted);
+                if !ghost.entity.wall_collision(x, y, actual_map, false) {
+                    ghost.entity.mod_direction(wanted);
+                }
+            }
+            ghost.update_facing();
+
+            let direction = ghost.entity.get_direction();
+            let (x, y) = ghost.entity.get_possible_position(direction);
+            if ghost.entity.wall_collision(x, y, actual_map, false) {
+                break;
+            }
+            ghost.entity.move_entity(direction);
+            ghost.entity.check_wrap();
+        }
+    }
+}
+
+pub use crate::game::collision::GhostType;
+
+/// What the tile graph says about a ghost's next move
+enum GraphStep {
+    /// Away from the tile grid, for instance in the doorway: probe the walls
+    OffGrid,
+    /// Between two tiles, carrying straight on
+    Between,
+    /// On a tile with only one way on other than back
+    Bend(Direction),
+    /// On a tile with a real choice to make
+    Intersection(Tile),
 }
 
+/// How a ghost is drawn, derived from its mode and the energizer timer
 #[derive(Debug, Clone, Copy, PartialEq)]
-#[allow(dead_code)]
-pub enum GhostType {
-    Blinky,
-    Pinky,
-    Inky,
-    Clyde,
+pub enum GhostRenderState {
+    /// Body in the ghost's own color
+    Normal,
+    /// Body in the frightened color, flashing white as the energizer runs out
+    Frightened { flashing: bool },
+    /// Eaten ghost heading home: eyes only, never tinted, animating at double speed
+    EyesReturning,
 }
 
-pub const GHOST_BODY_FRAMES: usize = 2;
 pub const GHOST_EYE_FRAMES: usize = 5;
-
-pub struct Ghost<'a> {
+/// Frames of positions a frightened ghost's trail remembers, and how many apart its
+/// afterimages are drawn
+pub const TRAIL_FRAMES: usize = 12;
+const TRAIL_STEP: usize = 3;
+/// Opacity of the afterimage nearest the ghost; the rest fade out from there
+const TRAIL_ALPHA: usize = 120;
+/// How long eyes that made it home take to grow a body back before heading out
+pub const REGENERATE_MS: u32 = 500;
+/// How far below its eyes the body starts as it slides back into place
+const REGENERATE_SLIDE: u32 = 8;
+
+pub struct Ghost {
     pub entity: BaseEntity,
-    pub body: GameTexture<'a>,
-    pub eyes: GameTexture<'a>,
-    pub ghost_body_sprite_clips: [Rect; GHOST_BODY_FRAMES],
+    pub ghost_body_sprite_clips: Vec<Rect>,
     pub ghost_eye_sprite_clips: [Rect; GHOST_EYE_FRAMES],
     pub color: Color,
     pub current_body_frame: u8,
-    pub can_use_door: bool,
-    pub status: bool,
+    /// Where the ghost is in its life, changed only through `transition`
+    mode: GhostMode,
     pub target: Position,
     pub scatter_target: Position,
     pub door_target: Position,
     pub home: Position,
+    /// Scatter by turning at random instead of heading for the corner
+    pub random_scatter: bool,
+    /// Reproduce the arcade overflow that also pushes look-ahead targets left when Pac-Man faces up
+    pub arcade_quirks: bool,
+    /// Chase by aiming straight at Pac-Man's tile as Blinky does, whatever the kind's own aim
+    pub direct_chase: bool,
+    /// Maze marker an extra ghost starts on, in place of its kind's own
+    pub spawn_marker: Option<char>,
+    /// Tiles the targeting reaches: Pinky's lead, Inky's pivot or Clyde's shyness radius
+    pub reach_tiles: u32,
+    /// Speed while alive as a percentage of the usual, set by the difficulty
+    pub speed_percent: u32,
+    /// Hundredths of a pixel owed from earlier updates when `speed_percent` is not 100
+    speed_remainder: u32,
+    /// Frames of trail kept while frightened; 0 turns trails off
+    pub trail_length: usize,
+    /// Where the ghost was drawn on recent frames, newest first; purely cosmetic
+    trail: VecDeque<(i32, i32)>,
+    /// Time left growing a body back at home after being eaten; the ghost holds still meanwhile
+    pub regenerate_ms: u32,
 }
 
-impl<'a> Ghost<'a> {
+impl Ghost {
     pub fn new(
         color: Color,
         identity: EntityType,
         scatter_target: Position,
         home_position: Position,
-        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut body = GameTexture::new();
-        let mut eyes = GameTexture::new();
-
-        body.load_from_file(texture_creator, "assets/GhostBody32.png")?;
-        eyes.load_from_file(texture_creator, "assets/GhostEyes32.png")?;
-
-        let ghost_body_sprite_clips = [
-            Rect::new(0, 0, BLOCK_SIZE_32 as u32, BLOCK_SIZE_32 as u32),
-            Rect::new(BLOCK_SIZE_32 as i32, 0, BLOCK_SIZE_32, BLOCK_SIZE_32),
-        ];
+        theme: &Theme,
+    ) -> Self {
+        let ghost_body_sprite_clips = (0..theme.manifest.ghost_body_frames)
+            .map(|frame| {
+                Rect::new(
+                    frame as i32 * BLOCK_SIZE_32 as i32,
+                    0,
+                    BLOCK_SIZE_32,
+                    BLOCK_SIZE_32,
+                )
+            })
+            .collect();
 
         let ghost_eye_sprite_clips = [
             Rect::new(0, 0, BLOCK_SIZE_32 as u32, BLOCK_SIZE_32 as u32),
@@ -147,16 +268,13 @@ impl<'a> Ghost<'a> {
         let mut entity = BaseEntity::new(identity);
         entity.position = home_position;
 
-        Ok(Ghost {
+        Ghost {
             entity,
-            body,
-            eyes,
             ghost_body_sprite_clips,
             ghost_eye_sprite_clips,
             color,
             current_body_frame: 0,
-            can_use_door: false,
-            status: false,
+            mode: GhostMode::LeavingHouse,
             target: Position::new(0, 0),
             scatter_target,
             door_target: Position::new(
@@ -164,23 +282,17 @@ impl<'a> Ghost<'a> {
                 (15 * BLOCK_SIZE_24) as i16,
             ),
             home: home_position,
-        })
-    }
-
-    pub fn is_in_energized_home_containment(&self, pacman_energized: bool) -> bool {
-        if !pacman_energized || !self.entity.is_alive() {
-            return false;
-        }
-
-        let x = self.entity.position.x;
-        let y = self.entity.position.y;
-
-        if x > (11 * BLOCK_SIZE_24) as i16 && x < (17 * BLOCK_SIZE_24) as i16 {
-            if y > (14 * BLOCK_SIZE_24) as i16 && y < (18 * BLOCK_SIZE_24) as i16 {
-                return true;
-            }
+            random_scatter: false,
+            arcade_quirks: false,
+            direct_chase: false,
+            spawn_marker: None,
+            reach_tiles: 0,
+            speed_percent: 100,
+            speed_remainder: 0,
+            trail_length: 0,
+            trail: VecDeque::new(),
+            regenerate_ms: 0,
         }
-        false
     }
 
     pub fn is_home(&self) -> bool {
@@ -195,98 +307,149 @@ impl<'a> Ghost<'a> {
         false
     }
 
-    pub fn should_calculate_normal_target(&mut self, pacman_energized: bool) -> bool {
-        if !self.entity.is_alive() {
-            self.can_use_door = true;
-            self.target = self.home;
+    pub fn is_regenerating(&self) -> bool {
+        self.regenerate_ms > 0
+    }
 
-            if self.entity.position.x == self.home.x && self.entity.position.y == self.home.y {
-                self.entity.mod_life_statement(true);
-            } else {
-                let dx = (self.entity.position.x - self.home.x).abs();
-                let dy = (self.entity.position.y - self.home.y).abs();
-                if dx <= 2 && dy <= 2 {
-                    self.entity.mod_life_statement(true);
-                    self.entity.set_position(self.home);
-                }
-            }
+    /// Spend a tick growing the body back, returning whether the ghost is still at it.
+    ///
+    /// Once whole it turns up towards the door and leaves the house like any other ghost.
+    pub fn regenerate(&mut self) -> bool {
+        if self.regenerate_ms == 0 {
             return false;
         }
-
-        if self.is_home() && pacman_energized {
-            if self.entity.position.x == self.home.x && self.entity.position.y == self.home.y {
-                self.target.y = self.home.y - BLOCK_SIZE_24 as i16;
-            } else if self.entity.position.x == self.home.x
-                && self.entity.position.y == self.home.y - BLOCK_SIZE_24 as i16
-            {
-                self.target.y = self.home.y;
-            }
-            return false;
+        self.regenerate_ms = self.regenerate_ms.saturating_sub(TICK_MS);
+        if self.regenerate_ms == 0 {
+            self.transition(GhostEvent::Regenerated);
+            self.entity.mod_direction(Direction::Up);
         }
+        true
+    }
 
-        if self.is_home() && self.entity.is_alive() {
-            self.can_use_door = true;
-            self.target = self.door_target;
-            return false;
+    pub fn get_mode(&self) -> GhostMode {
+        self.mode
+    }
+
+    /// Move the mode along for `event`, keeping the entity's life in step with it
+    pub fn transition(&mut self, event: GhostEvent) {
+        self.mode = self.mode.next(event);
+        self.entity.mod_life_statement(self.mode.is_alive());
+    }
+
+    /// Put the ghost in `mode` outright, for a new life or a restored game
+    pub fn set_mode(&mut self, mode: GhostMode) {
+        self.mode = mode;
+        self.entity.mod_life_statement(mode.is_alive());
+        self.regenerate_ms = 0;
+    }
+
+    /// The transitions driven by the energizer and the scatter/chase schedule, once an update
+    pub fn update_mode(&mut self, energized: bool, scatter: bool) {
+        if !energized {
+            self.transition(GhostEvent::EnergizerOver { scatter });
         }
+        self.transition(GhostEvent::Schedule { scatter });
+        self.update_house_mode(scatter);
+    }
 
-        self.can_use_door = false;
-        match self.status {
-            false => true,
-            true => {
-                self.target = self.scatter_target;
-                false
+    /// The transitions driven by where the ghost is: clearing the house, or eyes getting home
+    pub fn update_house_mode(&mut self, scatter: bool) {
+        match self.mode {
+            GhostMode::LeavingHouse if !self.is_home() => {
+                self.transition(GhostEvent::LeftHouse { scatter });
             }
+            GhostMode::Eaten if self.entity.get_position() == self.home => {
+                self.transition(GhostEvent::ReachedHome);
+                self.regenerate_ms = REGENERATE_MS;
+            }
+            _ => {}
         }
     }
 
-    pub fn update_speed(&mut self, pacman_is_energized: bool) {
-        if !self.entity.is_alive() && self.entity.get_speed() != 6 {
-            self.entity.mod_speed(6);
-            return;
+    /// Set the target for the mode, returning true when it is the ghost's own chase target
+    /// and so has to be worked out by `calculate_target`
+    pub fn should_calculate_normal_target(&mut self) -> bool {
+        match self.mode {
+            GhostMode::Eaten => self.target = self.home,
+            GhostMode::InHouse => {
+                // Bob up and down on the spot until let out
+                if self.entity.position.x == self.home.x && self.entity.position.y == self.home.y {
+                    self.target.y = self.home.y - BLOCK_SIZE_24 as i16;
+                } else if self.entity.position.x == self.home.x
+                    && self.entity.position.y == self.home.y - BLOCK_SIZE_24 as i16
+                {
+                    self.target.y = self.home.y;
+                }
+            }
+            GhostMode::LeavingHouse => self.target = self.door_target,
+            GhostMode::Scatter | GhostMode::Frightened => self.target = self.scatter_target,
+            GhostMode::Chase => return true,
         }
+        false
+    }
 
-        if self.is_home() && self.entity.is_alive() {
-            if self.entity.get_speed() != 2 {
-                self.entity.mod_speed(2);
-            }
-            return;
+    /// Tile just above the door, where returning eyes leave their path and drop into the house
+    pub fn house_entrance(&self) -> Tile {
+        Tile::from_position(Position::new(
+            self.door_target.x,
+            self.door_target.y - BLOCK_SIZE_24 as i16,
+        ))
+    }
+
+    /// Steer returning eyes along the shortest path to the house entrance.
+    ///
+    /// Turns are only made on whole tiles; in between the eyes carry on. Returns false for
+    /// living ghosts, and for eyes at the entrance or past it, or with no path there, which
+    /// then head for home the usual way, through the door.
+    pub fn steer_eyes(&mut self, actual_map: &[BlockType]) -> bool {
+        if self.entity.is_alive() || self.is_home() {
+            return false;
+        }
+        let position = self.entity.get_position();
+        let tile = Tile::from_position(position);
+        let entrance = self.house_entrance();
+        let in_doorway = tile.y == entrance.y + 1 && (tile.x - entrance.x).abs() <= 1;
+        if tile == entrance || in_doorway {
+            return false;
         }
 
-        if pacman_is_energized {
-            if self.entity.get_speed() != 1 {
-                self.entity.mod_speed(1);
-            }
-        } else {
-            if self.entity.get_speed() != 2 {
-                self.entity.mod_speed(2);
+        let block = BLOCK_SIZE_24 as i16;
+        if position.x.rem_euclid(block) != 0 || position.y.rem_euclid(block) != 0 {
+            return true;
+        }
+        match pathfind::first_step(actual_map, tile, entrance) {
+            Some(direction) => {
+                self.entity.mod_direction(direction);
+                true
             }
+            None => false,
         }
     }
 
-    pub fn update_status(&mut self, pacman_is_energized: bool, timed_status: bool) {
-        if pacman_is_energized {
-            if !self.status {
-                self.status = true;
-            }
-            return;
+    /// Pixels to move this update: the speed for the ghost's state, scaled by `speed_percent`
+    /// while alive, with any fraction carried over to the next update
+    pub fn steps(&mut self) -> u8 {
+        let speed = self.entity.get_speed();
+        if !self.entity.is_alive() || self.speed_percent == 100 {
+            return speed;
         }
+        let scaled = speed as u32 * self.speed_percent + self.speed_remainder;
+        self.speed_remainder = scaled % 100;
+        (scaled / 100) as u8
+    }
 
-        match timed_status {
-            false => {
-                if self.status {
-                    self.status = false;
-                }
-            }
-            true => {
-                if !self.status {
-                    self.status = true;
-                }
-            }
+    pub fn update_speed(&mut self) {
+        let speed = match self.mode {
+            GhostMode::Eaten => 6,
+            GhostMode::Frightened => 1,
+            _ => 2,
+        };
+        if self.entity.get_speed() != speed {
+            self.entity.mod_speed(speed);
         }
     }
 
-    pub fn update_facing(&mut self, pacman_is_energized: bool) {
+    pub fn update_facing(&mut self) {
         if self.is_home() {
             match self.entity.get_direction() {
                 Direction::Down => self.entity.set_facing(Direction::Down),
@@ -295,139 +458,202 @@ impl<'a> Ghost<'a> {
             return;
         }
 
-        if pacman_is_energized {
-            if !self.entity.is_alive() {
-                self.entity.set_facing(self.entity.get_direction());
-            } else {
-                // Set to scared facing (special case for energized ghosts)
-                self.entity.facing = Facing::Scared;
-            }
+        if self.mode == GhostMode::Frightened {
+            // Set to scared facing (special case for energized ghosts)
+            self.entity.facing = Facing::Scared;
             return;
         }
 
         self.entity.set_facing(self.entity.get_direction());
     }
 
-    pub fn calculate_direction(&mut self, actual_map: &[BlockType]) {
-        let mut distances = Vec::new();
-        let mut possible_directions = Vec::new();
-
-        for i in 0..4 {
-            let direction = match i {
-                0 => Direction::Right,
-                1 => Direction::Up,
-                2 => Direction::Left,
-                3 => Direction::Down,
-                _ => Direction::Right,
-            };
+    /// Pick the next direction; with `wander` set, turn randomly instead of chasing the target.
+    ///
+    /// The reason that comes with the generator labels the draw for replay audits. Out in the
+    /// maze the choice is only made at intersections, from the tile graph: between tiles and
+    /// round bends there is nothing to decide. In the house, through the door, and anywhere
+    /// off the tile grid the ghost probes the walls around it instead.
+    pub fn calculate_direction(&mut self, graph: &TileGraph, wander: Option<(&mut GameRng, &str)>) {
+        let graph_tile = match self.graph_step(graph) {
+            GraphStep::Between => return,
+            GraphStep::Bend(direction) => {
+                self.entity.mod_direction(direction);
+                return;
+            }
+            GraphStep::Intersection(tile) => Some(tile),
+            GraphStep::OffGrid => None,
+        };
 
+        let actual_map = graph.layout();
+        let wraps_vertically = Board::wraps_vertically(actual_map);
+        let mut options = Vec::new();
+
+        for direction in [
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+        ] {
             let (x, y) = self.entity.get_possible_position(direction);
+            let open = match graph_tile {
+                Some(tile) => graph.has_exit(tile, direction),
+                None => !self
+                    .entity
+                    .wall_collision(x, y, actual_map, self.mode.can_use_door()),
+            };
 
-            if !self
-                .entity
-                .wall_collision(x, y, actual_map, self.can_use_door)
-            {
-                let mut dist_x = (x - self.target.get_x()).abs() as f32;
-                if dist_x > (WINDOW_WIDTH / 2) as f32 {
-                    dist_x = WINDOW_WIDTH as f32 - dist_x;
+            if open {
+                let mut dist_x = (x - self.target.get_x()).abs() as i32;
+                if dist_x > (SCREEN_WIDTH / 2) as i32 {
+                    dist_x = SCREEN_WIDTH as i32 - dist_x;
                 }
-                let dist = (dist_x.powi(2) + ((y - self.target.get_y()) as f32).powi(2)).sqrt();
-                distances.push(dist);
-                possible_directions.push(i);
+                let mut dist_y = (y - self.target.get_y()).abs() as i32;
+                if wraps_vertically && dist_y > (VERTICAL_WRAP / 2) as i32 {
+                    dist_y = VERTICAL_WRAP as i32 - dist_y;
+                }
+                options.push((direction, dist_x * dist_x + dist_y * dist_y));
             }
         }
 
-        if possible_directions.len() == 1 {
-            let direction = match possible_directions[0] {
-                0 => Direction::Right,
-                1 => Direction::Up,
-                2 => Direction::Left,
-                3 => Direction::Down,
-                _ => Direction::Right,
-            };
-            self.entity.mod_direction(direction);
+        if options.len() == 1 {
+            self.entity.mod_direction(options[0].0);
             return;
         }
 
-        for i in 0..distances.len() {
-            for j in 0..distances.len() {
-                if distances[i] < distances[j] {
-                    distances.swap(i, j);
-                    possible_directions.swap(i, j);
-                }
+        if let Some((rng, reason)) = wander {
+            let reverse = self.entity.get_direction().opposite();
+            let forward_options: Vec<Direction> = options
+                .iter()
+                .map(|&(direction, _)| direction)
+                .filter(|&direction| direction != reverse)
+                .collect();
+            if !forward_options.is_empty() {
+                let purpose = match self.entity.get_identity() {
+                    EntityType::Blinky => format!("blinky {}", reason),
+                    EntityType::Inky => format!("inky {}", reason),
+                    EntityType::Pinky => format!("pinky {}", reason),
+                    EntityType::Clyde => format!("clyde {}", reason),
+                    _ => reason.to_string(),
+                };
+                let choice = rng.next_index(forward_options.len(), &purpose);
+                self.entity.mod_direction(forward_options[choice]);
+                return;
             }
         }
 
-        let current_numeric_dir = match self.entity.get_direction() {
-            Direction::Right => 0,
-            Direction::Up => 1,
-            Direction::Left => 2,
-            Direction::Down => 3,
-            Direction::Nowhere => 0,
+        if let Some(direction) = targeting::choose_direction(&options, self.entity.get_direction())
+        {
+            self.entity.mod_direction(direction);
+        }
+    }
+
+    /// Where the ghost stands on the tile graph, which only living ghosts with the door shut use
+    fn graph_step(&self, graph: &TileGraph) -> GraphStep {
+        if self.mode.can_use_door() {
+            return GraphStep::OffGrid;
+        }
+        let position = self.entity.get_position();
+        let direction = self.entity.get_direction();
+        let block = BLOCK_SIZE_24 as i16;
+        let (along, across) = match direction {
+            Direction::Left | Direction::Right => (position.x, position.y),
+            Direction::Up | Direction::Down => (position.y, position.x),
+            Direction::Nowhere => return GraphStep::OffGrid,
         };
+        let tile = Tile::from_position(position);
+        if across.rem_euclid(block) != 0 || !graph.is_open(tile) {
+            return GraphStep::OffGrid;
+        }
+        if along.rem_euclid(block) != 0 {
+            return GraphStep::Between;
+        }
+        if graph.is_intersection(tile) {
+            return GraphStep::Intersection(tile);
+        }
 
-        for &numeric_dir in &possible_directions {
-            if numeric_dir != (current_numeric_dir + 2) % 4 {
-                let direction = match numeric_dir {
-                    0 => Direction::Right,
-                    1 => Direction::Up,
-                    2 => Direction::Left,
-                    3 => Direction::Down,
-                    _ => Direction::Right,
-                };
-                self.entity.mod_direction(direction);
-                return;
-            }
+        let ways_on: Vec<Direction> = [
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+        ]
+        .into_iter()
+        .filter(|&way| way != direction.opposite() && graph.has_exit(tile, way))
+        .collect();
+        match ways_on[..] {
+            [way] => GraphStep::Bend(way),
+            // A dead end, or arrived from a closed side: let the full decision sort it out
+            _ => GraphStep::Intersection(tile),
         }
+    }
 
-        if !possible_directions.is_empty() {
-            let direction = match possible_directions[0] {
-                0 => Direction::Right,
-                1 => Direction::Up,
-                2 => Direction::Left,
-                3 => Direction::Down,
-                _ => Direction::Right,
-            };
-            self.entity.mod_direction(direction);
+    pub fn get_render_state(&self, power: &PowerState) -> GhostRenderState {
+        match self.mode {
+            GhostMode::Eaten => GhostRenderState::EyesReturning,
+            GhostMode::Frightened => GhostRenderState::Frightened {
+                flashing: power.is_ending(WARNING_MS) && (power.get_remaining_ms() / 250) % 2 == 1,
+            },
+            _ => GhostRenderState::Normal,
         }
     }
 
     pub fn draw(
         &mut self,
         canvas: &mut WindowCanvas,
-        pacman_is_energized: bool,
-        ghost_timer_ticks: u128,
-        ghost_timer_target: u32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        assets: &mut AssetManager,
+        power: &PowerState,
+        palette: Palette,
+        show_pattern: bool,
+        frozen: bool,
+    ) -> Result<(), PacmanError> {
         let x = (self.entity.get_x() - 4) as i32;
         let y = (self.entity.get_y() - 4) as i32;
+        let render_state = self.get_render_state(power);
 
-        if pacman_is_energized
-            && self.entity.is_alive()
-            && !self.is_in_energized_home_containment(pacman_is_energized)
-        {
-            self.body.set_color(BLUE.r, BLUE.g, BLUE.b)?;
+        let body_color = match render_state {
+            GhostRenderState::Normal => {
+                Some(palette.ghost_color(self.entity.get_identity(), self.color))
+            }
+            GhostRenderState::Frightened { flashing: false } => Some(palette.frightened_color()),
+            GhostRenderState::Frightened { flashing: true } => Some(WHITE),
+            GhostRenderState::EyesReturning => None,
+        };
+        let eye_color = match render_state {
+            GhostRenderState::Frightened { flashing: true } => RED,
+            _ => WHITE,
+        };
 
-            if ghost_timer_ticks > (ghost_timer_target as u128 - 2000) {
-                if (ghost_timer_ticks / 250) % 2 == 1 {
-                    self.body.set_color(WHITE.r, WHITE.g, WHITE.b)?;
-                    self.eyes.set_color(RED.r, RED.g, RED.b)?;
-                } else {
-                    self.eyes.set_color(WHITE.r, WHITE.g, WHITE.b)?;
-                }
+        let frightened = matches!(render_state, GhostRenderState::Frightened { .. });
+        if !frightened || self.trail_length == 0 || !assets.can_fade() {
+            self.trail.clear();
+        } else if !frozen {
+            self.trail.push_front((x, y));
+            self.trail.truncate(self.trail_length);
+        }
+
+        if let Some(color) = body_color {
+            let body_clip = self.ghost_body_sprite_clips
+                [(self.current_body_frame / 8) as usize % self.ghost_body_sprite_clips.len()];
+            self.draw_trail(canvas, assets, body_clip, color)?;
+            if self.is_regenerating() && assets.can_fade() {
+                // Fade and slide the body up into place under the eyes
+                let grown = REGENERATE_MS - self.regenerate_ms;
+                let slide = (REGENERATE_SLIDE * self.regenerate_ms / REGENERATE_MS) as i32;
+                assets.set_alpha((255 * grown / REGENERATE_MS) as u8)?;
+                assets.render(canvas, Sprite::GhostBody, body_clip, x, y + slide, color)?;
+                assets.set_alpha(255)?;
             } else {
-                self.eyes.set_color(WHITE.r, WHITE.g, WHITE.b)?;
+                assets.render(canvas, Sprite::GhostBody, body_clip, x, y, color)?;
             }
-        } else {
-            self.body
-                .set_color(self.color.r, self.color.g, self.color.b)?;
-            self.eyes.set_color(WHITE.r, WHITE.g, WHITE.b)?;
-        }
 
-        if self.entity.is_alive() {
-            let body_clip = &self.ghost_body_sprite_clips
-                [(self.current_body_frame / 8) as usize % GHOST_BODY_FRAMES];
-            self.body.render(canvas, x, y, Some(*body_clip))?;
+            if show_pattern && render_state == GhostRenderState::Normal && !self.is_regenerating() {
+                Self::draw_pattern(
+                    canvas,
+                    x,
+                    y,
+                    Palette::ghost_pattern(self.entity.get_identity()),
+                )?;
+            }
         }
 
         let eye_frame = self.entity.get_facing().as_u8() as usize;
@@ -436,10 +662,72 @@ impl<'a> Ghost<'a> {
         } else {
             eye_frame
         };
-        let eye_clip = &self.ghost_eye_sprite_clips[eye_frame];
-        self.eyes.render(canvas, x, y, Some(*eye_clip))?;
+        let eye_clip = self.ghost_eye_sprite_clips[eye_frame];
+        assets.render(canvas, Sprite::GhostEyes, eye_clip, x, y, eye_color)?;
 
-        self.current_body_frame = (self.current_body_frame + 1) % (GHOST_BODY_FRAMES as u8 * 8);
+        let frame_step = match render_state {
+            _ if frozen => 0,
+            GhostRenderState::EyesReturning => 2,
+            _ => 1,
+        };
+        self.current_body_frame =
+            (self.current_body_frame + frame_step) % (self.ghost_body_sprite_clips.len() as u8 * 8);
+        Ok(())
+    }
+
+    /// Afterimages of the body along the trail, fainter the older they are
+    fn draw_trail(
+        &self,
+        canvas: &mut WindowCanvas,
+        assets: &mut AssetManager,
+        body_clip: Rect,
+        color: Color,
+    ) -> Result<(), PacmanError> {
+        let afterimages = self.trail.len() / TRAIL_STEP;
+        for image in (1..=afterimages).rev() {
+            let (x, y) = self.trail[image * TRAIL_STEP - 1];
+            let alpha = TRAIL_ALPHA * (afterimages + 1 - image) / (afterimages + 1);
+            assets.set_alpha(alpha as u8)?;
+            assets.render(canvas, Sprite::GhostBody, body_clip, x, y, color)?;
+        }
+        assets.set_alpha(255)
+    }
+
+    /// Overlay a pattern on the lower half of the body, below the eyes
+    fn draw_pattern(
+        canvas: &mut WindowCanvas,
+        x: i32,
+        y: i32,
+        pattern: GhostPattern,
+    ) -> Result<(), PacmanError> {
+        let mut marks = Vec::new();
+        match pattern {
+            GhostPattern::Plain => {}
+            GhostPattern::Dots => {
+                for col in 0..3 {
+                    marks.push(Rect::new(x + 8 + col * 7, y + 20, 3, 3));
+                }
+            }
+            GhostPattern::Stripes => {
+                for row in 0..2 {
+                    marks.push(Rect::new(x + 6, y + 18 + row * 5, 20, 2));
+                }
+            }
+            GhostPattern::Checker => {
+                for row in 0..2 {
+                    for col in 0..4 {
+                        if (row + col) % 2 == 0 {
+                            marks.push(Rect::new(x + 6 + col * 5, y + 18 + row * 4, 4, 4));
+                        }
+                    }
+                }
+            }
+        }
+
+        if !marks.is_empty() {
+            canvas.set_draw_color(BLACK);
+            canvas.fill_rects(&marks).map_err(PacmanError::Render)?;
+        }
         Ok(())
     }
 }
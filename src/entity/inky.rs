@@ -1,16 +1,15 @@
 use crate::board::{Direction, EntityType};
 use crate::entity::{Entity, Ghost, GhostBehavior, GhostType};
 use crate::position::Position;
+use crate::theme::Theme;
 use crate::{BLOCK_SIZE_24, CYAN};
 
-pub struct Inky<'a> {
-    ghost: Ghost<'a>,
+pub struct Inky {
+    ghost: Ghost,
 }
 
-impl<'a> Inky<'a> {
-    pub fn new(
-        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+impl Inky {
+    pub fn new(theme: &Theme) -> Self {
         let scatter_target = Position::new(
             (26 * BLOCK_SIZE_24 + BLOCK_SIZE_24 / 2) as i16,
             (35 * BLOCK_SIZE_24 + BLOCK_SIZE_24 / 2) as i16,
@@ -25,24 +24,16 @@ impl<'a> Inky<'a> {
             EntityType::Inky,
             scatter_target,
             home_position,
-            texture_creator,
-        )?;
+            theme,
+        );
 
+        ghost.reach_tiles = 2;
         ghost.entity.set_facing(Direction::Up);
-        Ok(Inky { ghost })
-    }
-
-    pub fn get_ghost_mut(&mut self) -> &mut Ghost<'a> {
-        &mut self.ghost
-    }
-
-    #[allow(dead_code)]
-    pub fn get_ghost(&self) -> &Ghost<'a> {
-        &self.ghost
+        Inky { ghost }
     }
 }
 
-impl<'a> GhostBehavior<'a> for Inky<'a> {
+impl GhostBehavior for Inky {
     fn get_ghost_type(&self) -> GhostType {
         GhostType::Inky
     }
@@ -51,6 +42,10 @@ impl<'a> GhostBehavior<'a> for Inky<'a> {
         self.ghost.scatter_target
     }
 
+    fn start_facing(&self) -> Direction {
+        Direction::Up
+    }
+
     fn calculate_target(
         &mut self,
         pacman_pos: Position,
@@ -58,13 +53,16 @@ impl<'a> GhostBehavior<'a> for Inky<'a> {
         blinky_pos: Option<Position>,
     ) {
         if let Some(blinky_position) = blinky_pos {
-            let offset = BLOCK_SIZE_24 * 2;
+            let offset = BLOCK_SIZE_24 * self.ghost.reach_tiles;
 
             let intermediate_pos = match pacman_dir {
-                Direction::Up => Position::new(
+                Direction::Up if self.ghost.arcade_quirks => Position::new(
                     pacman_pos.get_x() - offset as i16,
                     pacman_pos.get_y() - offset as i16,
                 ),
+                Direction::Up => {
+                    Position::new(pacman_pos.get_x(), pacman_pos.get_y() - offset as i16)
+                }
                 Direction::Down => {
                     Position::new(pacman_pos.get_x(), pacman_pos.get_y() + offset as i16)
                 }
@@ -87,22 +85,15 @@ impl<'a> GhostBehavior<'a> for Inky<'a> {
         }
     }
 
-    #[allow(dead_code)]
     fn get_can_use_door(&self) -> bool {
-        self.ghost.can_use_door
-    }
-
-    #[allow(dead_code)]
-    fn set_can_use_door(&mut self, can_use_door: bool) {
-        self.ghost.can_use_door = can_use_door;
+        self.ghost.get_mode().can_use_door()
     }
 
-    fn get_ghost_mut(&mut self) -> &mut Ghost<'a> {
+    fn get_ghost_mut(&mut self) -> &mut Ghost {
         &mut self.ghost
     }
 
-    #[allow(dead_code)]
-    fn get_ghost(&self) -> &Ghost<'a> {
+    fn get_ghost(&self) -> &Ghost {
         &self.ghost
     }
 }
@@ -1,6 +1,7 @@
 pub mod base_entity;
 pub mod blinky;
 pub mod clyde;
+pub mod fruit;
 pub mod ghost_trait;
 pub mod inky;
 pub mod pacman;
@@ -9,6 +10,7 @@ pub mod pinky;
 pub use base_entity::{BaseEntity, Entity, Facing};
 pub use blinky::Blinky;
 pub use clyde::Clyde;
+pub use fruit::Fruit;
 pub use ghost_trait::*;
 pub use inky::Inky;
 pub use pinky::Pinky;
@@ -1,106 +1,109 @@
-use crate::entity::Facing;
+use crate::assets::{AssetManager, Sprite};
 use crate::board::{BlockType, Direction, EntityType};
+use crate::entity::Facing;
 use crate::entity::{BaseEntity, Entity};
+use crate::error::PacmanError;
+use crate::game::collision::FoodCollisionEvent;
+use crate::game::input::InputQueue;
 use crate::position::Position;
-use crate::texture::GameTexture;
+use crate::theme::Theme;
 use crate::{BLOCK_SIZE_24, BLOCK_SIZE_32, BOARD_WIDTH};
 use sdl2::rect::Rect;
-use sdl2::render::{TextureCreator, WindowCanvas};
-use sdl2::video::WindowContext;
+use sdl2::render::WindowCanvas;
 
-const LIVING_PAC_FRAMES: usize = 3;
-const DEATH_PAC_FRAMES: usize = 10;
+/// Update ticks each animation frame stays on screen
+const LIVING_FRAME_TICKS: u8 = 12;
+const DEATH_FRAME_TICKS: u8 = 10;
+/// Updates the arcade holds Pac-Man still after he eats a pellet or an energizer
+const PELLET_STALL_TICKS: u8 = 1;
+const ENERGIZER_STALL_TICKS: u8 = 3;
 
-pub struct Pacman<'a> {
+pub struct Pacman {
     pub entity: BaseEntity,
 
-    living_pac: GameTexture<'a>,
-    death_pac: GameTexture<'a>,
-
-    living_pac_sprite_clips: [Rect; LIVING_PAC_FRAMES],
-    death_pac_sprite_clips: [Rect; DEATH_PAC_FRAMES],
+    living_pac_sprite_clips: Vec<Rect>,
+    death_pac_sprite_clips: Vec<Rect>,
 
     curr_living_pac_frame: u8,
     curr_death_pac_frame: u8,
 
-    energy_status: bool,
     dead_animation_statement: bool,
+    /// Walls are ignored, a debug console cheat
+    noclip: bool,
+    /// Updates left standing still to eat, when arcade stalls are on
+    stall_ticks: u8,
 }
 
-impl<'a> Pacman<'a> {
-    pub fn new(
-        texture_creator: &'a TextureCreator<WindowContext>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+impl Pacman {
+    /// Frames are cut from the `PacMan` and `PacManDeath` sheets of the atlas
+    pub fn new(theme: &Theme) -> Self {
         let mut pacman = Pacman {
             entity: BaseEntity::new(EntityType::PacMan),
-            living_pac: GameTexture::new(),
-            death_pac: GameTexture::new(),
-            living_pac_sprite_clips: [Rect::new(0, 0, 0, 0); LIVING_PAC_FRAMES],
-            death_pac_sprite_clips: [Rect::new(0, 0, 0, 0); DEATH_PAC_FRAMES],
+            living_pac_sprite_clips: Vec::new(),
+            death_pac_sprite_clips: Vec::new(),
             curr_living_pac_frame: 0,
             curr_death_pac_frame: 0,
-            energy_status: false,
             dead_animation_statement: false,
+            noclip: false,
+            stall_ticks: 0,
         };
 
-        pacman
-            .living_pac
-            .load_from_file(texture_creator, "assets/PacMan32.png")?;
-        pacman
-            .death_pac
-            .load_from_file(texture_creator, "assets/GameOver32.png")?;
-
-        pacman.init_frames();
+        pacman.init_frames(theme.manifest.pacman_frames, theme.manifest.death_frames);
 
-        Ok(pacman)
+        pacman
     }
 
-    fn init_frames(&mut self) {
+    fn init_frames(&mut self, living_frames: usize, death_frames: usize) {
         let mut counter = 0;
-        for i in 0..LIVING_PAC_FRAMES {
-            self.living_pac_sprite_clips[i] = Rect::new(counter, 0, BLOCK_SIZE_32, BLOCK_SIZE_32);
+        for _ in 0..living_frames {
+            self.living_pac_sprite_clips
+                .push(Rect::new(counter, 0, BLOCK_SIZE_32, BLOCK_SIZE_32));
             counter += BLOCK_SIZE_32 as i32;
         }
 
         counter = 0;
-        for i in 0..DEATH_PAC_FRAMES {
-            self.death_pac_sprite_clips[i] = Rect::new(counter, 0, BLOCK_SIZE_32, BLOCK_SIZE_32);
+        for _ in 0..death_frames {
+            self.death_pac_sprite_clips
+                .push(Rect::new(counter, 0, BLOCK_SIZE_32, BLOCK_SIZE_32));
             counter += BLOCK_SIZE_32 as i32;
         }
     }
 
-    pub fn update_pos(&mut self, mover: &mut Vec<Direction>, actual_map: &[BlockType]) {
-        if mover.is_empty() {
+    fn living_frame_count(&self) -> u8 {
+        self.living_pac_sprite_clips.len() as u8
+    }
+
+    fn death_frame_count(&self) -> u8 {
+        self.death_pac_sprite_clips.len() as u8
+    }
+
+    pub fn update_pos(&mut self, input: &mut InputQueue, actual_map: &[BlockType]) {
+        if self.stall_ticks > 0 {
+            self.stall_ticks -= 1;
             return;
         }
-
         for _ in 0..self.entity.get_speed() {
-            let (temp_x, temp_y) = self.entity.get_possible_position(mover[0]);
+            let current = input.current();
+            let (temp_x, temp_y) = self.entity.get_possible_position(current);
 
-            if !self
-                .entity
-                .wall_collision(temp_x, temp_y, actual_map, false)
-            {
+            if !self.is_blocked(temp_x, temp_y, actual_map) {
                 self.update_current_living_pac_frame();
-                self.entity.move_entity(mover[0]);
-                self.set_facing(mover[0]);
-                self.entity.mod_direction(mover[0]);
+                self.entity.move_entity(current);
+                self.set_facing(current);
+                self.entity.mod_direction(current);
             } else {
                 self.wall_collision_frame();
             }
 
-            if mover.len() > 1 && mover[0] != mover[1] {
-                let (temp_x, temp_y) = self.entity.get_possible_position(mover[1]);
+            if let Some(turn) = input.queued() {
+                let (temp_x, temp_y) = self.entity.get_possible_position(turn);
 
-                if !self
-                    .entity
-                    .wall_collision(temp_x, temp_y, actual_map, false)
-                {
+                if !self.is_blocked(temp_x, temp_y, actual_map) {
                     self.update_current_living_pac_frame();
-                    self.entity.move_entity(mover[1]);
-                    self.set_facing(mover[1]);
-                    self.entity.mod_direction(mover[1]);
-                    mover.remove(0);
+                    self.entity.move_entity(turn);
+                    self.set_facing(turn);
+                    self.entity.mod_direction(turn);
+                    input.take_turn();
                 }
             }
 
@@ -108,7 +111,30 @@ impl<'a> Pacman<'a> {
         }
     }
 
-    pub fn food_collision(&self, actual_map: &mut [BlockType]) -> u8 {
+    fn is_blocked(&self, x: i16, y: i16, actual_map: &[BlockType]) -> bool {
+        !self.noclip && self.entity.wall_collision(x, y, actual_map, false)
+    }
+
+    pub fn set_noclip(&mut self, enabled: bool) {
+        self.noclip = enabled;
+    }
+
+    pub fn is_noclip(&self) -> bool {
+        self.noclip
+    }
+
+    /// Stop for as long as the arcade takes to eat `food`, which lets ghosts catch up in a
+    /// maze full of dots
+    pub fn stall(&mut self, food: FoodCollisionEvent) {
+        self.stall_ticks = match food {
+            FoodCollisionEvent::Pellet => PELLET_STALL_TICKS,
+            FoodCollisionEvent::Energizer => ENERGIZER_STALL_TICKS,
+            FoodCollisionEvent::Nothing => return,
+        };
+    }
+
+    /// Eat the first pellet or energizer Pac-Man overlaps, clearing it from the map
+    pub fn food_collision(&self, actual_map: &mut [BlockType]) -> FoodCollisionEvent {
         let cell_x = self.entity.get_x() as f32 / BLOCK_SIZE_24 as f32;
         let cell_y = self.entity.get_y() as f32 / BLOCK_SIZE_24 as f32;
 
@@ -121,38 +147,26 @@ impl<'a> Pacman<'a> {
                 let index = BOARD_WIDTH * board_y + board_x;
 
                 if index < actual_map.len() {
-                    match actual_map[index] {
-                        BlockType::Pellet => {
-                            actual_map[index] = BlockType::Nothing;
-                            return 0;
-                        }
-                        BlockType::Energizer => {
-                            actual_map[index] = BlockType::Nothing;
-                            return 1;
-                        }
-                        _ => {}
-                    }
+                    let eaten = match actual_map[index] {
+                        BlockType::Pellet => FoodCollisionEvent::Pellet,
+                        BlockType::Energizer => FoodCollisionEvent::Energizer,
+                        _ => continue,
+                    };
+                    actual_map[index] = BlockType::Nothing;
+                    return eaten;
                 }
             }
         }
-        2
-    }
-
-    pub fn is_energized(&self) -> bool {
-        self.energy_status
-    }
-
-    pub fn change_energy_status(&mut self, new_energy_status: bool) {
-        self.energy_status = new_energy_status;
+        FoodCollisionEvent::Nothing
     }
 
     fn set_facing(&mut self, mover: Direction) {
         // Pacman has different facing mapping than ghosts
         self.entity.facing = match mover {
             Direction::Right => Facing::Right,
-            Direction::Up => Facing::Down,    // Pacman up sprite is index 3
+            Direction::Up => Facing::Down, // Pacman up sprite is index 3
             Direction::Left => Facing::Left,
-            Direction::Down => Facing::Up,    // Pacman down sprite is index 1
+            Direction::Down => Facing::Up, // Pacman down sprite is index 1
             Direction::Nowhere => self.entity.facing,
         };
     }
@@ -167,17 +181,31 @@ impl<'a> Pacman<'a> {
 
     fn update_current_living_pac_frame(&mut self) {
         self.curr_living_pac_frame += 1;
-        if self.curr_living_pac_frame / ((LIVING_PAC_FRAMES * 4) as u8) >= LIVING_PAC_FRAMES as u8 {
+        if self.curr_living_pac_frame / LIVING_FRAME_TICKS >= self.living_frame_count() {
             self.curr_living_pac_frame = 0;
         }
     }
 
+    /// Step the death animation, flagging its end once every frame has been shown
+    pub fn update_death_animation(&mut self) {
+        if self.entity.is_alive() || self.dead_animation_statement {
+            return;
+        }
+
+        self.curr_death_pac_frame += 1;
+        if self.curr_death_pac_frame >= self.death_frame_count() * DEATH_FRAME_TICKS {
+            self.dead_animation_statement = true;
+            self.curr_death_pac_frame = 0;
+        }
+    }
+
     pub fn reset_current_living_frame(&mut self) {
         self.curr_living_pac_frame = 0;
     }
 
     fn wall_collision_frame(&mut self) {
-        self.curr_living_pac_frame = 32;
+        // Hold the last (widest open) frame while blocked
+        self.curr_living_pac_frame = self.living_frame_count() * LIVING_FRAME_TICKS - 4;
     }
 
     pub fn is_alive(&self) -> bool {
@@ -200,12 +228,16 @@ impl<'a> Pacman<'a> {
         self.entity.set_position(position);
     }
 
-    #[allow(dead_code)]
+    /// Point Pacman in a direction without moving, e.g. when loading a saved game
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.entity.mod_direction(direction);
+        self.set_facing(direction);
+    }
+
     pub fn get_x(&self) -> i16 {
         self.entity.get_x()
     }
 
-    #[allow(dead_code)]
     pub fn get_y(&self) -> i16 {
         self.entity.get_y()
     }
@@ -214,37 +246,54 @@ impl<'a> Pacman<'a> {
         self.entity.is_colliding(other)
     }
 
-    pub fn draw(&mut self, canvas: &mut WindowCanvas) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn draw(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        assets: &mut AssetManager,
+    ) -> Result<(), PacmanError> {
         if self.entity.is_alive() {
-            let current_clip = &self.living_pac_sprite_clips
-                [(self.curr_living_pac_frame / ((LIVING_PAC_FRAMES * 4) as u8)) as usize];
-
-            self.living_pac.render_with_facing(
-                canvas,
-                (self.entity.get_x() - 4) as i32,
-                (self.entity.get_y() - 4) as i32,
-                self.entity.get_facing().as_u8(),
-                Some(*current_clip),
-            )?;
+            self.draw_living(canvas, assets)
         } else {
-            let current_clip = &self.death_pac_sprite_clips
-                [(self.curr_death_pac_frame / DEATH_PAC_FRAMES as u8) as usize];
-
-            self.death_pac.render_with_facing(
-                canvas,
-                (self.entity.get_x() - 4) as i32,
-                (self.entity.get_y() - 4) as i32,
-                self.entity.get_facing().as_u8(),
-                Some(*current_clip),
-            )?;
-
-            self.curr_death_pac_frame += 1;
-            if self.curr_death_pac_frame >= (DEATH_PAC_FRAMES * DEATH_PAC_FRAMES) as u8 {
-                self.dead_animation_statement = true;
-                self.curr_death_pac_frame = 0;
-            }
+            self.draw_dying(canvas, assets)
         }
+    }
+
+    /// Draw the current living frame even if Pacman was just caught
+    pub fn draw_living(
+        &self,
+        canvas: &mut WindowCanvas,
+        assets: &mut AssetManager,
+    ) -> Result<(), PacmanError> {
+        let current_clip = self.living_pac_sprite_clips
+            [(self.curr_living_pac_frame / LIVING_FRAME_TICKS) as usize];
+
+        assets.render_with_facing(
+            canvas,
+            Sprite::PacMan,
+            current_clip,
+            (self.entity.get_x() - 4) as i32,
+            (self.entity.get_y() - 4) as i32,
+            self.entity.get_facing().as_u8(),
+        )?;
+        Ok(())
+    }
 
+    fn draw_dying(
+        &self,
+        canvas: &mut WindowCanvas,
+        assets: &mut AssetManager,
+    ) -> Result<(), PacmanError> {
+        let current_clip =
+            self.death_pac_sprite_clips[(self.curr_death_pac_frame / DEATH_FRAME_TICKS) as usize];
+
+        assets.render_with_facing(
+            canvas,
+            Sprite::PacManDeath,
+            current_clip,
+            (self.entity.get_x() - 4) as i32,
+            (self.entity.get_y() - 4) as i32,
+            self.entity.get_facing().as_u8(),
+        )?;
         Ok(())
     }
 }
@@ -1,16 +1,15 @@
 use crate::board::{Direction, EntityType};
 use crate::entity::{Entity, Ghost, GhostBehavior, GhostType};
 use crate::position::Position;
+use crate::theme::Theme;
 use crate::{BLOCK_SIZE_24, PINK};
 
-pub struct Pinky<'a> {
-    ghost: Ghost<'a>,
+pub struct Pinky {
+    ghost: Ghost,
 }
 
-impl<'a> Pinky<'a> {
-    pub fn new(
-        texture_creator: &'a sdl2::render::TextureCreator<sdl2::video::WindowContext>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+impl Pinky {
+    pub fn new(theme: &Theme) -> Self {
         let scatter_target = Position::new(
             (2 * BLOCK_SIZE_24 + BLOCK_SIZE_24 / 2) as i16,
             (BLOCK_SIZE_24 / 2) as i16,
@@ -25,24 +24,16 @@ impl<'a> Pinky<'a> {
             EntityType::Pinky,
             scatter_target,
             home_position,
-            texture_creator,
-        )?;
+            theme,
+        );
 
+        ghost.reach_tiles = 4;
         ghost.entity.set_facing(Direction::Down);
-        Ok(Pinky { ghost })
-    }
-
-    pub fn get_ghost_mut(&mut self) -> &mut Ghost<'a> {
-        &mut self.ghost
-    }
-
-    #[allow(dead_code)]
-    pub fn get_ghost(&self) -> &Ghost<'a> {
-        &self.ghost
+        Pinky { ghost }
     }
 }
 
-impl<'a> GhostBehavior<'a> for Pinky<'a> {
+impl GhostBehavior for Pinky {
     fn get_ghost_type(&self) -> GhostType {
         GhostType::Pinky
     }
@@ -51,15 +42,23 @@ impl<'a> GhostBehavior<'a> for Pinky<'a> {
         self.ghost.scatter_target
     }
 
+    fn start_facing(&self) -> Direction {
+        Direction::Down
+    }
+
     fn calculate_target(
         &mut self,
         pacman_pos: Position,
         pacman_dir: Direction,
         _blinky_pos: Option<Position>,
     ) {
-        let offset = BLOCK_SIZE_24 * 4;
+        let offset = BLOCK_SIZE_24 * self.ghost.reach_tiles;
 
         let target_pos = match pacman_dir {
+            Direction::Up if self.ghost.arcade_quirks => Position::new(
+                pacman_pos.get_x() - offset as i16,
+                pacman_pos.get_y() - offset as i16,
+            ),
             Direction::Up => Position::new(pacman_pos.get_x(), pacman_pos.get_y() - offset as i16),
             Direction::Down => {
                 Position::new(pacman_pos.get_x(), pacman_pos.get_y() + offset as i16)
@@ -76,22 +75,15 @@ impl<'a> GhostBehavior<'a> for Pinky<'a> {
         self.ghost.target = target_pos;
     }
 
-    #[allow(dead_code)]
     fn get_can_use_door(&self) -> bool {
-        self.ghost.can_use_door
-    }
-
-    #[allow(dead_code)]
-    fn set_can_use_door(&mut self, can_use_door: bool) {
-        self.ghost.can_use_door = can_use_door;
+        self.ghost.get_mode().can_use_door()
     }
 
-    fn get_ghost_mut(&mut self) -> &mut Ghost<'a> {
+    fn get_ghost_mut(&mut self) -> &mut Ghost {
         &mut self.ghost
     }
 
-    #[allow(dead_code)]
-    fn get_ghost(&self) -> &Ghost<'a> {
+    fn get_ghost(&self) -> &Ghost {
         &self.ghost
     }
 }
@@ -0,0 +1,27 @@
+/// What went wrong while starting up, loading, drawing or saving.
+///
+/// Each kind is its own variant so callers can react to, say, a bad maze file differently
+/// from a missing image; the text says which file or option was at fault.
+#[derive(Debug, thiserror::Error)]
+pub enum PacmanError {
+    /// An image or font that is missing or could not be turned into a texture
+    #[error("{0}")]
+    AssetLoad(String),
+    /// SDL or one of its libraries would not start
+    #[error("SDL initialization failed: {0}")]
+    SdlInit(String),
+    /// SDL refused to draw to the window
+    #[error("Rendering failed: {0}")]
+    Render(String),
+    /// A maze file that is unreadable or not a valid board
+    #[error("{0}")]
+    MapParse(String),
+    /// A command-line option that is unknown, malformed or clashes with another
+    #[error("{0}")]
+    ConfigParse(String),
+    #[error("Unable to save {path}: {source}")]
+    SaveIo {
+        path: String,
+        source: std::io::Error,
+    },
+}
@@ -0,0 +1,245 @@
+use crate::error::PacmanError;
+use crate::theme::Theme;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::surface::Surface;
+use std::cell::RefCell;
+
+#[cfg(feature = "ttf")]
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+/// Glyphs are drawn on an 8x8 grid, the last row and column left empty as spacing
+const GLYPH_CELL: u32 = 8;
+/// How many times larger than the arcade's pixels the glyphs are drawn, to match the
+/// height of the TrueType font at `FONT_SIZE`
+const GLYPH_SCALE: u32 = 3;
+/// The first character in `GLYPHS`; they run from space to underscore in ASCII order
+const FIRST_GLYPH: char = ' ';
+
+/// The arcade's character set, one byte per row with the leftmost pixel in bit 6.
+///
+/// There are no lowercase letters, so text is drawn in capitals.
+const GLYPHS: [[u8; 7]; 64] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18], // !
+    [0x36, 0x36, 0x24, 0x00, 0x00, 0x00, 0x00], // "
+    [0x36, 0x7f, 0x36, 0x36, 0x36, 0x7f, 0x36], // #
+    [0x08, 0x3e, 0x48, 0x3e, 0x09, 0x3e, 0x08], // $
+    [0x63, 0x66, 0x0c, 0x18, 0x30, 0x66, 0x46], // %
+    [0x38, 0x6c, 0x38, 0x3b, 0x6e, 0x66, 0x3b], // &
+    [0x0c, 0x0c, 0x18, 0x00, 0x00, 0x00, 0x00], // '
+    [0x06, 0x0c, 0x18, 0x18, 0x18, 0x0c, 0x06], // (
+    [0x30, 0x18, 0x0c, 0x0c, 0x0c, 0x18, 0x30], // )
+    [0x00, 0x36, 0x1c, 0x7f, 0x1c, 0x36, 0x00], // *
+    [0x00, 0x0c, 0x0c, 0x3f, 0x0c, 0x0c, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c, 0x18], // ,
+    [0x00, 0x00, 0x00, 0x3e, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18], // .
+    [0x01, 0x03, 0x06, 0x0c, 0x18, 0x30, 0x60], // /
+    [0x1c, 0x26, 0x63, 0x63, 0x63, 0x32, 0x1c], // 0
+    [0x0c, 0x1c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3f], // 1
+    [0x3e, 0x63, 0x03, 0x0e, 0x3c, 0x70, 0x7f], // 2
+    [0x3f, 0x06, 0x0c, 0x1e, 0x03, 0x63, 0x3e], // 3
+    [0x0e, 0x1e, 0x36, 0x66, 0x7f, 0x06, 0x06], // 4
+    [0x7e, 0x60, 0x7e, 0x03, 0x03, 0x63, 0x3e], // 5
+    [0x1e, 0x30, 0x60, 0x7e, 0x63, 0x63, 0x3e], // 6
+    [0x7f, 0x63, 0x06, 0x0c, 0x18, 0x18, 0x18], // 7
+    [0x3c, 0x62, 0x72, 0x3c, 0x4f, 0x43, 0x3e], // 8
+    [0x3e, 0x63, 0x63, 0x3f, 0x03, 0x06, 0x3c], // 9
+    [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00], // :
+    [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x30], // ;
+    [0x06, 0x0c, 0x18, 0x30, 0x18, 0x0c, 0x06], // <
+    [0x00, 0x00, 0x7f, 0x00, 0x7f, 0x00, 0x00], // =
+    [0x30, 0x18, 0x0c, 0x06, 0x0c, 0x18, 0x30], // >
+    [0x3e, 0x63, 0x03, 0x0e, 0x0c, 0x00, 0x0c], // ?
+    [0x3e, 0x63, 0x6f, 0x6f, 0x6e, 0x60, 0x3e], // @
+    [0x1c, 0x36, 0x63, 0x63, 0x7f, 0x63, 0x63], // A
+    [0x7e, 0x63, 0x63, 0x7e, 0x63, 0x63, 0x7e], // B
+    [0x1e, 0x33, 0x60, 0x60, 0x60, 0x33, 0x1e], // C
+    [0x7c, 0x66, 0x63, 0x63, 0x63, 0x66, 0x7c], // D
+    [0x7f, 0x60, 0x60, 0x7e, 0x60, 0x60, 0x7f], // E
+    [0x7f, 0x60, 0x60, 0x7e, 0x60, 0x60, 0x60], // F
+    [0x1f, 0x30, 0x60, 0x67, 0x63, 0x33, 0x1f], // G
+    [0x63, 0x63, 0x63, 0x7f, 0x63, 0x63, 0x63], // H
+    [0x3f, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3f], // I
+    [0x0f, 0x03, 0x03, 0x03, 0x63, 0x63, 0x3e], // J
+    [0x63, 0x66, 0x6c, 0x78, 0x7c, 0x6e, 0x67], // K
+    [0x60, 0x60, 0x60, 0x60, 0x60, 0x60, 0x7f], // L
+    [0x63, 0x77, 0x7f, 0x7f, 0x6b, 0x63, 0x63], // M
+    [0x63, 0x73, 0x7b, 0x7f, 0x6f, 0x67, 0x63], // N
+    [0x3e, 0x63, 0x63, 0x63, 0x63, 0x63, 0x3e], // O
+    [0x7e, 0x63, 0x63, 0x63, 0x7e, 0x60, 0x60], // P
+    [0x3e, 0x63, 0x63, 0x63, 0x6f, 0x66, 0x3d], // Q
+    [0x7e, 0x63, 0x63, 0x67, 0x7c, 0x6e, 0x67], // R
+    [0x3c, 0x66, 0x60, 0x3e, 0x03, 0x63, 0x3e], // S
+    [0x3f, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c], // T
+    [0x63, 0x63, 0x63, 0x63, 0x63, 0x63, 0x3e], // U
+    [0x63, 0x63, 0x63, 0x77, 0x3e, 0x1c, 0x08], // V
+    [0x63, 0x63, 0x6b, 0x7f, 0x7f, 0x77, 0x63], // W
+    [0x63, 0x77, 0x3e, 0x1c, 0x3e, 0x77, 0x63], // X
+    [0x33, 0x33, 0x33, 0x1e, 0x0c, 0x0c, 0x0c], // Y
+    [0x7f, 0x07, 0x0e, 0x1c, 0x38, 0x70, 0x7f], // Z
+    [0x1e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1e], // [
+    [0x60, 0x30, 0x18, 0x0c, 0x06, 0x03, 0x01], // \
+    [0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3c], // ]
+    [0x08, 0x1c, 0x36, 0x63, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7f], // _
+];
+
+/// Where a character's glyph sits in `GLYPHS`; anything without one is drawn as `?`
+fn glyph_index(c: char) -> usize {
+    let c = c.to_ascii_uppercase();
+    let index = (c as u32).wrapping_sub(FIRST_GLYPH as u32) as usize;
+    if index < GLYPHS.len() {
+        index
+    } else {
+        '?' as usize - FIRST_GLYPH as usize
+    }
+}
+
+/// Fixed-width text copied out of a glyph atlas built once at startup, so drawing text
+/// needs neither SDL_ttf nor a font file.
+pub struct BitmapFont {
+    /// Every glyph in white, side by side; tinted while text is copied out of it
+    atlas: RefCell<Surface<'static>>,
+}
+
+impl BitmapFont {
+    pub fn new() -> Result<Self, PacmanError> {
+        let size = GLYPH_CELL * GLYPH_SCALE;
+        let width = size * GLYPHS.len() as u32;
+        let mut atlas = Surface::new(width, size, PixelFormatEnum::RGBA8888)
+            .map_err(|e| PacmanError::AssetLoad(format!("Unable to build the font: {}", e)))?;
+        let white = Color::RGBA(255, 255, 255, 255);
+        for (index, rows) in GLYPHS.iter().enumerate() {
+            for (y, row) in rows.iter().enumerate() {
+                for x in 0..GLYPH_CELL - 1 {
+                    if row & (0x40 >> x) == 0 {
+                        continue;
+                    }
+                    let pixel = Rect::new(
+                        ((index as u32 * GLYPH_CELL + x) * GLYPH_SCALE) as i32,
+                        (y as u32 * GLYPH_SCALE) as i32,
+                        GLYPH_SCALE,
+                        GLYPH_SCALE,
+                    );
+                    atlas
+                        .fill_rect(pixel, white)
+                        .map_err(PacmanError::AssetLoad)?;
+                }
+            }
+        }
+        Ok(BitmapFont {
+            atlas: RefCell::new(atlas),
+        })
+    }
+
+    pub fn render(&self, text: &str, color: Color) -> Result<Surface<'static>, PacmanError> {
+        let size = GLYPH_CELL * GLYPH_SCALE;
+        let glyphs: Vec<usize> = text.chars().map(glyph_index).collect();
+        // SDL will not make an empty surface, so empty text is one blank glyph wide
+        let width = size * glyphs.len().max(1) as u32;
+        let mut surface = Surface::new(width, size, PixelFormatEnum::RGBA8888)
+            .map_err(|e| PacmanError::Render(format!("Unable to render text surface: {}", e)))?;
+
+        let mut atlas = self.atlas.borrow_mut();
+        atlas.set_color_mod(color);
+        for (position, &glyph) in glyphs.iter().enumerate() {
+            let from = Rect::new((glyph as u32 * size) as i32, 0, size, size);
+            let to = Rect::new((position as u32 * size) as i32, 0, size, size);
+            atlas.blit(from, &mut surface, to).map_err(|e| {
+                PacmanError::Render(format!("Unable to render text surface: {}", e))
+            })?;
+        }
+        Ok(surface)
+    }
+}
+
+/// What text is drawn with: the built-in bitmap font, or the theme's TrueType font
+pub enum GameFont<'f> {
+    Bitmap(&'f BitmapFont),
+    #[cfg(feature = "ttf")]
+    TrueType(Font<'f, 'static>),
+}
+
+impl GameFont<'_> {
+    pub fn render(&self, text: &str, color: Color) -> Result<Surface<'static>, PacmanError> {
+        match self {
+            GameFont::Bitmap(font) => font.render(text, color),
+            #[cfg(feature = "ttf")]
+            GameFont::TrueType(font) => font
+                .render(text)
+                .solid(color)
+                .map_err(|e| PacmanError::Render(format!("Unable to render text surface: {}", e))),
+        }
+    }
+}
+
+/// Hands out the font text is drawn with.
+///
+/// SDL_ttf is only started when the theme's TrueType font is wanted, and builds without the
+/// `ttf` feature always use the bitmap font.
+pub struct Fonts {
+    bitmap: BitmapFont,
+    #[cfg(feature = "ttf")]
+    ttf_context: Option<Sdl2TtfContext>,
+}
+
+impl Fonts {
+    pub fn new(bitmap_only: bool) -> Result<Self, PacmanError> {
+        #[cfg(feature = "ttf")]
+        let ttf_context = if bitmap_only {
+            None
+        } else {
+            Some(sdl2::ttf::init().map_err(|e| PacmanError::SdlInit(e.to_string()))?)
+        };
+        #[cfg(not(feature = "ttf"))]
+        let _ = bitmap_only;
+        Ok(Fonts {
+            bitmap: BitmapFont::new()?,
+            #[cfg(feature = "ttf")]
+            ttf_context,
+        })
+    }
+
+    /// The theme's font, or the bitmap font when TrueType is off
+    pub fn load(&self, theme: &Theme) -> Result<GameFont<'_>, PacmanError> {
+        #[cfg(feature = "ttf")]
+        if let Some(ref ttf_context) = self.ttf_context {
+            return crate::assets::load_font(ttf_context, theme).map(GameFont::TrueType);
+        }
+        #[cfg(not(feature = "ttf"))]
+        let _ = theme;
+        Ok(GameFont::Bitmap(&self.bitmap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_glyph_fits_its_cell() {
+        for rows in GLYPHS {
+            assert!(rows.iter().all(|row| row & 0x80 == 0));
+        }
+        for c in ('0'..='9').chain('A'..='Z') {
+            assert!(
+                GLYPHS[glyph_index(c)].iter().any(|&row| row != 0),
+                "{} is blank",
+                c
+            );
+        }
+        assert!(GLYPHS[glyph_index(' ')].iter().all(|&row| row == 0));
+    }
+
+    #[test]
+    fn test_characters_without_glyphs() {
+        assert_eq!(glyph_index('a'), glyph_index('A'));
+        assert_eq!(glyph_index('z'), glyph_index('Z'));
+        assert_eq!(glyph_index('_'), GLYPHS.len() - 1);
+        assert_eq!(glyph_index('{'), glyph_index('?'));
+        assert_eq!(glyph_index('\u{e9}'), glyph_index('?'));
+        assert_eq!(glyph_index('\n'), glyph_index('?'));
+    }
+}
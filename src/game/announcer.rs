@@ -0,0 +1,169 @@
+use super::state::GameState;
+use std::process::{Child, Command};
+
+/// A change in the game worth telling a player who cannot see the screen
+#[derive(Debug, Clone, PartialEq)]
+pub enum Announcement {
+    Ready { level: u16, lives: i8 },
+    Paused,
+    Resumed,
+    LevelComplete { level: u16 },
+    ExtraLife { lives: i8 },
+    GameOver { level: u16, score: u32 },
+}
+
+impl Announcement {
+    /// One `announce:` line of `key=value` fields, for tools following stdout
+    pub fn line(&self) -> String {
+        let fields = match self {
+            Announcement::Ready { level, lives } => {
+                format!("event=ready level={} lives={}", level, lives)
+            }
+            Announcement::Paused => "event=paused".to_string(),
+            Announcement::Resumed => "event=resumed".to_string(),
+            Announcement::LevelComplete { level } => {
+                format!("event=level_complete level={}", level)
+            }
+            Announcement::ExtraLife { lives } => format!("event=extra_life lives={}", lives),
+            Announcement::GameOver { level, score } => {
+                format!("event=game_over level={} score={}", level, score)
+            }
+        };
+        format!("announce: {}", fields)
+    }
+
+    /// The same as a sentence, for a speech synthesizer
+    pub fn spoken(&self) -> String {
+        match self {
+            Announcement::Ready { level, lives } => {
+                format!("Level {}. Ready. {} lives.", level, lives)
+            }
+            Announcement::Paused => "Paused.".to_string(),
+            Announcement::Resumed => "Resumed.".to_string(),
+            Announcement::LevelComplete { level } => format!("Level {} cleared.", level),
+            Announcement::ExtraLife { lives } => format!("Extra life. {} lives.", lives),
+            Announcement::GameOver { level, score } => {
+                format!("Game over on level {}. Final score {}.", level, score)
+            }
+        }
+    }
+}
+
+/// Tells the player about state changes on stdout and, optionally, through a speech command.
+///
+/// The command is run with the sentence as its last argument, e.g. `espeak` or `say`, and is
+/// not waited for, so a slow synthesizer never holds up a frame.
+pub struct Announcer {
+    enabled: bool,
+    speech: Option<Vec<String>>,
+    speaking: Vec<Child>,
+    state: GameState,
+}
+
+impl Announcer {
+    pub fn new(enabled: bool, speech_command: Option<&str>) -> Self {
+        Announcer {
+            enabled,
+            speech: speech_command
+                .map(|command| command.split_whitespace().map(str::to_string).collect()),
+            speaking: Vec::new(),
+            state: GameState::InsertCoin,
+        }
+    }
+
+    pub fn announce(&mut self, announcement: Announcement) {
+        if !self.enabled {
+            return;
+        }
+        println!("{}", announcement.line());
+
+        // Reap the sentences already spoken before starting the next
+        self.speaking
+            .retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+        if let Some((program, args)) = self.speech.as_ref().and_then(|speech| speech.split_first())
+        {
+            match Command::new(program)
+                .args(args)
+                .arg(announcement.spoken())
+                .spawn()
+            {
+                Ok(child) => self.speaking.push(child),
+                Err(e) => {
+                    println!("Could not run {}: {}", program, e);
+                    self.speech = None;
+                }
+            }
+        }
+    }
+
+    /// Announce `state` if it changed since the last update, returning what changed
+    pub fn watch(
+        &mut self,
+        state: &GameState,
+        level: u16,
+        lives: i8,
+        score: u32,
+    ) -> Option<Announcement> {
+        if *state == self.state {
+            return None;
+        }
+        let announcement = match (&self.state, state) {
+            (_, GameState::Ready) => Some(Announcement::Ready { level, lives }),
+            (_, GameState::Paused) => Some(Announcement::Paused),
+            (GameState::Paused, GameState::Playing) => Some(Announcement::Resumed),
+            (_, GameState::LevelComplete) => Some(Announcement::LevelComplete { level }),
+            (_, GameState::GameOver) => Some(Announcement::GameOver { level, score }),
+            _ => None,
+        };
+        self.state = state.clone();
+        if let Some(ref announcement) = announcement {
+            self.announce(announcement.clone());
+        }
+        announcement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announcement_formats() {
+        let over = Announcement::GameOver {
+            level: 3,
+            score: 12340,
+        };
+        assert_eq!(over.line(), "announce: event=game_over level=3 score=12340");
+        assert_eq!(over.spoken(), "Game over on level 3. Final score 12340.");
+        assert_eq!(
+            Announcement::Ready { level: 1, lives: 3 }.line(),
+            "announce: event=ready level=1 lives=3"
+        );
+    }
+
+    #[test]
+    fn test_only_state_changes_are_announced() {
+        let mut announcer = Announcer::new(false, None);
+        assert_eq!(
+            announcer.watch(&GameState::Ready, 1, 3, 0),
+            Some(Announcement::Ready { level: 1, lives: 3 })
+        );
+        assert_eq!(announcer.watch(&GameState::Ready, 1, 3, 0), None);
+        assert_eq!(announcer.watch(&GameState::Playing, 1, 3, 0), None);
+        assert_eq!(
+            announcer.watch(&GameState::Paused, 1, 3, 0),
+            Some(Announcement::Paused)
+        );
+        assert_eq!(
+            announcer.watch(&GameState::Playing, 1, 3, 0),
+            Some(Announcement::Resumed)
+        );
+        assert_eq!(
+            announcer.watch(&GameState::GameOver, 2, 0, 4200),
+            Some(Announcement::GameOver {
+                level: 2,
+                score: 4200
+            })
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::board::{BlockType, Direction, TUNNEL_ROWS};
+use crate::game::ghost_mode::GhostMode;
+use crate::position::Tile;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+use std::collections::VecDeque;
+
+/// Tiles this close to a dangerous ghost are treated as walls
+const DANGER_RADIUS: i16 = 2;
+
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Right,
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+];
+
+/// What the bot gets to see of the game on each update
+pub struct BotView<'m> {
+    pub map: &'m [BlockType],
+    pub pacman: Tile,
+    pub ghosts: Vec<(Tile, GhostMode)>,
+}
+
+/// Route to the closest pellet that stays clear of chasing ghosts, or flee if none is reachable
+pub fn choose_direction(view: &BotView) -> Option<Direction> {
+    let start = wrap(view.pacman)?;
+    let danger: Vec<Tile> = view
+        .ghosts
+        .iter()
+        .filter(|(_, mode)| matches!(mode, GhostMode::Chase | GhostMode::Scatter))
+        .map(|(tile, _)| *tile)
+        .collect();
+    let is_dangerous = |tile: Tile| {
+        danger
+            .iter()
+            .any(|ghost| distance(*ghost, tile) <= DANGER_RADIUS)
+    };
+
+    // Breadth-first search, remembering which first step led to each tile
+    let mut first_step: Vec<Option<Direction>> = vec![None; BOARD_WIDTH * BOARD_HEIGHT];
+    let mut visited = vec![false; BOARD_WIDTH * BOARD_HEIGHT];
+    let mut queue = VecDeque::new();
+    visited[index(start)] = true;
+    queue.push_back(start);
+
+    while let Some(tile) = queue.pop_front() {
+        if tile != start
+            && matches!(
+                view.map[index(tile)],
+                BlockType::Pellet | BlockType::Energizer
+            )
+        {
+            return first_step[index(tile)];
+        }
+
+        for direction in DIRECTIONS {
+            let next = match step(tile, direction) {
+                Some(next) => next,
+                None => continue,
+            };
+            if visited[index(next)] || !is_open(view.map, next) || is_dangerous(next) {
+                continue;
+            }
+            visited[index(next)] = true;
+            first_step[index(next)] = first_step[index(tile)].or(Some(direction));
+            queue.push_back(next);
+        }
+    }
+
+    flee(view, start, &danger)
+}
+
+/// Step to whichever open neighbour is furthest from the nearest dangerous ghost
+fn flee(view: &BotView, start: Tile, danger: &[Tile]) -> Option<Direction> {
+    DIRECTIONS
+        .iter()
+        .filter_map(|&direction| {
+            let next = step(start, direction)?;
+            if !is_open(view.map, next) {
+                return None;
+            }
+            let nearest = danger
+                .iter()
+                .map(|ghost| distance(*ghost, next))
+                .min()
+                .unwrap_or(i16::MAX);
+            Some((nearest, direction))
+        })
+        .max_by_key(|(nearest, _)| *nearest)
+        .map(|(_, direction)| direction)
+}
+
+/// The neighbouring tile in a direction, through any tunnel
+pub fn step(tile: Tile, direction: Direction) -> Option<Tile> {
+    let (dx, dy) = match direction {
+        Direction::Right => (1, 0),
+        Direction::Up => (0, -1),
+        Direction::Left => (-1, 0),
+        Direction::Down => (0, 1),
+        Direction::Nowhere => (0, 0),
+    };
+    wrap(Tile {
+        x: tile.x + dx,
+        y: tile.y + dy,
+    })
+}
+
+/// Fold tunnel tiles back onto the board; `None` above or below it.
+///
+/// Leaving the maze through its top or bottom wall comes back in at the other one, which
+/// only happens where a vertical tunnel opens the wall.
+fn wrap(tile: Tile) -> Option<Tile> {
+    let (top, bottom) = (TUNNEL_ROWS.0 as i16, TUNNEL_ROWS.1 as i16);
+    let y = match tile.y {
+        y if y == top - 1 => bottom,
+        y if y == bottom + 1 => top,
+        y => y,
+    };
+    if y < 0 || y >= BOARD_HEIGHT as i16 {
+        return None;
+    }
+    Some(Tile {
+        x: tile.x.rem_euclid(BOARD_WIDTH as i16),
+        y,
+    })
+}
+
+fn index(tile: Tile) -> usize {
+    tile.y as usize * BOARD_WIDTH + tile.x as usize
+}
+
+/// Whether Pac-Man, or a ghost outside the house, can stand on the tile
+pub fn is_open(map: &[BlockType], tile: Tile) -> bool {
+    !matches!(map[index(tile)], BlockType::Wall | BlockType::Door)
+}
+
+fn distance(a: Tile, b: Tile) -> i16 {
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::stock_map;
+
+    fn far_away_ghosts() -> Vec<(Tile, GhostMode)> {
+        vec![(Tile { x: 13, y: 17 }, GhostMode::InHouse); 4]
+    }
+
+    #[test]
+    fn test_heads_for_nearest_pellet() {
+        let mut map = stock_map();
+        // Clear the start row so the only nearby pellet is to the left
+        for x in 1..27 {
+            map[26 * BOARD_WIDTH + x] = BlockType::Nothing;
+        }
+        map[26 * BOARD_WIDTH + 10] = BlockType::Pellet;
+
+        let view = BotView {
+            map: &map,
+            pacman: Tile { x: 13, y: 26 },
+            ghosts: far_away_ghosts(),
+        };
+        assert_eq!(choose_direction(&view), Some(Direction::Left));
+    }
+
+    #[test]
+    fn test_avoids_chasing_ghost() {
+        let map = stock_map();
+        let mut ghosts = far_away_ghosts();
+        ghosts[0] = (Tile { x: 10, y: 26 }, GhostMode::Chase);
+
+        let view = BotView {
+            map: &map,
+            pacman: Tile { x: 13, y: 26 },
+            ghosts,
+        };
+        assert_ne!(choose_direction(&view), Some(Direction::Left));
+    }
+
+    #[test]
+    fn test_ignores_frightened_ghost() {
+        let mut map = stock_map();
+        for x in 1..27 {
+            map[26 * BOARD_WIDTH + x] = BlockType::Nothing;
+        }
+        map[26 * BOARD_WIDTH + 10] = BlockType::Pellet;
+        let mut ghosts = far_away_ghosts();
+        ghosts[0] = (Tile { x: 11, y: 26 }, GhostMode::Frightened);
+
+        let view = BotView {
+            map: &map,
+            pacman: Tile { x: 13, y: 26 },
+            ghosts,
+        };
+        assert_eq!(choose_direction(&view), Some(Direction::Left));
+    }
+
+    #[test]
+    fn test_tunnel_wraps() {
+        assert_eq!(
+            step(Tile { x: 0, y: 17 }, Direction::Left),
+            Some(Tile { x: 27, y: 17 })
+        );
+        assert_eq!(step(Tile { x: 5, y: 0 }, Direction::Up), None);
+        assert_eq!(
+            step(Tile { x: 5, y: 3 }, Direction::Up),
+            Some(Tile { x: 5, y: 33 })
+        );
+        assert_eq!(
+            step(Tile { x: 5, y: 33 }, Direction::Down),
+            Some(Tile { x: 5, y: 3 })
+        );
+    }
+}
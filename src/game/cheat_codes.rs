@@ -0,0 +1,157 @@
+use sdl2::keyboard::Keycode;
+
+use Keycode::{Down, Left, Right, Up};
+
+/// A fun or debug toggle unlocked by typing its code on the menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cheat {
+    /// The number keys pick the level the next game starts on
+    LevelSelect,
+    /// Pac-Man is drawn twice his size; he still fits the same corridors
+    BigPacman,
+    /// Each ghost's target tile is marked, with a line from the ghost to it
+    GhostVision,
+}
+
+impl Cheat {
+    pub const ALL: [Cheat; 3] = [Cheat::LevelSelect, Cheat::BigPacman, Cheat::GhostVision];
+
+    /// Shown when the cheat is switched on or off
+    pub fn name(self) -> &'static str {
+        match self {
+            Cheat::LevelSelect => "LEVEL SELECT",
+            Cheat::BigPacman => "BIG PAC-MAN",
+            Cheat::GhostVision => "GHOST VISION",
+        }
+    }
+
+    /// Arrow keys that make up the code; none is the end of another, so at most one matches
+    fn code(self) -> &'static [Keycode] {
+        match self {
+            Cheat::LevelSelect => &[Up, Up, Down, Down, Left, Right, Left, Right],
+            Cheat::BigPacman => &[Down, Down, Up, Up, Right, Left, Right, Left],
+            Cheat::GhostVision => &[Left, Right, Left, Right, Up, Down, Up, Down],
+        }
+    }
+}
+
+/// The level a number key picks under level select, 1 to 9
+pub fn level_for_key(keycode: Keycode) -> Option<u16> {
+    let level = match keycode {
+        Keycode::Num1 => 1,
+        Keycode::Num2 => 2,
+        Keycode::Num3 => 3,
+        Keycode::Num4 => 4,
+        Keycode::Num5 => 5,
+        Keycode::Num6 => 6,
+        Keycode::Num7 => 7,
+        Keycode::Num8 => 8,
+        Keycode::Num9 => 9,
+        _ => return None,
+    };
+    Some(level)
+}
+
+/// Watches the keys pressed on the menu for cheat codes.
+///
+/// Typing a code switches its cheat on, and typing it again switches it back off. Any other key
+/// simply becomes part of the next attempt, so codes can be retried without waiting.
+pub struct CheatCodes {
+    /// The latest keys, oldest first, as many as the longest code
+    recent: Vec<Keycode>,
+    unlocked: Vec<Cheat>,
+}
+
+impl Default for CheatCodes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheatCodes {
+    pub fn new() -> Self {
+        CheatCodes {
+            recent: Vec::new(),
+            unlocked: Vec::new(),
+        }
+    }
+
+    /// Note a key, returning the cheat it toggled when it finished a code
+    pub fn press(&mut self, keycode: Keycode) -> Option<Cheat> {
+        let longest = Cheat::ALL.iter().map(|cheat| cheat.code().len()).max()?;
+        if self.recent.len() == longest {
+            self.recent.remove(0);
+        }
+        self.recent.push(keycode);
+
+        let cheat = Cheat::ALL
+            .into_iter()
+            .find(|cheat| self.recent.ends_with(cheat.code()))?;
+        // A finished code never counts towards the next one
+        self.recent.clear();
+        match self.unlocked.iter().position(|&unlocked| unlocked == cheat) {
+            Some(index) => {
+                self.unlocked.remove(index);
+            }
+            None => self.unlocked.push(cheat),
+        }
+        Some(cheat)
+    }
+
+    pub fn is_unlocked(&self, cheat: Cheat) -> bool {
+        self.unlocked.contains(&cheat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_code(codes: &mut CheatCodes, keys: &[Keycode]) -> Option<Cheat> {
+        keys.iter().filter_map(|&key| codes.press(key)).last()
+    }
+
+    #[test]
+    fn test_codes_toggle_their_cheats() {
+        let mut codes = CheatCodes::new();
+        // Fumbled keys before the code do not get in its way
+        let keys = [
+            Up,
+            Keycode::Space,
+            Up,
+            Up,
+            Down,
+            Down,
+            Left,
+            Right,
+            Left,
+            Right,
+        ];
+        assert_eq!(type_code(&mut codes, &keys), Some(Cheat::LevelSelect));
+        assert!(codes.is_unlocked(Cheat::LevelSelect));
+        assert!(!codes.is_unlocked(Cheat::BigPacman));
+
+        assert_eq!(
+            type_code(&mut codes, Cheat::LevelSelect.code()),
+            Some(Cheat::LevelSelect)
+        );
+        assert!(!codes.is_unlocked(Cheat::LevelSelect));
+
+        // Half a code followed by another is still the other
+        let mut keys = Cheat::GhostVision.code()[..4].to_vec();
+        keys.extend_from_slice(Cheat::BigPacman.code());
+        assert_eq!(type_code(&mut codes, &keys), Some(Cheat::BigPacman));
+        assert!(codes.is_unlocked(Cheat::BigPacman));
+    }
+
+    #[test]
+    fn test_no_code_ends_another() {
+        for cheat in Cheat::ALL {
+            for other in Cheat::ALL {
+                if cheat != other {
+                    assert!(!cheat.code().ends_with(other.code()));
+                }
+            }
+        }
+    }
+}
@@ -1,22 +1,29 @@
 use crate::board::BlockType;
 use crate::entity::pacman::Pacman;
-use crate::entity::{Blinky, Clyde, Entity, GhostBehavior, Inky, Pinky};
-use crate::position::Position;
+use crate::entity::{Entity, GhostBehavior};
+use crate::game::ghost_manager::GhostManager;
+use crate::game::ghost_mode::GhostMode;
+use crate::position::{Position, Tile};
 
 #[derive(Debug)]
 pub enum CollisionEvent {
     PacmanEatsGhost {
+        /// Which ghost in the `GhostManager` was eaten, as there may be several of a type
+        index: usize,
         ghost_type: GhostType,
         position: Position,
+        tile: Tile,
+        mode: GhostMode,
     },
     GhostKillsPacman {
-        #[allow(dead_code)]
         ghost_type: GhostType,
+        tile: Tile,
+        mode: GhostMode,
     },
     NoCollision,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GhostType {
     Blinky,
     Inky,
@@ -24,8 +31,30 @@ pub enum GhostType {
     Clyde,
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
+impl GhostType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GhostType::Blinky => "blinky",
+            GhostType::Inky => "inky",
+            GhostType::Pinky => "pinky",
+            GhostType::Clyde => "clyde",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            GhostType::Blinky,
+            GhostType::Inky,
+            GhostType::Pinky,
+            GhostType::Clyde,
+        ]
+        .into_iter()
+        .find(|ghost_type| ghost_type.name() == name)
+    }
+}
+
+/// What Pac-Man found to eat on the tile he is passing over
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FoodCollisionEvent {
     Nothing,
     Pellet,
@@ -34,92 +63,72 @@ pub enum FoodCollisionEvent {
 
 pub struct CollisionSystem;
 
+impl Default for CollisionSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CollisionSystem {
     pub fn new() -> Self {
         CollisionSystem
     }
 
     /// Check collision between Pacman and a specific ghost
-    pub fn check_pacman_ghost_collision<'a>(
+    pub fn check_pacman_ghost_collision(
         &self,
-        pacman: &Pacman<'a>,
-        ghost: &impl GhostBehavior<'a>,
-        ghost_type: GhostType,
-        pacman_is_energized: bool,
+        pacman: &Pacman,
+        index: usize,
+        ghost: &dyn GhostBehavior,
     ) -> CollisionEvent {
+        let ghost_type = ghost.get_ghost_type();
         let pacman_pos = pacman.get_position();
         let ghost_pos = ghost.get_ghost().entity.get_position();
 
         if pacman.is_colliding(ghost_pos) && ghost.get_ghost().entity.is_alive() {
-            if pacman_is_energized {
+            let tile = Tile::from_position(ghost_pos);
+            let mode = ghost.get_ghost().get_mode();
+            if mode == GhostMode::Frightened {
                 CollisionEvent::PacmanEatsGhost {
+                    index,
                     ghost_type,
                     position: pacman_pos,
+                    tile,
+                    mode,
                 }
             } else {
-                CollisionEvent::GhostKillsPacman { ghost_type }
+                CollisionEvent::GhostKillsPacman {
+                    ghost_type,
+                    tile,
+                    mode,
+                }
             }
         } else {
             CollisionEvent::NoCollision
         }
     }
 
-    /// Check all ghost collisions and return the first collision found
-    pub fn check_all_ghost_collisions<'a>(
+    /// Every ghost Pac-Man is touching, in `GhostManager` order
+    pub fn check_all_ghost_collisions(
         &self,
-        pacman: &Pacman<'a>,
-        blinky: &Blinky<'a>,
-        inky: &Inky<'a>,
-        pinky: &Pinky<'a>,
-        clyde: &Clyde<'a>,
-        pacman_is_energized: bool,
+        pacman: &Pacman,
+        ghosts: &GhostManager,
     ) -> Vec<CollisionEvent> {
-        let mut collisions = Vec::new();
-
-        // Check each ghost individually
-        let blinky_collision = self.check_pacman_ghost_collision(
-            pacman,
-            blinky,
-            GhostType::Blinky,
-            pacman_is_energized,
-        );
-        if !matches!(blinky_collision, CollisionEvent::NoCollision) {
-            collisions.push(blinky_collision);
-        }
-
-        let inky_collision =
-            self.check_pacman_ghost_collision(pacman, inky, GhostType::Inky, pacman_is_energized);
-        if !matches!(inky_collision, CollisionEvent::NoCollision) {
-            collisions.push(inky_collision);
-        }
-
-        let pinky_collision =
-            self.check_pacman_ghost_collision(pacman, pinky, GhostType::Pinky, pacman_is_energized);
-        if !matches!(pinky_collision, CollisionEvent::NoCollision) {
-            collisions.push(pinky_collision);
-        }
-
-        let clyde_collision =
-            self.check_pacman_ghost_collision(pacman, clyde, GhostType::Clyde, pacman_is_energized);
-        if !matches!(clyde_collision, CollisionEvent::NoCollision) {
-            collisions.push(clyde_collision);
-        }
-
-        collisions
+        ghosts
+            .iter()
+            .enumerate()
+            .map(|(index, ghost)| self.check_pacman_ghost_collision(pacman, index, ghost))
+            .filter(|collision| !matches!(collision, CollisionEvent::NoCollision))
+            .collect()
     }
 
-    /// Check food collision and return the type of food consumed
-    #[allow(dead_code)]
-    pub fn check_food_collision<'a>(
+    /// Eat any food under Pac-Man, clearing it from the map, and say what it was
+    pub fn check_food_collision(
         &self,
-        pacman: &Pacman<'a>,
+        pacman: &Pacman,
         actual_map: &mut [BlockType],
     ) -> FoodCollisionEvent {
-        match pacman.food_collision(actual_map) {
-            0 => FoodCollisionEvent::Nothing,
-            1 => FoodCollisionEvent::Energizer,
-            _ => FoodCollisionEvent::Pellet,
-        }
+        pacman.food_collision(actual_map)
     }
 }
 
@@ -139,14 +148,22 @@ mod tests {
         // Test collision event variants
         let ghost_kill_event = CollisionEvent::GhostKillsPacman {
             ghost_type: GhostType::Blinky,
+            tile: Tile { x: 13, y: 26 },
+            mode: GhostMode::Chase,
         };
 
         match ghost_kill_event {
-            CollisionEvent::GhostKillsPacman { ghost_type } => {
+            CollisionEvent::GhostKillsPacman {
+                ghost_type,
+                tile,
+                mode,
+            } => {
                 match ghost_type {
                     GhostType::Blinky => {} // Expected case
                     _ => panic!("Expected Blinky ghost type"),
                 }
+                assert_eq!(tile, Tile { x: 13, y: 26 });
+                assert_eq!(mode, GhostMode::Chase);
             }
             _ => panic!("Expected GhostKillsPacman event"),
         }
@@ -170,6 +187,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_food_collision_clears_what_was_eaten() {
+        let theme = crate::theme::Theme::new();
+        let collision_system = CollisionSystem::new();
+        let mut pacman = Pacman::new(&theme);
+        let mut map: Vec<BlockType> = crate::board::Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let block = crate::BLOCK_SIZE_24 as i16;
+
+        // The pellet in the top left corner, then the energizer two rows below it
+        pacman.set_position(Position::new(block, 4 * block));
+        assert_eq!(
+            collision_system.check_food_collision(&pacman, &mut map),
+            FoodCollisionEvent::Pellet
+        );
+        assert_eq!(
+            collision_system.check_food_collision(&pacman, &mut map),
+            FoodCollisionEvent::Nothing
+        );
+        pacman.set_position(Position::new(block, 6 * block));
+        assert_eq!(
+            collision_system.check_food_collision(&pacman, &mut map),
+            FoodCollisionEvent::Energizer
+        );
+        assert_eq!(map[6 * crate::BOARD_WIDTH + 1], BlockType::Nothing);
+    }
+
+    #[test]
+    fn test_eating_stalls_pacman() {
+        let theme = crate::theme::Theme::new();
+        let mut pacman = Pacman::new(&theme);
+        let map: Vec<BlockType> = crate::board::Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let mut input = crate::game::input::InputQueue::new(crate::board::Direction::Right, None);
+        let block = crate::BLOCK_SIZE_24 as i16;
+        pacman.set_position(Position::new(block, 4 * block));
+        pacman.entity.mod_speed(1);
+
+        pacman.stall(FoodCollisionEvent::Energizer);
+        for _ in 0..3 {
+            pacman.update_pos(&mut input, &map);
+        }
+        assert_eq!(pacman.get_x(), block);
+        pacman.update_pos(&mut input, &map);
+        assert_eq!(pacman.get_x(), block + 1);
+
+        pacman.stall(FoodCollisionEvent::Pellet);
+        pacman.update_pos(&mut input, &map);
+        pacman.update_pos(&mut input, &map);
+        assert_eq!(pacman.get_x(), block + 2);
+    }
+
+    #[test]
+    fn test_food_is_eaten_at_the_edge_of_a_tile() {
+        let theme = crate::theme::Theme::new();
+        let mut pacman = Pacman::new(&theme);
+        let mut map: Vec<BlockType> = crate::board::Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let block = crate::BLOCK_SIZE_24 as i16;
+        let row = 4 * crate::BOARD_WIDTH;
+
+        // One pixel past the first pellet: the tile he is leaving goes first, then the next
+        pacman.set_position(Position::new(block + 1, 4 * block));
+        assert_eq!(pacman.food_collision(&mut map), FoodCollisionEvent::Pellet);
+        assert_eq!(map[row + 1], BlockType::Nothing);
+        assert_eq!(map[row + 2], BlockType::Pellet);
+        assert_eq!(pacman.food_collision(&mut map), FoodCollisionEvent::Pellet);
+        assert_eq!(map[row + 2], BlockType::Nothing);
+        assert_eq!(pacman.food_collision(&mut map), FoodCollisionEvent::Nothing);
+
+        // Aligned on a tile, the neighbours are left alone
+        pacman.set_position(Position::new(4 * block, 4 * block));
+        assert_eq!(pacman.food_collision(&mut map), FoodCollisionEvent::Pellet);
+        assert_eq!(map[row + 3], BlockType::Pellet);
+        assert_eq!(map[row + 5], BlockType::Pellet);
+
+        // Half off the board in the tunnel, with nothing there to eat
+        pacman.set_position(Position::new(-block / 2, 17 * block));
+        assert_eq!(pacman.food_collision(&mut map), FoodCollisionEvent::Nothing);
+    }
+
     #[test]
     fn test_ghost_types() {
         // Test that all ghost types can be created
@@ -189,4 +293,3 @@ mod tests {
         }
     }
 }
-
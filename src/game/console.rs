@@ -0,0 +1,202 @@
+use crate::game::collision::GhostType;
+
+/// Longest line the console accepts
+const MAX_LINE: usize = 40;
+
+/// A cheat typed into the debug console
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConsoleCommand {
+    /// Jump to the ready screen of a level
+    Level(u16),
+    Score(u32),
+    Lives(i8),
+    /// Send every ghost of a kind home as eyes, without scoring it
+    Kill(GhostType),
+    /// Frighten the ghosts as if an energizer had been eaten
+    Energize,
+    /// Let Pac-Man pass through walls, or stop him again
+    Noclip,
+    Help,
+}
+
+impl ConsoleCommand {
+    pub const HELP: &'static str = "level N, score N, lives N, kill GHOST, energize, noclip";
+
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("Type help for commands")?;
+        let argument = words.next();
+        if words.next().is_some() {
+            return Err(format!("Too many arguments for {}", name));
+        }
+
+        let command = match name {
+            "level" => ConsoleCommand::Level(number(name, argument, "level")?),
+            "score" => ConsoleCommand::Score(number(name, argument, "score")?),
+            "lives" => ConsoleCommand::Lives(number(name, argument, "number of lives")?),
+            "kill" => {
+                let ghost = argument.ok_or("kill expects a ghost")?;
+                ConsoleCommand::Kill(
+                    GhostType::from_name(ghost)
+                        .ok_or_else(|| format!("Unknown ghost: {}", ghost))?,
+                )
+            }
+            "energize" => ConsoleCommand::Energize,
+            "noclip" => ConsoleCommand::Noclip,
+            "help" => ConsoleCommand::Help,
+            _ => return Err(format!("Unknown command: {}", name)),
+        };
+
+        let takes_argument = matches!(
+            command,
+            ConsoleCommand::Level(_)
+                | ConsoleCommand::Score(_)
+                | ConsoleCommand::Lives(_)
+                | ConsoleCommand::Kill(_)
+        );
+        if argument.is_some() && !takes_argument {
+            return Err(format!("{} takes no arguments", name));
+        }
+        if command == ConsoleCommand::Level(0)
+            || matches!(command, ConsoleCommand::Lives(n) if n < 0)
+        {
+            return Err(format!("Invalid {}", name));
+        }
+        Ok(command)
+    }
+}
+
+/// A command's numeric argument, `what` naming it in errors
+fn number<T: std::str::FromStr>(
+    name: &str,
+    argument: Option<&str>,
+    what: &str,
+) -> Result<T, String> {
+    let value = argument.ok_or_else(|| format!("{} expects a {}", name, what))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid {}: {}", what, value))
+}
+
+/// The line being typed into the debug console and the reply to the last command
+pub struct Console {
+    open: bool,
+    line: String,
+    reply: Option<String>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            open: false,
+            line: String::new(),
+            reply: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open or close the console; closing throws away a half-typed line
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.line.clear();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.line.clear();
+    }
+
+    /// Add typed text; the backquote that opens the console is left out
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars().filter(|&c| c != '`' && !c.is_control()) {
+            if self.line.len() < MAX_LINE {
+                self.line.push(c.to_ascii_lowercase());
+            }
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.line.pop();
+    }
+
+    /// Take the typed line for running, leaving the console empty
+    pub fn submit(&mut self) -> String {
+        std::mem::take(&mut self.line)
+    }
+
+    pub fn set_reply(&mut self, reply: String) {
+        println!("Console: {}", reply);
+        self.reply = Some(reply);
+    }
+
+    /// The prompt line as drawn, e.g. `> level 5`
+    pub fn prompt(&self) -> String {
+        format!("> {}", self.line)
+    }
+
+    pub fn reply(&self) -> Option<&str> {
+        self.reply.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            ConsoleCommand::parse("level 5"),
+            Ok(ConsoleCommand::Level(5))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("  score   10000 "),
+            Ok(ConsoleCommand::Score(10000))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("kill blinky"),
+            Ok(ConsoleCommand::Kill(GhostType::Blinky))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("energize"),
+            Ok(ConsoleCommand::Energize)
+        );
+        assert_eq!(ConsoleCommand::parse("noclip"), Ok(ConsoleCommand::Noclip));
+
+        assert!(ConsoleCommand::parse("").is_err());
+        assert!(ConsoleCommand::parse("level").is_err());
+        assert!(ConsoleCommand::parse("level 0").is_err());
+        assert!(ConsoleCommand::parse("level five").is_err());
+        assert!(ConsoleCommand::parse("lives -1").is_err());
+        assert!(ConsoleCommand::parse("kill sue").is_err());
+        assert!(ConsoleCommand::parse("noclip on").is_err());
+        assert!(ConsoleCommand::parse("score 1 2").is_err());
+        assert!(ConsoleCommand::parse("warp").is_err());
+    }
+
+    #[test]
+    fn test_typing() {
+        let mut console = Console::new();
+        console.toggle();
+        console.type_text("`Level 5x");
+        console.backspace();
+        assert_eq!(console.prompt(), "> level 5");
+        assert_eq!(console.submit(), "level 5");
+        assert_eq!(console.prompt(), "> ");
+
+        console.type_text("kill");
+        console.toggle();
+        assert!(!console.is_open());
+        console.toggle();
+        assert_eq!(console.prompt(), "> ");
+    }
+}
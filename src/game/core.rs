@@ -1,56 +1,292 @@
-use super::collision::{CollisionEvent, CollisionSystem, GhostType};
-use super::ghost_manager::GhostManager;
-use super::scoring::ScoringSystem;
+use super::announcer::{Announcement, Announcer};
+use super::bot::{self, BotView};
+use super::cheat_codes::{self, Cheat, CheatCodes};
+use super::collision::{CollisionEvent, CollisionSystem, FoodCollisionEvent};
+use super::console::{Console, ConsoleCommand};
+use super::events::{EventBus, GameEvent};
+use super::flashlight::Flashlight;
+use super::ghost_manager::{GhostManager, MAX_EXTRA_GHOSTS};
+use super::ghost_mode::{GhostEvent, GhostMode};
+use super::input::InputQueue;
+use super::levels::Difficulty;
+use super::menu::Menu;
+use super::particles::ParticleSystem;
+use super::perf::{
+    self, FrameHistory, FrameSample, PerfStats, SimSystem, SystemTimings, GRAPH_FRAMES,
+    OVERLAY_REFRESH_MS,
+};
+use super::power::{PowerState, WARNING_MS};
+use super::replay::{InputAction, Playback, Replay, ReplayMetadata, BEST_REPLAY_PATH};
+use super::rewind::{RewindBuffer, REWIND_CAPACITY};
+use super::rng::GameRng;
+use super::rules_mode::{FruitEntry, RulesMode, FRUIT_DOTS};
+use super::scoring::{self, BonusTally, ScoringRules, ScoringSystem};
+use super::screen_effects::ScreenEffects;
+use super::snapshot::{GhostState, ResumeState, Snapshot, QUICK_SAVE_PATH, SAVE_GAME_PATH};
+use super::sound_indicators::{self, Indicator, SoundIndicators};
 use super::state::GameState;
+use super::states;
+use super::stats::SessionStats;
+use super::survival::Survival;
+use super::telemetry::Telemetry;
+use super::teleport;
+use super::tile_graph::TileGraph;
 use super::timers::TimerSystem;
+use crate::assets::AssetManager;
+use crate::audio::{Sound, SoundCue, Track};
 use crate::board::{BlockType, Board, Direction};
+use crate::config::{Config, Practice};
 use crate::entity::pacman::Pacman;
-use crate::entity::Entity;
+use crate::entity::{Entity, Fruit};
+use crate::font::{Fonts, GameFont};
+use crate::locale::Locale;
+use crate::palette::Palette;
+use crate::position::{Position, Tile};
+use crate::profiling::profile_scope;
+use crate::renderer::Renderer;
+use crate::settings::Settings;
 use crate::texture::GameTexture;
-use crate::{BOARD_HEIGHT, BOARD_WIDTH, RED, YELLOW};
+use crate::theme::{MazeColors, Theme};
+use crate::{BLACK, BLOCK_SIZE_24, BOARD_HEIGHT, BOARD_WIDTH, CYAN, RED, TICK_MS, WHITE, YELLOW};
 use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
 use sdl2::render::{TextureCreator, WindowCanvas};
-use sdl2::ttf::{Font, Sdl2TtfContext};
 use sdl2::video::WindowContext;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long everything holds still after Pacman is caught, before the death animation
+pub(super) const DEATH_FREEZE_MS: u32 = 1000;
+/// How long everything holds still while the points for an eaten ghost are shown
+const GHOST_EATEN_FREEZE_MS: u32 = 500;
+/// How long the ghost house door stays open after a ghost goes through it
+const DOOR_OPEN_MS: u32 = 250;
+/// How long a notice such as "MAZE LOADED" stays on screen
+const TOAST_MS: u128 = 2500;
+/// How long the maze flashes white after it is cleared, before the next level
+pub(super) const LEVEL_FLASH_MS: u32 = 1600;
+/// Time between switches of the flash
+const FLASH_STEP_MS: u32 = 200;
+/// Height of the frame-time graph; it spans two frames' worth of time
+const FRAME_GRAPH_HEIGHT: u32 = 64;
+const FRAME_GRAPH_COLOR: Color = Color::RGB(96, 96, 96);
 
 pub struct Game<'a> {
-    board: Board<'a>,
-    pacman: Pacman<'a>,
-    ghosts_manager: GhostManager<'a>,
+    pub(super) board: Board<'a>,
+    pub(super) pacman: Pacman,
+    pub(super) ghosts_manager: GhostManager,
+    pub(super) fruit: Fruit,
+    /// Sprite atlas shared by the board, Pac-Man, the ghosts and the fruit
+    assets: AssetManager<'a>,
+    /// Fruits already put out on this level
+    pub(super) fruits_shown: usize,
+    rules_mode: RulesMode,
+    pub(super) difficulty: Difficulty,
+    /// The difficulty to go back to when kids mode is switched off on the menu
+    pub(super) grown_up_difficulty: Difficulty,
+    pub(super) practice: Option<Practice>,
+    /// Clock of an endless survival run
+    pub(super) survival: Option<Survival>,
+    /// Ghosts chase from the start and move faster, and pellets pay double
+    pub(super) ghost_rush: bool,
+    /// Percent of full speed; slowed runs never become the best replay
+    game_speed: u32,
+    /// Pac-Man stops briefly for each dot he eats, as in the arcade
+    arcade_stalls: bool,
+    /// Cheat prompt opened with the backquote key in debug mode
+    console: Console,
+    console_texture: GameTexture<'a>,
+    console_text: String,
+    console_reply_texture: GameTexture<'a>,
+    console_reply_text: String,
+    /// Codes typed on the menu and the toggles they unlocked
+    cheat_codes: CheatCodes,
+    /// Level new games start on, picked with the level select cheat
+    start_level: u16,
+    /// Title menu of windowed games; coin-op, ranked and versus games have none
+    pub(super) menu: Option<Menu>,
+    pub(super) menu_textures: Vec<GameTexture<'a>>,
+    /// The lines the menu textures show, each with whether it had the focus
+    pub(super) menu_text: Vec<(String, bool)>,
 
-    actual_map: [BlockType; BOARD_HEIGHT * BOARD_WIDTH],
-    mover: Vec<Direction>,
+    pub(super) actual_map: [BlockType; BOARD_HEIGHT * BOARD_WIDTH],
+    /// Exits of every tile of `actual_map`, rebuilt whenever the layout is reloaded
+    tile_graph: TileGraph,
+    /// Dots in a fresh copy of the maze
+    food_total: usize,
+    pub(super) mover: InputQueue,
+    /// Where the second player last steered Blinky; `None` while Blinky is computer-controlled
+    blinky_mover: Option<Direction>,
 
     game_state: GameState,
-    timer_system: TimerSystem,
+    pub(super) power_state: PowerState,
+    rng: GameRng,
+    pub(super) timer_system: TimerSystem,
     collision_system: CollisionSystem,
-    scoring_system: ScoringSystem,
+    /// What happened this update, for particles, stats and the HUD to react to
+    pub(super) events: EventBus,
+    /// The events the last update handed out, for `step`
+    stepped_events: Vec<GameEvent>,
+    /// Sounds asked for since the window last collected them
+    sounds: Vec<Sound>,
+    pub(super) music: bool,
+    sound_indicators: SoundIndicators,
+    announcer: Announcer,
+    pub(super) scoring_system: ScoringSystem,
+    pub(super) session_stats: SessionStats,
+    telemetry: Telemetry,
+    renderer: Renderer,
+    perf_stats: PerfStats,
+    /// Per-system simulation timings for `--bench-sim`
+    system_timings: SystemTimings,
+    frame_history: FrameHistory,
+    particles: ParticleSystem,
+    screen_effects: ScreenEffects,
+    /// Darkness over the maze away from Pac-Man, from `--flashlight`
+    flashlight: Option<Flashlight>,
+    perf_refresh: Instant,
+    show_perf: bool,
+    /// Plot recent frame times under the perf readout
+    show_frame_graph: bool,
 
-    ready_texture: GameTexture<'a>,
-    game_over_texture: GameTexture<'a>,
-    paused_texture: GameTexture<'a>,
+    pub(super) ready_texture: GameTexture<'a>,
+    pub(super) game_over_texture: GameTexture<'a>,
+    pub(super) play_again_texture: GameTexture<'a>,
+    pub(super) play_again_pad_texture: GameTexture<'a>,
+    pub(super) insert_coin_texture: GameTexture<'a>,
+    credits_texture: GameTexture<'a>,
+    /// Credits `credits_texture` was last rendered for
+    credits_text: Option<u32>,
+    pub(super) paused_texture: GameTexture<'a>,
+    quit_texture: GameTexture<'a>,
+    controller_texture: GameTexture<'a>,
+    /// Escape was pressed once during a run; a second press quits
+    confirm_quit: bool,
+    /// The gamepad went away mid-run; play stays paused until it is back or Space is pressed
+    controller_lost: bool,
+    pub(super) milestone_texture: GameTexture<'a>,
+    pub(super) summary_level_texture: GameTexture<'a>,
+    pub(super) summary_score_texture: GameTexture<'a>,
+    pub(super) summary_date_texture: GameTexture<'a>,
+    pub(super) continue_texture: GameTexture<'a>,
+    pub(super) continue_pad_texture: GameTexture<'a>,
+    /// Prompts name gamepad buttons rather than keys
+    pub(super) gamepad_prompts: bool,
+    perf_fps_texture: GameTexture<'a>,
+    perf_timing_texture: GameTexture<'a>,
+    caption_texture: GameTexture<'a>,
+    caption_text: Option<String>,
+    freeze_score_texture: GameTexture<'a>,
+    /// Points `freeze_score_texture` was last rendered for
+    freeze_score_text: u32,
+    dots_texture: GameTexture<'a>,
+    dots_text: String,
+    level_texture: GameTexture<'a>,
+    /// Level `level_texture` was last rendered for; 0 until the first draw
+    level_text: u16,
+    indicator_texture: GameTexture<'a>,
+    /// Indicator `indicator_texture` was last rendered for
+    indicator_shown: Option<Indicator>,
+    auto_texture: GameTexture<'a>,
+    kids_texture: GameTexture<'a>,
+    toast_texture: GameTexture<'a>,
+    /// Notice waiting to be rendered, then when the rendered one went up
+    toast_text: Option<String>,
+    toast_since: Option<Instant>,
+    pub(super) bonus_textures: [GameTexture<'a>; 3],
 
-    level: u16,
+    pub(super) level: u16,
+    /// Time left in the freeze frame that opens the death sequence
+    pub(super) death_freeze_ms: u32,
+    /// Time left in the pause after eating a ghost, and the points shown where it was caught
+    pub(super) ghost_freeze_ms: u32,
+    freeze_score: (Position, u32),
+    door_open_ms: u32,
+    /// Time spent playing the current level, for the clear bonus
+    pub(super) level_ms: u32,
+    pub(super) bonus_tally: Option<BonusTally>,
+    /// Time the cleared maze has been flashing
+    pub(super) level_flash_ms: u32,
+    /// Updates played so far, not counting paused ones; replays are keyed on it
+    tick: u32,
+    pub(super) recording: Option<Replay>,
+    /// Where a finished run is kept if it beats the best so far; headless games keep nothing
+    /// unless given a path
+    best_replay_path: Option<String>,
+    /// Where an unfinished run is saved on shutdown for `--resume`; likewise headless games
+    /// save nothing unless given a path
+    save_game_path: Option<String>,
+    playback: Option<Playback>,
+    pub(super) rewind: Option<RewindBuffer>,
+    rewinding: bool,
 
-    #[allow(dead_code)]
-    is_to_scatter_sound: bool,
-    is_to_waka_sound: bool,
-    is_to_death_sound: bool,
+    palette: Palette,
+    maze_colors: MazeColors,
+    ghost_patterns: bool,
+    show_dots: bool,
+    theme: Theme,
+    debug: bool,
+    /// Inputs arrive from a netplay session rather than the keyboard
+    pub(super) networked: bool,
+    /// The built-in bot steers Pac-Man
+    pub(super) autoplay: bool,
+    /// Hand Pac-Man to the bot after this long without a key press
+    idle_takeover_ms: Option<u32>,
+    idle_ms: u32,
+    /// The bot is only playing because the player went idle, and stops at the next key press
+    idle_autoplay: bool,
+    /// Coins inserted and not yet spent; `None` outside coin mode, where play is free
+    pub(super) credits: Option<u32>,
 }
 
 impl<'a> Game<'a> {
     pub fn new(
         texture_creator: &'a TextureCreator<WindowContext>,
-        ttf_context: &'a Sdl2TtfContext,
+        fonts: &Fonts,
+        config: &Config,
+        theme: &Theme,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let board = Board::new(texture_creator, ttf_context)?;
-        let mut pacman = Pacman::new(texture_creator)?;
+        let mut game = Game::headless(config, theme);
+        game.assets.set_placeholders(config.placeholder_assets);
+        game.assets.set_shapes_only(config.no_assets);
+        game.load_textures(texture_creator, fonts)?;
+        game.set_best_replay_path(BEST_REPLAY_PATH);
+        game.set_save_game_path(SAVE_GAME_PATH);
+        // Coin-op and ranked games go straight into play
+        if !config.coins && !config.ranked {
+            game.menu = Some(Menu::new(config));
+        }
+        if game.is_comparable_run() {
+            game.recording = Some(Replay::new(ReplayMetadata::new(config, game.get_seed())));
+        }
+        if config.ranked {
+            // Ranked runs are audited through their random draws and cannot be rewound
+            game.rng.set_tracing(true);
+        } else if !config.survival {
+            // Rewinding could not take back ghosts that joined a survival run
+            game.rewind = Some(RewindBuffer::new(REWIND_CAPACITY));
+        }
+        Ok(game)
+    }
 
-        // Use ghosts manager for all ghosts
-        let mut ghost_manager = GhostManager::new(texture_creator)?;
+    /// A game with no window or textures, for stepping the simulation in tests and tools.
+    ///
+    /// Every `update` advances the game by `TICK_MS` regardless of wall-clock time.
+    pub fn headless(config: &Config, theme: &Theme) -> Self {
+        let mut board = Board::new();
+        board.set_locale(match config.locale {
+            Some(ref tag) => Locale::from_tag(tag),
+            None => Locale::from_env(),
+        });
+        let mut pacman = Pacman::new(theme);
+        let mut ghost_manager = Game::new_ghosts(config, theme);
 
+        if let Some(ref sketch) = config.custom_maze {
+            board.set_custom_maze(sketch.clone());
+        }
         let mut actual_map = [BlockType::Nothing; BOARD_HEIGHT * BOARD_WIDTH];
         board.copy_board(&mut actual_map);
+        board.count_food(&actual_map);
 
         // Reset positions using ghosts manager
         let pacman_start = board.reset_position(crate::board::EntityType::PacMan);
@@ -58,375 +294,3260 @@ impl<'a> Game<'a> {
 
         ghost_manager.reset_all_ghost_positions(&board);
 
-        let font = ttf_context.load_font("assets/emulogic.ttf", 24)?;
-        let mut ready_texture = GameTexture::new();
-        ready_texture.load_from_rendered_text(texture_creator, "READY!", &font, YELLOW)?;
-
-        let mut game_over_texture = GameTexture::new();
-        game_over_texture.load_from_rendered_text(texture_creator, "GAME  OVER", &font, RED)?;
-
-        let mut paused_texture = GameTexture::new();
-        paused_texture.load_from_rendered_text(texture_creator, "PAUSED", &font, RED)?;
-
+        let mut first_level = config.difficulty.level_params(1);
+        if config.ghost_rush {
+            first_level = first_level.rushed();
+        }
+        board.restore(0, config.difficulty.starting_lives(), 0);
+        ghost_manager.set_speed_percent(first_level.ghost_speed_percent);
         let mut timer_system = TimerSystem::new();
+        timer_system.apply_level_params(first_level);
         timer_system.set_start_ticks(2500); // 2.5 seconds before game starts
         timer_system.start_game();
 
-        Ok(Game {
+        let mut game = Game {
             board,
             pacman,
             ghosts_manager: ghost_manager,
+            fruit: Fruit::new(),
+            assets: AssetManager::new(),
+            fruits_shown: 0,
+            rules_mode: config.rules_mode,
+            difficulty: config.difficulty,
+            grown_up_difficulty: match config.difficulty {
+                Difficulty::Kids => Difficulty::Normal,
+                difficulty => difficulty,
+            },
+            practice: config.practice,
+            survival: config.survival.then(Survival::new),
+            ghost_rush: config.ghost_rush,
+            game_speed: config.game_speed,
+            arcade_stalls: config.arcade_quirks,
+            console: Console::new(),
+            console_texture: GameTexture::new(),
+            console_text: String::new(),
+            console_reply_texture: GameTexture::new(),
+            console_reply_text: String::new(),
+            cheat_codes: CheatCodes::new(),
+            start_level: 1,
+            menu: None,
+            menu_textures: Vec::new(),
+            menu_text: Vec::new(),
+            food_total: actual_map.iter().filter(|block| block.is_food()).count(),
+            tile_graph: TileGraph::new(&actual_map),
             actual_map,
-            mover: vec![Direction::Right],
+            mover: InputQueue::new(Direction::Right, config.input_buffer),
+            blinky_mover: config.coop.then_some(Direction::Nowhere),
 
-            game_state: GameState::Ready,
+            game_state: if config.coins {
+                GameState::InsertCoin
+            } else {
+                GameState::Ready
+            },
+            power_state: PowerState::new(),
+            rng: match config.seed {
+                Some(seed) => GameRng::new(seed),
+                None => GameRng::from_entropy(),
+            },
 
             timer_system,
 
             collision_system: CollisionSystem::new(),
-            scoring_system: ScoringSystem::new(),
+            events: EventBus::new(),
+            stepped_events: Vec::new(),
+            sounds: Vec::new(),
+            music: config.music,
+            sound_indicators: SoundIndicators::new(config.sound_indicators),
+            announcer: Announcer::new(config.announce, config.announce_command.as_deref()),
+            scoring_system: ScoringSystem::with_rules(Game::run_scoring_rules(config)),
+            session_stats: SessionStats::new(),
+            telemetry: Telemetry::new(config.telemetry),
+            renderer: Renderer::new(),
+            perf_stats: PerfStats::new(),
+            system_timings: SystemTimings::new(),
+            frame_history: FrameHistory::new(),
+            particles: ParticleSystem::new(config.particles),
+            screen_effects: ScreenEffects::new(config.screen_effects),
+            flashlight: config.flashlight.map(Flashlight::new),
+            perf_refresh: Instant::now(),
+            show_perf: false,
+            show_frame_graph: false,
 
-            ready_texture,
-            game_over_texture,
-            paused_texture,
+            ready_texture: GameTexture::new(),
+            game_over_texture: GameTexture::new(),
+            play_again_texture: GameTexture::new(),
+            play_again_pad_texture: GameTexture::new(),
+            insert_coin_texture: GameTexture::new(),
+            credits_texture: GameTexture::new(),
+            credits_text: None,
+            paused_texture: GameTexture::new(),
+            quit_texture: GameTexture::new(),
+            controller_texture: GameTexture::new(),
+            confirm_quit: false,
+            controller_lost: false,
+            milestone_texture: GameTexture::new(),
+            summary_level_texture: GameTexture::new(),
+            summary_score_texture: GameTexture::new(),
+            summary_date_texture: GameTexture::new(),
+            continue_texture: GameTexture::new(),
+            continue_pad_texture: GameTexture::new(),
+            gamepad_prompts: false,
+            perf_fps_texture: GameTexture::new(),
+            perf_timing_texture: GameTexture::new(),
+            caption_texture: GameTexture::new(),
+            caption_text: None,
+            freeze_score_texture: GameTexture::new(),
+            freeze_score_text: 0,
+            dots_texture: GameTexture::new(),
+            dots_text: String::new(),
+            level_texture: GameTexture::new(),
+            level_text: 0,
+            indicator_texture: GameTexture::new(),
+            indicator_shown: None,
+            auto_texture: GameTexture::new(),
+            kids_texture: GameTexture::new(),
+            toast_texture: GameTexture::new(),
+            toast_text: None,
+            toast_since: None,
+            bonus_textures: std::array::from_fn(|_| GameTexture::new()),
 
             level: 1,
+            death_freeze_ms: 0,
+            ghost_freeze_ms: 0,
+            freeze_score: (Position::new(0, 0), 0),
+            door_open_ms: 0,
+            level_ms: 0,
+            bonus_tally: None,
+            level_flash_ms: 0,
+            tick: 0,
+            recording: None,
+            best_replay_path: None,
+            save_game_path: None,
+            playback: None,
+            rewind: None,
+            rewinding: false,
 
-            is_to_scatter_sound: true,
-            is_to_waka_sound: true,
-            is_to_death_sound: true,
-        })
+            palette: config.palette,
+            maze_colors: config.maze_colors,
+            ghost_patterns: config.ghost_patterns,
+            show_dots: config.dots_hud,
+            theme: theme.clone(),
+            debug: config.debug,
+            networked: false,
+            autoplay: config.bot,
+            idle_takeover_ms: config.idle_takeover.map(|seconds| seconds * 1000),
+            idle_ms: 0,
+            idle_autoplay: false,
+            credits: config.coins.then_some(0),
+        };
+        if let Some(practice) = config.practice {
+            game.start_at_level(practice.level);
+        }
+        game
+    }
+
+    /// Points for a run; a ghost rush pays double for pellets
+    pub(super) fn run_scoring_rules(config: &Config) -> ScoringRules {
+        let mut rules = config.scoring_rules.clone();
+        if config.ghost_rush {
+            rules.pellet = rules.pellet.saturating_mul(2);
+        }
+        rules
+    }
+
+    /// The four ghosts plus any extras, tuned the way `config` asks
+    fn new_ghosts(config: &Config, theme: &Theme) -> GhostManager {
+        let mut ghost_manager = GhostManager::new(theme);
+        ghost_manager.set_personality(&config.ghost_personality);
+        for extra in &config.extra_ghosts {
+            ghost_manager.add_ghost(extra, theme);
+        }
+        ghost_manager.set_arcade_quirks(config.arcade_quirks);
+        ghost_manager.set_direct_chase(config.ghost_rush);
+        ghost_manager.set_trails(config.ghost_trails);
+        ghost_manager
+    }
+
+    fn load_textures(
+        &mut self,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        fonts: &Fonts,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.assets.load(texture_creator, &self.theme)?;
+        let font = fonts.load(&self.theme)?;
+        self.board
+            .load_textures(texture_creator, &self.assets, &font, &self.theme)?;
+        self.ready_texture
+            .load_from_rendered_text(texture_creator, "READY!", &font, YELLOW)?;
+        self.game_over_texture.load_from_rendered_text(
+            texture_creator,
+            "GAME  OVER",
+            &font,
+            RED,
+        )?;
+        self.play_again_texture.load_from_rendered_text(
+            texture_creator,
+            "PRESS ENTER",
+            &font,
+            WHITE,
+        )?;
+        self.play_again_pad_texture.load_from_rendered_text(
+            texture_creator,
+            "PRESS START",
+            &font,
+            WHITE,
+        )?;
+        self.insert_coin_texture.load_from_rendered_text(
+            texture_creator,
+            "INSERT COIN",
+            &font,
+            WHITE,
+        )?;
+        self.paused_texture
+            .load_from_rendered_text(texture_creator, "PAUSED", &font, RED)?;
+        self.quit_texture.load_from_rendered_text(
+            texture_creator,
+            "ESC AGAIN TO QUIT",
+            &font,
+            WHITE,
+        )?;
+        self.controller_texture.load_from_rendered_text(
+            texture_creator,
+            "CONTROLLER DISCONNECTED",
+            &font,
+            WHITE,
+        )?;
+        self.milestone_texture.load_from_rendered_text(
+            texture_creator,
+            "MAX DIFFICULTY",
+            &font,
+            YELLOW,
+        )?;
+        self.continue_texture.load_from_rendered_text(
+            texture_creator,
+            "PRESS SPACE",
+            &font,
+            WHITE,
+        )?;
+        self.continue_pad_texture.load_from_rendered_text(
+            texture_creator,
+            "PRESS A",
+            &font,
+            WHITE,
+        )?;
+        self.auto_texture
+            .load_from_rendered_text(texture_creator, "AUTO", &font, YELLOW)?;
+        self.kids_texture
+            .load_from_rendered_text(texture_creator, "KIDS", &font, CYAN)?;
+
+        Ok(())
+    }
+
+    /// Rebuild every texture from the theme's files after an artist edited them.
+    ///
+    /// Labels drawn on demand are forgotten so they come back in a changed font too.
+    pub fn reload_textures(
+        &mut self,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        fonts: &Fonts,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.load_textures(texture_creator, fonts)?;
+        self.console_text.clear();
+        self.console_reply_text.clear();
+        self.credits_text = None;
+        self.caption_text = None;
+        // No ghost is worth this many points, so the next one is drawn afresh
+        self.freeze_score_text = u32::MAX;
+        self.dots_text.clear();
+        self.menu_text.clear();
+        self.level_text = 0;
+        self.indicator_shown = None;
+        Ok(())
     }
 
     pub fn handle_input(&mut self, keycode: Keycode) {
+        self.idle_ms = 0;
+        if self.idle_autoplay {
+            self.idle_autoplay = false;
+            self.autoplay = false;
+            println!("Player back in control");
+        }
+        if self.console.is_open() {
+            self.console_input(keycode);
+            return;
+        }
+        self.confirm_quit = false;
+        if self.on_menu() && !self.networked {
+            if let Some(cheat) = self.cheat_codes.press(keycode) {
+                self.toggle_cheat(cheat);
+            } else if let Some(level) = cheat_codes::level_for_key(keycode) {
+                if self.cheat_codes.is_unlocked(Cheat::LevelSelect) {
+                    self.select_start_level(level);
+                    return;
+                }
+            }
+        }
+        if states::hooks(&self.game_state).handle_input(self, keycode) {
+            return;
+        }
         match keycode {
-            Keycode::Right | Keycode::D => {
-                self.mover.push(Direction::Right);
+            Keycode::Right | Keycode::D => self.player_input(InputAction::Turn(Direction::Right)),
+            Keycode::Up | Keycode::W => self.player_input(InputAction::Turn(Direction::Up)),
+            Keycode::Left | Keycode::A => self.player_input(InputAction::Turn(Direction::Left)),
+            Keycode::Down | Keycode::S => self.player_input(InputAction::Turn(Direction::Down)),
+            Keycode::C => self.insert_coin(),
+            Keycode::Tab if self.is_between_games() => self.toggle_kids_mode(),
+            Keycode::L => self.steer_ghost(Direction::Right),
+            Keycode::I => self.steer_ghost(Direction::Up),
+            Keycode::J => self.steer_ghost(Direction::Left),
+            Keycode::K => self.steer_ghost(Direction::Down),
+            Keycode::Space => match self.game_state {
+                GameState::Playing => self.set_state(GameState::Paused),
+                GameState::Paused => {
+                    self.controller_lost = false;
+                    self.resume();
+                }
+                GameState::Ready | GameState::Milestone => {
+                    self.player_input(InputAction::Continue);
+                }
+                _ => {}
+            },
+            Keycode::F1 => {
+                self.palette = self.palette.next();
+                println!("Palette: {}", self.palette.name());
             }
-            Keycode::Up | Keycode::W => {
-                self.mover.push(Direction::Up);
+            Keycode::F2 => {
+                self.ghost_patterns = !self.ghost_patterns;
+                println!("Ghost patterns: {}", self.ghost_patterns);
             }
-            Keycode::Left | Keycode::A => {
-                self.mover.push(Direction::Left);
+            // Cycles through the frame timing readout, then the readout with its graph
+            Keycode::F3 => {
+                (self.show_perf, self.show_frame_graph) =
+                    match (self.show_perf, self.show_frame_graph) {
+                        (false, _) => (true, false),
+                        (true, false) => (true, true),
+                        (true, true) => (false, false),
+                    };
             }
-            Keycode::Down | Keycode::S => {
-                self.mover.push(Direction::Down);
+            Keycode::F10 => {
+                self.show_dots = !self.show_dots;
+                println!("Dots counter: {}", self.show_dots);
             }
-            Keycode::Space => match self.game_state {
-                GameState::Playing => {
-                    self.game_state = GameState::Paused;
-                    self.timer_system.pause_all();
-                    println!("Game paused");
-                }
-                GameState::Paused => {
-                    self.game_state = GameState::Playing;
-                    self.timer_system.unpause_all();
-                    println!("Game resumed");
+            Keycode::M => {
+                self.music = !self.music;
+                println!("Music: {}", self.music);
+            }
+            Keycode::V => {
+                let enabled = !self.sound_indicators.is_enabled();
+                self.sound_indicators.set_enabled(enabled);
+                println!("Sound indicators: {}", enabled);
+            }
+            Keycode::F12 => {
+                self.particles.set_enabled(!self.particles.is_enabled());
+                println!("Particles: {}", self.particles.is_enabled());
+            }
+            Keycode::E => {
+                let enabled = !self.screen_effects.is_enabled();
+                self.screen_effects.set_enabled(enabled);
+                println!("Screen shake and flash: {}", enabled);
+            }
+            Keycode::F11 if self.playback.is_none() && !self.networked => {
+                self.idle_autoplay = false;
+                self.set_autoplay(!self.autoplay);
+                println!("CPU demo: {}", self.autoplay);
+            }
+            // Held to run time backwards; playback always runs forwards
+            Keycode::Backspace => {
+                self.rewinding = self.rewind.is_some() && self.playback.is_none();
+                // A rewound run is no longer one that could be played straight through
+                if self.rewinding && self.recording.take().is_some() {
+                    println!("Rewound runs are not recorded");
+                    self.show_toast("RUN NO LONGER RECORDED");
                 }
-                _ => {
-                    if self.game_state == GameState::Ready {
-                        self.start_game();
-                    }
+            }
+            Keycode::F4 => {
+                let stamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_millis())
+                    .unwrap_or(0);
+                let path = format!("snapshot_{}.txt", stamp);
+                match self.snapshot().save(&path) {
+                    Ok(()) => println!("Saved snapshot to {}", path),
+                    Err(e) => println!("Failed to save snapshot: {}", e),
                 }
+            }
+            Keycode::Backquote if self.debug && !self.networked => self.console.toggle(),
+            Keycode::F8 if self.debug => match self.save_game() {
+                Some(saved) => match saved.save(QUICK_SAVE_PATH) {
+                    Ok(()) => println!("Quick-saved to {}", QUICK_SAVE_PATH),
+                    Err(e) => println!("Failed to quick-save: {}", e),
+                },
+                None => println!("Nothing to quick-save right now"),
             },
+            Keycode::F9 if self.debug && !self.networked => {
+                match Snapshot::load(QUICK_SAVE_PATH).and_then(|saved| self.resume_game(&saved)) {
+                    Ok(()) => println!("Quick-loaded {}", QUICK_SAVE_PATH),
+                    Err(e) => println!("Failed to quick-load: {}", e),
+                }
+            }
             _ => {}
         }
+    }
+
+    /// Between games, or on the countdown before the first update of one, where cheat codes
+    /// are listened for
+    fn on_menu(&self) -> bool {
+        self.is_between_games() || (self.game_state == GameState::Ready && self.tick == 0)
+    }
+
+    /// On the menu, game over or insert coin screen, where the next game can be set up
+    fn is_between_games(&self) -> bool {
+        matches!(
+            self.game_state,
+            GameState::GameOver | GameState::InsertCoin | GameState::Menu
+        )
+    }
+
+    /// Switch the next game into kids mode, or back to the difficulty it had before
+    fn toggle_kids_mode(&mut self) {
+        self.difficulty = match self.difficulty {
+            Difficulty::Kids => self.grown_up_difficulty,
+            _ => Difficulty::Kids,
+        };
+        let enabled = self.difficulty == Difficulty::Kids;
+        self.show_toast(if enabled {
+            "KIDS MODE ON"
+        } else {
+            "KIDS MODE OFF"
+        });
+        println!("Kids mode: {}", enabled);
+    }
 
-        if self.mover.len() > 2 {
-            self.mover.remove(1);
+    /// Announce a cheat switched on or off with a sound, a flash and a notice
+    fn toggle_cheat(&mut self, cheat: Cheat) {
+        let enabled = self.cheat_codes.is_unlocked(cheat);
+        if cheat == Cheat::LevelSelect && !enabled {
+            self.start_level = 1;
         }
+        self.sounds.push(Sound::centered(SoundCue::Unlock));
+        self.screen_effects.flash();
+        let state = if enabled { "ON" } else { "OFF" };
+        self.show_toast(&format!("{} {}", cheat.name(), state));
+        println!("Cheat: {} {}", cheat.name(), state);
     }
 
-    pub fn update(&mut self) -> bool {
-        match self.game_state {
-            GameState::Ready => {
-                if self.timer_system.get_game_ticks() >= self.timer_system.get_start_ticks() as u128
-                {
-                    self.start_game();
-                }
+    /// Start new games on `level`, and jump there now if a game is about to begin.
+    ///
+    /// Replays always start on level 1, so a game begun elsewhere is not recorded.
+    fn select_start_level(&mut self, level: u16) {
+        self.start_level = level;
+        self.show_toast(&format!("START LEVEL {}", level));
+        if self.game_state == GameState::Ready && self.practice.is_none() {
+            if self.recording.take().is_some() {
+                println!("Replay recording stopped for level select");
             }
-            GameState::Playing => {
-                if self.pacman.is_alive() {
-                    if !self.is_level_completed() {
-                        self.update_game_logic();
-                    } else {
-                        self.game_state = GameState::LevelComplete;
-                        println!("Level {} completed!", self.level);
-                    }
-                } else {
-                    self.game_state = GameState::PacmanDeath;
-                    println!("Pacman died!");
-                }
+            self.start_at_level(level);
+            self.reset_for_next_life();
+        }
+    }
+
+    /// Editing keys while the console is open; nothing reaches the game
+    fn console_input(&mut self, keycode: Keycode) {
+        match keycode {
+            Keycode::Backquote | Keycode::Escape => self.console.close(),
+            Keycode::Backspace => self.console.backspace(),
+            Keycode::Return | Keycode::KpEnter => {
+                let line = self.console.submit();
+                self.run_console_command(&line);
             }
-            GameState::PacmanDeath => {
-                if self.pacman.is_dead_animation_ended() {
-                    if self.board.get_lives() > 0 {
-                        // Reset positions using entity manager
-                        let pacman_start =
-                            self.board.reset_position(crate::board::EntityType::PacMan);
-                        self.pacman.set_position(pacman_start);
+            _ => {}
+        }
+    }
 
-                        self.ghosts_manager.reset_all_ghost_positions(&self.board);
+    /// Name gamepad buttons in the on-screen prompts instead of keys
+    pub fn show_gamepad_prompts(&mut self, gamepad: bool) {
+        self.gamepad_prompts = gamepad;
+    }
 
-                        self.game_state = GameState::Ready;
-                        self.reset_game_for_death();
-                    } else {
-                        self.game_state = GameState::GameOver;
-                        println!("Game Over!");
-                    }
-                }
-            }
-            GameState::LevelComplete => {
-                // TODO: Map flashing animation
-                self.level += 1;
-                self.update_difficulty();
+    /// Text typed on the keyboard, which only the open console takes
+    pub fn type_text(&mut self, text: &str) {
+        if self.console.is_open() {
+            self.console.type_text(text);
+        }
+    }
 
-                // Reset positions using entity manager
-                let pacman_start = self.board.reset_position(crate::board::EntityType::PacMan);
-                self.pacman.set_position(pacman_start);
+    /// Sounds to play for the updates run since the last call
+    pub fn take_sounds(&mut self) -> Vec<Sound> {
+        std::mem::take(&mut self.sounds)
+    }
 
-                self.ghosts_manager.reset_all_ghost_positions(&self.board);
+    /// What should be playing behind the current screen, if music is on
+    pub fn music_track(&self) -> Option<Track> {
+        self.music.then(|| Track::for_state(&self.game_state))
+    }
 
-                self.game_state = GameState::Ready;
-                self.timer_system.set_start_ticks(2500);
-                self.timer_system.start_game();
-                println!("Starting level {}", self.level);
-            }
-            GameState::GameOver => {}
-            GameState::Paused => {}
+    /// The display options as toggled in game, to carry over to the next game or run
+    pub fn settings(&self) -> Settings {
+        Settings {
+            palette: self.palette,
+            ghost_patterns: self.ghost_patterns,
+            dots_hud: self.show_dots,
+            particles: self.particles.is_enabled(),
+            screen_effects: self.screen_effects.is_enabled(),
+            music: self.music,
+            sound_indicators: self.sound_indicators.is_enabled(),
+            difficulty: self.difficulty,
         }
-
-        true
     }
 
-    pub fn draw(
-        &mut self,
-        canvas: &mut WindowCanvas,
-        texture_creator: &'a TextureCreator<WindowContext>,
-        font: &Font,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.board.set_score(texture_creator, font)?;
-        self.board.set_high_score(texture_creator, font)?;
+    pub fn is_console_open(&self) -> bool {
+        self.console.is_open()
+    }
 
-        self.board.draw(canvas, &self.actual_map)?;
+    /// Carry out a console line and put the outcome up as the console's reply.
+    ///
+    /// A cheat makes the run impossible to replay, so recording stops at the first one.
+    fn run_console_command(&mut self, line: &str) {
+        let command = match ConsoleCommand::parse(line) {
+            Ok(command) => command,
+            Err(e) => {
+                self.console.set_reply(e);
+                return;
+            }
+        };
+        if command != ConsoleCommand::Help && self.recording.take().is_some() {
+            println!("Replay recording stopped for the debug console");
+        }
 
-        match self.game_state {
-            GameState::Ready => {
-                self.ready_texture
-                    .render(canvas, 11 * 24, 20 * 24 - 5, None)?;
+        let reply = match command {
+            ConsoleCommand::Level(level) => {
+                self.fruit.remove();
+                self.fruits_shown = 0;
+                self.level_ms = 0;
+                self.start_at_level(level);
+                self.reset_for_next_life();
+                self.set_state(GameState::Ready);
+                format!("Level {}", self.level)
             }
-            GameState::GameOver => {
-                self.game_over_texture
-                    .render(canvas, 9 * 24, 20 * 24 - 5, None)?;
-                return Ok(());
+            ConsoleCommand::Score(score) => {
+                self.board.overwrite_score(score);
+                format!("Score {}", score)
             }
-            GameState::Paused => {
-                self.paused_texture
-                    .render(canvas, 11 * 24, 20 * 24 - 5, None)?;
+            ConsoleCommand::Lives(lives) => {
+                self.board.set_lives(lives);
+                format!("Lives {}", lives)
             }
-            _ => {}
+            ConsoleCommand::Kill(ghost_type) => match self.ghosts_manager.kill(ghost_type) {
+                0 => format!("No {} to kill", ghost_type.name()),
+                _ => format!("Killed {}", ghost_type.name()),
+            },
+            ConsoleCommand::Energize => {
+                self.energize();
+                "Energized".to_string()
+            }
+            ConsoleCommand::Noclip => {
+                let enabled = !self.pacman.is_noclip();
+                self.pacman.set_noclip(enabled);
+                format!("Noclip {}", if enabled { "on" } else { "off" })
+            }
+            ConsoleCommand::Help => ConsoleCommand::HELP.to_string(),
+        };
+        self.console.set_reply(reply);
+    }
+
+    pub fn handle_key_up(&mut self, keycode: Keycode) {
+        if keycode == Keycode::Backspace {
+            self.rewinding = false;
         }
+    }
 
-        if self.game_state != GameState::LevelComplete {
-            // Use ghosts manager to draw all ghosts
-            self.ghosts_manager.draw_all_ghosts(
-                canvas,
-                self.pacman.is_energized(),
-                self.timer_system.get_ghost_ticks(),
-                self.timer_system.get_ghost_timer_target(),
-            )?;
+    /// Apply and record an action from the keyboard; ignored during playback and netplay
+    fn player_input(&mut self, action: InputAction) {
+        if self.playback.is_some() || self.networked {
+            return;
+        }
+        if let Some(ref mut recording) = self.recording {
+            recording.record_input(self.tick, action);
+        }
+        self.apply_input(action);
+    }
 
-            self.draw_little_score();
+    /// Second player's input for Blinky, from IJKL or a gamepad; ignored outside co-op
+    pub fn steer_ghost(&mut self, direction: Direction) {
+        if self.blinky_mover.is_some() {
+            self.player_input(InputAction::Steer(direction));
         }
+    }
 
-        self.pacman.draw(canvas)?;
+    fn apply_input(&mut self, action: InputAction) {
+        match action {
+            InputAction::Turn(direction) => self.queue_direction(direction),
+            InputAction::Steer(direction) => {
+                if let Some(ref mut wanted) = self.blinky_mover {
+                    *wanted = direction;
+                }
+            }
+            InputAction::Continue => match self.game_state {
+                GameState::Ready => self.set_state(GameState::Playing),
+                GameState::Milestone => self.set_state(GameState::Ready),
+                _ => {}
+            },
+        }
+    }
 
-        Ok(())
+    /// Take input only through `apply_inputs`, in lockstep with another instance.
+    ///
+    /// Rewinding and quick-loading are off, since they would only happen on one side.
+    pub fn start_netplay(&mut self) {
+        self.networked = true;
+        self.menu = None;
+        self.rewind = None;
+        self.rewinding = false;
     }
 
-    fn start_game(&mut self) {
-        if self.game_state == GameState::Ready {
-            if self.is_level_completed() {
-                self.board.copy_board(&mut self.actual_map);
+    /// Apply and record actions both netplay peers agreed on for the coming update
+    pub fn apply_inputs(&mut self, actions: &[InputAction]) {
+        for &action in actions {
+            if let Some(ref mut recording) = self.recording {
+                recording.record_input(self.tick, action);
             }
+            self.apply_input(action);
+        }
+    }
 
-            self.pacman.change_energy_status(false);
-            self.ghosts_manager.reset_all_ghost_life_statements();
-            self.ghosts_manager.reset_all_ghost_facing();
-            self.pacman.reset_current_living_frame();
+    /// Drive the game from a recorded replay instead of the keyboard
+    pub fn start_playback(&mut self, replay: Replay) {
+        println!(
+            "Playing back {}'s run from {} ({} points)",
+            replay.metadata.player,
+            replay.metadata.date.to_iso(),
+            replay.metadata.final_score
+        );
+        self.recording = None;
+        let playback = Playback::new(replay);
+        self.rng.set_tracing(playback.is_audited());
+        self.playback = Some(playback);
+    }
 
-            self.timer_system.restart_ghost_timer();
-            self.timer_system.start_ghost_timing();
+    /// Whether the run can be held up against others for the best replay; practice, kids mode,
+    /// survival and the ghost rush play by other rules, and the bot's turns are never recorded
+    fn is_comparable_run(&self) -> bool {
+        !self.autoplay
+            && self.practice.is_none()
+            && self.difficulty.counts_for_high_scores()
+            && self.survival.is_none()
+            && !self.ghost_rush
+    }
 
-            self.game_state = GameState::Playing;
-            println!("Game started!");
+    /// Store the finished run as the best replay if it beat the previous best
+    pub(super) fn finish_recording(&mut self) {
+        let mut replay = match self.recording.take() {
+            Some(replay) => replay,
+            None => return,
+        };
+        replay.metadata.final_score = self.board.get_score();
+        if self.game_speed < 100 {
+            println!(
+                "Runs at {}% speed are not kept as the best replay",
+                self.game_speed
+            );
+            return;
+        }
+        let Some(ref path) = self.best_replay_path else {
+            return;
+        };
+        match replay.save_if_best(path) {
+            Ok(true) => println!("New high score! Replay saved to {}", path),
+            Ok(false) => {}
+            Err(e) => println!("Failed to save replay: {}", e),
         }
     }
 
-    fn update_game_logic(&mut self) {
-        self.clock();
-        self.update_positions();
-        self.food_collision();
-        self.entity_collisions();
+    /// Buffer a turn for Pacman; only the latest pending turn is kept
+    pub fn queue_direction(&mut self, direction: Direction) {
+        self.mover.push(direction);
     }
 
-    fn clock(&mut self) {
-        if self.timer_system.update_ghost_timing() {
-            // Ghost mode changed, check if we need to end energizer
-            if !self.timer_system.is_scatter_mode() && self.pacman.is_energized() {
-                self.pacman.change_energy_status(false);
+    /// Updates run so far, not counting paused ones
+    pub fn get_tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.rng.get_seed()
+    }
+
+    pub fn get_game_state(&self) -> GameState {
+        self.game_state.clone()
+    }
+
+    pub fn get_level(&self) -> u16 {
+        self.level
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.board.get_score()
+    }
+
+    pub fn get_lives(&self) -> i8 {
+        self.board.get_lives()
+    }
+
+    pub fn get_map(&self) -> &[BlockType] {
+        &self.actual_map
+    }
+
+    pub fn get_pacman_position(&self) -> Position {
+        self.pacman.get_position()
+    }
+
+    /// Ghost positions in manager order: Blinky, Inky, Pinky and Clyde
+    pub fn get_ghost_positions(&self) -> Vec<Position> {
+        self.ghosts_manager.get_ghost_positions()
+    }
+
+    /// Ghost directions and modes in manager order
+    pub fn get_ghost_states(&self) -> Vec<GhostState> {
+        self.ghosts_manager.get_ghost_states()
+    }
+
+    /// Apply `actions`, run one update and return the events it produced, oldest first.
+    ///
+    /// For bots, tests and frontends that drive a headless game without SDL input. Actions
+    /// are recorded like netplay inputs.
+    pub fn step(&mut self, actions: &[InputAction]) -> Vec<GameEvent> {
+        self.apply_inputs(actions);
+        self.update();
+        std::mem::take(&mut self.stepped_events)
+    }
+
+    pub fn update(&mut self) -> bool {
+        profile_scope!("update");
+        self.stepped_events.clear();
+        if self.console.is_open() {
+            // Everything holds still while a command is typed
+            return true;
+        }
+        let paused = self.game_state == GameState::Paused;
+        if self.rewinding && !paused {
+            self.rewind_step();
+            return true;
+        }
+        if !paused {
+            let actions = match self.playback {
+                Some(ref mut playback) => playback.take_inputs(self.tick),
+                None => Vec::new(),
+            };
+            for action in actions {
+                self.apply_input(action);
+            }
+            self.check_idle();
+            if self.autoplay {
+                self.autoplay_input();
             }
         }
-    }
 
-    fn update_positions(&mut self) {
-        // Use entity manager to update all ghost positions
-        self.ghosts_manager.update_all_ghosts(
-            &self.actual_map,
-            &self.pacman,
-            self.timer_system.is_scatter_mode(),
+        self.timer_system.advance(TICK_MS);
+
+        states::hooks(&self.game_state).update(self);
+
+        if !paused {
+            self.check_extra_life();
+            self.dispatch_events();
+            self.watch_sounds();
+            self.particles.update(TICK_MS);
+            self.screen_effects.update(TICK_MS);
+            if let Some(ref mut flashlight) = self.flashlight {
+                flashlight.update(TICK_MS);
+            }
+            self.audit_rng();
+            self.tick += 1;
+            self.capture_rewind_frame();
+        }
+        if self.controller_lost {
+            self.pause();
+        }
+        self.announcer.watch(
+            &self.game_state,
+            self.level,
+            self.board.get_lives(),
+            self.board.get_score(),
         );
+        true
+    }
 
-        self.pacman.update_pos(&mut self.mover, &self.actual_map);
+    /// Move to `next`, running the hooks for the state being left and the one entered.
+    ///
+    /// Restoring a save or rewinding puts the state back without them, since the timers and
+    /// actors come back from the save as they were.
+    pub(super) fn set_state(&mut self, next: GameState) {
+        states::hooks(&self.game_state).on_exit(self);
+        let previous = std::mem::replace(&mut self.game_state, next);
+        states::hooks(&self.game_state).on_enter(self, &previous);
     }
 
-    fn food_collision(&mut self) {
-        match self.pacman.food_collision(&mut self.actual_map) {
-            0 => {
-                self.board.score_increase(0);
-                // TODO: Play waka sound
-            }
-            1 => {
-                self.board.score_increase(1);
-                self.pacman.change_energy_status(true);
-                self.scoring_system.reset_for_energizer();
-                self.timer_system.set_scatter_mode();
-                // Reverse all ghost directions when energizer is consumed
-                // self.ghosts_manager.reverse_all_ghost_directions();
-                // TODO: Play waka sound
+    /// The level's maze color, or white on alternate steps of the flash after clearing it
+    fn maze_color(&self) -> Color {
+        let flashing = self.game_state == GameState::LevelComplete
+            && self.bonus_tally.is_none()
+            && (self.level_flash_ms / FLASH_STEP_MS) % 2 == 1;
+        if flashing {
+            return WHITE;
+        }
+        self.theme.maze_color(
+            self.palette,
+            self.maze_colors,
+            self.level,
+            self.board.get_maze(),
+        )
+    }
+
+    /// Log this update's random draws into a ranked recording, or check them during playback
+    fn audit_rng(&mut self) {
+        if !self.rng.is_tracing() {
+            return;
+        }
+        let draws = self.rng.take_trace();
+        if let Some(ref mut playback) = self.playback {
+            if let Some(report) = playback.audit(self.tick, &draws) {
+                println!("{}", report);
             }
-            _ => {}
+        }
+        if let Some(ref mut recording) = self.recording {
+            recording.record_draws(self.tick, draws);
         }
     }
 
-    fn entity_collisions(&mut self) {
-        if !self.pacman.is_energized() {
-            self.scoring_system.reset_ghost_counter();
+    /// Remember the current frame so it can be rewound to later.
+    ///
+    /// Only casual runs are rewound: a run being recorded for the best replay keeps its
+    /// history, so nothing is kept for it.
+    fn capture_rewind_frame(&mut self) {
+        if self.rewind.is_none() || self.game_state != GameState::Playing {
+            return;
+        }
+        if let (Some(frame), Some(rewind)) = (self.save_game(), self.rewind.as_mut()) {
+            rewind.push(&frame);
         }
-        self.check_ghost_collisions();
     }
 
-    fn check_ghost_collisions(&mut self) {
-        let collisions = self.collision_system.check_all_ghost_collisions(
-            &self.pacman,
-            &self.ghosts_manager.blinky,
-            &self.ghosts_manager.inky,
-            &self.ghosts_manager.pinky,
-            &self.ghosts_manager.clyde,
-            self.pacman.is_energized(),
-        );
+    /// Go back one update. Play carries on from there once the key is released.
+    fn rewind_step(&mut self) {
+        let frame = match self.rewind.as_mut().and_then(|rewind| rewind.pop()) {
+            Some(frame) => frame,
+            None => return,
+        };
+        if let Err(e) = self.resume_game(&frame) {
+            println!("Failed to rewind: {}", e);
+            return;
+        }
+        // Saved games come back paused, but rewinding should flow straight back into play
+        if self.game_state == GameState::Paused {
+            self.game_state = GameState::Playing;
+            self.timer_system.unpause_all();
+        }
+    }
 
-        for collision in collisions {
-            match collision {
-                CollisionEvent::PacmanEatsGhost {
-                    ghost_type,
-                    position,
-                } => {
-                    // Handle Pacman eating a ghost
-                    match ghost_type {
-                        GhostType::Blinky => {
-                            self.ghosts_manager
-                                .blinky
-                                .get_ghost_mut()
-                                .entity
-                                .mod_life_statement(false);
-                        }
-                        GhostType::Inky => {
-                            self.ghosts_manager
-                                .inky
-                                .get_ghost_mut()
-                                .entity
-                                .mod_life_statement(false);
-                        }
-                        GhostType::Pinky => {
-                            self.ghosts_manager
-                                .pinky
-                                .get_ghost_mut()
-                                .entity
-                                .mod_life_statement(false);
-                        }
-                        GhostType::Clyde => {
-                            self.ghosts_manager
-                                .clyde
-                                .get_ghost_mut()
-                                .entity
-                                .mod_life_statement(false);
-                        }
-                    }
+    /// Escape: quits straight away outside a run, otherwise pauses and asks first.
+    ///
+    /// Returns true once quitting is confirmed. Versus games cannot pause on one side, so
+    /// they only ask.
+    pub fn request_quit(&mut self) -> bool {
+        let in_run = matches!(
+            self.game_state,
+            GameState::Ready
+                | GameState::Playing
+                | GameState::PacmanDeath
+                | GameState::LevelComplete
+                | GameState::Paused
+        );
+        if self.confirm_quit || !in_run || self.playback.is_some() || self.idle_autoplay {
+            return true;
+        }
+        self.confirm_quit = true;
+        self.pause();
+        false
+    }
 
-                    // Award points and add floating score
-                    let score_value = self.scoring_system.add_ghost_score(position);
-                    self.board.score_increase_by_value(score_value);
-                }
-                CollisionEvent::GhostKillsPacman { ghost_type: _ } => {
-                    // Handle ghost killing Pacman
-                    self.pacman.mod_life_statement(false);
-                    // Only need to handle one death, so break after first
-                    break;
-                }
-                CollisionEvent::NoCollision => {
-                    // This shouldn't happen since we filter out NoCollision events
-                }
-            }
+    /// Pause a run in progress, e.g. when the window loses focus; versus games play on
+    pub fn pause(&mut self) {
+        if self.game_state == GameState::Playing && !self.networked {
+            self.set_state(GameState::Paused);
         }
     }
 
-    fn is_level_completed(&self) -> bool {
-        for &block in &self.actual_map {
-            if block == BlockType::Pellet || block == BlockType::Energizer {
-                return false;
-            }
+    fn resume(&mut self) {
+        if self.game_state == GameState::Paused {
+            self.set_state(GameState::Playing);
         }
-        true
     }
 
-    fn reset_game_for_death(&mut self) {
-        self.clear_mover();
-        self.pacman.mod_dead_animation_statement(false);
-        self.pacman.mod_life_statement(true);
-        self.pacman.change_energy_status(false);
-        self.pacman.reset_current_living_frame();
-        self.board.decrease_lives();
+    /// The gamepad being played with was unplugged: hold the run, or the next one once the
+    /// ready countdown ends, until it is plugged back in or Space is pressed
+    pub fn controller_disconnected(&mut self) {
+        if self.networked || self.playback.is_some() || self.autoplay {
+            return;
+        }
+        self.controller_lost = true;
+        self.pause();
+    }
 
-        self.ghosts_manager.reset_all_ghost_life_statements();
-        self.ghosts_manager.reset_all_ghost_facing();
+    pub fn controller_reconnected(&mut self) {
+        if std::mem::take(&mut self.controller_lost) {
+            self.resume();
+        }
+    }
 
-        // TODO: Despawn fruit
-        self.is_to_waka_sound = true;
-        self.is_to_death_sound = true;
+    /// Persist session data that outlives the window
+    pub fn shutdown(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.finish_recording();
+        if let Some(ref playback) = self.playback {
+            if playback.is_audited() {
+                println!(
+                    "RNG audit: {}",
+                    if playback.audit_passed() {
+                        "every draw matched the replay"
+                    } else {
+                        "playback diverged from the replay"
+                    }
+                );
+            }
+        }
+        // Each file is written even if an earlier one fails; the first failure is returned
+        let mut saved_game = Ok(());
+        if self.playback.is_none() && !self.networked {
+            if let (Some(path), Some(saved)) = (&self.save_game_path, self.save_game()) {
+                match saved.save(path) {
+                    Ok(()) => println!("Saved game to {}; continue with --resume", path),
+                    Err(e) => {
+                        println!("Failed to save the game: {}", e);
+                        saved_game = Err(e.into());
+                    }
+                }
+            }
+        }
 
-        // Reset ghost timer and start ghost timing
-        self.timer_system.restart_ghost_timer();
-        self.timer_system.start_ghost_timing();
+        let stats = self.renderer.average_stats();
+        println!(
+            "Batched draws per frame: {} copies, {} texture switches ({} unbatched)",
+            stats.draw_calls, stats.batched_switches, stats.unbatched_switches
+        );
 
-        // Reset game timer for 2.5 second delay - order is important!
-        self.timer_system.set_start_ticks(2500);
-        self.timer_system.start_game();
+        let telemetry = self.telemetry.save();
+        saved_game.and(telemetry)
     }
 
-    fn clear_mover(&mut self) {
-        self.mover.clear();
-        self.mover.push(Direction::Right);
+    pub fn draw(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        profile_scope!("draw");
+        let screen = self.board.hud().screen();
+        // Shaking moves the picture, never the entities in it
+        let (dx, dy) = self.screen_effects.offset();
+        if (dx, dy) != (0, 0) {
+            canvas.set_viewport(Rect::new(dx, dy, screen.width(), screen.height()));
+        }
+        let drawn = self.draw_frame(canvas, texture_creator, font);
+        canvas.set_viewport(None);
+        drawn?;
+        self.screen_effects.draw_flash(canvas, screen)
     }
 
-    fn update_difficulty(&mut self) {
-        if self.level.is_multiple_of(3) {
-            self.timer_system.update_difficulty();
+    fn draw_frame(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.renderer.begin_frame();
+
+        self.board.set_maze_color(self.maze_color())?;
+        self.board.set_score(texture_creator, font)?;
+        self.board.set_high_score(texture_creator, font)?;
+
+        self.board.draw(
+            canvas,
+            &mut self.renderer,
+            &mut self.assets,
+            &self.actual_map,
+            self.door_open_ms > 0,
+        )?;
+
+        if states::hooks(&self.game_state).draw(self, canvas, texture_creator, font)? {
+            self.draw_actors(canvas, texture_creator, font)?;
+            self.draw_flashlight(canvas)?;
         }
-    }
 
-    fn draw_little_score(&mut self) {
-        self.scoring_system.update_little_scores();
-        // TODO: Render remaining floating scores using self.scoring_system.get_little_scores()
+        // The HUD goes over the play field, so neither the actors nor the dark hide it
+        self.draw_level(canvas, texture_creator, font)?;
+        if self.shows_dots_counter() {
+            self.draw_dots_counter(canvas, texture_creator, font)?;
+        }
+        self.draw_sound_indicator(canvas, texture_creator, font)?;
+        self.draw_caption(canvas, texture_creator, font)?;
+        self.draw_credits(canvas, texture_creator, font)?;
+        if self.console.is_open() {
+            self.draw_console(canvas, texture_creator, font)?;
+        }
+        if self.autoplay {
+            let hud = self.board.hud();
+            let (x, y) = hud.bottom_right(self.auto_texture.get_width(), 2);
+            self.auto_texture.render(canvas, x, y, None)?;
+        }
+        // Kids mode runs never count for high scores, so it stays on show
+        if self.difficulty == Difficulty::Kids {
+            let row = if self.autoplay { 3 } else { 2 };
+            let (x, y) = self
+                .board
+                .hud()
+                .bottom_right(self.kids_texture.get_width(), row);
+            self.kids_texture.render(canvas, x, y, None)?;
+        }
+        if self.confirm_quit {
+            let (x, y) = self.board.hud().prompt(self.quit_texture.get_width());
+            self.quit_texture.render(canvas, x, y, None)?;
+        } else if self.controller_lost && self.game_state == GameState::Paused {
+            let (x, y) = self.board.hud().prompt(self.controller_texture.get_width());
+            self.controller_texture.render(canvas, x, y, None)?;
+        }
+        // Toasts sit over the ghost house, so they go over whoever is passing
+        self.draw_toast(canvas, texture_creator, font)?;
+
+        // Diagnostics go over everything so nothing hides the readout
+        if self.show_perf {
+            self.draw_perf_overlay(canvas, texture_creator, font)?;
+        }
+        if self.show_frame_graph {
+            self.draw_frame_graph(canvas)?;
+        }
+        Ok(())
+    }
+
+    /// Fruit, ghosts and Pac-Man, with everything drawn about them
+    fn draw_actors(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let death_frozen = self.is_death_frozen();
+        let ghost_frozen = self.is_ghost_frozen();
+        if !self.are_ghosts_hidden() {
+            self.fruit.draw(canvas, &mut self.assets)?;
+
+            // Use ghosts manager to draw all ghosts
+            self.ghosts_manager.draw_all_ghosts(
+                canvas,
+                &mut self.assets,
+                &self.power_state,
+                self.palette,
+                self.ghost_patterns,
+                death_frozen || ghost_frozen,
+            )?;
+
+            self.draw_little_score();
+            if self.cheat_codes.is_unlocked(Cheat::GhostVision) {
+                self.draw_ghost_targets(canvas)?;
+            }
+            if self.difficulty.shows_ghost_intent() {
+                self.draw_ghost_intent(canvas)?;
+            }
+        }
+
+        let scale = if self.cheat_codes.is_unlocked(Cheat::BigPacman) {
+            2
+        } else {
+            1
+        };
+        self.assets.set_scale(scale);
+        let drawn = if ghost_frozen {
+            // Pac-Man steps aside for the points
+            self.draw_freeze_score(canvas, texture_creator, font)
+        } else if death_frozen {
+            self.pacman
+                .draw_living(canvas, &mut self.assets)
+                .map_err(Into::into)
+        } else {
+            self.pacman
+                .draw(canvas, &mut self.assets)
+                .map_err(Into::into)
+        };
+        self.assets.set_scale(1);
+        drawn?;
+        self.particles.draw(canvas)
+    }
+
+    /// Darken the maze away from Pac-Man over the actors. The lights come up for the ready
+    /// countdown, so each life starts with a look round.
+    fn draw_flashlight(&self, canvas: &mut WindowCanvas) -> Result<(), Box<dyn std::error::Error>> {
+        match self.flashlight {
+            Some(ref flashlight)
+                if matches!(self.game_state, GameState::Playing | GameState::PacmanDeath) =>
+            {
+                flashlight.draw(canvas, Tile::from_position(self.pacman.get_position()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Pacman has been caught but the death animation has not started yet
+    fn is_death_frozen(&self) -> bool {
+        self.game_state == GameState::PacmanDeath && self.death_freeze_ms > 0
+    }
+
+    /// Ghosts and fruit leave the stage once the death animation starts and on a cleared level
+    fn are_ghosts_hidden(&self) -> bool {
+        match self.game_state {
+            GameState::LevelComplete => true,
+            GameState::PacmanDeath => !self.is_death_frozen(),
+            _ => false,
+        }
+    }
+
+    /// Play is held for a moment after a ghost has been eaten
+    fn is_ghost_frozen(&self) -> bool {
+        self.game_state == GameState::Playing && self.ghost_freeze_ms > 0
+    }
+
+    pub(super) fn update_game_logic(&mut self) {
+        self.level_ms += TICK_MS;
+        if self.ghost_freeze_ms > 0 {
+            // Nothing moves and the energizer does not wear off while the points are up
+            self.ghost_freeze_ms = self.ghost_freeze_ms.saturating_sub(TICK_MS);
+            self.scoring_system.update_little_scores(TICK_MS);
+            return;
+        }
+        self.clock();
+        self.scoring_system.update_little_scores(TICK_MS);
+        self.update_positions();
+        self.update_door();
+        self.food_collision();
+        self.update_fruit();
+        self.entity_collisions();
+        self.update_survival();
+    }
+
+    /// Score the time survived, and refill the maze or add a ghost when one is due
+    fn update_survival(&mut self) {
+        let Some(ref mut survival) = self.survival else {
+            return;
+        };
+        let due = survival.advance(TICK_MS);
+        self.board.score_increase(due.points);
+        if due.refill || self.remaining_food() == 0 {
+            self.refill_food();
+        }
+        if let Some(ghost) = due.ghost {
+            if self.ghosts_manager.len() < 4 + MAX_EXTRA_GHOSTS {
+                self.ghosts_manager
+                    .spawn_ghost(&ghost, &self.theme, &self.board);
+                println!("{} joined", ghost.kind.name());
+                self.show_toast("NEW GHOST");
+            }
+        }
+    }
+
+    /// Put back every pellet and energizer eaten so far, leaving everything else in the maze
+    fn refill_food(&mut self) {
+        let mut fresh = [BlockType::Nothing; BOARD_HEIGHT * BOARD_WIDTH];
+        self.board.copy_board(&mut fresh);
+        for (block, fresh) in self.actual_map.iter_mut().zip(fresh) {
+            if fresh.is_food() {
+                *block = fresh;
+            }
+        }
+        self.board.count_food(&self.actual_map);
+        println!("Pellets refilled");
+    }
+
+    /// Put out a fruit once enough dots are gone, move it along and let Pac-Man eat it
+    fn update_fruit(&mut self) {
+        let eaten = self.food_total.saturating_sub(self.remaining_food());
+        if FRUIT_DOTS
+            .get(self.fruits_shown)
+            .is_some_and(|&dots| eaten >= dots)
+        {
+            self.fruits_shown += 1;
+            self.spawn_fruit();
+        }
+
+        self.fruit.update(TICK_MS, &self.actual_map, &mut self.rng);
+        if self.fruit.is_active() {
+            teleport::update(&mut self.fruit.entity, &self.actual_map, TICK_MS);
+        }
+        if self.fruit.is_active()
+            && self
+                .pacman
+                .entity
+                .is_colliding(self.fruit.entity.get_position())
+        {
+            let position = self.fruit.entity.get_position();
+            self.fruit.remove();
+            let points = self.scoring_system.get_rules().fruit_points(self.level);
+            self.board.score_increase(points);
+            println!("Fruit eaten for {} points", points);
+            self.events.emit(GameEvent::FruitEaten { position, points });
+        }
+    }
+
+    fn spawn_fruit(&mut self) {
+        let kind = scoring::fruit_index(self.level);
+        match self.rules_mode.fruit_entry() {
+            FruitEntry::Fixed => self.fruit.place(kind, self.board.fruit_position()),
+            FruitEntry::Tunnel => {
+                let rows = self.board.tunnel_rows();
+                if rows.is_empty() {
+                    return;
+                }
+                let row = rows[self.rng.next_index(rows.len(), "fruit tunnel")];
+                let y = (row as u32 * BLOCK_SIZE_24) as i16;
+                if self.rng.next_index(2, "fruit side") == 0 {
+                    self.fruit
+                        .enter(kind, Position::new(0, y), Direction::Right);
+                } else {
+                    let x = ((BOARD_WIDTH - 1) as u32 * BLOCK_SIZE_24) as i16;
+                    self.fruit.enter(kind, Position::new(x, y), Direction::Left);
+                }
+            }
+        }
+        self.events.emit(GameEvent::FruitSpawned { kind });
+    }
+
+    /// Hold the door open while any ghost is standing in the doorway
+    fn update_door(&mut self) {
+        let in_doorway = self
+            .ghosts_manager
+            .get_ghost_positions()
+            .iter()
+            .any(|&position| {
+                let tile = Tile::from_position(position);
+                tile.x >= 0
+                    && (tile.x as usize) < BOARD_WIDTH
+                    && tile.y >= 0
+                    && (tile.y as usize) < BOARD_HEIGHT
+                    && self.actual_map[tile.y as usize * BOARD_WIDTH + tile.x as usize]
+                        == BlockType::Door
+            });
+        self.door_open_ms = if in_doorway {
+            DOOR_OPEN_MS
+        } else {
+            self.door_open_ms.saturating_sub(TICK_MS)
+        };
+    }
+
+    fn clock(&mut self) {
+        // The scatter/chase schedule stands still while the ghosts are frightened
+        if !self.power_state.is_active() {
+            self.timer_system.update_ghost_timing(TICK_MS);
+        }
+        self.ghosts_manager.set_random_scatter(
+            self.rules_mode.random_first_scatter() && self.timer_system.wave() == 0,
+        );
+        if self.power_state.tick(TICK_MS) {
+            println!("Energizer wore off");
+        }
+    }
+
+    fn update_positions(&mut self) {
+        {
+            profile_scope!("ghost_ai");
+            let started = self.system_timings.start();
+            self.ghosts_manager.update_all_ghosts(
+                &self.tile_graph,
+                &self.pacman,
+                self.timer_system.is_scatter_mode(),
+                &self.power_state,
+                &mut self.rng,
+                self.blinky_mover,
+            );
+            self.system_timings.stop(SimSystem::GhostAi, started);
+        }
+        self.ghosts_manager.teleport_all(&self.actual_map, TICK_MS);
+
+        self.pacman.update_pos(&mut self.mover, &self.actual_map);
+        self.mover.tick();
+        teleport::update(&mut self.pacman.entity, &self.actual_map, TICK_MS);
+    }
+
+    fn food_collision(&mut self) {
+        profile_scope!("food_collision");
+        let position = self.pacman.entity.get_position();
+        let started = self.system_timings.start();
+        let food = self
+            .collision_system
+            .check_food_collision(&self.pacman, &mut self.actual_map);
+        self.system_timings.stop(SimSystem::Collision, started);
+        if food != FoodCollisionEvent::Nothing {
+            self.board.food_eaten();
+        }
+        if self.arcade_stalls {
+            self.pacman.stall(food);
+        }
+        match food {
+            FoodCollisionEvent::Pellet => {
+                self.board
+                    .score_increase(self.scoring_system.food_points(false));
+                self.events.emit(GameEvent::PelletEaten { position });
+            }
+            FoodCollisionEvent::Energizer => {
+                self.board
+                    .score_increase(self.scoring_system.food_points(true));
+                self.events.emit(GameEvent::EnergizerEaten { position });
+                self.energize();
+            }
+            FoodCollisionEvent::Nothing => {}
+        }
+    }
+
+    /// What an energizer does to the ghosts
+    fn energize(&mut self) {
+        // Ghosts always turn around, even on levels too late for a blue period
+        self.ghosts_manager.reverse_all_ghost_directions();
+        if let Some(ref mut flashlight) = self.flashlight {
+            flashlight.light_up();
+        }
+        let fright_time = self.difficulty.level_params(self.level).fright_time;
+        if fright_time > 0 {
+            self.power_state.activate(fright_time);
+            self.ghosts_manager.frighten_all_ghosts();
+            self.scoring_system.reset_for_energizer();
+        }
+    }
+
+    fn entity_collisions(&mut self) {
+        if !self.power_state.is_active() {
+            self.scoring_system.reset_ghost_counter();
+        }
+        self.check_ghost_collisions();
+    }
+
+    fn check_ghost_collisions(&mut self) {
+        profile_scope!("ghost_collision");
+        let started = self.system_timings.start();
+        let collisions = self
+            .collision_system
+            .check_all_ghost_collisions(&self.pacman, &self.ghosts_manager);
+        self.system_timings.stop(SimSystem::Collision, started);
+
+        for collision in collisions {
+            self.telemetry.record_collision(self.level, &collision);
+
+            match collision {
+                CollisionEvent::PacmanEatsGhost {
+                    index,
+                    ghost_type,
+                    position,
+                    ..
+                } => {
+                    if let Some(ghost) = self.ghosts_manager.nth_mut(index) {
+                        ghost.get_ghost_mut().transition(GhostEvent::Eaten);
+                    }
+
+                    // Award points and add floating score
+                    let points = self.scoring_system.add_ghost_score(position);
+                    self.board.score_increase(points);
+                    self.ghost_freeze_ms = GHOST_EATEN_FREEZE_MS;
+                    self.freeze_score = (position, points);
+                    self.events.emit(GameEvent::GhostEaten {
+                        ghost: ghost_type,
+                        position,
+                        points,
+                    });
+                }
+                CollisionEvent::GhostKillsPacman { ghost_type, .. } => {
+                    // Handle ghost killing Pacman
+                    self.pacman.mod_life_statement(false);
+                    self.events.emit(GameEvent::PacmanDied {
+                        killed_by: ghost_type,
+                    });
+                    // Only need to handle one death, so break after first
+                    break;
+                }
+                CollisionEvent::NoCollision => {
+                    // This shouldn't happen since we filter out NoCollision events
+                }
+            }
+        }
+    }
+
+    /// Award the extra life once the score reaches the rules' threshold
+    fn check_extra_life(&mut self) {
+        let threshold = self.scoring_system.get_rules().extra_life;
+        if self.board.award_extra_life(threshold) {
+            self.events.emit(GameEvent::ExtraLife);
+        }
+    }
+
+    /// Let particles, session stats, sound and the HUD react to this update's events
+    fn dispatch_events(&mut self) {
+        let half = BLOCK_SIZE_24 as i32 / 2;
+        let center = |position: Position| (position.x as i32 + half, position.y as i32 + half);
+        let width = self.board.hud().width();
+
+        let events = self.events.drain();
+        for &event in &events {
+            match event {
+                GameEvent::PelletEaten { position } => {
+                    let (x, y) = center(position);
+                    self.particles.crumbs(x, y);
+                    self.sounds.push(Sound::at(SoundCue::Waka, x, width));
+                }
+                GameEvent::EnergizerEaten { position } => {
+                    let (x, y) = center(position);
+                    self.particles.sparkles(x, y);
+                    self.screen_effects.flash();
+                    self.sounds.push(Sound::at(SoundCue::Waka, x, width));
+                }
+                GameEvent::GhostEaten { position, .. } => {
+                    let (x, y) = center(position);
+                    self.particles
+                        .burst(x, y, &[self.palette.frightened_color(), WHITE]);
+                    self.session_stats.record_ghost_eaten();
+                    self.sounds.push(Sound::at(SoundCue::GhostEaten, x, width));
+                }
+                GameEvent::FruitSpawned { .. } => {
+                    self.sound_indicators.show(Indicator::FruitSpawned);
+                }
+                GameEvent::FruitEaten { position, .. } => {
+                    let (x, _) = center(position);
+                    self.sounds.push(Sound::at(SoundCue::FruitEaten, x, width));
+                }
+                GameEvent::PacmanDied { killed_by } => {
+                    self.screen_effects.shake();
+                    self.session_stats.record_death();
+                    self.session_stats.record_killed_by(killed_by.name());
+                    self.sounds.push(Sound::centered(SoundCue::Death));
+                }
+                GameEvent::LevelCompleted { .. } => {
+                    self.session_stats.record_level_completed();
+                }
+                GameEvent::ExtraLife => {
+                    println!("Extra life at {} points", self.board.get_score());
+                    self.show_toast("EXTRA LIFE");
+                    self.sound_indicators.show(Indicator::ExtraLife);
+                    self.announcer.announce(Announcement::ExtraLife {
+                        lives: self.board.get_lives(),
+                    });
+                }
+            }
+        }
+        self.stepped_events = events;
+    }
+
+    /// Catch the siren stepping up and the energizer's warning for the sound indicators
+    fn watch_sounds(&mut self) {
+        let eaten = self.food_total.saturating_sub(self.remaining_food());
+        self.sound_indicators
+            .siren(sound_indicators::siren_tier(eaten, self.food_total));
+        self.sound_indicators
+            .power(self.power_state.is_ending(WARNING_MS));
+        self.sound_indicators.update(TICK_MS);
+    }
+
+    /// Survival runs never finish a level; the maze fills up again instead
+    pub(super) fn is_level_completed(&self) -> bool {
+        self.remaining_food() == 0 && self.survival.is_none()
+    }
+
+    fn remaining_food(&self) -> usize {
+        self.board.get_food_left()
+    }
+
+    /// Practice and survival runs always count the dots; otherwise it is the F10 toggle
+    fn shows_dots_counter(&self) -> bool {
+        self.show_dots || self.practice.is_some() || self.survival.is_some()
+    }
+
+    /// Eaten and total dots, e.g. `DOTS 143/244`, or just the ones left, e.g. `LEFT 101`, when
+    /// practicing; survival runs show the time survived instead, e.g. `TIME 3:07`
+    fn dots_label(&self) -> String {
+        if let Some(ref survival) = self.survival {
+            return format!("TIME {}", survival.clock());
+        }
+        if self.practice.is_some() {
+            return format!("LEFT {}", self.remaining_food());
+        }
+        format!(
+            "DOTS {}/{}",
+            self.food_total.saturating_sub(self.remaining_food()),
+            self.food_total
+        )
+    }
+
+    pub(super) fn reset_game_for_death(&mut self) {
+        let infinite_lives = self
+            .practice
+            .is_some_and(|practice| practice.infinite_lives)
+            || self.difficulty.has_infinite_lives();
+        if !infinite_lives {
+            self.board.decrease_lives();
+        }
+        self.reset_for_next_life();
+    }
+
+    /// Bring Pac-Man back for the next ready countdown; positions are set separately
+    pub(super) fn reset_for_next_life(&mut self) {
+        self.clear_mover();
+        self.pacman.mod_dead_animation_statement(false);
+        self.pacman.mod_life_statement(true);
+        self.power_state.deactivate();
+        self.pacman.reset_current_living_frame();
+
+        self.ghosts_manager.reset_all_ghost_modes();
+        self.ghosts_manager.reset_all_ghost_facing();
+
+        self.fruit.remove();
+
+        // Reset ghost timer and start ghost timing; the ready countdown restarts the game timer
+        self.timer_system.restart_ghost_timer();
+        self.timer_system.start_ghost_timing();
+    }
+
+    fn clear_mover(&mut self) {
+        self.mover.reset(Direction::Right);
+    }
+
+    pub(super) fn update_difficulty(&mut self) {
+        let mut params = self.difficulty.level_params(self.level);
+        if self.ghost_rush {
+            params = params.rushed();
+        }
+        self.timer_system.apply_level_params(params);
+        self.ghosts_manager
+            .set_speed_percent(params.ghost_speed_percent);
+        let maze = match self.practice.and_then(|practice| practice.maze) {
+            Some(maze) => maze,
+            None => self.rules_mode.maze_for_level(self.level),
+        };
+        self.board.set_maze(maze);
+        let mut layout = [BlockType::Nothing; BOARD_HEIGHT * BOARD_WIDTH];
+        self.board.copy_board(&mut layout);
+        self.food_total = layout.iter().filter(|block| block.is_food()).count();
+    }
+
+    /// Begin the first countdown at a later level, skipping the ones before it
+    pub fn start_at_level(&mut self, level: u16) {
+        self.level = level.max(1);
+        self.update_difficulty();
+        self.reload_maze();
+
+        // The level's maze may put everyone somewhere else
+        let pacman_start = self.board.reset_position(crate::board::EntityType::PacMan);
+        self.pacman.set_position(pacman_start);
+        self.ghosts_manager.reset_all_ghost_positions(&self.board);
+    }
+
+    /// Start a new game in place, keeping the window, textures and display settings.
+    ///
+    /// Play begins on level 1, or on the practice level when practicing, or on the level
+    /// picked with the level select cheat.
+    /// Score, lives, timers and every actor go back to how a fresh `Game` starts. The new run
+    /// gets its own seed (unless `config` fixes one), replay recording and rewind history.
+    pub fn reset(&mut self, config: &Config) {
+        self.board.restore(0, self.difficulty.starting_lives(), 0);
+        self.level = self
+            .practice
+            .map_or(self.start_level, |practice| practice.level);
+        self.timer_system = TimerSystem::new();
+        self.ghosts_manager = Game::new_ghosts(config, &self.theme);
+        self.arcade_stalls = config.arcade_quirks;
+        self.update_difficulty();
+        self.reload_maze();
+
+        self.scoring_system = ScoringSystem::with_rules(self.scoring_system.get_rules().clone());
+        self.power_state.deactivate();
+        self.fruit.remove();
+        self.fruits_shown = 0;
+        self.death_freeze_ms = 0;
+        self.ghost_freeze_ms = 0;
+        self.freeze_score = (Position::new(0, 0), 0);
+        self.door_open_ms = 0;
+        self.level_ms = 0;
+        self.bonus_tally = None;
+        self.level_flash_ms = 0;
+        self.events = EventBus::new();
+        self.particles.clear();
+
+        self.clear_mover();
+        if let Some(ref mut wanted) = self.blinky_mover {
+            *wanted = Direction::Nowhere;
+        }
+        self.pacman = Pacman::new(&self.theme);
+        let pacman_start = self.board.reset_position(crate::board::EntityType::PacMan);
+        self.pacman.set_position(pacman_start);
+        self.ghosts_manager.reset_all_ghost_positions(&self.board);
+
+        self.rng = match config.seed {
+            Some(seed) => GameRng::new(seed),
+            None => GameRng::from_entropy(),
+        };
+        self.rng.set_tracing(config.ranked);
+        self.tick = 0;
+        self.playback = None;
+        self.survival = config.survival.then(Survival::new);
+        self.recording = (self.start_level == 1 && self.is_comparable_run()).then(|| {
+            // Kids mode may have been switched off on the menu since `config` was read
+            let played = Config {
+                difficulty: self.difficulty,
+                ..config.clone()
+            };
+            Replay::new(ReplayMetadata::new(&played, self.get_seed()))
+        });
+        if self.rewind.is_some() {
+            self.rewind = Some(RewindBuffer::new(REWIND_CAPACITY));
+        }
+        self.rewinding = false;
+
+        println!("New game; seed: {}", self.get_seed());
+        self.set_state(GameState::Ready);
+    }
+
+    /// Drop a coin in the slot; only counted in coin mode
+    pub fn insert_coin(&mut self) {
+        if let Some(ref mut credits) = self.credits {
+            *credits += 1;
+            println!("Credits: {}", credits);
+        }
+    }
+
+    pub fn get_credits(&self) -> Option<u32> {
+        self.credits
+    }
+
+    /// Enter on the menu, game-over or coin screen.
+    ///
+    /// The menu starts the run it shows, and a game over goes back to it. Without a menu, free
+    /// play starts a new game. In coin mode a credit is spent, either on a new game or, after a
+    /// game over, on continuing the level that was lost; nothing happens without one.
+    pub fn press_start(&mut self, config: &Config) {
+        states::hooks(&self.game_state).press_start(self, config);
+    }
+
+    /// Refill the maze for a new level, which may also change its layout
+    pub(super) fn reload_maze(&mut self) {
+        self.board.copy_board(&mut self.actual_map);
+        self.board.count_food(&self.actual_map);
+        self.tile_graph = TileGraph::new(&self.actual_map);
+    }
+
+    pub fn get_session_stats(&self) -> &SessionStats {
+        &self.session_stats
+    }
+
+    /// Keep finished runs that beat the best so far in `path`
+    pub fn set_best_replay_path(&mut self, path: &str) {
+        self.best_replay_path = Some(path.to_string());
+    }
+
+    /// Save an unfinished run to `path` on shutdown
+    pub fn set_save_game_path(&mut self, path: &str) {
+        self.save_game_path = Some(path.to_string());
+    }
+
+    /// Let the bot play; its moves go straight into `mover` and are not recorded as a replay
+    pub fn set_autoplay(&mut self, enabled: bool) {
+        self.autoplay = enabled;
+        if enabled && self.recording.take().is_some() {
+            println!("Replay recording stopped for the CPU demo");
+        }
+    }
+
+    /// Count time spent playing without input and let the bot take over once it runs out
+    fn check_idle(&mut self) {
+        let Some(limit) = self.idle_takeover_ms else {
+            return;
+        };
+        let watching = self.game_state == GameState::Playing
+            && !self.autoplay
+            && self.playback.is_none()
+            && !self.networked;
+        if !watching {
+            return;
+        }
+        self.idle_ms += TICK_MS;
+        if self.idle_ms >= limit {
+            self.set_autoplay(true);
+            self.idle_autoplay = true;
+            println!("No input for {} seconds, the bot takes over", limit / 1000);
+        }
+    }
+
+    /// Steer Pac-Man for the bot and carry on past the milestone screen
+    fn autoplay_input(&mut self) {
+        match self.game_state {
+            GameState::Playing => {
+                if let Some(direction) = bot::choose_direction(&self.bot_view()) {
+                    self.queue_direction(direction);
+                }
+            }
+            GameState::Milestone => self.set_state(GameState::Ready),
+            _ => {}
+        }
+    }
+
+    /// The maze and every actor reduced to tiles, for automated players
+    pub fn bot_view(&self) -> BotView<'_> {
+        let positions = self.ghosts_manager.get_ghost_positions();
+        let modes = self.ghosts_manager.get_ghost_modes();
+        BotView {
+            map: &self.actual_map,
+            pacman: Tile::from_position(self.pacman.get_position()),
+            ghosts: positions
+                .iter()
+                .zip(modes)
+                .map(|(&position, mode)| (Tile::from_position(position), mode))
+                .collect(),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            level: self.level,
+            score: self.board.get_score(),
+            lives: self.board.get_lives(),
+            pacman: self.pacman.get_position(),
+            ghosts: self.ghosts_manager.get_ghost_positions(),
+            map: self.actual_map.to_vec(),
+            resume: None,
+        }
+    }
+
+    /// A snapshot that can be resumed, if the game is somewhere it makes sense to resume from
+    pub fn save_game(&self) -> Option<Snapshot> {
+        let ready = match self.game_state {
+            GameState::Ready => true,
+            GameState::Playing | GameState::Paused => false,
+            _ => return None,
+        };
+        Some(Snapshot {
+            resume: Some(ResumeState {
+                ready,
+                seed: self.rng.get_seed(),
+                rng_position: self.rng.get_position(),
+                tick: self.tick,
+                pacman_direction: self.pacman.get_direction(),
+                ghosts: self.ghosts_manager.get_ghost_states(),
+                power_ms: self.power_state.get_remaining_ms(),
+                timers: self.timer_system.get_state(),
+            }),
+            ..self.snapshot()
+        })
+    }
+
+    /// Continue a saved game. Play resumes paused, and is no longer recorded as a replay.
+    ///
+    /// The random stream carries on from the saved position, so ghosts wander just as they
+    /// would have in the original run.
+    pub fn resume_game(&mut self, saved: &Snapshot) -> Result<(), Box<dyn std::error::Error>> {
+        let resume = saved
+            .resume
+            .as_ref()
+            .ok_or("Snapshot has no saved game state")?;
+
+        self.restore_snapshot(saved);
+        self.rng = GameRng::resume(resume.seed, resume.rng_position);
+        self.tick = resume.tick;
+        self.mover.reset(resume.pacman_direction);
+        self.pacman.set_direction(resume.pacman_direction);
+        self.pacman.mod_life_statement(true);
+        self.pacman.mod_dead_animation_statement(false);
+        self.ghosts_manager.set_ghost_states(&resume.ghosts);
+        self.power_state.activate(resume.power_ms);
+        self.timer_system.restore_state(resume.timers);
+        self.recording = None;
+        self.playback = None;
+
+        if resume.ready {
+            self.game_state = GameState::Ready;
+        } else {
+            self.game_state = GameState::Paused;
+            self.timer_system.pause_all();
+        }
+        Ok(())
+    }
+
+    /// Jump straight into the middle of a level as described by a snapshot
+    pub fn restore_snapshot(&mut self, snapshot: &Snapshot) {
+        self.level = snapshot.level;
+        self.update_difficulty();
+        self.board.restore(
+            snapshot.score,
+            snapshot.lives,
+            self.scoring_system.get_rules().extra_life,
+        );
+        self.actual_map.copy_from_slice(&snapshot.map);
+        self.board.count_food(&self.actual_map);
+        self.tile_graph = TileGraph::new(&self.actual_map);
+        // Fruit is not saved; any already due on this level counts as gone
+        let eaten = self.food_total.saturating_sub(self.remaining_food());
+        self.fruits_shown = FRUIT_DOTS.iter().filter(|&&dots| eaten >= dots).count();
+        self.fruit.remove();
+        self.pacman.set_position(snapshot.pacman);
+        self.ghosts_manager.set_ghost_positions(&snapshot.ghosts);
+        self.game_state = GameState::Playing;
+    }
+
+    pub fn record_frame_timing(&mut self, frame: Duration, update: Duration, draw: Duration) {
+        self.perf_stats.record_frame(frame, update, draw);
+        self.frame_history.push(FrameSample {
+            frame,
+            update,
+            draw,
+        });
+    }
+
+    /// Start timing the ghost AI and collisions on their own
+    pub fn enable_system_timings(&mut self) {
+        self.system_timings.enable();
+    }
+
+    pub fn get_system_timings(&self) -> &SystemTimings {
+        &self.system_timings
+    }
+
+    /// Flash a short notice in the middle of the screen
+    pub fn show_toast(&mut self, text: &str) {
+        self.toast_text = Some(text.to_string());
+    }
+
+    fn draw_toast(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(text) = self.toast_text.take() {
+            self.toast_texture
+                .load_from_rendered_text(texture_creator, &text, font, YELLOW)?;
+            self.toast_since = Some(Instant::now());
+        }
+        match self.toast_since {
+            Some(since) if since.elapsed().as_millis() < TOAST_MS => {}
+            _ => {
+                self.toast_since = None;
+                return Ok(());
+            }
+        }
+
+        let width = self.toast_texture.get_width();
+        let height = self.toast_texture.get_height();
+        let (x, y) = self.board.hud().centered(width, height);
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(Rect::new(x - 8, y - 8, width + 16, height + 16))?;
+        self.toast_texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// Points for the ghost just eaten, in place of Pac-Man and the ghost
+    fn draw_freeze_score(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (position, points) = self.freeze_score;
+        if points != self.freeze_score_text {
+            self.freeze_score_texture.load_from_rendered_text(
+                texture_creator,
+                &points.to_string(),
+                font,
+                CYAN,
+            )?;
+            self.freeze_score_text = points;
+        }
+
+        let width = self.freeze_score_texture.get_width();
+        let height = self.freeze_score_texture.get_height();
+        let x = position.x as i32 + (BLOCK_SIZE_24 as i32 - width as i32) / 2;
+        let y = position.y as i32 + (BLOCK_SIZE_24 as i32 - height as i32) / 2;
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(Rect::new(x, y, width, height))?;
+        self.freeze_score_texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// Replay annotation for the current update, centered along the bottom edge
+    fn draw_caption(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let caption = self
+            .playback
+            .as_ref()
+            .and_then(|playback| playback.caption_at(self.tick));
+        if caption != self.caption_text.as_deref() {
+            self.caption_text = caption.map(str::to_string);
+            match self.caption_text {
+                Some(ref text) => self.caption_texture.load_from_rendered_text(
+                    texture_creator,
+                    text,
+                    font,
+                    WHITE,
+                )?,
+                None => self.caption_texture.reset(),
+            }
+        }
+
+        if self.caption_text.is_some() {
+            let (x, y) = self.board.hud().bottom(self.caption_texture.get_width());
+            self.caption_texture.render(canvas, x, y, None)?;
+        }
+        Ok(())
+    }
+
+    /// The level being played, in the bottom right corner across from the lives
+    fn draw_level(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.level != self.level_text {
+            self.level_texture.load_from_rendered_text(
+                texture_creator,
+                &format!("LEVEL {}", self.level),
+                font,
+                WHITE,
+            )?;
+            self.level_text = self.level;
+        }
+
+        let (x, y) = self
+            .board
+            .hud()
+            .bottom_right(self.level_texture.get_width(), 1);
+        self.level_texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// Maze progress, right-aligned on the score row
+    fn draw_dots_counter(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let label = self.dots_label();
+        if label != self.dots_text {
+            self.dots_texture
+                .load_from_rendered_text(texture_creator, &label, font, WHITE)?;
+            self.dots_text = label;
+        }
+
+        let (x, y) = self
+            .board
+            .hud()
+            .score_row_right(self.dots_texture.get_width());
+        self.dots_texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// The latest sound indicator, blinking in the top right corner
+    fn draw_sound_indicator(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(indicator) = self.sound_indicators.visible() else {
+            return Ok(());
+        };
+        if self.indicator_shown != Some(indicator) {
+            self.indicator_texture.load_from_rendered_text(
+                texture_creator,
+                &indicator.label(),
+                font,
+                YELLOW,
+            )?;
+            self.indicator_shown = Some(indicator);
+        }
+
+        let (x, y) = self
+            .board
+            .hud()
+            .top_right(self.indicator_texture.get_width());
+        self.indicator_texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// The prompt across the bottom of the maze, with the last reply above it
+    fn draw_console(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let prompt = self.console.prompt();
+        if prompt != self.console_text {
+            self.console_texture
+                .load_from_rendered_text(texture_creator, &prompt, font, YELLOW)?;
+            self.console_text = prompt;
+        }
+        let reply = self.console.reply().unwrap_or("");
+        if !reply.is_empty() && reply != self.console_reply_text {
+            self.console_reply_texture.load_from_rendered_text(
+                texture_creator,
+                reply,
+                font,
+                WHITE,
+            )?;
+            self.console_reply_text = reply.to_string();
+        }
+
+        let band = self.board.hud().bottom_band(3);
+        let top = band.y();
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(band)?;
+        if !self.console_reply_text.is_empty() {
+            self.console_reply_texture.render(canvas, 4, top, None)?;
+        }
+        self.console_texture
+            .render(canvas, 4, top + BLOCK_SIZE_24 as i32 + 6, None)?;
+        Ok(())
+    }
+
+    /// `CREDIT n` along the bottom in coin mode, like a cabinet
+    fn draw_credits(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(credits) = self.credits else {
+            return Ok(());
+        };
+        if self.credits_text != Some(credits) {
+            let label = format!("CREDIT {}", credits);
+            self.credits_texture
+                .load_from_rendered_text(texture_creator, &label, font, WHITE)?;
+            self.credits_text = Some(credits);
+        }
+
+        let (x, y) = self.board.hud().bottom(self.credits_texture.get_width());
+        self.credits_texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// Frame timing readout in the bottom right corner, re-rendered a few times a second
+    fn draw_perf_overlay(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.perf_refresh.elapsed().as_millis() >= OVERLAY_REFRESH_MS {
+            let [fps_line, timing_line] = self.perf_stats.summary();
+            self.perf_fps_texture.load_from_rendered_text(
+                texture_creator,
+                &fps_line,
+                font,
+                WHITE,
+            )?;
+            self.perf_timing_texture.load_from_rendered_text(
+                texture_creator,
+                &timing_line,
+                font,
+                WHITE,
+            )?;
+            self.perf_stats.reset();
+            self.perf_refresh = Instant::now();
+        }
+
+        let hud = self.board.hud();
+        let (x, y) = hud.bottom_right(self.perf_fps_texture.get_width(), 3);
+        self.perf_fps_texture.render(canvas, x, y, None)?;
+        let (x, y) = hud.bottom_right(self.perf_timing_texture.get_width(), 2);
+        self.perf_timing_texture.render(canvas, x, y, None)?;
+
+        Ok(())
+    }
+
+    /// The last few seconds of frame times as bars in the bottom left corner.
+    ///
+    /// Each grey bar is a whole frame, red when it ran over budget, with the time spent
+    /// updating in cyan and drawing in yellow stacked at its foot. The white line is the
+    /// frame budget, halfway up.
+    fn draw_frame_graph(
+        &self,
+        canvas: &mut WindowCanvas,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let area = self
+            .board
+            .hud()
+            .bottom_left(GRAPH_FRAMES as u32, FRAME_GRAPH_HEIGHT);
+        let bottom = area.bottom();
+        let budget = perf::budget_height(area.height());
+        let bar = |x: i32, base: u32, height: u32| {
+            Rect::new(x, bottom - (base + height) as i32, 1, height)
+        };
+
+        let (mut frames, mut slow, mut updates, mut draws) = (vec![], vec![], vec![], vec![]);
+        for (i, sample) in self.frame_history.samples().enumerate() {
+            let x = area.x() + i as i32;
+            let frame = perf::bar_height(sample.frame, area.height());
+            let update = perf::bar_height(sample.update, area.height());
+            let draw = perf::bar_height(sample.draw, area.height()).min(area.height() - update);
+            let bars = if frame > budget {
+                &mut slow
+            } else {
+                &mut frames
+            };
+            bars.push(bar(x, 0, frame.max(1)));
+            if update > 0 {
+                updates.push(bar(x, 0, update));
+            }
+            if draw > 0 {
+                draws.push(bar(x, update, draw));
+            }
+        }
+
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(area)?;
+        for (rects, color) in [
+            (frames, FRAME_GRAPH_COLOR),
+            (slow, RED),
+            (updates, CYAN),
+            (draws, YELLOW),
+        ] {
+            canvas.set_draw_color(color);
+            canvas.fill_rects(&rects)?;
+        }
+        canvas.set_draw_color(WHITE);
+        canvas.fill_rect(Rect::new(area.x(), bottom - budget as i32, area.width(), 1))?;
+        Ok(())
+    }
+
+    /// Mark the point each ghost is heading for, with a line to it, for the ghost vision
+    /// cheat; frightened ghosts wander and have none
+    fn draw_ghost_targets(
+        &self,
+        canvas: &mut WindowCanvas,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let half = BLOCK_SIZE_24 as i32 / 2;
+        for ghost in self.ghosts_manager.iter() {
+            let state = ghost.get_ghost();
+            if state.get_mode() == GhostMode::Frightened {
+                continue;
+            }
+            let (x, y) = (state.entity.get_x() as i32, state.entity.get_y() as i32);
+            let target_x = state.target.get_x() as i32;
+            let target_y = state.target.get_y() as i32;
+            let color = self
+                .palette
+                .ghost_color(state.entity.get_identity(), state.color);
+            canvas.set_draw_color(color);
+            canvas.draw_line(
+                Point::new(x + half, y + half),
+                Point::new(target_x + half, target_y + half),
+            )?;
+            canvas.draw_rect(Rect::new(target_x, target_y, BLOCK_SIZE_24, BLOCK_SIZE_24))?;
+        }
+        Ok(())
+    }
+
+    /// An arrow beside each ghost pointing the way it is moving, in its own colour
+    fn draw_ghost_intent(
+        &self,
+        canvas: &mut WindowCanvas,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let half = BLOCK_SIZE_24 as i32 / 2;
+        for ghost in self.ghosts_manager.iter() {
+            let state = ghost.get_ghost();
+            let (dx, dy) = match state.entity.get_direction() {
+                Direction::Right => (1, 0),
+                Direction::Up => (0, -1),
+                Direction::Left => (-1, 0),
+                Direction::Down => (0, 1),
+                Direction::Nowhere => continue,
+            };
+            let center_x = state.entity.get_x() as i32 + half;
+            let center_y = state.entity.get_y() as i32 + half;
+            // From just past the body's edge to a tip half a tile further on
+            let tip = Point::new(center_x + dx * 30, center_y + dy * 30);
+            let base = Point::new(center_x + dx * 18, center_y + dy * 18);
+            let (back_x, back_y) = (tip.x() - dx * 6, tip.y() - dy * 6);
+            let color = self
+                .palette
+                .ghost_color(state.entity.get_identity(), state.color);
+            canvas.set_draw_color(color);
+            canvas.draw_lines(
+                &[
+                    Point::new(back_x + dy * 6, back_y + dx * 6),
+                    tip,
+                    Point::new(back_x - dy * 6, back_y - dx * 6),
+                ][..],
+            )?;
+            canvas.draw_line(base, tip)?;
+        }
+        Ok(())
+    }
+
+    fn draw_little_score(&mut self) {
+        // TODO: Render remaining floating scores using self.scoring_system.get_little_scores()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::collision::GhostType;
+    use crate::game::levels::LevelParams;
+
+    fn headless_game() -> Game<'static> {
+        Game::headless(&Config::new(), &Theme::new())
+    }
+
+    fn seeded_game(seed: u64) -> Game<'static> {
+        let mut config = Config::new();
+        config.seed = Some(seed);
+        Game::headless(&config, &Theme::new())
+    }
+
+    /// Steer Pacman around with a fixed input pattern
+    fn play_scripted(game: &mut Game, updates: u32) {
+        let turns = [
+            Direction::Left,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+        ];
+        for tick in 0..updates {
+            if tick % 45 == 0 {
+                game.queue_direction(turns[(tick / 45) as usize % turns.len()]);
+            }
+            game.update();
+        }
+    }
+
+    #[test]
+    fn test_ready_countdown_runs_on_simulated_time() {
+        let mut game = headless_game();
+        let countdown_updates = 2500 / TICK_MS;
+
+        for _ in 0..countdown_updates {
+            game.update();
+        }
+        assert_eq!(game.get_game_state(), GameState::Ready);
+
+        game.update();
+        assert_eq!(game.get_game_state(), GameState::Playing);
+    }
+
+    #[test]
+    fn test_headless_game_eats_pellets() {
+        let mut game = headless_game();
+        for _ in 0..400 {
+            game.update();
+        }
+        assert!(game.get_score() > 0);
+        assert!(game
+            .take_sounds()
+            .iter()
+            .any(|sound| sound.cue == SoundCue::Waka));
+    }
+
+    #[test]
+    fn test_step_returns_what_happened() {
+        let mut game = seeded_game(3);
+        assert!(game.step(&[InputAction::Continue]).is_empty());
+        assert_eq!(game.get_game_state(), GameState::Playing);
+
+        let start = game.get_pacman_position();
+        let mut events = Vec::new();
+        while events.is_empty() {
+            events = game.step(&[InputAction::Turn(Direction::Left)]);
+        }
+        assert!(matches!(events[0], GameEvent::PelletEaten { .. }));
+        assert_ne!(game.get_pacman_position(), start);
+        assert_eq!(game.get_score(), 10);
+        assert_eq!(
+            game.get_ghost_positions().len(),
+            game.get_ghost_states().len()
+        );
+
+        // Events are handed out once
+        game.pause();
+        assert!(game.step(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_extra_life_once_at_ten_thousand_points() {
+        let mut game = headless_game();
+        let lives = game.get_lives();
+        game.board.score_increase(9_990);
+        game.update();
+        assert_eq!(game.get_lives(), lives);
+
+        game.board.score_increase(10);
+        game.update();
+        assert_eq!(game.get_lives(), lives + 1);
+        assert_eq!(game.toast_text.as_deref(), Some("EXTRA LIFE"));
+
+        game.board.score_increase(10_000);
+        game.update();
+        assert_eq!(game.get_lives(), lives + 1);
+    }
+
+    #[test]
+    fn test_particles_are_purely_cosmetic() {
+        let mut plain = {
+            let mut config = Config::new();
+            config.seed = Some(7);
+            config.particles = false;
+            Game::headless(&config, &Theme::new())
+        };
+        let mut showy = seeded_game(7);
+        let mut shown = 0;
+        for _ in 0..400 {
+            play_scripted(&mut plain, 1);
+            play_scripted(&mut showy, 1);
+            assert_eq!(plain.particles.active_count(), 0);
+            shown = shown.max(showy.particles.active_count());
+        }
+        assert!(shown > 0);
+        assert_eq!(plain.snapshot(), showy.snapshot());
+    }
+
+    #[test]
+    fn test_same_seed_and_inputs_replay_identically() {
+        let mut first = seeded_game(99);
+        let mut second = seeded_game(99);
+        for _ in 0..20 {
+            play_scripted(&mut first, 250);
+            play_scripted(&mut second, 250);
+            assert_eq!(first.snapshot(), second.snapshot());
+        }
+    }
+
+    #[test]
+    fn test_recorded_inputs_play_back_identically() {
+        let keys = [Keycode::Left, Keycode::Up, Keycode::Right, Keycode::Down];
+        let mut recorded = seeded_game(7);
+        recorded.recording = Some(Replay::new(ReplayMetadata::new(&Config::new(), 7)));
+        recorded.handle_input(Keycode::Space);
+        for tick in 0..1500 {
+            if tick % 45 == 0 {
+                recorded.handle_input(keys[(tick / 45) % keys.len()]);
+            }
+            recorded.update();
+        }
+        assert_ne!(recorded.get_game_state(), GameState::GameOver);
+
+        let mut played = seeded_game(7);
+        played.start_playback(recorded.recording.clone().unwrap());
+        played.handle_input(Keycode::Up);
+        for _ in 0..1500 {
+            played.update();
+        }
+        assert_eq!(played.snapshot(), recorded.snapshot());
+    }
+
+    #[test]
+    fn test_ranked_run_logs_and_audits_random_draws() {
+        let mut config = Config::new();
+        config.ranked = true;
+        let mut recorded = seeded_game(11);
+        recorded.rng.set_tracing(true);
+        recorded.recording = Some(Replay::new(ReplayMetadata::new(&config, 11)));
+        recorded.handle_input(Keycode::Space);
+
+        // Let the bot play until a frightened ghost has had to pick a turn
+        let mut updates = 0;
+        while recorded.recording.as_ref().unwrap().rng_log.is_empty() && updates < 20_000 {
+            if recorded.get_game_state() == GameState::Playing {
+                let key = match bot::choose_direction(&recorded.bot_view()) {
+                    Some(Direction::Right) => Some(Keycode::Right),
+                    Some(Direction::Up) => Some(Keycode::Up),
+                    Some(Direction::Left) => Some(Keycode::Left),
+                    Some(Direction::Down) => Some(Keycode::Down),
+                    _ => None,
+                };
+                if let Some(key) = key {
+                    recorded.handle_input(key);
+                }
+            }
+            recorded.update();
+            updates += 1;
+        }
+        let replay = recorded.recording.clone().unwrap();
+        assert!(!replay.rng_log.is_empty());
+
+        let mut played = seeded_game(11);
+        played.start_playback(Replay::parse(&replay.to_text()).unwrap());
+        for _ in 0..updates {
+            played.update();
+        }
+        assert!(played.playback.as_ref().unwrap().audit_passed());
+        assert_eq!(played.snapshot(), recorded.snapshot());
+    }
+
+    #[test]
+    fn test_cpu_demo_plays_without_input() {
+        let mut idle = seeded_game(3);
+        let mut demo = seeded_game(3);
+        demo.recording = Some(Replay::new(ReplayMetadata::new(&Config::new(), 3)));
+        demo.set_autoplay(true);
+        assert!(demo.recording.is_none());
+
+        for _ in 0..1200 {
+            idle.update();
+            demo.update();
+        }
+        assert!(demo.get_score() > idle.get_score());
+    }
+
+    #[test]
+    fn test_bot_takes_over_when_idle_until_a_key_press() {
+        let mut config = Config::new();
+        config.idle_takeover = Some(1);
+        let mut game = Game::headless(&config, &Theme::new());
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+
+        for _ in 0..1000u32.div_ceil(TICK_MS) - 1 {
+            game.update();
+        }
+        assert!(!game.autoplay);
+        game.update();
+        assert!(game.autoplay);
+
+        game.handle_input(Keycode::Left);
+        assert!(!game.autoplay);
+        assert_eq!(game.idle_ms, 0);
+    }
+
+    #[test]
+    fn test_death_freezes_before_animation() {
+        let mut game = headless_game();
+        while game.get_game_state() != GameState::PacmanDeath {
+            game.update();
+        }
+        assert!(game.is_death_frozen());
+        assert!(!game.are_ghosts_hidden());
+        let positions = game.ghosts_manager.get_ghost_positions();
+
+        for _ in 0..DEATH_FREEZE_MS.div_ceil(TICK_MS) {
+            game.update();
+        }
+        assert!(!game.is_death_frozen());
+        assert!(game.are_ghosts_hidden());
+        assert_eq!(game.get_game_state(), GameState::PacmanDeath);
+        assert_eq!(game.ghosts_manager.get_ghost_positions(), positions);
+    }
+
+    #[test]
+    fn test_eating_a_ghost_freezes_play() {
+        let mut game = headless_game();
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        game.power_state.activate(6000);
+        game.ghosts_manager.frighten_all_ghosts();
+        let pacman = game.pacman.entity.get_position();
+        game.ghosts_manager
+            .get_mut(GhostType::Blinky)
+            .unwrap()
+            .get_ghost_mut()
+            .entity
+            .set_position(pacman);
+        game.update();
+        assert!(game.is_ghost_frozen());
+        assert_eq!(game.freeze_score.1, 200);
+
+        let pacman = game.pacman.entity.get_position();
+        let positions = game.ghosts_manager.get_ghost_positions();
+        for _ in 0..GHOST_EATEN_FREEZE_MS.div_ceil(TICK_MS) {
+            assert_eq!(game.pacman.entity.get_position(), pacman);
+            assert_eq!(game.ghosts_manager.get_ghost_positions(), positions);
+            game.update();
+        }
+        assert!(!game.is_ghost_frozen());
+        game.update();
+        assert_ne!(game.ghosts_manager.get_ghost_positions(), positions);
+    }
+
+    #[test]
+    fn test_blocked_turn_waits_for_an_opening() {
+        let mut game = headless_game();
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        // The wall above the start holds the turn until the gap at column 15
+        game.queue_direction(Direction::Up);
+        game.update();
+        assert_eq!(game.pacman.entity.get_direction(), Direction::Right);
+        assert_eq!(game.mover.queued(), Some(Direction::Up));
+
+        for _ in 0..30 {
+            game.update();
+        }
+        assert_eq!(game.pacman.entity.get_direction(), Direction::Up);
+        assert_eq!(game.mover.current(), Direction::Up);
+        let tile = Tile::from_position(game.pacman.entity.get_position());
+        assert_eq!(tile.x, 15);
+    }
+
+    #[test]
+    fn test_vertical_tunnel_wraps_pacman() {
+        let mut sketch: Vec<char> = Board::CHAR_BOARD.chars().collect();
+        sketch[3 * BOARD_WIDTH + 1] = '|';
+        sketch[33 * BOARD_WIDTH + 1] = '|';
+        let mut config = Config::new();
+        config.custom_maze = Some(sketch.into_iter().collect());
+        let mut game = Game::headless(&config, &Theme::new());
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+
+        let block_size = BLOCK_SIZE_24 as i16;
+        game.pacman
+            .entity
+            .set_position(Position::new(block_size, 4 * block_size));
+        game.mover.reset(Direction::Up);
+        for _ in 0..40 {
+            game.update();
+        }
+        let tile = Tile::from_position(game.pacman.entity.get_position());
+        assert_eq!((tile.x, tile.y), (1, 32));
+    }
+
+    #[test]
+    fn test_saved_game_resumes_where_it_left_off() {
+        let mut original = seeded_game(5);
+        play_scripted(&mut original, 600);
+        let saved = original.save_game().unwrap();
+
+        let mut resumed = seeded_game(1);
+        resumed.resume_game(&saved).unwrap();
+        assert_eq!(resumed.get_game_state(), GameState::Paused);
+        assert_eq!(resumed.save_game().unwrap(), saved);
+
+        resumed.handle_input(Keycode::Space);
+        assert_eq!(resumed.get_game_state(), GameState::Playing);
+        assert!(resumed.resume_game(&resumed.snapshot()).is_err());
+    }
+
+    #[test]
+    fn test_second_player_steers_blinky() {
+        let mut config = Config::new();
+        config.coop = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        game.handle_input(Keycode::Space);
+        for _ in 0..120 {
+            game.update();
+        }
+        let blinky = |game: &Game| {
+            let blinky = game.ghosts_manager.get(GhostType::Blinky).unwrap();
+            blinky.get_ghost().entity.get_position()
+        };
+
+        // Without input Blinky runs into a wall and waits there
+        let stopped = blinky(&game);
+        game.update();
+        assert_eq!(blinky(&game), stopped);
+
+        game.handle_input(Keycode::J);
+        game.update();
+        assert!(blinky(&game).x < stopped.x);
+    }
+
+    #[test]
+    fn test_door_opens_for_ghosts_leaving_the_house() {
+        let mut game = headless_game();
+        assert_eq!(game.door_open_ms, 0);
+        game.handle_input(Keycode::Space);
+
+        let mut opened = false;
+        for _ in 0..300 {
+            game.update();
+            opened |= game.door_open_ms == DOOR_OPEN_MS;
+        }
+        assert!(opened);
+    }
+
+    #[test]
+    fn test_clear_bonus_tallies_before_next_level() {
+        let mut config = Config::new();
+        config.scoring_rules.add_arcade_plus();
+        let mut game = Game::headless(&config, &Theme::new());
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        for block in game.actual_map.iter_mut().filter(|block| block.is_food()) {
+            *block = BlockType::Nothing;
+        }
+        game.board.count_food(&game.actual_map);
+
+        let score = game.get_score();
+        game.update();
+        assert_eq!(game.get_game_state(), GameState::LevelComplete);
+        let bonus = game.bonus_tally.as_ref().unwrap().get_bonus();
+        assert_eq!(bonus.lives, game.get_lives() as u32 * 1000);
+        assert!(bonus.time > 0);
+
+        while game.get_game_state() == GameState::LevelComplete {
+            assert_eq!(game.level, 1);
+            game.update();
+        }
+        assert_eq!(game.level, 2);
+        assert_eq!(game.get_score(), score + bonus.total());
+    }
+
+    #[test]
+    fn test_escape_pauses_before_quitting() {
+        let mut game = Game::headless(&Config::new(), &Theme::new());
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        assert!(!game.request_quit());
+        assert_eq!(game.get_game_state(), GameState::Paused);
+
+        // Any other key takes the question back; Space then resumes as usual
+        game.handle_input(Keycode::Space);
+        assert_eq!(game.get_game_state(), GameState::Playing);
+        assert!(!game.request_quit());
+        assert!(game.request_quit());
+
+        game.game_state = GameState::GameOver;
+        game.confirm_quit = false;
+        assert!(game.request_quit());
+    }
+
+    #[test]
+    fn test_unplugged_controller_holds_play_until_it_is_back() {
+        let mut game = Game::headless(&Config::new(), &Theme::new());
+        game.controller_disconnected();
+        // The countdown runs out into a paused game rather than one nobody can steer
+        while game.get_game_state() == GameState::Ready {
+            game.update();
+        }
+        assert_eq!(game.get_game_state(), GameState::Paused);
+
+        game.controller_reconnected();
+        assert_eq!(game.get_game_state(), GameState::Playing);
+
+        // Or the keyboard takes over
+        game.controller_disconnected();
+        game.handle_input(Keycode::Space);
+        game.update();
+        assert_eq!(game.get_game_state(), GameState::Playing);
+    }
+
+    #[test]
+    fn test_pause_only_stops_a_run_in_progress() {
+        let mut game = Game::headless(&Config::new(), &Theme::new());
+        game.pause();
+        assert_ne!(game.get_game_state(), GameState::Paused);
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+
+        game.pause();
+        assert_eq!(game.get_game_state(), GameState::Paused);
+        let tick = game.get_tick();
+        game.update();
+        assert_eq!(game.get_tick(), tick);
+    }
+
+    #[test]
+    fn test_leaving_a_pause_for_another_level_counts_down_again() {
+        let mut config = Config::new();
+        config.debug = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        game.pause();
+
+        game.handle_input(Keycode::Backquote);
+        game.type_text("level 3");
+        game.handle_input(Keycode::Return);
+        game.handle_input(Keycode::Backquote);
+        assert_eq!(game.get_game_state(), GameState::Ready);
+
+        // The ready countdown runs on timers the pause no longer holds
+        for _ in 0..2500 / TICK_MS + 5 {
+            game.update();
+        }
+        assert_eq!(game.get_game_state(), GameState::Playing);
+        assert_eq!(game.get_level(), 3);
+        assert!(game.timer_system.get_ghost_ticks() > 0);
+    }
+
+    #[test]
+    fn test_cleared_maze_flashes_before_the_next_level() {
+        let mut config = Config::new();
+        config.maze_colors = MazeColors::PerLevel;
+        let mut game = Game::headless(&config, &Theme::new());
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        let first_color = game.maze_color();
+        remove_food(&mut game, usize::MAX);
+        game.update();
+
+        let mut flashes = 0;
+        let mut updates = 0;
+        while game.get_game_state() == GameState::LevelComplete {
+            if game.maze_color() == WHITE {
+                flashes += 1;
+            }
+            game.update();
+            updates += 1;
+        }
+        assert!(flashes > 0);
+        assert!(updates * TICK_MS >= LEVEL_FLASH_MS);
+        assert_eq!(game.level, 2);
+        assert_ne!(game.maze_color(), first_color);
+    }
+
+    /// Eat `count` dots without moving Pac-Man
+    fn remove_food(game: &mut Game, count: usize) {
+        for block in game
+            .actual_map
+            .iter_mut()
+            .filter(|block| block.is_food())
+            .take(count)
+        {
+            *block = BlockType::Nothing;
+        }
+        game.board.count_food(&game.actual_map);
+    }
+
+    #[test]
+    fn test_fruit_appears_after_seventy_dots() {
+        let mut game = headless_game();
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        remove_food(&mut game, FRUIT_DOTS[0] - 1);
+        game.update();
+        assert!(!game.fruit.is_active());
+
+        remove_food(&mut game, 1);
+        game.update();
+        assert!(game.fruit.is_active());
+        assert_eq!(
+            game.fruit.entity.get_position(),
+            game.board.fruit_position()
+        );
+    }
+
+    #[test]
+    fn test_ms_pacman_rules() {
+        let mut config = Config::new();
+        config.rules_mode = RulesMode::MsPacman;
+        let mut game = Game::headless(&config, &Theme::new());
+        game.start_at_level(3);
+        let alternate: Vec<BlockType> =
+            Board::ALT_BOARD.chars().map(BlockType::from_char).collect();
+        assert_eq!(game.actual_map.to_vec(), alternate);
+        assert_eq!(game.food_total, 252);
+
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        remove_food(&mut game, FRUIT_DOTS[0]);
+        game.update();
+        assert!(game.fruit.is_active());
+        let tile = Tile::from_position(game.fruit.entity.get_position());
+        assert!(tile.x <= 1 || tile.x >= BOARD_WIDTH as i16 - 2);
+        assert!(game.board.tunnel_rows().contains(&(tile.y as usize)));
+    }
+
+    #[test]
+    fn test_dots_label_counts_eaten_food() {
+        let mut game = headless_game();
+        assert_eq!(game.dots_label(), format!("DOTS 0/{}", game.food_total));
+        assert_eq!(game.food_total, 244);
+
+        for _ in 0..400 {
+            game.update();
+        }
+        assert!(game.get_score() > 0);
+        assert_ne!(game.dots_label(), "DOTS 0/244");
+        assert!(game.dots_label().ends_with("/244"));
+    }
+
+    #[test]
+    fn test_food_counter_matches_the_maze() {
+        let scan = |game: &Game| {
+            game.actual_map
+                .iter()
+                .filter(|block| block.is_food())
+                .count()
+        };
+        let mut game = seeded_game(2);
+        assert_eq!(game.remaining_food(), 244);
+        play_scripted(&mut game, 600);
+        assert!(game.remaining_food() < 244);
+        assert_eq!(game.remaining_food(), scan(&game));
+
+        // Loading a snapshot counts its maze afresh
+        let snapshot = game.snapshot();
+        let mut other = seeded_game(2);
+        other.restore_snapshot(&snapshot);
+        assert_eq!(other.remaining_food(), scan(&game));
+    }
+
+    #[test]
+    fn test_rewind_returns_to_earlier_frame() {
+        let mut game = seeded_game(5);
+        game.rewind = Some(RewindBuffer::new(REWIND_CAPACITY));
+        play_scripted(&mut game, 400);
+        assert_eq!(game.get_game_state(), GameState::Playing);
+        let earlier = game.snapshot();
+
+        play_scripted(&mut game, 60);
+        assert_eq!(game.get_game_state(), GameState::Playing);
+        assert_ne!(game.snapshot(), earlier);
+
+        // The newest frame is the one on screen, so one extra step is needed
+        game.handle_input(Keycode::Backspace);
+        for _ in 0..61 {
+            game.update();
+        }
+        game.handle_key_up(Keycode::Backspace);
+        assert_eq!(game.snapshot(), earlier);
+        assert_eq!(game.get_game_state(), GameState::Playing);
+
+        game.update();
+        assert_eq!(game.get_game_state(), GameState::Playing);
+    }
+
+    #[test]
+    fn test_rewind_keeps_the_random_draws() {
+        let mut game = seeded_game(8);
+        game.rewind = Some(RewindBuffer::new(REWIND_CAPACITY));
+        play_scripted(&mut game, 400);
+        // Stand-ins for the frightened turns, which a short scripted run rarely sees
+        for _ in 0..5 {
+            game.rng.next_index(4, "test");
+        }
+        game.update();
+        let earlier = game.rng.get_position();
+        assert_ne!(earlier, 0);
+
+        for _ in 0..30 {
+            game.rng.next_index(4, "test");
+            game.update();
+        }
+        game.handle_input(Keycode::Backspace);
+        for _ in 0..31 {
+            game.update();
+        }
+        game.handle_key_up(Keycode::Backspace);
+        assert_eq!(game.rng.get_position(), earlier);
+        assert_eq!(game.get_seed(), 8);
+    }
+
+    #[test]
+    fn test_rewinding_stops_the_recording() {
+        let mut game = seeded_game(5);
+        game.rewind = Some(RewindBuffer::new(REWIND_CAPACITY));
+        game.recording = Some(Replay::new(ReplayMetadata::new(&Config::new(), 5)));
+        play_scripted(&mut game, 400);
+        let earlier = game.snapshot();
+        play_scripted(&mut game, 30);
+
+        game.handle_input(Keycode::Backspace);
+        assert_eq!(game.toast_text.as_deref(), Some("RUN NO LONGER RECORDED"));
+        assert!(game.recording.is_none());
+        for _ in 0..31 {
+            game.update();
+        }
+        game.handle_key_up(Keycode::Backspace);
+        assert_eq!(game.snapshot(), earlier);
+
+        // The run plays on, but is never kept as the best replay
+        play_scripted(&mut game, 60);
+        assert!(game.recording.is_none());
+    }
+
+    #[test]
+    fn test_idle_pacman_eventually_loses_every_life() {
+        let mut game = headless_game();
+        let mut updates = 0;
+        while game.get_game_state() != GameState::GameOver && updates < 100_000 {
+            game.update();
+            updates += 1;
+        }
+        assert_eq!(game.get_game_state(), GameState::GameOver);
+        assert_eq!(game.get_lives(), 0);
+    }
+
+    #[test]
+    fn test_reset_after_game_over_plays_like_a_new_game() {
+        let mut config = Config::new();
+        config.seed = Some(11);
+        let mut game = Game::headless(&config, &Theme::new());
+        play_scripted(&mut game, 600);
+        while game.get_game_state() != GameState::GameOver {
+            game.update();
+        }
+
+        game.reset(&config);
+        let mut fresh = Game::headless(&config, &Theme::new());
+        assert_eq!(game.get_game_state(), GameState::Ready);
+        assert_eq!(game.get_score(), 0);
+        assert_eq!(game.get_lives(), crate::board::STARTING_LIVES);
+        assert_eq!(game.get_level(), 1);
+        assert_eq!(game.snapshot(), fresh.snapshot());
+
+        play_scripted(&mut game, 600);
+        play_scripted(&mut fresh, 600);
+        assert!(game.get_score() > 0);
+        assert_eq!(game.snapshot(), fresh.snapshot());
+    }
+
+    #[test]
+    fn test_difficulty_sets_lives_and_energizer_length() {
+        let mut config = Config::new();
+        config.difficulty = Difficulty::Hard;
+        let mut game = Game::headless(&config, &Theme::new());
+        assert_eq!(game.get_lives(), Difficulty::Hard.starting_lives());
+        game.reset(&config);
+        assert_eq!(game.get_lives(), Difficulty::Hard.starting_lives());
+
+        // Eat the top left energizer on level 1
+        game.set_state(GameState::Playing);
+        let block = BLOCK_SIZE_24 as i16;
+        game.pacman.set_position(Position::new(block, 6 * block));
+        game.food_collision();
+        assert_eq!(
+            game.power_state.get_remaining_ms(),
+            Difficulty::Hard.level_params(1).fright_time
+        );
+        assert!(game.power_state.get_remaining_ms() < LevelParams::for_level(1).fright_time);
+    }
+
+    #[test]
+    fn test_kids_mode_never_ends_and_is_not_recorded() {
+        let mut config = Config::new();
+        config.seed = Some(4);
+        config.difficulty = Difficulty::Kids;
+        let mut game = Game::headless(&config, &Theme::new());
+        game.reset(&config);
+        assert!(game.recording.is_none());
+
+        let lives = game.get_lives();
+        let mut deaths = 0;
+        for _ in 0..20_000 {
+            game.update();
+            if game.get_game_state() == GameState::PacmanDeath && game.death_freeze_ms > 0 {
+                deaths += 1;
+                while game.get_game_state() == GameState::PacmanDeath {
+                    game.update();
+                }
+            }
+        }
+        assert!(deaths > 1);
+        assert_eq!(game.get_lives(), lives);
+        assert_ne!(game.get_game_state(), GameState::GameOver);
+
+        // Tab between games goes back to the usual difficulty, and the next game is recorded
+        let mut config = Config::new();
+        config.difficulty = Difficulty::Hard;
+        let mut game = Game::headless(&config, &Theme::new());
+        game.handle_input(Keycode::Tab);
+        assert_eq!(game.difficulty, Difficulty::Hard);
+        game.set_state(GameState::GameOver);
+        game.handle_input(Keycode::Tab);
+        assert_eq!(game.difficulty, Difficulty::Kids);
+        game.handle_input(Keycode::Tab);
+        assert_eq!(game.difficulty, Difficulty::Hard);
+        game.reset(&config);
+        assert!(game.recording.is_some());
+    }
+
+    #[test]
+    fn test_survival_refills_the_maze_and_adds_ghosts() {
+        let mut config = Config::new();
+        config.seed = Some(6);
+        config.survival = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        game.reset(&config);
+        assert!(game.recording.is_none());
+        assert_eq!(game.dots_label(), "TIME 0:00");
+        game.board.set_lives(100);
+
+        // Clearing the maze fills it straight back up instead of ending the level
+        for _ in 0..200 {
+            game.update();
+        }
+        for block in game.actual_map.iter_mut().filter(|block| block.is_food()) {
+            *block = BlockType::Nothing;
+        }
+        game.board.count_food(&game.actual_map);
+        while game.get_game_state() != GameState::Playing {
+            game.update();
+        }
+        game.update();
+        assert_eq!(game.remaining_food(), game.food_total);
+        assert_eq!(game.get_level(), 1);
+
+        let mut playing_ms = 0;
+        while playing_ms < crate::game::survival::GHOST_MS {
+            if game.get_game_state() == GameState::Playing && game.ghost_freeze_ms == 0 {
+                playing_ms += TICK_MS;
+            }
+            game.update();
+        }
+        assert_eq!(game.ghosts_manager.len(), 5);
+        assert_eq!(game.dots_label(), "TIME 2:00");
+        assert!(game.get_score() >= 120 * crate::game::survival::POINTS_PER_SECOND);
+        assert_eq!(game.get_level(), 1);
+    }
+
+    #[test]
+    fn test_ghost_rush_never_scatters_and_pays_double() {
+        let mut config = Config::new();
+        config.seed = Some(6);
+        config.ghost_rush = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        game.reset(&config);
+        assert!(game.recording.is_none());
+        assert!(game
+            .ghosts_manager
+            .iter()
+            .all(|ghost| ghost.get_ghost().direct_chase));
+
+        while game.get_score() == 0 {
+            assert!(!game
+                .ghosts_manager
+                .get_ghost_modes()
+                .contains(&GhostMode::Scatter));
+            game.update();
+        }
+        assert_eq!(game.get_score(), 20);
+        for _ in 0..600 {
+            game.update();
+            assert!(!game
+                .ghosts_manager
+                .get_ghost_modes()
+                .contains(&GhostMode::Scatter));
+        }
+    }
+
+    #[test]
+    fn test_practice_starts_anywhere_and_keeps_lives() {
+        let mut config = Config::new();
+        config.seed = Some(4);
+        config.practice = Some(Practice {
+            level: 5,
+            maze: Some(1),
+            infinite_lives: true,
+        });
+        let mut game = Game::headless(&config, &Theme::new());
+        assert_eq!(game.get_level(), 5);
+        let walls = |sketch: &str| -> Vec<bool> {
+            sketch
+                .chars()
+                .map(|c| BlockType::from_char(c) == BlockType::Wall)
+                .collect()
+        };
+        let played: Vec<bool> = game
+            .actual_map
+            .iter()
+            .map(|&block| block == BlockType::Wall)
+            .collect();
+        assert_eq!(played, walls(Board::ALT_BOARD));
+        assert_ne!(played, walls(Board::CHAR_BOARD));
+        assert!(game.shows_dots_counter());
+        assert_eq!(game.dots_label(), format!("LEFT {}", game.food_total));
+
+        // Standing still, Pac-Man is caught again and again without ever running out
+        for _ in 0..20_000 {
+            game.update();
+        }
+        assert!(game.get_session_stats().get_deaths() > 0);
+        assert_eq!(game.get_lives(), crate::board::STARTING_LIVES);
+        assert_ne!(game.get_game_state(), GameState::GameOver);
+
+        game.reset(&config);
+        assert_eq!(game.get_level(), 5);
+        assert!(game.recording.is_none());
+    }
+
+    #[test]
+    fn test_console_cheats() {
+        let mut config = Config::new();
+        config.debug = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        let enter = |game: &mut Game, line: &str| {
+            game.type_text(line);
+            game.handle_input(Keycode::Return);
+        };
+
+        // Typing goes nowhere until the console is opened
+        game.type_text("score 5");
+        assert!(!game.is_console_open());
+        game.handle_input(Keycode::Backquote);
+        assert!(game.is_console_open());
+
+        enter(&mut game, "level 7");
+        enter(&mut game, "score 10000");
+        enter(&mut game, "kill blinky");
+        enter(&mut game, "noclip");
+        assert_eq!(game.get_level(), 7);
+        assert_eq!(game.get_game_state(), GameState::Ready);
+        assert_eq!(game.get_score(), 10000);
+        assert!(!game
+            .ghosts_manager
+            .get(GhostType::Blinky)
+            .unwrap()
+            .get_ghost()
+            .entity
+            .is_alive());
+        assert!(game.pacman.is_noclip());
+        assert!(game.recording.is_none());
+
+        // Nothing moves while the console is up, and typed keys do not steer
+        let tick = game.get_tick();
+        game.handle_input(Keycode::Up);
+        game.update();
+        assert_eq!(game.get_tick(), tick);
+        assert_eq!(game.mover.queued(), None);
+
+        game.handle_input(Keycode::Escape);
+        assert!(!game.is_console_open());
+
+        let mut plain = headless_game();
+        plain.handle_input(Keycode::Backquote);
+        assert!(!plain.is_console_open());
+    }
+
+    #[test]
+    fn test_menu_cheat_codes() {
+        let config = Config::new();
+        let mut game = Game::headless(&config, &Theme::new());
+        let type_keys = |game: &mut Game, keys: &[Keycode]| {
+            for &key in keys {
+                game.handle_input(key);
+            }
+        };
+        let level_select = [
+            Keycode::Up,
+            Keycode::Up,
+            Keycode::Down,
+            Keycode::Down,
+            Keycode::Left,
+            Keycode::Right,
+            Keycode::Left,
+            Keycode::Right,
+        ];
+
+        // Number keys do nothing until level select is unlocked
+        game.handle_input(Keycode::Num4);
+        assert_eq!(game.get_level(), 1);
+        type_keys(&mut game, &level_select);
+        assert!(game.cheat_codes.is_unlocked(Cheat::LevelSelect));
+        assert_eq!(game.take_sounds(), vec![Sound::centered(SoundCue::Unlock)]);
+        game.handle_input(Keycode::Num4);
+        assert_eq!(game.get_level(), 4);
+        assert!(game.recording.is_none());
+
+        // New games start there too, until the code is typed again
+        game.reset(&config);
+        assert_eq!(game.get_level(), 4);
+        assert!(game.recording.is_none());
+        type_keys(&mut game, &level_select);
+        assert!(!game.cheat_codes.is_unlocked(Cheat::LevelSelect));
+        game.reset(&config);
+        assert_eq!(game.get_level(), 1);
+        assert!(game.recording.is_some());
+
+        // Codes are only listened for on the menu
+        game.update();
+        type_keys(&mut game, &level_select);
+        assert!(!game.cheat_codes.is_unlocked(Cheat::LevelSelect));
+    }
+
+    #[test]
+    fn test_credits_start_and_continue_games() {
+        let mut config = Config::new();
+        config.seed = Some(3);
+        config.coins = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        assert_eq!(game.get_game_state(), GameState::InsertCoin);
+        assert_eq!(game.get_credits(), Some(0));
+
+        // No credit, no game
+        game.press_start(&config);
+        game.update();
+        assert_eq!(game.get_game_state(), GameState::InsertCoin);
+
+        game.handle_input(Keycode::C);
+        game.handle_input(Keycode::C);
+        game.press_start(&config);
+        assert_eq!(game.get_game_state(), GameState::Ready);
+        assert_eq!(game.get_credits(), Some(1));
+
+        play_scripted(&mut game, 600);
+        while game.get_game_state() != GameState::GameOver {
+            game.update();
+        }
+        let level = game.get_level();
+        let dots = game.dots_label();
+
+        game.press_start(&config);
+        assert_eq!(game.get_game_state(), GameState::Ready);
+        assert_eq!(game.get_credits(), Some(0));
+        assert_eq!(game.get_score(), 0);
+        assert_eq!(game.get_lives(), crate::board::STARTING_LIVES);
+        assert_eq!(game.get_level(), level);
+        assert_eq!(game.dots_label(), dots);
+        for _ in 0..300 {
+            game.update();
+        }
+        assert_eq!(game.get_game_state(), GameState::Playing);
+
+        // Free play ignores coins
+        let mut free = seeded_game(3);
+        free.insert_coin();
+        assert_eq!(free.get_credits(), None);
+    }
+
+    #[test]
+    fn test_bot_runs_are_not_comparable() {
+        let mut config = Config::new();
+        config.seed = Some(3);
+        assert!(Game::headless(&config, &Theme::new()).is_comparable_run());
+
+        config.bot = true;
+        let mut game = Game::headless(&config, &Theme::new());
+        assert!(!game.is_comparable_run());
+        game.reset(&config);
+        assert!(game.recording.is_none());
+    }
+
+    #[test]
+    fn test_finished_runs_are_only_kept_where_asked() {
+        let path = std::env::temp_dir().join(format!("core_best_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let mut config = Config::new();
+        config.seed = Some(3);
+        let mut game = Game::headless(&config, &Theme::new());
+        game.reset(&config);
+        play_scripted(&mut game, 600);
+        game.finish_recording();
+        assert!(!std::path::Path::new(path).exists());
+
+        game.reset(&config);
+        assert!(game.recording.is_some());
+        game.set_best_replay_path(path);
+        play_scripted(&mut game, 600);
+        game.finish_recording();
+        let saved = Replay::load(path).unwrap();
+        assert_eq!(saved.metadata.final_score, game.get_score());
+        std::fs::remove_file(path).unwrap();
     }
 }
@@ -0,0 +1,72 @@
+use crate::game::collision::GhostType;
+use crate::position::Position;
+
+/// Something that happened during an update, for the systems that react to it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    PelletEaten {
+        position: Position,
+    },
+    EnergizerEaten {
+        position: Position,
+    },
+    GhostEaten {
+        ghost: GhostType,
+        position: Position,
+        points: u32,
+    },
+    /// A bonus fruit of `kind` (cherry first) was put out
+    FruitSpawned {
+        kind: usize,
+    },
+    FruitEaten {
+        position: Position,
+        points: u32,
+    },
+    PacmanDied {
+        killed_by: GhostType,
+    },
+    LevelCompleted {
+        level: u16,
+    },
+    ExtraLife,
+}
+
+/// Events queued while an update runs, handed out in order once it is done
+#[derive(Debug, Default)]
+pub struct EventBus {
+    pending: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus::default()
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.pending.push(event);
+    }
+
+    /// Take every queued event, oldest first
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_drain_in_order() {
+        let mut bus = EventBus::new();
+        bus.emit(GameEvent::FruitSpawned { kind: 0 });
+        bus.emit(GameEvent::ExtraLife);
+
+        assert_eq!(
+            bus.drain(),
+            vec![GameEvent::FruitSpawned { kind: 0 }, GameEvent::ExtraLife]
+        );
+        assert!(bus.drain().is_empty());
+    }
+}
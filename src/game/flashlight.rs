@@ -0,0 +1,138 @@
+use crate::board::TUNNEL_ROWS;
+use crate::position::Tile;
+use crate::{BLOCK_SIZE_24, BOARD_WIDTH};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, WindowCanvas};
+
+/// How dark the maze is away from Pac-Man; a little light gets through so the walls can
+/// still be made out
+const DARK_ALPHA: f32 = 235.0;
+/// How long an energizer lights up the whole maze; the dark creeps back in over that time
+const LIGHT_UP_MS: u32 = 2000;
+
+/// Fog of war, where only the tiles around Pac-Man are seen at full brightness.
+///
+/// The darkness is a mask drawn over the maze rows once the board and actors are drawn, and
+/// before the HUD. Nothing in the game itself changes, so replays play out the same with or
+/// without it.
+pub struct Flashlight {
+    /// Tiles from Pac-Man's that stay lit
+    radius: u16,
+    lit_ms: u32,
+}
+
+impl Flashlight {
+    pub fn new(radius: u16) -> Self {
+        Flashlight { radius, lit_ms: 0 }
+    }
+
+    /// Light the whole maze, as an energizer does
+    pub fn light_up(&mut self) {
+        self.lit_ms = LIGHT_UP_MS;
+    }
+
+    pub fn update(&mut self, elapsed_ms: u32) {
+        self.lit_ms = self.lit_ms.saturating_sub(elapsed_ms);
+    }
+
+    fn is_lit(&self, pacman: Tile, tile: Tile) -> bool {
+        let (dx, dy) = ((tile.x - pacman.x) as i32, (tile.y - pacman.y) as i32);
+        dx * dx + dy * dy <= (self.radius as i32).pow(2)
+    }
+
+    fn dark_alpha(&self) -> u8 {
+        (DARK_ALPHA * (LIGHT_UP_MS - self.lit_ms) as f32 / LIGHT_UP_MS as f32) as u8
+    }
+
+    /// Darken the maze outside the light around Pac-Man's tile
+    pub fn draw(
+        &self,
+        canvas: &mut WindowCanvas,
+        pacman: Tile,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let alpha = self.dark_alpha();
+        if alpha == 0 {
+            return Ok(());
+        }
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, alpha));
+        canvas.fill_rects(&self.dark_runs(pacman))?;
+        canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+
+    /// The unlit tiles of the maze rows, one rectangle for each run of them along a row
+    fn dark_runs(&self, pacman: Tile) -> Vec<Rect> {
+        let block = BLOCK_SIZE_24 as i32;
+        let mut runs = Vec::new();
+        for y in TUNNEL_ROWS.0..=TUNNEL_ROWS.1 {
+            let mut start = None;
+            for x in 0..=BOARD_WIDTH {
+                let tile = Tile {
+                    x: x as i16,
+                    y: y as i16,
+                };
+                let dark = x < BOARD_WIDTH && !self.is_lit(pacman, tile);
+                match start {
+                    None if dark => start = Some(x),
+                    Some(first) if !dark => {
+                        let width = (x - first) as u32 * BLOCK_SIZE_24;
+                        runs.push(Rect::new(
+                            first as i32 * block,
+                            y as i32 * block,
+                            width,
+                            BLOCK_SIZE_24,
+                        ));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_tiles_near_pacman_are_lit() {
+        let flashlight = Flashlight::new(3);
+        let pacman = Tile { x: 13, y: 26 };
+        let runs = flashlight.dark_runs(pacman);
+        let block = BLOCK_SIZE_24 as i32;
+        let covered = |x: i32, y: i32| {
+            runs.iter()
+                .any(|run| run.contains_point((x * block + 1, y * block + 1)))
+        };
+
+        assert!(!covered(13, 26));
+        assert!(!covered(16, 26));
+        assert!(!covered(15, 28));
+        assert!(covered(17, 26));
+        assert!(covered(16, 29));
+        assert!(covered(0, TUNNEL_ROWS.0 as i32));
+        // The score rows above the maze and the lives below it are left alone
+        assert!(!covered(0, TUNNEL_ROWS.0 as i32 - 1));
+        assert!(!covered(0, TUNNEL_ROWS.1 as i32 + 1));
+        // Pac-Man's row is dark on either side of him
+        assert_eq!(runs.iter().filter(|run| run.y() == 26 * block).count(), 2);
+    }
+
+    #[test]
+    fn test_energizer_lights_the_maze_for_a_moment() {
+        let mut flashlight = Flashlight::new(4);
+        assert_eq!(flashlight.dark_alpha(), DARK_ALPHA as u8);
+
+        flashlight.light_up();
+        assert_eq!(flashlight.dark_alpha(), 0);
+        flashlight.update(LIGHT_UP_MS / 2);
+        let halfway = flashlight.dark_alpha();
+        assert!(halfway > 0 && halfway < DARK_ALPHA as u8);
+        flashlight.update(LIGHT_UP_MS);
+        assert_eq!(flashlight.dark_alpha(), DARK_ALPHA as u8);
+    }
+}
@@ -1,128 +1,763 @@
-use crate::board::{BlockType, Direction, EntityType};
+use crate::assets::AssetManager;
+use crate::board::{BlockType, Board, Direction, EntityType};
+use crate::entity::ghost_trait::{Ghost, TRAIL_FRAMES};
 use crate::entity::pacman::Pacman;
-use crate::entity::{Blinky, Clyde, Entity, GhostBehavior, Inky, Pinky};
-use sdl2::render::{Canvas, TextureCreator};
-use sdl2::video::WindowContext;
-
-/// Simplified ghost management system for all ghosts
-#[allow(dead_code)]
-pub struct GhostManager<'a> {
-    pub blinky: Blinky<'a>,
-    pub inky: Inky<'a>,
-    pub pinky: Pinky<'a>,
-    pub clyde: Clyde<'a>,
+use crate::entity::{Blinky, Clyde, Entity, GhostBehavior, GhostType, Inky, Pinky};
+use crate::game::ghost_mode::{GhostEvent, GhostMode};
+use crate::game::personality::GhostPersonality;
+use crate::game::power::PowerState;
+use crate::game::rng::GameRng;
+use crate::game::snapshot::GhostState;
+use crate::game::teleport;
+use crate::game::tile_graph::TileGraph;
+use crate::palette::Palette;
+use crate::position::Position;
+use crate::theme::Theme;
+use crate::BLOCK_SIZE_24;
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+
+/// Start marker of the first extra ghost; later ones take the digits after it
+const FIRST_EXTRA_MARKER: u8 = b'5';
+/// Extra ghosts a game can hold, one per marker from '5' to '9'
+pub const MAX_EXTRA_GHOSTS: usize = 5;
+
+/// A ghost added on top of the usual four, from `--extra-ghost KIND[:RRGGBB[:COL,ROW]]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtraGhost {
+    /// Whose chasing behavior it copies
+    pub kind: GhostType,
+    /// Body color, or the kind's own when absent
+    pub color: Option<Color>,
+    /// Tile it heads for when scattering, or the kind's corner when absent
+    pub corner: Option<(i16, i16)>,
 }
 
-#[allow(dead_code)]
-impl<'a> GhostManager<'a> {
-    /// Create new ghost manager with all ghosts
-    pub fn new(
-        texture_creator: &'a TextureCreator<WindowContext>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let blinky = Blinky::new(texture_creator)?;
-        let inky = Inky::new(texture_creator)?;
-        let pinky = Pinky::new(texture_creator)?;
-        let clyde = Clyde::new(texture_creator)?;
-
-        Ok(GhostManager {
-            blinky,
-            inky,
-            pinky,
-            clyde,
+impl ExtraGhost {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid extra ghost: {}", spec);
+        let mut parts = spec.split(':');
+        let kind = parts
+            .next()
+            .and_then(GhostType::from_name)
+            .ok_or_else(invalid)?;
+        // An empty color, as in `clyde::1,4`, keeps the kind's own
+        let color = match parts.next() {
+            Some("") | None => None,
+            Some(hex) => {
+                if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(invalid());
+                }
+                let rgb = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+                Some(Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+            }
+        };
+        let corner = match parts.next() {
+            Some(tile) => {
+                let (column, row) = tile.split_once(',').ok_or_else(invalid)?;
+                let column = column.parse().map_err(|_| invalid())?;
+                let row = row.parse().map_err(|_| invalid())?;
+                Some((column, row))
+            }
+            None => None,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(ExtraGhost {
+            kind,
+            color,
+            corner,
         })
     }
 
-    /// Update all ghost positions
+    /// The spec written back out, for the config fingerprint
+    pub fn fingerprint(&self) -> String {
+        let color = self
+            .color
+            .map(|color| format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+            .unwrap_or_default();
+        match self.corner {
+            Some((column, row)) => format!("{}:{}:{},{}", self.kind.name(), color, column, row),
+            None if self.color.is_some() => format!("{}:{}", self.kind.name(), color),
+            None => self.kind.name().to_string(),
+        }
+    }
+}
+
+/// Middle of a (column, row) tile
+fn tile_center((column, row): (i16, i16)) -> Position {
+    let half = (BLOCK_SIZE_24 / 2) as i16;
+    Position::new(
+        column * BLOCK_SIZE_24 as i16 + half,
+        row * BLOCK_SIZE_24 as i16 + half,
+    )
+}
+
+/// Where a ghost starts: its marker, or for an extra ghost whose marker the maze lacks, the
+/// ghost house
+fn start_position(ghost: &Ghost, board: &Board) -> Position {
+    match ghost.spawn_marker {
+        Some(marker) => board
+            .marker_position(marker)
+            .unwrap_or_else(|| board.reset_position(EntityType::Pinky)),
+        None => board.reset_position(ghost.entity.get_identity()),
+    }
+}
+
+/// Every ghost in the maze: Blinky, Inky, Pinky, Clyde, then any extras in the order added
+pub struct GhostManager {
+    ghosts: Vec<Box<dyn GhostBehavior>>,
+}
+
+impl GhostManager {
+    /// Create new ghost manager with all ghosts
+    pub fn new(theme: &Theme) -> Self {
+        GhostManager {
+            ghosts: vec![
+                Box::new(Blinky::new(theme)),
+                Box::new(Inky::new(theme)),
+                Box::new(Pinky::new(theme)),
+                Box::new(Clyde::new(theme)),
+            ],
+        }
+    }
+
+    /// Add a ghost after the others, starting on the next free marker from '5'.
+    ///
+    /// It takes the reach and corner of the first ghost of its kind unless the spec says otherwise.
+    pub fn add_ghost(&mut self, spec: &ExtraGhost, theme: &Theme) {
+        let mut ghost: Box<dyn GhostBehavior> = match spec.kind {
+            GhostType::Blinky => Box::new(Blinky::new(theme)),
+            GhostType::Inky => Box::new(Inky::new(theme)),
+            GhostType::Pinky => Box::new(Pinky::new(theme)),
+            GhostType::Clyde => Box::new(Clyde::new(theme)),
+        };
+        if let Some(original) = self.get(spec.kind) {
+            let original = original.get_ghost();
+            ghost.get_ghost_mut().reach_tiles = original.reach_tiles;
+            ghost.get_ghost_mut().scatter_target = original.scatter_target;
+        }
+        let extras = self.ghosts.len().saturating_sub(4) as u8;
+        let state = ghost.get_ghost_mut();
+        state.spawn_marker = Some((FIRST_EXTRA_MARKER + extras) as char);
+        if let Some(color) = spec.color {
+            state.color = color;
+        }
+        if let Some(corner) = spec.corner {
+            state.scatter_target = tile_center(corner);
+        }
+        self.ghosts.push(ghost);
+    }
+
+    /// Add a ghost in the middle of a game, on its start marker.
+    ///
+    /// It also takes the speed, scatter, quirks, chase and trail of the first ghost of its kind,
+    /// which the game only hands out to the ghosts it starts with.
+    pub fn spawn_ghost(&mut self, spec: &ExtraGhost, theme: &Theme, board: &Board) {
+        let original = self.get(spec.kind).map(|original| {
+            let original = original.get_ghost();
+            (
+                original.speed_percent,
+                original.random_scatter,
+                original.arcade_quirks,
+                original.direct_chase,
+                original.trail_length,
+            )
+        });
+        self.add_ghost(spec, theme);
+        let Some(ghost) = self.ghosts.last_mut() else {
+            return;
+        };
+        let ghost = ghost.get_ghost_mut();
+        if let Some((speed_percent, random_scatter, arcade_quirks, direct_chase, trail_length)) =
+            original
+        {
+            ghost.speed_percent = speed_percent;
+            ghost.random_scatter = random_scatter;
+            ghost.arcade_quirks = arcade_quirks;
+            ghost.direct_chase = direct_chase;
+            ghost.trail_length = trail_length;
+        }
+        let start = start_position(ghost, board);
+        ghost.entity.set_position(start);
+    }
+
+    /// Retune every ghost's targeting reach and scatter corner by type
+    pub fn set_personality(&mut self, personality: &GhostPersonality) {
+        for ghost in self.ghosts.iter_mut() {
+            let ghost_type = ghost.get_ghost_type();
+            let ghost = ghost.get_ghost_mut();
+            ghost.reach_tiles = personality.reach(ghost_type);
+            ghost.scatter_target = tile_center(personality.corner(ghost_type));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ghosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ghosts.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn GhostBehavior> {
+        self.ghosts.iter().map(|ghost| ghost.as_ref())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn GhostBehavior>> {
+        self.ghosts.iter_mut()
+    }
+
+    pub fn get(&self, ghost_type: GhostType) -> Option<&dyn GhostBehavior> {
+        self.iter()
+            .find(|ghost| ghost.get_ghost_type() == ghost_type)
+    }
+
+    pub fn get_mut(&mut self, ghost_type: GhostType) -> Option<&mut Box<dyn GhostBehavior>> {
+        self.iter_mut()
+            .find(|ghost| ghost.get_ghost_type() == ghost_type)
+    }
+
+    /// The ghost at `index` in manager order, which tells apart ghosts of the same type
+    pub fn nth_mut(&mut self, index: usize) -> Option<&mut Box<dyn GhostBehavior>> {
+        self.ghosts.get_mut(index)
+    }
+
+    /// Have Pinky and Inky, and any extras copying them, aim with the arcade's upward
+    /// look-ahead bug
+    pub fn set_arcade_quirks(&mut self, enabled: bool) {
+        for ghost in self.ghosts.iter_mut() {
+            if matches!(ghost.get_ghost_type(), GhostType::Pinky | GhostType::Inky) {
+                ghost.get_ghost_mut().arcade_quirks = enabled;
+            }
+        }
+    }
+
+    /// Have every ghost chase Pac-Man's own tile as Blinky does, for the ghost rush
+    pub fn set_direct_chase(&mut self, enabled: bool) {
+        for ghost in self.ghosts.iter_mut() {
+            ghost.get_ghost_mut().direct_chase = enabled;
+        }
+    }
+
+    /// Leave a fading trail behind frightened ghosts
+    pub fn set_trails(&mut self, enabled: bool) {
+        let length = if enabled { TRAIL_FRAMES } else { 0 };
+        for ghost in self.ghosts.iter_mut() {
+            ghost.get_ghost_mut().trail_length = length;
+        }
+    }
+
+    /// Send every living ghost of a kind home as eyes; returns how many there were
+    pub fn kill(&mut self, ghost_type: GhostType) -> usize {
+        let mut killed = 0;
+        for ghost in self.ghosts.iter_mut() {
+            if ghost.get_ghost_type() == ghost_type && ghost.get_ghost().entity.is_alive() {
+                ghost.get_ghost_mut().transition(GhostEvent::Eaten);
+                killed += 1;
+            }
+        }
+        killed
+    }
+
+    /// Speed every living ghost up or down by a percentage, for the difficulty presets
+    pub fn set_speed_percent(&mut self, percent: u32) {
+        for ghost in self.ghosts.iter_mut() {
+            ghost.get_ghost_mut().speed_percent = percent;
+        }
+    }
+
+    /// Have Blinky and Pinky, and any extras copying them, scatter by turning at random,
+    /// as in Ms. Pac-Man
+    pub fn set_random_scatter(&mut self, enabled: bool) {
+        for ghost in self.ghosts.iter_mut() {
+            if matches!(ghost.get_ghost_type(), GhostType::Blinky | GhostType::Pinky) {
+                ghost.get_ghost_mut().random_scatter = enabled;
+            }
+        }
+    }
+
+    /// Update all ghost positions; `blinky_player` hands Blinky to a second player
     pub fn update_all_ghosts(
         &mut self,
-        actual_map: &[BlockType],
+        graph: &TileGraph,
         pacman: &Pacman,
         is_scatter_mode: bool,
+        power: &PowerState,
+        rng: &mut GameRng,
+        blinky_player: Option<Direction>,
     ) {
-        // Get blinky position for inky's special targeting
-        let blinky_pos = self.blinky.get_ghost().entity.get_position();
+        // Inky aims relative to where the first Blinky was before anyone moved
+        let blinky_pos = self
+            .get(GhostType::Blinky)
+            .map(|blinky| blinky.get_ghost().entity.get_position());
 
-        self.blinky
-            .update_pos(actual_map, pacman, None, is_scatter_mode);
-        self.inky
-            .update_pos(actual_map, pacman, Some(blinky_pos), is_scatter_mode);
-        self.pinky
-            .update_pos(actual_map, pacman, None, is_scatter_mode);
-        self.clyde
-            .update_pos(actual_map, pacman, None, is_scatter_mode);
+        for ghost in self.ghosts.iter_mut() {
+            match blinky_player {
+                Some(wanted) if ghost.get_ghost_type() == GhostType::Blinky => {
+                    ghost.update_pos_controlled(graph, pacman, wanted, is_scatter_mode, power, rng)
+                }
+                _ => ghost.update_pos(graph, pacman, blinky_pos, is_scatter_mode, power, rng),
+            }
+        }
     }
 
-    /// Draw all ghosts
+    /// Carry any ghost standing on a teleporter over to its twin
+    pub fn teleport_all(&mut self, actual_map: &[BlockType], elapsed_ms: u32) {
+        for ghost in self.ghosts.iter_mut() {
+            teleport::update(&mut ghost.get_ghost_mut().entity, actual_map, elapsed_ms);
+        }
+    }
+
+    /// Draw all ghosts; frozen ghosts hold their current animation frame
     pub fn draw_all_ghosts(
         &mut self,
         canvas: &mut Canvas<sdl2::video::Window>,
-        pacman_energized: bool,
-        ghost_ticks: u128,
-        ghost_timer_target: u32,
+        assets: &mut AssetManager,
+        power: &PowerState,
+        palette: Palette,
+        show_patterns: bool,
+        frozen: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        self.blinky
-            .get_ghost_mut()
-            .draw(canvas, pacman_energized, ghost_ticks, ghost_timer_target)?;
-        self.inky
-            .get_ghost_mut()
-            .draw(canvas, pacman_energized, ghost_ticks, ghost_timer_target)?;
-        self.pinky
-            .get_ghost_mut()
-            .draw(canvas, pacman_energized, ghost_ticks, ghost_timer_target)?;
-        self.clyde
-            .get_ghost_mut()
-            .draw(canvas, pacman_energized, ghost_ticks, ghost_timer_target)?;
+        for ghost in self.ghosts.iter_mut() {
+            ghost
+                .get_ghost_mut()
+                .draw(canvas, assets, power, palette, show_patterns, frozen)?;
+        }
         Ok(())
     }
 
-    /// Reset all ghost life statements (alive)
-    pub fn reset_all_ghost_life_statements(&mut self) {
-        self.blinky.get_ghost_mut().entity.mod_life_statement(true);
-        self.inky.get_ghost_mut().entity.mod_life_statement(true);
-        self.pinky.get_ghost_mut().entity.mod_life_statement(true);
-        self.clyde.get_ghost_mut().entity.mod_life_statement(true);
+    /// Turn every ghost out in the maze around, as happens whenever an energizer is eaten
+    pub fn reverse_all_ghost_directions(&mut self) {
+        for ghost in self.ghosts.iter_mut() {
+            let ghost = ghost.get_ghost_mut();
+            if ghost.get_mode().is_alive() && !ghost.get_mode().is_housed() {
+                let direction = ghost.entity.get_direction().opposite();
+                ghost.entity.mod_direction(direction);
+            }
+        }
+    }
+
+    /// Frighten every ghost out in the maze for a new energizer; those in the house stay put
+    pub fn frighten_all_ghosts(&mut self) {
+        for ghost in self.ghosts.iter_mut() {
+            ghost.get_ghost_mut().transition(GhostEvent::Energizer);
+        }
+    }
+
+    /// Start every ghost on a new life, on its way out of the house.
+    ///
+    /// Ghosts starting outside, like Blinky, are out already; the schedule sets them chasing
+    /// or scattering on the next update.
+    pub fn reset_all_ghost_modes(&mut self) {
+        for ghost in self.ghosts.iter_mut() {
+            let ghost = ghost.get_ghost_mut();
+            ghost.set_mode(GhostMode::LeavingHouse);
+            ghost.update_house_mode(false);
+        }
     }
 
     /// Reset all ghost facing directions
     pub fn reset_all_ghost_facing(&mut self) {
-        self.blinky.get_ghost_mut().entity.set_facing(Direction::Left);
-        self.inky.get_ghost_mut().entity.set_facing(Direction::Up);
-        self.pinky.get_ghost_mut().entity.set_facing(Direction::Down);
-        self.clyde.get_ghost_mut().entity.set_facing(Direction::Up);
+        for ghost in self.ghosts.iter_mut() {
+            let facing = ghost.start_facing();
+            ghost.get_ghost_mut().entity.set_facing(facing);
+        }
     }
 
-    /// Set all ghost positions to their home positions
+    /// Set all ghost positions to their home positions.
+    ///
+    /// Extra ghosts whose marker the maze lacks start inside the house, on Pinky's spot.
     pub fn reset_all_ghost_positions(&mut self, board: &crate::board::Board) {
-        let blinky_start = board.reset_position(EntityType::Blinky);
-        self.blinky.get_ghost_mut().entity.set_position(blinky_start);
+        for ghost in self.ghosts.iter_mut() {
+            let ghost = ghost.get_ghost_mut();
+            let start = start_position(ghost, board);
+            ghost.entity.set_position(start);
+        }
+    }
+
+    /// Positions in manager order
+    pub fn get_ghost_positions(&self) -> Vec<Position> {
+        self.iter()
+            .map(|ghost| ghost.get_ghost().entity.get_position())
+            .collect()
+    }
+
+    /// Directions and modes in manager order
+    pub fn get_ghost_states(&self) -> Vec<GhostState> {
+        self.iter()
+            .map(|ghost| GhostState {
+                direction: ghost.get_ghost().entity.get_direction(),
+                mode: ghost.get_ghost().get_mode(),
+            })
+            .collect()
+    }
+
+    pub fn set_ghost_states(&mut self, states: &[GhostState]) {
+        for (ghost, state) in self.ghosts.iter_mut().zip(states) {
+            let ghost = ghost.get_ghost_mut();
+            ghost.entity.mod_direction(state.direction);
+            ghost.set_mode(state.mode);
+        }
+    }
+
+    /// Modes in manager order
+    pub fn get_ghost_modes(&self) -> Vec<GhostMode> {
+        self.iter()
+            .map(|ghost| ghost.get_ghost().get_mode())
+            .collect()
+    }
+
+    pub fn set_ghost_positions(&mut self, positions: &[Position]) {
+        for (ghost, position) in self.ghosts.iter_mut().zip(positions) {
+            ghost.get_ghost_mut().entity.set_position(*position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::ghost_trait::REGENERATE_MS;
+
+    #[test]
+    fn test_arcade_quirks_shift_upward_targets_left() {
+        let mut ghosts = GhostManager::new(&Theme::new());
+        let pacman = Position::new(300, 300);
+        let aim = |ghosts: &mut GhostManager, ghost_type, direction| {
+            let ghost = ghosts.get_mut(ghost_type).unwrap();
+            ghost.calculate_target(pacman, direction, Some(pacman));
+            ghost.get_ghost().target
+        };
+
+        assert_eq!(
+            aim(&mut ghosts, GhostType::Pinky, Direction::Up),
+            Position::new(300, 204)
+        );
+        assert_eq!(
+            aim(&mut ghosts, GhostType::Inky, Direction::Up),
+            Position::new(300, 204)
+        );
+
+        ghosts.set_arcade_quirks(true);
+        assert_eq!(
+            aim(&mut ghosts, GhostType::Pinky, Direction::Up),
+            Position::new(204, 204)
+        );
+        assert_eq!(
+            aim(&mut ghosts, GhostType::Inky, Direction::Up),
+            Position::new(204, 204)
+        );
+
+        // Only facing up is affected
+        assert_eq!(
+            aim(&mut ghosts, GhostType::Pinky, Direction::Left),
+            Position::new(204, 300)
+        );
+    }
+
+    #[test]
+    fn test_speed_percent_scales_living_ghosts() {
+        let mut ghosts = GhostManager::new(&Theme::new());
+        let travel = |ghosts: &mut GhostManager, updates| {
+            let ghost = ghosts.get_mut(GhostType::Blinky).unwrap().get_ghost_mut();
+            (0..updates).map(|_| ghost.steps() as u32).sum::<u32>()
+        };
+        assert_eq!(travel(&mut ghosts, 20), 40);
 
-        let inky_start = board.reset_position(EntityType::Inky);
-        self.inky.get_ghost_mut().entity.set_position(inky_start);
+        ghosts.set_speed_percent(115);
+        assert_eq!(travel(&mut ghosts, 20), 46);
+        ghosts.set_speed_percent(85);
+        assert_eq!(travel(&mut ghosts, 100), 170);
 
-        let pinky_start = board.reset_position(EntityType::Pinky);
-        self.pinky.get_ghost_mut().entity.set_position(pinky_start);
+        // Eyes race home at their own speed whatever the difficulty
+        let ghost = ghosts.get_mut(GhostType::Blinky).unwrap().get_ghost_mut();
+        ghost.transition(GhostEvent::Eaten);
+        ghost.update_speed();
+        assert_eq!(ghost.steps(), 6);
+    }
+
+    #[test]
+    fn test_eaten_ghost_regrows_before_heading_out() {
+        let mut ghosts = GhostManager::new(&Theme::new());
+        let ghost = ghosts.get_mut(GhostType::Pinky).unwrap().get_ghost_mut();
+        ghost.transition(GhostEvent::Eaten);
+        ghost.entity.set_position(ghost.home);
+        ghost.entity.mod_direction(Direction::Down);
+
+        ghost.update_house_mode(false);
+        assert_eq!(ghost.get_mode(), GhostMode::InHouse);
+        assert!(ghost.entity.is_alive() && ghost.is_regenerating());
+        let ticks = (0..).take_while(|_| ghost.regenerate()).count() as u32;
+        assert_eq!(ticks, REGENERATE_MS.div_ceil(crate::TICK_MS));
+        assert_eq!(ghost.entity.get_direction(), Direction::Up);
+        assert_eq!(ghost.get_mode(), GhostMode::LeavingHouse);
+
+        ghost.regenerate_ms = REGENERATE_MS;
+        ghosts.reset_all_ghost_modes();
+        assert!(!ghosts
+            .get(GhostType::Pinky)
+            .unwrap()
+            .get_ghost()
+            .is_regenerating());
+    }
+
+    #[test]
+    fn test_energizer_spares_ghosts_in_the_house() {
+        let mut ghosts = GhostManager::new(&Theme::new());
+        let blinky = ghosts.get_mut(GhostType::Blinky).unwrap().get_ghost_mut();
+        blinky.entity.set_position(Position::new(300, 300));
+        blinky.update_mode(false, false);
+        let pinky = ghosts.get_mut(GhostType::Pinky).unwrap().get_ghost_mut();
+        pinky.entity.set_position(pinky.home);
+        pinky.update_mode(false, false);
+        assert_eq!(
+            ghosts.get_ghost_modes()[..3],
+            [
+                GhostMode::Chase,
+                GhostMode::LeavingHouse,
+                GhostMode::LeavingHouse
+            ]
+        );
+
+        ghosts.frighten_all_ghosts();
+        assert_eq!(ghosts.get_ghost_modes()[0], GhostMode::Frightened);
+        assert_eq!(ghosts.get_ghost_modes()[2], GhostMode::InHouse);
+
+        // Nobody stays blue or shut in once the energizer is over
+        for ghost in ghosts.iter_mut() {
+            ghost.get_ghost_mut().update_mode(false, true);
+        }
+        assert_eq!(ghosts.get_ghost_modes()[0], GhostMode::Scatter);
+        assert_eq!(ghosts.get_ghost_modes()[2], GhostMode::LeavingHouse);
+    }
+
+    #[test]
+    fn test_ghosts_are_kept_in_order() {
+        let ghosts = GhostManager::new(&Theme::new());
+        let order: Vec<GhostType> = ghosts.iter().map(|ghost| ghost.get_ghost_type()).collect();
+        assert_eq!(
+            order,
+            [
+                GhostType::Blinky,
+                GhostType::Inky,
+                GhostType::Pinky,
+                GhostType::Clyde
+            ]
+        );
+        assert_eq!(
+            ghosts.get(GhostType::Pinky).unwrap().get_ghost_type(),
+            GhostType::Pinky
+        );
+    }
+
+    #[test]
+    fn test_personality_retunes_targeting() {
+        let theme = Theme::new();
+        let mut ghosts = GhostManager::new(&theme);
+        let clyde = ghosts
+            .get(GhostType::Clyde)
+            .unwrap()
+            .get_ghost()
+            .entity
+            .get_position();
+        let pacman = Position::new(clyde.get_x() + 5 * BLOCK_SIZE_24 as i16, clyde.get_y());
+        let aim = |ghosts: &mut GhostManager, ghost_type| {
+            let ghost = ghosts.get_mut(ghost_type).unwrap();
+            ghost.calculate_target(pacman, Direction::Right, Some(pacman));
+            ghost.get_ghost().target
+        };
 
-        let clyde_start = board.reset_position(EntityType::Clyde);
-        self.clyde.get_ghost_mut().entity.set_position(clyde_start);
+        // Five tiles away is inside Clyde's usual eight, so Clyde heads for the corner
+        assert_eq!(aim(&mut ghosts, GhostType::Clyde), Position::new(12, 852));
+
+        let mut personality = GhostPersonality::new();
+        personality.clyde_shyness = 4;
+        personality.pinky_lead = 1;
+        personality.corners[3] = (27, 35);
+        ghosts.set_personality(&personality);
+        ghosts.add_ghost(&ExtraGhost::parse("pinky").unwrap(), &theme);
+
+        assert_eq!(aim(&mut ghosts, GhostType::Clyde), pacman);
+        assert_eq!(
+            aim(&mut ghosts, GhostType::Pinky),
+            Position::new(pacman.get_x() + 24, pacman.get_y())
+        );
+        assert_eq!(
+            ghosts.get(GhostType::Clyde).unwrap().get_scatter_target(),
+            Position::new(660, 852)
+        );
+        assert_eq!(ghosts.nth_mut(4).unwrap().get_ghost().reach_tiles, 1);
+    }
+
+    #[test]
+    fn test_eyes_take_the_shortest_way_home_through_the_door() {
+        let theme = Theme::new();
+        let mut ghosts = GhostManager::new(&theme);
+        let map: Vec<BlockType> = crate::board::Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let graph = TileGraph::new(&map);
+        let pacman = Pacman::new(&theme);
+        let power = PowerState::new();
+        let mut rng = GameRng::new(1);
+
+        // Eaten in the bottom left corner
+        let blinky = ghosts.get_mut(GhostType::Blinky).unwrap().get_ghost_mut();
+        blinky
+            .entity
+            .set_position(Position::new(24, 32 * BLOCK_SIZE_24 as i16));
+        blinky.entity.mod_direction(Direction::Left);
+        blinky.transition(GhostEvent::Eaten);
+        let home = blinky.home;
+
+        let mut through_door = false;
+        let mut updates = 0;
+        while !ghosts
+            .get(GhostType::Blinky)
+            .unwrap()
+            .get_ghost()
+            .entity
+            .is_alive()
+        {
+            ghosts.update_all_ghosts(&graph, &pacman, false, &power, &mut rng, None);
+            let blinky = ghosts.get(GhostType::Blinky).unwrap().get_ghost();
+            let tile = crate::position::Tile::from_position(blinky.entity.get_position());
+            through_door |=
+                map[tile.y as usize * crate::BOARD_WIDTH + tile.x as usize] == BlockType::Door;
+            updates += 1;
+            // The shortest way in is under 40 tiles, and eyes cover 6 pixels an update
+            assert!(updates <= 40 * 4, "eyes took the long way home");
+        }
+        // Revived at home, where it stops to re-form
+        assert!(through_door);
+        let blinky = ghosts.get(GhostType::Blinky).unwrap().get_ghost();
+        assert!(blinky.is_home());
+        assert!((blinky.entity.get_position().get_y() - home.get_y()).abs() <= 6);
     }
 
-    /// Get blinky for individual access
-    pub fn get_blinky_mut(&mut self) -> &mut Blinky<'a> {
-        &mut self.blinky
+    #[test]
+    fn test_direct_chase_overrides_clyde_shyness() {
+        let theme = Theme::new();
+        let map: Vec<BlockType> = crate::board::Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let graph = TileGraph::new(&map);
+        let mut pacman = Pacman::new(&theme);
+        let block = BLOCK_SIZE_24 as i16;
+        pacman.set_position(Position::new(13 * block + block / 2, 26 * block));
+        let power = PowerState::new();
+        let mut rng = GameRng::new(7);
+
+        let clyde_target = |direct_chase: bool, rng: &mut GameRng| {
+            let mut ghosts = GhostManager::new(&theme);
+            ghosts.set_direct_chase(direct_chase);
+            let clyde = ghosts.get_mut(GhostType::Clyde).unwrap().get_ghost_mut();
+            clyde
+                .entity
+                .set_position(Position::new(10 * block, 26 * block));
+            clyde.set_mode(GhostMode::Chase);
+            ghosts.update_all_ghosts(&graph, &pacman, false, &power, rng, None);
+            ghosts.get(GhostType::Clyde).unwrap().get_ghost().target
+        };
+        // Within eight tiles Clyde backs off to his corner, unless he is rushing
+        let corner = GhostManager::new(&theme)
+            .get(GhostType::Clyde)
+            .unwrap()
+            .get_scatter_target();
+        assert_eq!(clyde_target(false, &mut rng), corner);
+        assert_eq!(clyde_target(true, &mut rng), pacman.get_position());
     }
 
-    /// Get inky for individual access
-    pub fn get_inky_mut(&mut self) -> &mut Inky<'a> {
-        &mut self.inky
+    #[test]
+    fn test_ghosts_keep_to_the_corridors() {
+        let theme = Theme::new();
+        let mut ghosts = GhostManager::new(&theme);
+        let map: Vec<BlockType> = crate::board::Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let graph = TileGraph::new(&map);
+        let mut pacman = Pacman::new(&theme);
+        let block = BLOCK_SIZE_24 as i16;
+        pacman.set_position(Position::new(13 * block + block / 2, 26 * block));
+        let power = PowerState::new();
+        let mut rng = GameRng::new(7);
+        ghosts.reset_all_ghost_positions(&crate::board::Board::new());
+
+        for update in 0..3000 {
+            let scatter = (update / 500) % 2 == 0;
+            ghosts.update_all_ghosts(&graph, &pacman, scatter, &power, &mut rng, None);
+            for ghost in ghosts.iter() {
+                let position = ghost.get_ghost().entity.get_position();
+                let tile = crate::position::Tile::from_position(position);
+                let on_board = (0..crate::BOARD_WIDTH as i16).contains(&tile.x);
+                if on_board {
+                    let block = map[tile.y as usize * crate::BOARD_WIDTH + tile.x as usize];
+                    assert_ne!(block, BlockType::Wall, "{:?} in a wall", position);
+                }
+            }
+        }
+        // Everyone has left the house by now
+        assert!(ghosts.iter().all(|ghost| !ghost.get_ghost().is_home()));
     }
 
-    /// Get pinky for individual access
-    pub fn get_pinky_mut(&mut self) -> &mut Pinky<'a> {
-        &mut self.pinky
+    #[test]
+    fn test_extra_ghosts_join_the_end() {
+        let theme = Theme::new();
+        let mut ghosts = GhostManager::new(&theme);
+        let sue = ExtraGhost::parse("clyde:800080:1,4").unwrap();
+        ghosts.add_ghost(&sue, &theme);
+        ghosts.add_ghost(&ExtraGhost::parse("blinky").unwrap(), &theme);
+        assert_eq!(ghosts.len(), 6);
+        assert_eq!(ExtraGhost::parse(&sue.fingerprint()), Ok(sue));
+
+        let fifth = ghosts.iter().nth(4).unwrap();
+        assert_eq!(fifth.get_ghost_type(), GhostType::Clyde);
+        assert_eq!(fifth.get_ghost().color, Color::RGB(128, 0, 128));
+        assert_eq!(fifth.get_scatter_target(), Position::new(36, 108));
+        assert_eq!(fifth.get_ghost().spawn_marker, Some('5'));
+        // Lookups by type still find the original
+        assert_eq!(
+            ghosts
+                .get(GhostType::Clyde)
+                .unwrap()
+                .get_ghost()
+                .spawn_marker,
+            None
+        );
+
+        // The second Blinky scatters at random along with the first
+        ghosts.set_random_scatter(true);
+        let sixth = ghosts.nth_mut(5).unwrap();
+        assert!(sixth.get_ghost().random_scatter);
+        assert_eq!(sixth.get_ghost().spawn_marker, Some('6'));
+
+        // Without markers '5' and '6' in the maze, both start in the house
+        ghosts.reset_all_ghost_positions(&crate::board::Board::new());
+        let positions = ghosts.get_ghost_positions();
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions[4], positions[2]);
+        assert_eq!(positions[5], positions[2]);
     }
 
-    /// Get clyde for individual access
-    pub fn get_clyde_mut(&mut self) -> &mut Clyde<'a> {
-        &mut self.clyde
+    #[test]
+    fn test_spawned_ghost_matches_its_kind() {
+        let theme = Theme::new();
+        let board = crate::board::Board::new();
+        let mut ghosts = GhostManager::new(&theme);
+        ghosts.set_speed_percent(85);
+        ghosts.set_random_scatter(true);
+        ghosts.reset_all_ghost_positions(&board);
+
+        ghosts.spawn_ghost(&ExtraGhost::parse("pinky").unwrap(), &theme, &board);
+        let spawned = ghosts.nth_mut(4).unwrap().get_ghost();
+        assert_eq!(spawned.speed_percent, 85);
+        assert!(spawned.random_scatter);
+        let positions = ghosts.get_ghost_positions();
+        assert_eq!(positions[4], positions[2]);
     }
 }
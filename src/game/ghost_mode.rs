@@ -0,0 +1,204 @@
+/// What a ghost is doing. Each ghost keeps one and moves it along with `GhostMode::next`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GhostMode {
+    /// Held in the house, bobbing up and down, or re-forming after being eaten
+    InHouse,
+    /// Heading out through the door
+    LeavingHouse,
+    Chase,
+    Scatter,
+    Frightened,
+    /// Eyes on their way back to the house
+    Eaten,
+}
+
+/// Something that can change a ghost's mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GhostEvent {
+    /// Pac-Man ate an energizer
+    Energizer,
+    /// The energizer wore off; `scatter` says which way the schedule stands
+    EnergizerOver { scatter: bool },
+    /// Pac-Man caught the ghost
+    Eaten,
+    /// The eyes made it back to their spot in the house
+    ReachedHome,
+    /// The body has grown back
+    Regenerated,
+    /// The ghost cleared the house
+    LeftHouse { scatter: bool },
+    /// The scatter/chase schedule says what living ghosts out in the maze should do
+    Schedule { scatter: bool },
+}
+
+impl GhostMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GhostMode::InHouse => "in-house",
+            GhostMode::LeavingHouse => "leaving-house",
+            GhostMode::Chase => "chase",
+            GhostMode::Scatter => "scatter",
+            GhostMode::Frightened => "frightened",
+            GhostMode::Eaten => "eaten",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GhostMode> {
+        [
+            GhostMode::InHouse,
+            GhostMode::LeavingHouse,
+            GhostMode::Chase,
+            GhostMode::Scatter,
+            GhostMode::Frightened,
+            GhostMode::Eaten,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+    }
+
+    /// Chase or scatter, whichever the schedule asks for
+    fn roaming(scatter: bool) -> GhostMode {
+        if scatter {
+            GhostMode::Scatter
+        } else {
+            GhostMode::Chase
+        }
+    }
+
+    /// The mode after `event`; events that mean nothing in this mode leave it as it is.
+    ///
+    /// Ghosts in the house when an energizer is eaten stay there until it wears off, while
+    /// eyes that get home during one re-form and come out unfrightened.
+    pub fn next(self, event: GhostEvent) -> GhostMode {
+        use GhostMode::*;
+        match (self, event) {
+            (Chase | Scatter | Frightened, GhostEvent::Energizer) => Frightened,
+            (LeavingHouse, GhostEvent::Energizer) => InHouse,
+            (Frightened, GhostEvent::EnergizerOver { scatter }) => GhostMode::roaming(scatter),
+            (InHouse, GhostEvent::EnergizerOver { .. }) => LeavingHouse,
+            (_, GhostEvent::Eaten) => Eaten,
+            (Eaten, GhostEvent::ReachedHome) => InHouse,
+            (InHouse, GhostEvent::Regenerated) => LeavingHouse,
+            (LeavingHouse, GhostEvent::LeftHouse { scatter }) => GhostMode::roaming(scatter),
+            (Chase | Scatter, GhostEvent::Schedule { scatter }) => GhostMode::roaming(scatter),
+            (mode, _) => mode,
+        }
+    }
+
+    pub fn is_alive(self) -> bool {
+        self != GhostMode::Eaten
+    }
+
+    /// Whether the ghost may pass through the house door
+    pub fn can_use_door(self) -> bool {
+        matches!(
+            self,
+            GhostMode::InHouse | GhostMode::LeavingHouse | GhostMode::Eaten
+        )
+    }
+
+    /// Whether the ghost is in the house, or on its way out of it
+    pub fn is_housed(self) -> bool {
+        matches!(self, GhostMode::InHouse | GhostMode::LeavingHouse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energizer_frightens_only_ghosts_in_the_maze() {
+        assert_eq!(
+            GhostMode::Chase.next(GhostEvent::Energizer),
+            GhostMode::Frightened
+        );
+        assert_eq!(
+            GhostMode::Scatter.next(GhostEvent::Energizer),
+            GhostMode::Frightened
+        );
+        assert_eq!(
+            GhostMode::Frightened.next(GhostEvent::Energizer),
+            GhostMode::Frightened
+        );
+        assert_eq!(
+            GhostMode::LeavingHouse.next(GhostEvent::Energizer),
+            GhostMode::InHouse
+        );
+        assert_eq!(
+            GhostMode::InHouse.next(GhostEvent::Energizer),
+            GhostMode::InHouse
+        );
+        assert_eq!(
+            GhostMode::Eaten.next(GhostEvent::Energizer),
+            GhostMode::Eaten
+        );
+    }
+
+    #[test]
+    fn test_energizer_wearing_off_follows_the_schedule() {
+        let over = |scatter| GhostEvent::EnergizerOver { scatter };
+        assert_eq!(GhostMode::Frightened.next(over(true)), GhostMode::Scatter);
+        assert_eq!(GhostMode::Frightened.next(over(false)), GhostMode::Chase);
+        assert_eq!(
+            GhostMode::InHouse.next(over(false)),
+            GhostMode::LeavingHouse
+        );
+        assert_eq!(GhostMode::Chase.next(over(true)), GhostMode::Chase);
+        assert_eq!(GhostMode::Eaten.next(over(true)), GhostMode::Eaten);
+    }
+
+    #[test]
+    fn test_eaten_ghost_goes_home_and_back_out() {
+        let mode = GhostMode::Frightened.next(GhostEvent::Eaten);
+        assert_eq!(mode, GhostMode::Eaten);
+        assert!(!mode.is_alive() && mode.can_use_door());
+
+        // An energizer on the way home changes nothing
+        let mode = mode.next(GhostEvent::Energizer);
+        assert_eq!(mode, GhostMode::Eaten);
+        assert_eq!(mode.next(GhostEvent::Regenerated), GhostMode::Eaten);
+
+        let mode = mode.next(GhostEvent::ReachedHome);
+        assert_eq!(mode, GhostMode::InHouse);
+        let mode = mode.next(GhostEvent::Regenerated);
+        assert_eq!(mode, GhostMode::LeavingHouse);
+        assert_eq!(
+            mode.next(GhostEvent::LeftHouse { scatter: false }),
+            GhostMode::Chase
+        );
+    }
+
+    #[test]
+    fn test_schedule_only_moves_ghosts_in_the_maze() {
+        let scatter = GhostEvent::Schedule { scatter: true };
+        let chase = GhostEvent::Schedule { scatter: false };
+        assert_eq!(GhostMode::Chase.next(scatter), GhostMode::Scatter);
+        assert_eq!(GhostMode::Scatter.next(chase), GhostMode::Chase);
+        assert_eq!(GhostMode::Frightened.next(chase), GhostMode::Frightened);
+        assert_eq!(
+            GhostMode::LeavingHouse.next(scatter),
+            GhostMode::LeavingHouse
+        );
+        assert_eq!(GhostMode::Eaten.next(scatter), GhostMode::Eaten);
+        assert_eq!(
+            GhostMode::Chase.next(GhostEvent::LeftHouse { scatter: true }),
+            GhostMode::Chase
+        );
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for mode in [
+            GhostMode::InHouse,
+            GhostMode::LeavingHouse,
+            GhostMode::Chase,
+            GhostMode::Scatter,
+            GhostMode::Frightened,
+            GhostMode::Eaten,
+        ] {
+            assert_eq!(GhostMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(GhostMode::from_name("alive"), None);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::board::Direction;
+
+/// Pac-Man's heading plus at most one turn waiting for an opening in the maze.
+///
+/// A queued turn is taken the first time the maze allows it. With a buffering window it is
+/// forgotten after that many ticks without an opening; without one it waits indefinitely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputQueue {
+    current: Direction,
+    /// The waiting turn and the ticks it has waited so far
+    queued: Option<(Direction, u32)>,
+    window_ticks: Option<u32>,
+}
+
+impl InputQueue {
+    pub fn new(current: Direction, window_ticks: Option<u32>) -> Self {
+        InputQueue {
+            current,
+            queued: None,
+            window_ticks,
+        }
+    }
+
+    /// The direction Pac-Man keeps moving in
+    pub fn current(&self) -> Direction {
+        self.current
+    }
+
+    /// The turn waiting to be taken, if any
+    pub fn queued(&self) -> Option<Direction> {
+        self.queued.map(|(direction, _)| direction)
+    }
+
+    /// Queue a turn, replacing any turn already waiting.
+    ///
+    /// Asking for the current direction again cancels the waiting turn.
+    pub fn push(&mut self, direction: Direction) {
+        self.queued = (direction != self.current).then_some((direction, 0));
+    }
+
+    /// The queued turn has been taken and is now the current direction
+    pub fn take_turn(&mut self) {
+        if let Some((direction, _)) = self.queued.take() {
+            self.current = direction;
+        }
+    }
+
+    /// Drop any waiting turn and head in `direction`
+    pub fn reset(&mut self, direction: Direction) {
+        self.current = direction;
+        self.queued = None;
+    }
+
+    /// Age the waiting turn by one tick, dropping it once the window has passed
+    pub fn tick(&mut self) {
+        if let Some((direction, waited)) = self.queued {
+            let waited = waited + 1;
+            self.queued = match self.window_ticks {
+                Some(window) if waited >= window => None,
+                _ => Some((direction, waited)),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reversal_is_queued_like_any_turn() {
+        let mut input = InputQueue::new(Direction::Right, None);
+        input.push(Direction::Left);
+        assert_eq!(input.queued(), Some(Direction::Left));
+        input.take_turn();
+        assert_eq!(input.current(), Direction::Left);
+        assert_eq!(input.queued(), None);
+    }
+
+    #[test]
+    fn test_buffered_turn_expires_after_window() {
+        let mut input = InputQueue::new(Direction::Right, Some(3));
+        input.push(Direction::Up);
+        input.tick();
+        input.tick();
+        assert_eq!(input.queued(), Some(Direction::Up));
+        input.tick();
+        assert_eq!(input.queued(), None);
+        assert_eq!(input.current(), Direction::Right);
+
+        // Without a window the turn waits for as long as it takes
+        let mut input = InputQueue::new(Direction::Right, None);
+        input.push(Direction::Up);
+        for _ in 0..1000 {
+            input.tick();
+        }
+        assert_eq!(input.queued(), Some(Direction::Up));
+    }
+
+    #[test]
+    fn test_latest_turn_wins_and_current_direction_cancels() {
+        let mut input = InputQueue::new(Direction::Right, Some(10));
+        input.push(Direction::Up);
+        input.push(Direction::Down);
+        assert_eq!(input.queued(), Some(Direction::Down));
+
+        input.push(Direction::Right);
+        assert_eq!(input.queued(), None);
+        input.take_turn();
+        assert_eq!(input.current(), Direction::Right);
+    }
+}
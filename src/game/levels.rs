@@ -0,0 +1,318 @@
+/// Level at which the difficulty curve stops changing, as in the arcade
+pub const MAX_DIFFICULTY_LEVEL: u16 = 21;
+
+/// Difficulty ramps up once every this many levels
+const LEVELS_PER_STEP: u16 = 3;
+
+/// From this level on energizers no longer frighten ghosts, as in the arcade
+pub const NO_FRIGHT_LEVEL: u16 = 19;
+
+/// Ghost speed in a ghost rush, as a percentage of the difficulty's; about the arcade's
+/// second Cruise Elroy spurt
+const RUSH_SPEED_PERCENT: u32 = 115;
+
+/// Timed phases in a level, alternating scatter and chase from scatter; chase then lasts
+/// for the rest of the level
+pub const WAVES: usize = 7;
+
+/// The arcade's phase lengths in ms for level 1, levels 2 to 4, and level 5 on. The long
+/// chases and the one-frame scatters after them are as in the original.
+const ARCADE_WAVES: [[u32; WAVES]; 3] = [
+    [7000, 20000, 7000, 20000, 5000, 20000, 5000],
+    [7000, 20000, 7000, 20000, 5000, 1_033_000, 16],
+    [5000, 20000, 5000, 20000, 5000, 1_037_000, 16],
+];
+
+/// How a difficulty preset plays, and how its schedule tightens level after level
+struct Schedule {
+    starting_lives: i8,
+    /// Scatter and chase phases as a percentage of the arcade's
+    scatter_percent: u32,
+    chase_percent: u32,
+    base_fright_time: u32,
+    min_fright_time: u32,
+    /// Taken off the energizer's length at every step
+    fright_step: u32,
+    /// Level from which energizers stop frightening ghosts; `None` keeps them working
+    no_fright_level: Option<u16>,
+    ghost_speed_percent: u32,
+    /// Pac-Man keeps his lives however often he is caught, so the game never ends
+    infinite_lives: bool,
+}
+
+/// Rows in `Difficulty` order; Normal is the arcade curve the game always had
+const SCHEDULES: [Schedule; 4] = [
+    Schedule {
+        starting_lives: 5,
+        scatter_percent: 130,
+        chase_percent: 75,
+        base_fright_time: 13500,
+        min_fright_time: 6000,
+        fright_step: 750,
+        no_fright_level: None,
+        ghost_speed_percent: 85,
+        infinite_lives: false,
+    },
+    Schedule {
+        starting_lives: crate::board::STARTING_LIVES,
+        scatter_percent: 100,
+        chase_percent: 100,
+        base_fright_time: 7000,
+        min_fright_time: 2000,
+        fright_step: 1000,
+        no_fright_level: Some(NO_FRIGHT_LEVEL),
+        ghost_speed_percent: 100,
+        infinite_lives: false,
+    },
+    Schedule {
+        starting_lives: 3,
+        scatter_percent: 70,
+        chase_percent: 125,
+        base_fright_time: 2500,
+        min_fright_time: 500,
+        fright_step: 500,
+        no_fright_level: Some(13),
+        ghost_speed_percent: 115,
+        infinite_lives: false,
+    },
+    Schedule {
+        starting_lives: 3,
+        scatter_percent: 200,
+        chase_percent: 50,
+        base_fright_time: 15000,
+        min_fright_time: 10000,
+        fright_step: 500,
+        no_fright_level: None,
+        ghost_speed_percent: 60,
+        infinite_lives: true,
+    },
+];
+
+/// Preset picked with `--difficulty`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    /// For young players: slow ghosts that show where they are heading, and no game over.
+    /// Its scores are kept out of the high scores.
+    Kids,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Kids,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            "kids" => Some(Difficulty::Kids),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Kids => "kids",
+        }
+    }
+
+    pub fn starting_lives(self) -> i8 {
+        self.schedule().starting_lives
+    }
+
+    pub fn has_infinite_lives(self) -> bool {
+        self.schedule().infinite_lives
+    }
+
+    /// Arrows over the ghosts show which way each is heading
+    pub fn shows_ghost_intent(self) -> bool {
+        self == Difficulty::Kids
+    }
+
+    /// Whether a run can become the best replay
+    pub fn counts_for_high_scores(self) -> bool {
+        self != Difficulty::Kids
+    }
+
+    /// Look up the parameters for a level, clamped at the difficulty plateau
+    pub fn level_params(self, level: u16) -> LevelParams {
+        let schedule = self.schedule();
+        let steps = level.min(MAX_DIFFICULTY_LEVEL) / LEVELS_PER_STEP;
+        let arcade = match level {
+            0..=1 => &ARCADE_WAVES[0],
+            2..=4 => &ARCADE_WAVES[1],
+            _ => &ARCADE_WAVES[2],
+        };
+        let mut waves = *arcade;
+        for (wave, ms) in waves.iter_mut().enumerate() {
+            let percent = if wave % 2 == 0 {
+                schedule.scatter_percent
+            } else {
+                schedule.chase_percent
+            };
+            *ms = *ms * percent / 100;
+        }
+
+        LevelParams {
+            waves,
+            // Energizers get shorter every few levels until late levels remove them
+            fright_time: if schedule.no_fright_level.is_some_and(|first| level >= first) {
+                0
+            } else {
+                schedule
+                    .base_fright_time
+                    .saturating_sub(steps as u32 * schedule.fright_step)
+                    .max(schedule.min_fright_time)
+            },
+            ghost_speed_percent: schedule.ghost_speed_percent,
+        }
+    }
+
+    fn schedule(self) -> &'static Schedule {
+        &SCHEDULES[self as usize]
+    }
+}
+
+/// Tuning values used by the timing system for a given level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelParams {
+    /// Scatter and chase phase lengths in ms, starting with scatter
+    pub waves: [u32; WAVES],
+    pub fright_time: u32,
+    /// Living ghosts' speed as a percentage of the usual
+    pub ghost_speed_percent: u32,
+}
+
+impl LevelParams {
+    /// Parameters for a level at Normal difficulty
+    pub fn for_level(level: u16) -> Self {
+        Difficulty::Normal.level_params(level)
+    }
+
+    /// The ghost rush challenge: every wave is empty, so the schedule goes straight to its
+    /// endless chase, and the ghosts all move like Blinky as Cruise Elroy
+    pub fn rushed(self) -> Self {
+        LevelParams {
+            waves: [0; WAVES],
+            ghost_speed_percent: self.ghost_speed_percent * RUSH_SPEED_PERCENT / 100,
+            ..self
+        }
+    }
+
+    /// Check if the level sits on the terminal difficulty plateau
+    pub fn is_max_difficulty(level: u16) -> bool {
+        level >= MAX_DIFFICULTY_LEVEL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_level_uses_arcade_waves() {
+        let params = LevelParams::for_level(1);
+        assert_eq!(params.waves, [7000, 20000, 7000, 20000, 5000, 20000, 5000]);
+        assert_eq!(params.fright_time, 7000);
+    }
+
+    #[test]
+    fn test_waves_change_on_levels_two_and_five() {
+        assert_eq!(LevelParams::for_level(2).waves[5], 1_033_000);
+        assert_eq!(
+            LevelParams::for_level(4).waves,
+            LevelParams::for_level(2).waves
+        );
+        assert_eq!(LevelParams::for_level(5).waves[0], 5000);
+        assert_eq!(
+            LevelParams::for_level(50).waves,
+            LevelParams::for_level(5).waves
+        );
+    }
+
+    #[test]
+    fn test_fright_time_steps_every_three_levels_to_a_floor() {
+        assert_eq!(LevelParams::for_level(2).fright_time, 7000);
+        assert_eq!(LevelParams::for_level(3).fright_time, 6000);
+        assert_eq!(LevelParams::for_level(15).fright_time, 2000);
+        assert_eq!(LevelParams::for_level(18).fright_time, 2000);
+    }
+
+    #[test]
+    fn test_late_levels_have_no_fright_time() {
+        assert_eq!(
+            LevelParams::for_level(NO_FRIGHT_LEVEL - 1).fright_time,
+            2000
+        );
+        assert_eq!(LevelParams::for_level(NO_FRIGHT_LEVEL).fright_time, 0);
+        assert_eq!(LevelParams::for_level(MAX_DIFFICULTY_LEVEL).fright_time, 0);
+    }
+
+    #[test]
+    fn test_presets_bracket_normal() {
+        assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from_name("nightmare"), None);
+        assert!(Difficulty::Easy.starting_lives() > Difficulty::Hard.starting_lives());
+
+        for level in [1, 3, 12, NO_FRIGHT_LEVEL, MAX_DIFFICULTY_LEVEL] {
+            let easy = Difficulty::Easy.level_params(level);
+            let normal = LevelParams::for_level(level);
+            let hard = Difficulty::Hard.level_params(level);
+            assert!(easy.waves[0] > normal.waves[0] && hard.waves[0] < normal.waves[0]);
+            assert!(easy.waves[1] < normal.waves[1] && hard.waves[1] > normal.waves[1]);
+            assert!(easy.fright_time >= normal.fright_time);
+            assert!(hard.fright_time <= normal.fright_time);
+            assert!(easy.ghost_speed_percent < hard.ghost_speed_percent);
+        }
+        // Energizers keep working on Easy however far the player gets
+        assert!(Difficulty::Easy.level_params(u16::MAX).fright_time > 0);
+    }
+
+    #[test]
+    fn test_kids_mode_is_gentler_than_easy() {
+        assert_eq!(Difficulty::from_name("kids"), Some(Difficulty::Kids));
+        assert!(Difficulty::Kids.has_infinite_lives());
+        assert!(!Difficulty::Easy.has_infinite_lives());
+        assert!(!Difficulty::Kids.counts_for_high_scores());
+        assert!(Difficulty::Hard.counts_for_high_scores());
+
+        for level in [1, 12, u16::MAX] {
+            let kids = Difficulty::Kids.level_params(level);
+            let easy = Difficulty::Easy.level_params(level);
+            assert!(kids.ghost_speed_percent < easy.ghost_speed_percent);
+            assert!(kids.fright_time > easy.fright_time);
+            assert!(kids.waves[1] < easy.waves[1]);
+        }
+    }
+
+    #[test]
+    fn test_ghost_rush_skips_scatter_and_speeds_up() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let params = difficulty.level_params(5);
+            let rushed = params.rushed();
+            assert_eq!(rushed.waves, [0; WAVES]);
+            assert!(rushed.ghost_speed_percent > params.ghost_speed_percent);
+            assert_eq!(rushed.fright_time, params.fright_time);
+        }
+    }
+
+    #[test]
+    fn test_parameters_plateau_at_max_difficulty() {
+        let plateau = LevelParams::for_level(MAX_DIFFICULTY_LEVEL);
+        assert_eq!(LevelParams::for_level(50), plateau);
+        assert_eq!(LevelParams::for_level(u16::MAX), plateau);
+
+        assert!(!LevelParams::is_max_difficulty(MAX_DIFFICULTY_LEVEL - 1));
+        assert!(LevelParams::is_max_difficulty(MAX_DIFFICULTY_LEVEL));
+    }
+}
@@ -0,0 +1,272 @@
+use super::levels::MAX_DIFFICULTY_LEVEL;
+use super::rng::GameRng;
+use crate::config::{Config, Practice};
+use crate::maze_gen;
+
+/// A line of the title menu, top to bottom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuItem {
+    Play,
+    Mode,
+    /// Level a practice run starts on
+    Level,
+    Difficulty,
+    Maze,
+    Music,
+    CpuDemo,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 7] = [
+        MenuItem::Play,
+        MenuItem::Mode,
+        MenuItem::Level,
+        MenuItem::Difficulty,
+        MenuItem::Maze,
+        MenuItem::Music,
+        MenuItem::CpuDemo,
+    ];
+}
+
+/// The kind of run the next game is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    Arcade,
+    Practice,
+    Survival,
+    GhostRush,
+}
+
+impl RunMode {
+    const ALL: [RunMode; 4] = [
+        RunMode::Arcade,
+        RunMode::Practice,
+        RunMode::Survival,
+        RunMode::GhostRush,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RunMode::Arcade => "ARCADE",
+            RunMode::Practice => "PRACTICE",
+            RunMode::Survival => "SURVIVAL",
+            RunMode::GhostRush => "GHOST RUSH",
+        }
+    }
+}
+
+/// The title menu, where the next run is set up with the arrow keys or a d-pad.
+///
+/// Up and down move between lines, wrapping around, and left and right step through the
+/// focused line's choices, wrapping too. Every cheat code is made of moves that undo each
+/// other, so typing one here leaves the menu as it was. Difficulty, music and the CPU demo
+/// are the game's own settings; the menu only keeps the mode and maze for runs it starts.
+pub struct Menu {
+    /// What the game was started with; each run is set up from it
+    base: Config,
+    focus: usize,
+    mode: RunMode,
+    level: u16,
+    random_maze: bool,
+}
+
+impl Menu {
+    /// Start out on the mode and maze asked for on the command line
+    pub fn new(config: &Config) -> Self {
+        let mode = if config.practice.is_some() {
+            RunMode::Practice
+        } else if config.survival {
+            RunMode::Survival
+        } else if config.ghost_rush {
+            RunMode::GhostRush
+        } else {
+            RunMode::Arcade
+        };
+        Menu {
+            base: config.clone(),
+            focus: 0,
+            mode,
+            level: config.practice.map_or(1, |practice| practice.level),
+            random_maze: false,
+        }
+    }
+
+    pub fn focused(&self) -> MenuItem {
+        MenuItem::ALL[self.focus]
+    }
+
+    /// Back to the top line, where Enter plays
+    pub fn focus_play(&mut self) {
+        self.focus = 0;
+    }
+
+    /// Move the focus one line down, or up when `down` is false
+    pub fn move_focus(&mut self, down: bool) {
+        self.focus = step(self.focus, MenuItem::ALL.len(), down);
+    }
+
+    /// Step the focused line's choice forwards or back.
+    ///
+    /// Returns false for the lines whose setting belongs to the game rather than the menu.
+    pub fn change(&mut self, forward: bool) -> bool {
+        match self.focused() {
+            MenuItem::Mode => self.mode = cycle(&RunMode::ALL, self.mode, forward),
+            MenuItem::Level => {
+                let index = step(
+                    self.level as usize - 1,
+                    MAX_DIFFICULTY_LEVEL as usize,
+                    forward,
+                );
+                self.level = index as u16 + 1;
+            }
+            MenuItem::Maze => self.random_maze = !self.random_maze,
+            MenuItem::Play | MenuItem::Difficulty | MenuItem::Music | MenuItem::CpuDemo => {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn mode(&self) -> RunMode {
+        self.mode
+    }
+
+    pub fn level(&self) -> u16 {
+        self.level
+    }
+
+    pub fn maze_name(&self) -> &'static str {
+        match (self.random_maze, self.base.custom_maze.is_some()) {
+            (true, _) => "RANDOM",
+            (false, true) => "LOADED",
+            (false, false) => "CLASSIC",
+        }
+    }
+
+    /// The configuration for a run with the choices on the menu.
+    ///
+    /// A random maze is built from the run's seed, so the seed alone brings it back; without
+    /// a fixed seed every run draws a new one.
+    pub fn run_config(&self) -> Config {
+        let mut config = self.base.clone();
+        config.practice = (self.mode == RunMode::Practice).then(|| Practice {
+            level: self.level,
+            ..self.base.practice.unwrap_or_default()
+        });
+        config.survival = self.mode == RunMode::Survival;
+        config.ghost_rush = self.mode == RunMode::GhostRush;
+        if self.random_maze {
+            let seed = *config
+                .seed
+                .get_or_insert_with(|| GameRng::from_entropy().get_seed());
+            config.custom_maze = Some(maze_gen::generate(seed));
+            config.maze_path = None;
+        }
+        config
+    }
+}
+
+/// The choice after `current` in `choices`, or before it, wrapping at either end
+pub fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, forward: bool) -> T {
+    let index = choices.iter().position(|&choice| choice == current);
+    choices[step(index.unwrap_or(0), choices.len(), forward)]
+}
+
+/// The index after `index` in a list of `len`, or before it, wrapping at either end
+fn step(index: usize, len: usize, forward: bool) -> usize {
+    if forward {
+        (index + 1) % len
+    } else {
+        (index + len - 1) % len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn focus_on(menu: &mut Menu, item: MenuItem) {
+        while menu.focused() != item {
+            menu.move_focus(true);
+        }
+    }
+
+    #[test]
+    fn test_focus_wraps_around() {
+        let mut menu = Menu::new(&Config::new());
+        assert_eq!(menu.focused(), MenuItem::Play);
+        menu.move_focus(false);
+        assert_eq!(menu.focused(), MenuItem::CpuDemo);
+        menu.move_focus(true);
+        menu.move_focus(true);
+        assert_eq!(menu.focused(), MenuItem::Mode);
+        menu.focus_play();
+        assert_eq!(menu.focused(), MenuItem::Play);
+    }
+
+    #[test]
+    fn test_choices_shape_the_run() {
+        let mut config = Config::new();
+        config.seed = Some(7);
+        let mut menu = Menu::new(&config);
+        assert_eq!(menu.mode(), RunMode::Arcade);
+        let run = menu.run_config();
+        assert!(run.practice.is_none() && !run.survival && !run.ghost_rush);
+        assert!(run.custom_maze.is_none());
+
+        focus_on(&mut menu, MenuItem::Mode);
+        assert!(menu.change(true));
+        focus_on(&mut menu, MenuItem::Level);
+        menu.change(false);
+        assert_eq!(menu.level(), MAX_DIFFICULTY_LEVEL);
+        menu.change(true);
+        menu.change(true);
+        focus_on(&mut menu, MenuItem::Maze);
+        menu.change(true);
+        assert_eq!(menu.maze_name(), "RANDOM");
+
+        let run = menu.run_config();
+        assert_eq!(run.practice.map(|practice| practice.level), Some(2));
+        assert_eq!(run.custom_maze, Some(maze_gen::generate(7)));
+        assert_eq!(run.seed, Some(7));
+
+        focus_on(&mut menu, MenuItem::Mode);
+        menu.change(false);
+        menu.change(false);
+        assert_eq!(menu.mode(), RunMode::GhostRush);
+        let run = menu.run_config();
+        assert!(run.ghost_rush && run.practice.is_none());
+    }
+
+    #[test]
+    fn test_starts_on_the_mode_asked_for() {
+        let mut config = Config::new();
+        config.survival = true;
+        assert_eq!(Menu::new(&config).mode(), RunMode::Survival);
+
+        let mut config = Config::new();
+        config.practice = Some(Practice {
+            level: 5,
+            maze: Some(1),
+            infinite_lives: true,
+        });
+        let menu = Menu::new(&config);
+        assert_eq!((menu.mode(), menu.level()), (RunMode::Practice, 5));
+        // The practice maze and lives from the command line carry over to the menu's runs
+        assert_eq!(menu.run_config().practice, config.practice);
+    }
+
+    #[test]
+    fn test_game_settings_are_left_to_the_game() {
+        let mut menu = Menu::new(&Config::new());
+        for item in [
+            MenuItem::Play,
+            MenuItem::Difficulty,
+            MenuItem::Music,
+            MenuItem::CpuDemo,
+        ] {
+            focus_on(&mut menu, item);
+            assert!(!menu.change(true));
+        }
+    }
+}
@@ -1,8 +1,37 @@
+pub mod announcer;
+pub mod bot;
+pub mod cheat_codes;
 pub mod collision;
+pub mod console;
+pub mod core;
+pub mod events;
+pub mod flashlight;
 pub mod ghost_manager;
+pub mod ghost_mode;
+pub mod input;
+pub mod levels;
+pub mod menu;
+pub mod particles;
+pub mod pathfind;
+pub mod perf;
+pub mod personality;
+pub mod power;
+pub mod replay;
+pub mod rewind;
+pub mod rng;
+pub mod rules_mode;
 pub mod scoring;
+pub mod screen_effects;
+pub mod snapshot;
+pub mod sound_indicators;
 pub mod state;
+mod states;
+pub mod stats;
+pub mod survival;
+pub mod targeting;
+pub mod telemetry;
+pub mod teleport;
+pub mod tile_graph;
 pub mod timers;
-pub mod core;
 
-pub use core::Game;
\ No newline at end of file
+pub use core::Game;
@@ -0,0 +1,190 @@
+use super::rng::CosmeticNoise;
+use crate::{WHITE, YELLOW};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+/// Most particles alive at once; new ones are dropped while the pool is full
+pub const MAX_PARTICLES: usize = 256;
+/// Colour of the crumbs a pellet leaves behind
+const CRUMB_COLOR: Color = Color::RGB(255, 184, 151);
+
+/// How many particles an effect throws out and how they move
+struct Emission {
+    count: usize,
+    /// Typical speed in pixels per second
+    speed: f32,
+    lifetime_ms: u32,
+    size: u32,
+}
+
+const CRUMBS: Emission = Emission {
+    count: 4,
+    speed: 60.0,
+    lifetime_ms: 250,
+    size: 3,
+};
+const SPARKLES: Emission = Emission {
+    count: 16,
+    speed: 140.0,
+    lifetime_ms: 600,
+    size: 4,
+};
+const BURST: Emission = Emission {
+    count: 24,
+    speed: 200.0,
+    lifetime_ms: 700,
+    size: 5,
+};
+
+/// A small square drifting away from where something was eaten
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    x: f32,
+    y: f32,
+    /// Pixels per second
+    vx: f32,
+    vy: f32,
+    remaining_ms: u32,
+    lifetime_ms: u32,
+    size: u32,
+    color: Color,
+}
+
+/// Pool of purely cosmetic particles.
+///
+/// Particles draw from their own `CosmeticNoise`, never the game's generator, so they cannot
+/// change how a seeded game or a replay plays out.
+pub struct ParticleSystem {
+    pool: Vec<Particle>,
+    enabled: bool,
+    noise: CosmeticNoise,
+}
+
+impl ParticleSystem {
+    pub fn new(enabled: bool) -> Self {
+        ParticleSystem {
+            pool: Vec::with_capacity(MAX_PARTICLES),
+            enabled,
+            noise: CosmeticNoise::new(0x9e37_79b9),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning particles off also clears the ones in flight
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pool.clear();
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// A few crumbs where a pellet was eaten, around pixel `(x, y)`
+    pub fn crumbs(&mut self, x: i32, y: i32) {
+        self.emit(x, y, &CRUMBS, &[CRUMB_COLOR]);
+    }
+
+    /// Sparkles thrown out by an energizer
+    pub fn sparkles(&mut self, x: i32, y: i32) {
+        self.emit(x, y, &SPARKLES, &[WHITE, YELLOW]);
+    }
+
+    /// A ring of debris where a ghost was eaten, in the ghost's colours
+    pub fn burst(&mut self, x: i32, y: i32, colors: &[Color]) {
+        self.emit(x, y, &BURST, colors);
+    }
+
+    fn emit(&mut self, x: i32, y: i32, emission: &Emission, colors: &[Color]) {
+        if !self.enabled || colors.is_empty() {
+            return;
+        }
+        let count = emission.count.min(MAX_PARTICLES - self.pool.len());
+        for index in 0..count {
+            // Evenly spread around the circle, jittered so repeated bursts do not line up
+            let angle = (index as f32 + self.noise.unit()) / count as f32 * std::f32::consts::TAU;
+            let speed = emission.speed * (0.5 + self.noise.unit());
+            let lifetime_ms =
+                emission.lifetime_ms / 2 + (emission.lifetime_ms as f32 * self.noise.unit()) as u32;
+            self.pool.push(Particle {
+                x: x as f32,
+                y: y as f32,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                remaining_ms: lifetime_ms,
+                lifetime_ms,
+                size: emission.size,
+                color: colors[index % colors.len()],
+            });
+        }
+    }
+
+    /// Move every particle on by `elapsed_ms` and drop the ones that have burnt out
+    pub fn update(&mut self, elapsed_ms: u32) {
+        let seconds = elapsed_ms as f32 / 1000.0;
+        for particle in self.pool.iter_mut() {
+            particle.x += particle.vx * seconds;
+            particle.y += particle.vy * seconds;
+            particle.remaining_ms = particle.remaining_ms.saturating_sub(elapsed_ms);
+        }
+        self.pool.retain(|particle| particle.remaining_ms > 0);
+    }
+
+    /// Particles shrink as they burn out
+    pub fn draw(&self, canvas: &mut WindowCanvas) -> Result<(), Box<dyn std::error::Error>> {
+        for particle in &self.pool {
+            let size = (particle.size * particle.remaining_ms / particle.lifetime_ms.max(1)).max(1);
+            let half = (size / 2) as i32;
+            canvas.set_draw_color(particle.color);
+            canvas.fill_rect(Rect::new(
+                particle.x as i32 - half,
+                particle.y as i32 - half,
+                size,
+                size,
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particles_burn_out() {
+        let mut particles = ParticleSystem::new(true);
+        particles.crumbs(100, 100);
+        particles.sparkles(100, 100);
+        assert_eq!(particles.active_count(), 20);
+
+        particles.update(100);
+        assert!(particles.active_count() > 0);
+        particles.update(1000);
+        assert_eq!(particles.active_count(), 0);
+    }
+
+    #[test]
+    fn test_pool_is_capped_and_toggleable() {
+        let mut particles = ParticleSystem::new(true);
+        for _ in 0..20 {
+            particles.burst(0, 0, &[WHITE]);
+        }
+        assert_eq!(particles.active_count(), MAX_PARTICLES);
+
+        particles.set_enabled(false);
+        assert_eq!(particles.active_count(), 0);
+        particles.burst(0, 0, &[WHITE]);
+        assert_eq!(particles.active_count(), 0);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::board::{BlockType, Direction, TUNNEL_ROWS};
+use crate::game::bot::{is_open, step, DIRECTIONS};
+use crate::position::Tile;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// First move of a shortest path between two tiles, by A* over the open tiles of the maze.
+///
+/// Paths wrap through the side and vertical tunnels but never cross the ghost house door.
+/// `None` when already there or when `to` cannot be reached.
+pub fn first_step(map: &[BlockType], from: Tile, to: Tile) -> Option<Direction> {
+    if from == to || !in_board(from) || !in_board(to) {
+        return None;
+    }
+
+    let mut cost = vec![u32::MAX; BOARD_WIDTH * BOARD_HEIGHT];
+    let mut first: Vec<Option<Direction>> = vec![None; BOARD_WIDTH * BOARD_HEIGHT];
+    let mut open = BinaryHeap::new();
+    cost[index(from)] = 0;
+    open.push(Reverse((estimate(from, to), 0, index(from))));
+
+    while let Some(Reverse((_, spent, at))) = open.pop() {
+        if spent > cost[at] {
+            continue;
+        }
+        let tile = Tile {
+            x: (at % BOARD_WIDTH) as i16,
+            y: (at / BOARD_WIDTH) as i16,
+        };
+        if tile == to {
+            return first[at];
+        }
+
+        for direction in DIRECTIONS {
+            let next = match step(tile, direction) {
+                Some(next) if is_open(map, next) => next,
+                _ => continue,
+            };
+            let next_cost = spent + 1;
+            if next_cost < cost[index(next)] {
+                cost[index(next)] = next_cost;
+                first[index(next)] = first[at].or(Some(direction));
+                open.push(Reverse((
+                    next_cost + estimate(next, to),
+                    next_cost,
+                    index(next),
+                )));
+            }
+        }
+    }
+    None
+}
+
+/// Tiles between two others, never more than the real path thanks to counting the short way
+/// round through either tunnel
+fn estimate(a: Tile, b: Tile) -> u32 {
+    let width = BOARD_WIDTH as i16;
+    let height = (TUNNEL_ROWS.1 - TUNNEL_ROWS.0 + 1) as i16;
+    let dx = (a.x - b.x).abs();
+    let dy = (a.y - b.y).abs();
+    (dx.min(width - dx) + dy.min((height - dy).abs())) as u32
+}
+
+fn in_board(tile: Tile) -> bool {
+    tile.x >= 0
+        && (tile.x as usize) < BOARD_WIDTH
+        && tile.y >= 0
+        && (tile.y as usize) < BOARD_HEIGHT
+}
+
+fn index(tile: Tile) -> usize {
+    tile.y as usize * BOARD_WIDTH + tile.x as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::stock_map;
+
+    /// Follow `first_step` all the way, counting the moves
+    fn walk(map: &[BlockType], from: Tile, to: Tile) -> usize {
+        let mut tile = from;
+        let mut moves = 0;
+        while let Some(direction) = first_step(map, tile, to) {
+            tile = step(tile, direction).unwrap();
+            assert!(is_open(map, tile));
+            moves += 1;
+            assert!(moves < BOARD_WIDTH * BOARD_HEIGHT);
+        }
+        assert_eq!(tile, to);
+        moves
+    }
+
+    #[test]
+    fn test_paths_reach_the_house_entrance() {
+        let map = stock_map();
+        let entrance = Tile { x: 14, y: 14 };
+        // Straight along the corridor above the house
+        assert_eq!(walk(&map, Tile { x: 18, y: 14 }, entrance), 4);
+        // From the bottom left corner, going round the walls
+        assert!(walk(&map, Tile { x: 1, y: 32 }, entrance) > 20);
+        assert_eq!(first_step(&map, entrance, entrance), None);
+    }
+
+    #[test]
+    fn test_paths_use_the_side_tunnel() {
+        let map = stock_map();
+        // The tunnel row, one tile in from each edge: going round the board is far shorter
+        assert_eq!(
+            first_step(&map, Tile { x: 1, y: 17 }, Tile { x: 26, y: 17 }),
+            Some(Direction::Left)
+        );
+        assert_eq!(walk(&map, Tile { x: 1, y: 17 }, Tile { x: 26, y: 17 }), 3);
+    }
+
+    #[test]
+    fn test_walls_and_doors_are_not_crossed() {
+        let map = stock_map();
+        // Inside the house can only be reached through the door
+        assert_eq!(
+            first_step(&map, Tile { x: 14, y: 14 }, Tile { x: 14, y: 17 }),
+            None
+        );
+    }
+}
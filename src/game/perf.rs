@@ -0,0 +1,247 @@
+use crate::TARGET_FPS;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How often the overlay text is re-rendered
+pub const OVERLAY_REFRESH_MS: u128 = 250;
+/// Frames kept for the frame-time graph, four seconds at full speed
+pub const GRAPH_FRAMES: usize = 240;
+/// Time covered by the graph's full height; the frame budget sits halfway up
+const GRAPH_SPAN: Duration = Duration::from_micros(2_000_000 / TARGET_FPS as u64);
+
+/// Frame timings accumulated between overlay refreshes
+#[derive(Debug, Default)]
+pub struct PerfStats {
+    frames: u32,
+    frame_time: Duration,
+    update_time: Duration,
+    draw_time: Duration,
+}
+
+impl PerfStats {
+    pub fn new() -> Self {
+        PerfStats::default()
+    }
+
+    /// `frame` is the full interval between frame starts, sleep included
+    pub fn record_frame(&mut self, frame: Duration, update: Duration, draw: Duration) {
+        self.frames += 1;
+        self.frame_time += frame;
+        self.update_time += update;
+        self.draw_time += draw;
+    }
+
+    pub fn reset(&mut self) {
+        *self = PerfStats::default();
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.frame_time.is_zero() {
+            return 0.0;
+        }
+        self.frames as f32 / self.frame_time.as_secs_f32()
+    }
+
+    fn average_ms(&self, total: Duration) -> f32 {
+        if self.frames == 0 {
+            return 0.0;
+        }
+        total.as_secs_f32() * 1000.0 / self.frames as f32
+    }
+
+    pub fn average_frame_ms(&self) -> f32 {
+        self.average_ms(self.frame_time)
+    }
+
+    pub fn average_update_ms(&self) -> f32 {
+        self.average_ms(self.update_time)
+    }
+
+    pub fn average_draw_ms(&self) -> f32 {
+        self.average_ms(self.draw_time)
+    }
+
+    /// Overlay text, one entry per line
+    pub fn summary(&self) -> [String; 2] {
+        [
+            format!("FPS {:.0} {:.1}MS", self.fps(), self.average_frame_ms()),
+            format!(
+                "U {:.1} D {:.1}",
+                self.average_update_ms(),
+                self.average_draw_ms()
+            ),
+        ]
+    }
+}
+
+/// How long one frame took, and the parts of it spent updating and drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSample {
+    pub frame: Duration,
+    pub update: Duration,
+    pub draw: Duration,
+}
+
+/// The most recent frame timings, oldest first, for the frame-time graph
+#[derive(Debug, Default)]
+pub struct FrameHistory {
+    samples: VecDeque<FrameSample>,
+}
+
+impl FrameHistory {
+    pub fn new() -> Self {
+        FrameHistory::default()
+    }
+
+    pub fn push(&mut self, sample: FrameSample) {
+        if self.samples.len() == GRAPH_FRAMES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &FrameSample> {
+        self.samples.iter()
+    }
+}
+
+/// Parts of the simulation timed on their own by `--bench-sim`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimSystem {
+    GhostAi,
+    /// Pac-Man against food and ghosts
+    Collision,
+}
+
+impl SimSystem {
+    pub const ALL: [SimSystem; 2] = [SimSystem::GhostAi, SimSystem::Collision];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SimSystem::GhostAi => "ghost_ai",
+            SimSystem::Collision => "collision",
+        }
+    }
+}
+
+/// Time spent in each `SimSystem`, only measured once enabled so normal play skips the clock
+#[derive(Debug, Default)]
+pub struct SystemTimings {
+    enabled: bool,
+    totals: [Duration; SimSystem::ALL.len()],
+}
+
+impl SystemTimings {
+    pub fn new() -> Self {
+        SystemTimings::default()
+    }
+
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// The start of a timed section, if timings are on
+    pub fn start(&self) -> Option<Instant> {
+        self.enabled.then(Instant::now)
+    }
+
+    /// Add the time since `started` to `system`
+    pub fn stop(&mut self, system: SimSystem, started: Option<Instant>) {
+        if let Some(started) = started {
+            self.totals[system as usize] += started.elapsed();
+        }
+    }
+
+    pub fn total(&self, system: SimSystem) -> Duration {
+        self.totals[system as usize]
+    }
+}
+
+/// Height of the bar for `duration` in a graph `height` pixels tall, cut off at the top
+pub fn bar_height(duration: Duration, height: u32) -> u32 {
+    let fraction = duration.as_secs_f32() / GRAPH_SPAN.as_secs_f32();
+    ((fraction * height as f32).round() as u32).min(height)
+}
+
+/// Height of the line marking one frame's time budget
+pub fn budget_height(height: u32) -> u32 {
+    bar_height(GRAPH_SPAN / 2, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_timings_only_run_once_enabled() {
+        let mut timings = SystemTimings::new();
+        assert_eq!(timings.start(), None);
+        timings.stop(
+            SimSystem::GhostAi,
+            Some(Instant::now() - Duration::from_millis(5)),
+        );
+        assert!(timings.total(SimSystem::GhostAi) >= Duration::from_millis(5));
+
+        timings.enable();
+        let started = timings.start();
+        assert!(started.is_some());
+        timings.stop(SimSystem::Collision, started);
+        assert!(timings.total(SimSystem::Collision) < timings.total(SimSystem::GhostAi));
+    }
+
+    #[test]
+    fn test_empty_stats() {
+        let stats = PerfStats::new();
+        assert_eq!(stats.fps(), 0.0);
+        assert_eq!(stats.average_frame_ms(), 0.0);
+    }
+
+    #[test]
+    fn test_averages() {
+        let mut stats = PerfStats::new();
+        stats.record_frame(
+            Duration::from_millis(20),
+            Duration::from_millis(1),
+            Duration::from_millis(4),
+        );
+        stats.record_frame(
+            Duration::from_millis(30),
+            Duration::from_millis(3),
+            Duration::from_millis(6),
+        );
+
+        assert!((stats.fps() - 40.0).abs() < 0.01);
+        assert!((stats.average_frame_ms() - 25.0).abs() < 0.01);
+        assert!((stats.average_update_ms() - 2.0).abs() < 0.01);
+        assert!((stats.average_draw_ms() - 5.0).abs() < 0.01);
+        assert_eq!(stats.summary(), ["FPS 40 25.0MS", "U 2.0 D 5.0"]);
+
+        stats.reset();
+        assert_eq!(stats.fps(), 0.0);
+    }
+
+    #[test]
+    fn test_frame_history_keeps_the_latest_frames() {
+        let mut history = FrameHistory::new();
+        for ms in 0..GRAPH_FRAMES as u64 + 10 {
+            history.push(FrameSample {
+                frame: Duration::from_millis(ms),
+                update: Duration::ZERO,
+                draw: Duration::ZERO,
+            });
+        }
+        assert_eq!(history.samples().count(), GRAPH_FRAMES);
+        assert_eq!(
+            history.samples().next().unwrap().frame,
+            Duration::from_millis(10)
+        );
+    }
+
+    #[test]
+    fn test_bars_put_the_budget_halfway_up() {
+        assert_eq!(budget_height(64), 32);
+        assert_eq!(bar_height(Duration::from_millis(8), 64), 15);
+        assert_eq!(bar_height(Duration::from_millis(100), 64), 64);
+        assert_eq!(bar_height(Duration::ZERO, 64), 0);
+    }
+}
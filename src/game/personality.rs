@@ -0,0 +1,161 @@
+use crate::game::collision::GhostType;
+
+/// Ghost order used by `corners`
+const GHOST_TYPES: [GhostType; 4] = [
+    GhostType::Blinky,
+    GhostType::Inky,
+    GhostType::Pinky,
+    GhostType::Clyde,
+];
+
+/// How far each ghost's targeting reaches and where it scatters to, so presets can retune them.
+///
+/// Personality files hold `key = value` lines; `#` starts a comment. Recognized keys:
+/// `pinky_lead` (tiles Pinky aims ahead of Pac-Man), `inky_pivot` (tiles ahead of Pac-Man that
+/// Inky doubles Blinky's vector through), `clyde_shyness` (tiles from Pac-Man at which Clyde
+/// gives up the chase) and `blinky_corner`, `inky_corner`, `pinky_corner`, `clyde_corner` as
+/// `COL,ROW` tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostPersonality {
+    pub pinky_lead: u32,
+    pub inky_pivot: u32,
+    pub clyde_shyness: u32,
+    /// Scatter tiles as (column, row), in Blinky, Inky, Pinky, Clyde order
+    pub corners: [(i16, i16); 4],
+}
+
+impl Default for GhostPersonality {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GhostPersonality {
+    /// The arcade values
+    pub fn new() -> Self {
+        GhostPersonality {
+            pinky_lead: 4,
+            inky_pivot: 2,
+            clyde_shyness: 8,
+            corners: [(25, 0), (26, 35), (2, 0), (0, 35)],
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        GhostPersonality::parse(&text).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    /// Keys left out keep their arcade values
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut personality = GhostPersonality::new();
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let tiles = |text: &str| {
+                text.parse::<u32>()
+                    .map_err(|_| format!("Line {}: invalid tile count '{}'", line_number, text))
+            };
+            let corner = |text: &str| {
+                text.split_once(',')
+                    .and_then(|(column, row)| {
+                        Some((column.trim().parse().ok()?, row.trim().parse().ok()?))
+                    })
+                    .ok_or_else(|| format!("Line {}: invalid corner '{}'", line_number, text))
+            };
+
+            match key {
+                "pinky_lead" => personality.pinky_lead = tiles(value)?,
+                "inky_pivot" => personality.inky_pivot = tiles(value)?,
+                "clyde_shyness" => personality.clyde_shyness = tiles(value)?,
+                _ => match key.strip_suffix("_corner").and_then(GhostType::from_name) {
+                    Some(ghost_type) => {
+                        personality.corners[ghost_index(ghost_type)] = corner(value)?
+                    }
+                    None => {
+                        return Err(format!("Line {}: unknown key '{}'", line_number, key).into())
+                    }
+                },
+            }
+        }
+
+        Ok(personality)
+    }
+
+    /// Scatter tile of a ghost type
+    pub fn corner(&self, ghost_type: GhostType) -> (i16, i16) {
+        self.corners[ghost_index(ghost_type)]
+    }
+
+    /// Targeting reach in tiles of a ghost type; Blinky aims straight at Pac-Man and has none
+    pub fn reach(&self, ghost_type: GhostType) -> u32 {
+        match ghost_type {
+            GhostType::Blinky => 0,
+            GhostType::Inky => self.inky_pivot,
+            GhostType::Pinky => self.pinky_lead,
+            GhostType::Clyde => self.clyde_shyness,
+        }
+    }
+
+    /// Compact form of the values, for telling whether two runs played alike
+    pub fn fingerprint(&self) -> String {
+        let corners: Vec<String> = self
+            .corners
+            .iter()
+            .map(|(column, row)| format!("{},{}", column, row))
+            .collect();
+        format!(
+            "{}/{}/{}/{}",
+            self.pinky_lead,
+            self.inky_pivot,
+            self.clyde_shyness,
+            corners.join("/")
+        )
+    }
+}
+
+fn ghost_index(ghost_type: GhostType) -> usize {
+    GHOST_TYPES
+        .iter()
+        .position(|&other| other == ghost_type)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_personality() {
+        let personality = GhostPersonality::parse(
+            "# hard preset\npinky_lead = 6\nclyde_shyness = 4\nclyde_corner = 27, 35\n",
+        )
+        .unwrap();
+        assert_eq!(personality.reach(GhostType::Pinky), 6);
+        assert_eq!(personality.reach(GhostType::Inky), 2);
+        assert_eq!(personality.reach(GhostType::Clyde), 4);
+        assert_eq!(personality.corner(GhostType::Clyde), (27, 35));
+        assert_eq!(personality.corner(GhostType::Blinky), (25, 0));
+    }
+
+    #[test]
+    fn test_personality_errors() {
+        assert!(GhostPersonality::parse("pinky_lead = far").is_err());
+        assert!(GhostPersonality::parse("sue_corner = 1,1").is_err());
+        assert!(GhostPersonality::parse("inky_corner = 1").is_err());
+        assert!(GhostPersonality::parse("inky_pivot 2").is_err());
+        assert_eq!(
+            GhostPersonality::parse("").unwrap(),
+            GhostPersonality::new()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+/// How long before the energizer runs out the ghosts start flashing
+pub const WARNING_MS: u32 = 2000;
+
+/// Pac-Man's energizer state, counted down once per update tick
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PowerState {
+    active: bool,
+    remaining_ms: u32,
+}
+
+impl PowerState {
+    pub fn new() -> Self {
+        PowerState::default()
+    }
+
+    /// Start (or restart) the power period
+    pub fn activate(&mut self, duration_ms: u32) {
+        self.active = duration_ms > 0;
+        self.remaining_ms = duration_ms;
+    }
+
+    pub fn deactivate(&mut self) {
+        self.active = false;
+        self.remaining_ms = 0;
+    }
+
+    /// Advance by one update step, returning true when the power runs out
+    pub fn tick(&mut self, elapsed_ms: u32) -> bool {
+        if !self.active {
+            return false;
+        }
+
+        self.remaining_ms = self.remaining_ms.saturating_sub(elapsed_ms);
+        if self.remaining_ms == 0 {
+            self.active = false;
+            return true;
+        }
+        false
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn get_remaining_ms(&self) -> u32 {
+        self.remaining_ms
+    }
+
+    /// Check if the power is active but will run out within the given time
+    pub fn is_ending(&self, warning_ms: u32) -> bool {
+        self.active && self.remaining_ms <= warning_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inactive_by_default() {
+        let mut power = PowerState::new();
+        assert!(!power.is_active());
+        assert_eq!(power.get_remaining_ms(), 0);
+        assert!(!power.tick(16));
+    }
+
+    #[test]
+    fn test_counts_down_and_expires() {
+        let mut power = PowerState::new();
+        power.activate(40);
+        assert!(power.is_active());
+
+        assert!(!power.tick(16));
+        assert_eq!(power.get_remaining_ms(), 24);
+        assert!(!power.tick(16));
+        assert!(power.tick(16));
+        assert!(!power.is_active());
+        assert_eq!(power.get_remaining_ms(), 0);
+
+        // Only reports expiry once
+        assert!(!power.tick(16));
+    }
+
+    #[test]
+    fn test_reactivation_restarts_duration() {
+        let mut power = PowerState::new();
+        power.activate(100);
+        power.tick(60);
+        power.activate(100);
+        assert_eq!(power.get_remaining_ms(), 100);
+    }
+
+    #[test]
+    fn test_ending_warning() {
+        let mut power = PowerState::new();
+        power.activate(3000);
+        assert!(!power.is_ending(2000));
+        power.tick(1000);
+        assert!(power.is_ending(2000));
+
+        power.deactivate();
+        assert!(!power.is_ending(2000));
+    }
+
+    #[test]
+    fn test_zero_duration_never_activates() {
+        let mut power = PowerState::new();
+        power.activate(0);
+        assert!(!power.is_active());
+    }
+}
@@ -0,0 +1,695 @@
+use crate::board::{Board, Direction};
+use crate::config::Config;
+use crate::game::rng::RngDraw;
+use crate::game::snapshot::Snapshot;
+use crate::locale::Date;
+
+const HEADER: &str = "# pacman replay v1";
+
+/// How many updates an annotation stays on screen during playback
+pub const CAPTION_TICKS: u32 = 180;
+
+/// Where the replay of the best run so far is kept
+pub const BEST_REPLAY_PATH: &str = "best_replay.txt";
+
+/// 64-bit FNV-1a, stable across builds so hashes can be stored in files
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Fingerprint of the stock maze layout
+pub fn maze_hash() -> u64 {
+    fnv1a(Board::CHAR_BOARD.as_bytes())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayMetadata {
+    pub player: String,
+    pub date: Date,
+    pub maze_hash: u64,
+    pub config_hash: u64,
+    /// RNG seed the run was played with
+    pub seed: u64,
+    pub final_score: u32,
+}
+
+impl ReplayMetadata {
+    /// Metadata for a run starting today; the score is filled in when it ends
+    pub fn new(config: &Config, seed: u64) -> Self {
+        ReplayMetadata {
+            player: config.player.clone(),
+            date: Date::today(),
+            maze_hash: maze_hash(),
+            config_hash: fnv1a(config.fingerprint().as_bytes()),
+            seed,
+            final_score: 0,
+        }
+    }
+}
+
+/// A player action that changes how the simulation plays out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputAction {
+    Turn(Direction),
+    /// The second player steering Blinky in co-op
+    Steer(Direction),
+    /// Space on the ready or milestone screen
+    Continue,
+}
+
+impl InputAction {
+    /// Single-character form used in replay files and over the network
+    pub fn to_char(self) -> char {
+        match self {
+            InputAction::Turn(direction) => direction.to_char(),
+            InputAction::Steer(direction) => direction.to_char().to_ascii_lowercase(),
+            InputAction::Continue => 'C',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'C' => Some(InputAction::Continue),
+            c if c.is_ascii_lowercase() => Some(InputAction::Steer(Direction::from_char(
+                c.to_ascii_uppercase(),
+            )?)),
+            c => Some(InputAction::Turn(Direction::from_char(c)?)),
+        }
+    }
+}
+
+/// An action taken by the player before a given update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputEvent {
+    pub tick: u32,
+    pub action: InputAction,
+}
+
+/// A random draw made during a given update, kept for ranked runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RngAudit {
+    pub tick: u32,
+    pub draw: RngDraw,
+}
+
+/// A caption shown during playback starting at `tick`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub tick: u32,
+    pub text: String,
+}
+
+/// Recorded inputs for one game, plus metadata and playback captions.
+///
+/// The file starts with a version line, then `key = value` metadata, then
+/// `note <tick> = <text>` annotations, then `inputs:` and one `<tick> <R|U|L|D|N|C>` per line,
+/// where `C` continues past the ready or milestone screen. Ranked runs end with `rng:` and one
+/// `<tick> <value>/<len> <purpose>` line per random draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub metadata: ReplayMetadata,
+    pub annotations: Vec<Annotation>,
+    pub inputs: Vec<InputEvent>,
+    pub rng_log: Vec<RngAudit>,
+}
+
+impl Replay {
+    pub fn new(metadata: ReplayMetadata) -> Self {
+        Replay {
+            metadata,
+            annotations: Vec::new(),
+            inputs: Vec::new(),
+            rng_log: Vec::new(),
+        }
+    }
+
+    pub fn record_input(&mut self, tick: u32, action: InputAction) {
+        self.inputs.push(InputEvent { tick, action });
+    }
+
+    pub fn record_draws(&mut self, tick: u32, draws: Vec<RngDraw>) {
+        self.rng_log
+            .extend(draws.into_iter().map(|draw| RngAudit { tick, draw }));
+    }
+
+    /// Add a caption, keeping annotations ordered by tick
+    pub fn annotate(&mut self, tick: u32, text: &str) {
+        let index = self
+            .annotations
+            .partition_point(|annotation| annotation.tick <= tick);
+        self.annotations.insert(
+            index,
+            Annotation {
+                tick,
+                text: text.to_string(),
+            },
+        );
+    }
+
+    /// Caption to show on a given update, if an annotation started recently enough
+    pub fn caption_at(&self, tick: u32) -> Option<&str> {
+        self.annotations
+            .iter()
+            .rev()
+            .find(|annotation| annotation.tick <= tick)
+            .filter(|annotation| tick - annotation.tick < CAPTION_TICKS)
+            .map(|annotation| annotation.text.as_str())
+    }
+
+    pub fn to_text(&self) -> String {
+        let metadata = &self.metadata;
+        let mut text = String::new();
+        text.push_str(HEADER);
+        text.push('\n');
+        text.push_str(&format!("player = {}\n", metadata.player));
+        text.push_str(&format!("date = {}\n", metadata.date.to_iso()));
+        text.push_str(&format!("maze = {:016x}\n", metadata.maze_hash));
+        text.push_str(&format!("config = {:016x}\n", metadata.config_hash));
+        text.push_str(&format!("seed = {}\n", metadata.seed));
+        text.push_str(&format!("score = {}\n", metadata.final_score));
+        for annotation in &self.annotations {
+            text.push_str(&format!("note {} = {}\n", annotation.tick, annotation.text));
+        }
+
+        text.push_str("inputs:\n");
+        for input in &self.inputs {
+            text.push_str(&format!("{} {}\n", input.tick, input.action.to_char()));
+        }
+        if !self.rng_log.is_empty() {
+            text.push_str("rng:\n");
+            for entry in &self.rng_log {
+                text.push_str(&format!(
+                    "{} {}/{} {}\n",
+                    entry.tick, entry.draw.value, entry.draw.len, entry.draw.purpose
+                ));
+            }
+        }
+        text
+    }
+
+    /// Whether `text` claims to be a replay, judging by its first line alone
+    pub fn is_replay(text: &str) -> bool {
+        text.lines().next() == Some(HEADER)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err("Not a replay file, or an unsupported version".into()),
+        }
+
+        let mut player = None;
+        let mut date = None;
+        let mut maze_hash = None;
+        let mut config_hash = None;
+        let mut seed = None;
+        let mut final_score = None;
+        let mut annotations = Vec::new();
+        let mut inputs = Vec::new();
+        let mut rng_log = Vec::new();
+        let mut in_inputs = false;
+        let mut in_rng = false;
+
+        for (index, raw_line) in lines {
+            let line_number = index + 1;
+            let line = raw_line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if in_rng {
+                rng_log.push(parse_rng_audit(line).ok_or_else(|| {
+                    format!(
+                        "Replay line {}: expected '<tick> <value>/<len> <purpose>'",
+                        line_number
+                    )
+                })?);
+                continue;
+            }
+            if in_inputs && line == "rng:" {
+                in_rng = true;
+                continue;
+            }
+            if in_inputs {
+                inputs.push(parse_input(line).ok_or_else(|| {
+                    format!(
+                        "Replay line {}: expected '<tick> <R|U|L|D|N|C>'",
+                        line_number
+                    )
+                })?);
+                continue;
+            }
+            if line == "inputs:" {
+                in_inputs = true;
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Replay line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let bad_value = || format!("Replay line {}: invalid {} '{}'", line_number, key, value);
+
+            match key {
+                "player" => player = Some(value.to_string()),
+                "date" => date = Some(Date::parse_iso(value).ok_or_else(bad_value)?),
+                "maze" => {
+                    maze_hash = Some(u64::from_str_radix(value, 16).map_err(|_| bad_value())?)
+                }
+                "config" => {
+                    config_hash = Some(u64::from_str_radix(value, 16).map_err(|_| bad_value())?)
+                }
+                "seed" => seed = Some(value.parse().map_err(|_| bad_value())?),
+                "score" => final_score = Some(value.parse().map_err(|_| bad_value())?),
+                _ => match key.strip_prefix("note ") {
+                    Some(tick) => annotations.push(Annotation {
+                        tick: tick.trim().parse().map_err(|_| bad_value())?,
+                        text: value.to_string(),
+                    }),
+                    None => {
+                        return Err(
+                            format!("Replay line {}: unknown key '{}'", line_number, key).into(),
+                        )
+                    }
+                },
+            }
+        }
+
+        if !in_inputs {
+            return Err("Replay is missing its inputs section".into());
+        }
+        annotations.sort_by_key(|annotation: &Annotation| annotation.tick);
+
+        let missing = |key: &str| format!("Replay is missing '{}'", key);
+        Ok(Replay {
+            metadata: ReplayMetadata {
+                player: player.ok_or_else(|| missing("player"))?,
+                date: date.ok_or_else(|| missing("date"))?,
+                maze_hash: maze_hash.ok_or_else(|| missing("maze"))?,
+                config_hash: config_hash.ok_or_else(|| missing("config"))?,
+                seed: seed.ok_or_else(|| missing("seed"))?,
+                final_score: final_score.ok_or_else(|| missing("score"))?,
+            },
+            annotations,
+            inputs,
+            rng_log,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        Replay::parse(&text).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    /// Refuse replays recorded against a different maze or simulation options
+    pub fn check_compatible(&self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        if self.metadata.maze_hash != maze_hash() {
+            return Err("Replay was recorded on a different maze".into());
+        }
+        if self.metadata.config_hash != fnv1a(config.fingerprint().as_bytes()) {
+            return Err(format!(
+                "Replay was recorded with different options ({})",
+                config.fingerprint()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Overwrite `path` if this run beat the score stored there, returning whether it did.
+    ///
+    /// A missing or unreadable file counts as no previous best.
+    pub fn save_if_best(&self, path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let best = Replay::load(path)
+            .map(|best| best.metadata.final_score)
+            .unwrap_or(0);
+        if self.metadata.final_score <= best {
+            return Ok(false);
+        }
+        self.save(path)?;
+        Ok(true)
+    }
+}
+
+/// Where a game should stand after `tick` updates, kept next to the golden replays in
+/// `tests/replays` as `key = value` lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub tick: u32,
+    pub level: u16,
+    pub score: u32,
+    pub lives: i8,
+    /// Hash of the whole snapshot, so every position and the maze are compared too
+    pub state_hash: u64,
+}
+
+impl Checkpoint {
+    pub fn of(snapshot: &Snapshot, tick: u32) -> Self {
+        Checkpoint {
+            tick,
+            level: snapshot.level,
+            score: snapshot.score,
+            lives: snapshot.lives,
+            state_hash: fnv1a(snapshot.to_text().as_bytes()),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "tick = {}\nlevel = {}\nscore = {}\nlives = {}\nstate = {:016x}\n",
+            self.tick, self.level, self.score, self.lives, self.state_hash
+        )
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut tick = None;
+        let mut level = None;
+        let mut score = None;
+        let mut lives = None;
+        let mut state_hash = None;
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Checkpoint line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let bad_value = || {
+                format!(
+                    "Checkpoint line {}: invalid {} '{}'",
+                    line_number, key, value
+                )
+            };
+            match key {
+                "tick" => tick = Some(value.parse().map_err(|_| bad_value())?),
+                "level" => level = Some(value.parse().map_err(|_| bad_value())?),
+                "score" => score = Some(value.parse().map_err(|_| bad_value())?),
+                "lives" => lives = Some(value.parse().map_err(|_| bad_value())?),
+                "state" => {
+                    state_hash = Some(u64::from_str_radix(value, 16).map_err(|_| bad_value())?)
+                }
+                _ => {
+                    return Err(
+                        format!("Checkpoint line {}: unknown key '{}'", line_number, key).into(),
+                    )
+                }
+            }
+        }
+
+        let missing = |key: &str| format!("Checkpoint is missing '{}'", key);
+        Ok(Checkpoint {
+            tick: tick.ok_or_else(|| missing("tick"))?,
+            level: level.ok_or_else(|| missing("level"))?,
+            score: score.ok_or_else(|| missing("score"))?,
+            lives: lives.ok_or_else(|| missing("lives"))?,
+            state_hash: state_hash.ok_or_else(|| missing("state"))?,
+        })
+    }
+}
+
+/// Feeds a recorded replay back into the game one update at a time
+pub struct Playback {
+    replay: Replay,
+    next_input: usize,
+    next_draw: usize,
+    audit_failed: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            next_input: 0,
+            next_draw: 0,
+            audit_failed: false,
+        }
+    }
+
+    /// Actions recorded for updates up to and including `tick` that have not been fed yet
+    pub fn take_inputs(&mut self, tick: u32) -> Vec<InputAction> {
+        let pending = &self.replay.inputs[self.next_input..];
+        let count = pending.partition_point(|input| input.tick <= tick);
+        self.next_input += count;
+        pending[..count].iter().map(|input| input.action).collect()
+    }
+
+    pub fn caption_at(&self, tick: u32) -> Option<&str> {
+        self.replay.caption_at(tick)
+    }
+
+    /// Whether the replay carries an RNG log to check the playback against
+    pub fn is_audited(&self) -> bool {
+        !self.replay.rng_log.is_empty()
+    }
+
+    /// Audited, and every draw so far matched the log
+    pub fn audit_passed(&self) -> bool {
+        self.is_audited() && !self.audit_failed
+    }
+
+    /// Check the draws made on update `tick` against the log, describing the first mismatch.
+    ///
+    /// Only the first mismatch is reported; after that the two runs have diverged anyway.
+    pub fn audit(&mut self, tick: u32, draws: &[RngDraw]) -> Option<String> {
+        if self.audit_failed {
+            return None;
+        }
+        let pending = &self.replay.rng_log[self.next_draw..];
+        let count = pending.partition_point(|entry| entry.tick <= tick);
+        self.next_draw += count;
+        let expected: Vec<&RngDraw> = pending[..count].iter().map(|entry| &entry.draw).collect();
+        if expected.iter().copied().eq(draws.iter()) {
+            return None;
+        }
+
+        self.audit_failed = true;
+        Some(format!(
+            "RNG audit failed on update {}: replay logged [{}], playback drew [{}]",
+            tick,
+            describe_draws(expected.into_iter()),
+            describe_draws(draws.iter())
+        ))
+    }
+}
+
+fn describe_draws<'d>(draws: impl Iterator<Item = &'d RngDraw>) -> String {
+    draws
+        .map(|draw| format!("{} {}/{}", draw.purpose, draw.value, draw.len))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn parse_rng_audit(line: &str) -> Option<RngAudit> {
+    let mut fields = line.splitn(3, ' ');
+    let tick = fields.next()?.parse().ok()?;
+    let (value, len) = fields.next()?.split_once('/')?;
+    let purpose = fields.next()?.trim();
+    if purpose.is_empty() {
+        return None;
+    }
+    Some(RngAudit {
+        tick,
+        draw: RngDraw {
+            purpose: purpose.to_string(),
+            value: value.parse().ok()?,
+            len: len.parse().ok()?,
+        },
+    })
+}
+
+fn parse_input(line: &str) -> Option<InputEvent> {
+    let (tick, action) = line.split_once(' ')?;
+    let mut chars = action.trim().chars();
+    let action = match (chars.next()?, chars.next()) {
+        (c, None) => InputAction::from_char(c)?,
+        _ => return None,
+    };
+    Some(InputEvent {
+        tick: tick.parse().ok()?,
+        action,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Replay {
+        let mut replay = Replay::new(ReplayMetadata {
+            player: "Ada".to_string(),
+            date: Date {
+                year: 2024,
+                month: 5,
+                day: 17,
+            },
+            maze_hash: maze_hash(),
+            config_hash: 0xdead_beef,
+            seed: 42,
+            final_score: 15230,
+        });
+        replay.record_input(0, InputAction::Continue);
+        replay.record_input(0, InputAction::Turn(Direction::Left));
+        replay.record_input(42, InputAction::Turn(Direction::Up));
+        replay.record_input(42, InputAction::Steer(Direction::Left));
+        replay.annotate(900, "second energizer");
+        replay.annotate(300, "first energizer chain here");
+        replay.record_draws(610, vec![draw("pinky frightened turn", 1, 2)]);
+        replay.record_draws(
+            612,
+            vec![
+                draw("inky frightened turn", 0, 3),
+                draw("clyde frightened turn", 1, 2),
+            ],
+        );
+        replay
+    }
+
+    fn draw(purpose: &str, value: usize, len: usize) -> RngDraw {
+        RngDraw {
+            purpose: purpose.to_string(),
+            value,
+            len,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let replay = sample();
+        assert_eq!(Replay::parse(&replay.to_text()).unwrap(), replay);
+    }
+
+    #[test]
+    fn test_annotations_stay_sorted() {
+        let replay = sample();
+        let ticks: Vec<u32> = replay.annotations.iter().map(|a| a.tick).collect();
+        assert_eq!(ticks, vec![300, 900]);
+    }
+
+    #[test]
+    fn test_caption_window() {
+        let replay = sample();
+        assert_eq!(replay.caption_at(299), None);
+        assert_eq!(replay.caption_at(300), Some("first energizer chain here"));
+        assert_eq!(
+            replay.caption_at(300 + CAPTION_TICKS - 1),
+            Some("first energizer chain here")
+        );
+        assert_eq!(replay.caption_at(300 + CAPTION_TICKS), None);
+        assert_eq!(replay.caption_at(950), Some("second energizer"));
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        assert!(Replay::parse("player = x\ninputs:\n").is_err());
+
+        let text = sample().to_text().replace("score = 15230\n", "");
+        assert!(Replay::parse(&text)
+            .unwrap_err()
+            .to_string()
+            .contains("score"));
+
+        let text = sample().to_text().replace("42 U", "42 Q");
+        assert!(Replay::parse(&text).is_err());
+
+        let text = sample().to_text().replace("inputs:\n0 C\n0 L\n42 U\n", "");
+        assert!(Replay::parse(&text).is_err());
+
+        let text = sample().to_text().replace("seed = 42\n", "");
+        assert!(Replay::parse(&text).is_err());
+    }
+
+    #[test]
+    fn test_playback_feeds_inputs_in_order() {
+        let mut playback = Playback::new(sample());
+        assert_eq!(
+            playback.take_inputs(0),
+            vec![InputAction::Continue, InputAction::Turn(Direction::Left)]
+        );
+        assert!(playback.take_inputs(41).is_empty());
+        assert_eq!(
+            playback.take_inputs(42),
+            vec![
+                InputAction::Turn(Direction::Up),
+                InputAction::Steer(Direction::Left)
+            ]
+        );
+        assert!(playback.take_inputs(1000).is_empty());
+    }
+
+    #[test]
+    fn test_only_better_runs_replace_the_best() {
+        let path = std::env::temp_dir().join(format!("best_replay_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let mut replay = sample();
+        assert!(replay.save_if_best(path).unwrap());
+        assert!(!replay.save_if_best(path).unwrap());
+        replay.metadata.final_score += 10;
+        assert!(replay.save_if_best(path).unwrap());
+        assert_eq!(Replay::load(path).unwrap(), replay);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rng_audit() {
+        let mut playback = Playback::new(sample());
+        assert!(playback.is_audited());
+        assert_eq!(playback.audit(609, &[]), None);
+        assert_eq!(
+            playback.audit(610, &[draw("pinky frightened turn", 1, 2)]),
+            None
+        );
+        assert_eq!(playback.audit(611, &[]), None);
+        assert!(playback.audit_passed());
+
+        let report = playback
+            .audit(612, &[draw("inky frightened turn", 2, 3)])
+            .unwrap();
+        assert!(report.contains("update 612"));
+        assert!(report.contains("inky frightened turn 0/3, clyde frightened turn 1/2"));
+        // The runs have diverged, so later differences are not reported again
+        assert_eq!(playback.audit(700, &[draw("blinky", 0, 2)]), None);
+        assert!(!playback.audit_passed());
+
+        assert!(!Playback::new(Replay::new(sample().metadata)).is_audited());
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let checkpoint = Checkpoint {
+            tick: 8000,
+            level: 2,
+            score: 4690,
+            lives: 3,
+            state_hash: 0xf303_ceb7_7c5f_149b,
+        };
+        let text = checkpoint.to_text();
+        assert!(text.contains("state = f303ceb77c5f149b"));
+        assert_eq!(Checkpoint::parse(&text).unwrap(), checkpoint);
+
+        let err = Checkpoint::parse("tick = 8000\nlevel = 2").unwrap_err();
+        assert!(err.to_string().contains("missing 'score'"));
+        assert!(Checkpoint::parse("tick = soon").is_err());
+    }
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
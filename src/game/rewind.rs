@@ -0,0 +1,85 @@
+use super::snapshot::Snapshot;
+use std::collections::VecDeque;
+
+/// Roughly ten seconds of play at one snapshot per update
+pub const REWIND_CAPACITY: usize = 600;
+
+/// The most recent saved-game snapshots, kept serialized so rewinding reuses the save format
+pub struct RewindBuffer {
+    frames: VecDeque<String>,
+    capacity: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(capacity: usize) -> Self {
+        RewindBuffer {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Remember a frame, forgetting the oldest once the buffer is full
+    pub fn push(&mut self, snapshot: &Snapshot) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(snapshot.to_text());
+    }
+
+    /// Take back the most recent frame, or `None` once there is nothing left to rewind
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        let text = self.frames.pop_back()?;
+        match Snapshot::parse(&text) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                println!("Discarding unreadable rewind frame: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BlockType, Board};
+    use crate::position::Position;
+
+    fn frame(score: u32) -> Snapshot {
+        Snapshot {
+            level: 1,
+            score,
+            lives: 3,
+            pacman: Position::new(100, 200),
+            ghosts: vec![Position::new(0, 0); 4],
+            map: Board::CHAR_BOARD
+                .chars()
+                .map(BlockType::from_char)
+                .collect(),
+            resume: None,
+        }
+    }
+
+    #[test]
+    fn test_rewinds_newest_first() {
+        let mut buffer = RewindBuffer::new(10);
+        for score in [10, 20, 30] {
+            buffer.push(&frame(score));
+        }
+        assert_eq!(buffer.pop().map(|s| s.score), Some(30));
+        assert_eq!(buffer.pop().map(|s| s.score), Some(20));
+        assert_eq!(buffer.pop().map(|s| s.score), Some(10));
+        assert_eq!(buffer.pop(), None);
+    }
+
+    #[test]
+    fn test_forgets_oldest_when_full() {
+        let mut buffer = RewindBuffer::new(2);
+        for score in [10, 20, 30] {
+            buffer.push(&frame(score));
+        }
+        assert_eq!(buffer.pop().map(|s| s.score), Some(30));
+        assert_eq!(buffer.pop().map(|s| s.score), Some(20));
+        assert_eq!(buffer.pop(), None);
+    }
+}
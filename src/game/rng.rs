@@ -0,0 +1,169 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+/// One traced draw: what it was for, and the index picked out of `0..len`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RngDraw {
+    pub purpose: String,
+    pub value: usize,
+    pub len: usize,
+}
+
+/// The game's only source of randomness.
+///
+/// Everything that needs a random choice draws from the one generator owned by `Game`,
+/// so the same seed and the same inputs always replay the same game. With tracing on,
+/// every draw is also kept until `take_trace` collects it.
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha12Rng,
+    trace: Option<Vec<RngDraw>>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            trace: None,
+        }
+    }
+
+    /// Carry on from `position` in the stream of `seed`, as returned by `get_position`
+    pub fn resume(seed: u64, position: u128) -> Self {
+        let mut rng = GameRng::new(seed);
+        rng.rng.set_word_pos(position);
+        rng
+    }
+
+    /// Seed from the OS, for runs that did not ask for a specific seed
+    pub fn from_entropy() -> Self {
+        GameRng::new(rand::random())
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// How far into the seed's stream the draws have got
+    pub fn get_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    /// Uniform index in `0..len`; `len` must not be zero
+    pub fn next_index(&mut self, len: usize, purpose: &str) -> usize {
+        let value = self.rng.gen_range(0..len);
+        if let Some(ref mut trace) = self.trace {
+            trace.push(RngDraw {
+                purpose: purpose.to_string(),
+                value,
+                len,
+            });
+        }
+        value
+    }
+
+    pub fn set_tracing(&mut self, enabled: bool) {
+        self.trace = enabled.then(Vec::new);
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Draws made since the last call; always empty when tracing is off
+    pub fn take_trace(&mut self) -> Vec<RngDraw> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+}
+
+/// Xorshift noise for purely cosmetic effects such as particles and screen shake.
+///
+/// It is kept apart from `GameRng` on purpose: effects drawing from it can be switched off
+/// without changing a single draw the game makes, so seeded games and replays stay the same.
+pub struct CosmeticNoise {
+    state: u32,
+}
+
+impl CosmeticNoise {
+    /// Start from `seed`, which must not be zero
+    pub fn new(seed: u32) -> Self {
+        CosmeticNoise { state: seed }
+    }
+
+    /// The next value in `0.0..1.0`
+    pub fn unit(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = GameRng::new(1234);
+        let mut b = GameRng::new(1234);
+        let first: Vec<usize> = (0..32).map(|_| a.next_index(4, "test")).collect();
+        let second: Vec<usize> = (0..32).map(|_| b.next_index(4, "test")).collect();
+        assert_eq!(first, second);
+        assert_eq!(a.get_seed(), 1234);
+    }
+
+    #[test]
+    fn test_index_in_range() {
+        let mut rng = GameRng::new(7);
+        for len in 1..10 {
+            assert!(rng.next_index(len, "test") < len);
+        }
+    }
+
+    #[test]
+    fn test_tracing_records_draws_without_changing_them() {
+        let mut plain = GameRng::new(99);
+        let mut traced = GameRng::new(99);
+        traced.set_tracing(true);
+        assert!(plain.take_trace().is_empty());
+
+        let value = traced.next_index(3, "inky frightened turn");
+        assert_eq!(plain.next_index(3, "inky frightened turn"), value);
+        assert_eq!(
+            traced.take_trace(),
+            vec![RngDraw {
+                purpose: "inky frightened turn".to_string(),
+                value,
+                len: 3,
+            }]
+        );
+        assert!(traced.take_trace().is_empty());
+        assert!(plain.take_trace().is_empty());
+    }
+
+    #[test]
+    fn test_resume_carries_on_the_same_sequence() {
+        let mut original = GameRng::new(42);
+        for len in 1..20 {
+            original.next_index(len, "test");
+        }
+        let mut resumed = GameRng::resume(42, original.get_position());
+        let rest: Vec<usize> = (0..32).map(|_| original.next_index(7, "test")).collect();
+        let again: Vec<usize> = (0..32).map(|_| resumed.next_index(7, "test")).collect();
+        assert_eq!(rest, again);
+        assert_eq!(resumed.get_seed(), 42);
+    }
+
+    #[test]
+    fn test_cosmetic_noise_stays_in_range() {
+        let mut a = CosmeticNoise::new(0x9e37_79b9);
+        let mut b = CosmeticNoise::new(0x9e37_79b9);
+        for _ in 0..1000 {
+            let value = a.unit();
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(b.unit(), value);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+/// Pellets eaten before each bonus fruit of a level appears
+pub const FRUIT_DOTS: [usize; 2] = [70, 170];
+
+/// Which arcade game the mazes, fruit and ghost schedule follow
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RulesMode {
+    Arcade,
+    MsPacman,
+}
+
+/// How a bonus fruit arrives on the board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FruitEntry {
+    /// Sits still below the ghost house
+    Fixed,
+    /// Bounces in through a side tunnel and wanders the maze
+    Tunnel,
+}
+
+impl RulesMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "arcade" => Some(RulesMode::Arcade),
+            "ms" => Some(RulesMode::MsPacman),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RulesMode::Arcade => "arcade",
+            RulesMode::MsPacman => "ms",
+        }
+    }
+
+    /// Index of the maze layout played on `level`
+    pub fn maze_for_level(self, level: u16) -> usize {
+        match self {
+            RulesMode::Arcade => 0,
+            // Two levels on the first maze, then three, four and four, then alternating
+            RulesMode::MsPacman => match level {
+                0..=2 => 0,
+                3..=5 => 1,
+                6..=9 => 0,
+                _ => ((level as usize - 10) / 4 + 1) % 2,
+            },
+        }
+    }
+
+    /// Whether Blinky and Pinky turn at random during the first scatter of each life
+    pub fn random_first_scatter(self) -> bool {
+        self == RulesMode::MsPacman
+    }
+
+    pub fn fruit_entry(self) -> FruitEntry {
+        match self {
+            RulesMode::Arcade => FruitEntry::Fixed,
+            RulesMode::MsPacman => FruitEntry::Tunnel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for mode in [RulesMode::Arcade, RulesMode::MsPacman] {
+            assert_eq!(RulesMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(RulesMode::from_name("jr"), None);
+    }
+
+    #[test]
+    fn test_maze_schedule() {
+        let mazes: Vec<usize> = (1..=17)
+            .map(|level| RulesMode::MsPacman.maze_for_level(level))
+            .collect();
+        assert_eq!(mazes, [0, 0, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(RulesMode::Arcade.maze_for_level(5), 0);
+    }
+}
@@ -1,17 +1,212 @@
 use crate::game::state::GameTimer;
 use crate::position::Position;
 
+/// Points awarded for each thing Pac-Man eats, so variant modes can be pure data.
+///
+/// Rule files hold `key = value` lines; `#` starts a comment. Recognized keys: `pellet`,
+/// `energizer`, `ghost` (the first ghost of an energizer), `ghost_multiplier` (applied to
+/// each further ghost), `fruit` (comma-separated, cherry through key) and the arcade-plus
+/// clear bonus: `clear_par` (seconds), `time_bonus` (per second under par) and `life_bonus`
+/// (per spare life). The clear bonus is off unless those are set. `extra_life` is the score
+/// that earns a life, 0 for none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoringRules {
+    pub pellet: u32,
+    pub energizer: u32,
+    pub ghost: u32,
+    pub ghost_multiplier: u32,
+    pub fruit: Vec<u32>,
+    pub clear_par: u32,
+    pub time_bonus: u32,
+    pub life_bonus: u32,
+    pub extra_life: u32,
+}
+
+/// Extra points for clearing a level, as worked out when the last pellet is eaten
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelBonus {
+    pub time: u32,
+    pub lives: u32,
+}
+
+impl LevelBonus {
+    pub fn total(&self) -> u32 {
+        self.time.saturating_add(self.lives)
+    }
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScoringRules {
+    /// The arcade values
+    pub fn new() -> Self {
+        ScoringRules {
+            pellet: 10,
+            energizer: 50,
+            ghost: 200,
+            ghost_multiplier: 2,
+            fruit: vec![100, 300, 500, 700, 1000, 2000, 3000, 5000],
+            clear_par: 0,
+            time_bonus: 0,
+            life_bonus: 0,
+            extra_life: 10_000,
+        }
+    }
+
+    /// Turn on the clear bonus with its default values, keeping the other points as they are
+    pub fn add_arcade_plus(&mut self) {
+        self.clear_par = 120;
+        self.time_bonus = 20;
+        self.life_bonus = 1000;
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        ScoringRules::parse(&text).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    /// Keys left out keep their arcade values
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut rules = ScoringRules::new();
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let points = |text: &str| {
+                text.trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("Line {}: invalid points '{}'", line_number, text))
+            };
+
+            match key {
+                "pellet" => rules.pellet = points(value)?,
+                "energizer" => rules.energizer = points(value)?,
+                "ghost" => rules.ghost = points(value)?,
+                "ghost_multiplier" => rules.ghost_multiplier = points(value)?,
+                "clear_par" => rules.clear_par = points(value)?,
+                "time_bonus" => rules.time_bonus = points(value)?,
+                "life_bonus" => rules.life_bonus = points(value)?,
+                "extra_life" => rules.extra_life = points(value)?,
+                "fruit" => {
+                    rules.fruit = value.split(',').map(points).collect::<Result<_, _>>()?;
+                    if rules.fruit.is_empty() {
+                        return Err(format!("Line {}: fruit needs a value", line_number).into());
+                    }
+                }
+                _ => return Err(format!("Line {}: unknown key '{}'", line_number, key).into()),
+            }
+        }
+
+        Ok(rules)
+    }
+
+    /// Worth of the bonus fruit on a level; later levels repeat the last entry
+    pub fn fruit_points(&self, level: u16) -> u32 {
+        self.fruit[fruit_index(level).min(self.fruit.len() - 1)]
+    }
+
+    /// Bonus for a level cleared in `level_ms` of play with `spare_lives` left in reserve
+    pub fn level_bonus(&self, level_ms: u32, spare_lives: u32) -> LevelBonus {
+        let seconds_under_par = self.clear_par.saturating_sub(level_ms / 1000);
+        LevelBonus {
+            time: seconds_under_par.saturating_mul(self.time_bonus),
+            lives: spare_lives.saturating_mul(self.life_bonus),
+        }
+    }
+
+    /// Compact form of the values, for telling whether two runs scored alike
+    pub fn fingerprint(&self) -> String {
+        let fruit: Vec<String> = self.fruit.iter().map(|points| points.to_string()).collect();
+        format!(
+            "{}/{}/{}x{}/{}/{}s+{}+{}/{}up",
+            self.pellet,
+            self.energizer,
+            self.ghost,
+            self.ghost_multiplier,
+            fruit.join(","),
+            self.clear_par,
+            self.time_bonus,
+            self.life_bonus,
+            self.extra_life
+        )
+    }
+}
+
+/// Which fruit a level offers, counting from the cherry; past the key it keeps growing
+pub fn fruit_index(level: u16) -> usize {
+    // Cherry and strawberry get a level each, then every fruit stays for two
+    match level {
+        0..=2 => level.saturating_sub(1) as usize,
+        _ => (level as usize - 3) / 2 + 2,
+    }
+}
+
+/// How long the clear bonus takes to count into the score
+const TALLY_MS: u32 = 1500;
+/// How long the finished tally stays up before the next level
+const TALLY_HOLD_MS: u32 = 1000;
+
+/// A clear bonus being paid into the score a little at a time on the level-complete screen
+pub struct BonusTally {
+    bonus: LevelBonus,
+    paid: u32,
+    elapsed_ms: u32,
+}
+
+impl BonusTally {
+    pub fn new(bonus: LevelBonus) -> Self {
+        BonusTally {
+            bonus,
+            paid: 0,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// Move the tally on, returning the points to add to the score for this step
+    pub fn advance(&mut self, elapsed_ms: u32) -> u32 {
+        self.elapsed_ms += elapsed_ms;
+        let counted = self.elapsed_ms.min(TALLY_MS) as u64;
+        let due = (self.bonus.total() as u64 * counted / TALLY_MS as u64) as u32;
+        let points = due - self.paid;
+        self.paid = due;
+        points
+    }
+
+    pub fn get_bonus(&self) -> LevelBonus {
+        self.bonus
+    }
+
+    /// Points counted into the score so far
+    pub fn get_paid(&self) -> u32 {
+        self.paid
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_ms >= TALLY_MS + TALLY_HOLD_MS
+    }
+}
+
 #[derive(Debug)]
 pub struct LittleScore {
-    #[allow(dead_code)]
     pub position: Position,
-    #[allow(dead_code)]
-    pub value: u16,
+    pub value: u32,
     pub timer: GameTimer,
 }
 
 impl LittleScore {
-    pub fn new(position: Position, value: u16) -> Self {
+    pub fn new(position: Position, value: u32) -> Self {
         let mut timer = GameTimer::new();
         timer.start();
 
@@ -28,30 +223,57 @@ impl LittleScore {
 }
 
 pub struct ScoringSystem {
-    ghost_score_multiplier: u16,
+    rules: ScoringRules,
+    ghost_score_multiplier: u32,
     dead_ghosts_counter: u8,
     little_scores: Vec<LittleScore>,
     little_timer_target: u32,
 }
 
+impl Default for ScoringSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ScoringSystem {
     pub fn new() -> Self {
+        ScoringSystem::with_rules(ScoringRules::new())
+    }
+
+    pub fn with_rules(rules: ScoringRules) -> Self {
         ScoringSystem {
-            ghost_score_multiplier: 200, // First ghost worth 200
+            ghost_score_multiplier: rules.ghost,
+            rules,
             dead_ghosts_counter: 0,
             little_scores: Vec::new(),
             little_timer_target: 1000, // 1 second for floating score
         }
     }
 
+    pub fn get_rules(&self) -> &ScoringRules {
+        &self.rules
+    }
+
+    /// Points for a pellet or, if `energizer`, a power pellet
+    pub fn food_points(&self, energizer: bool) -> u32 {
+        if energizer {
+            self.rules.energizer
+        } else {
+            self.rules.pellet
+        }
+    }
+
     /// Add a ghost score at the given position
-    pub fn add_ghost_score(&mut self, position: Position) -> u16 {
+    pub fn add_ghost_score(&mut self, position: Position) -> u32 {
         let score_value = self.ghost_score_multiplier;
         let little_score = LittleScore::new(position, score_value);
         self.little_scores.push(little_score);
 
-        // Double the multiplier for next ghost
-        self.ghost_score_multiplier *= 2;
+        // Each further ghost is worth more, doubling with the arcade rules
+        self.ghost_score_multiplier = self
+            .ghost_score_multiplier
+            .saturating_mul(self.rules.ghost_multiplier);
         self.dead_ghosts_counter += 1;
 
         score_value
@@ -59,7 +281,7 @@ impl ScoringSystem {
 
     /// Reset scoring system for new energizer
     pub fn reset_for_energizer(&mut self) {
-        self.ghost_score_multiplier = 200;
+        self.ghost_score_multiplier = self.rules.ghost;
     }
 
     /// Reset when pacman is not energized
@@ -68,31 +290,30 @@ impl ScoringSystem {
     }
 
     /// Update little scores and remove expired ones
-    pub fn update_little_scores(&mut self) {
+    pub fn update_little_scores(&mut self, elapsed_ms: u32) {
+        for score in &mut self.little_scores {
+            score.timer.advance(elapsed_ms);
+        }
         self.little_scores
             .retain(|score| !score.is_expired(self.little_timer_target));
     }
 
     /// Get current ghost score multiplier
-    #[allow(dead_code)]
-    pub fn get_ghost_score_multiplier(&self) -> u16 {
+    pub fn get_ghost_score_multiplier(&self) -> u32 {
         self.ghost_score_multiplier
     }
 
     /// Get number of dead ghosts
-    #[allow(dead_code)]
     pub fn get_dead_ghosts_counter(&self) -> u8 {
         self.dead_ghosts_counter
     }
 
     /// Get reference to little scores for rendering
-    #[allow(dead_code)]
     pub fn get_little_scores(&self) -> &[LittleScore] {
         &self.little_scores
     }
 
     /// Get number of active little scores
-    #[allow(dead_code)]
     pub fn get_little_scores_count(&self) -> usize {
         self.little_scores.len()
     }
@@ -143,6 +364,86 @@ mod tests {
         assert_eq!(scoring_system.get_ghost_score_multiplier(), 200);
     }
 
+    #[test]
+    fn test_variant_rules() {
+        let rules = ScoringRules::parse(
+            "# pellets cheap, ghosts flat\npellet = 1\nghost = 1000\nghost_multiplier = 1\n",
+        )
+        .unwrap();
+        assert_eq!(rules.energizer, 50);
+        assert_eq!(rules.extra_life, 10_000);
+        assert_eq!(ScoringRules::parse("extra_life = 0").unwrap().extra_life, 0);
+
+        let mut scoring_system = ScoringSystem::with_rules(rules);
+        assert_eq!(scoring_system.food_points(false), 1);
+        assert_eq!(scoring_system.food_points(true), 50);
+        let position = Position::new(100, 100);
+        assert_eq!(scoring_system.add_ghost_score(position), 1000);
+        assert_eq!(scoring_system.add_ghost_score(position), 1000);
+    }
+
+    #[test]
+    fn test_rules_errors() {
+        assert!(ScoringRules::parse("pellet = ten").is_err());
+        assert!(ScoringRules::parse("bonus = 5").is_err());
+        assert!(ScoringRules::parse("pellet 10").is_err());
+        assert_eq!(ScoringRules::parse("").unwrap(), ScoringRules::new());
+    }
+
+    #[test]
+    fn test_clear_bonus() {
+        let mut rules = ScoringRules::new();
+        assert_eq!(rules.level_bonus(30_000, 2).total(), 0);
+
+        rules.add_arcade_plus();
+        let bonus = rules.level_bonus(100_500, 2);
+        assert_eq!(
+            bonus,
+            LevelBonus {
+                time: 400,
+                lives: 2000
+            }
+        );
+        assert_eq!(rules.level_bonus(500_000, 0).total(), 0);
+
+        // Values from a rules file can be as large as they like without overflowing
+        let mut rules =
+            ScoringRules::parse("time_bonus = 2000000000\nlife_bonus = 2000000000").unwrap();
+        rules.clear_par = 4_000_000;
+        let bonus = rules.level_bonus(0, 5);
+        assert_eq!(bonus.time, u32::MAX);
+        assert_eq!(bonus.lives, u32::MAX);
+        assert_eq!(bonus.total(), u32::MAX);
+        let mut tally = BonusTally::new(bonus);
+        assert_eq!(tally.advance(TALLY_MS), u32::MAX);
+    }
+
+    #[test]
+    fn test_bonus_tally_pays_everything_once() {
+        let mut tally = BonusTally::new(LevelBonus {
+            time: 400,
+            lives: 1000,
+        });
+        let mut paid = 0;
+        while !tally.is_finished() {
+            paid += tally.advance(16);
+            assert!(tally.get_paid() <= 1400);
+        }
+        assert_eq!(paid, 1400);
+        assert_eq!(tally.get_paid(), 1400);
+    }
+
+    #[test]
+    fn test_fruit_points() {
+        let rules = ScoringRules::new();
+        assert_eq!(rules.fruit_points(1), 100);
+        assert_eq!(rules.fruit_points(2), 300);
+        assert_eq!(rules.fruit_points(4), 500);
+        assert_eq!(rules.fruit_points(5), 700);
+        assert_eq!(rules.fruit_points(13), 5000);
+        assert_eq!(rules.fruit_points(40), 5000);
+    }
+
     #[test]
     fn test_little_score_creation() {
         let position = Position::new(50, 75);
@@ -155,5 +456,16 @@ mod tests {
         // Timer should be started
         assert!(little_score.timer.get_ticks() > 0 || little_score.timer.get_ticks() == 0);
     }
-}
 
+    #[test]
+    fn test_little_scores_expire() {
+        let mut scoring_system = ScoringSystem::new();
+        scoring_system.add_ghost_score(Position::new(100, 100));
+
+        scoring_system.update_little_scores(999);
+        assert_eq!(scoring_system.get_little_scores_count(), 1);
+
+        scoring_system.update_little_scores(1);
+        assert_eq!(scoring_system.get_little_scores_count(), 0);
+    }
+}
@@ -0,0 +1,132 @@
+use super::rng::CosmeticNoise;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, WindowCanvas};
+
+/// How long the screen shakes after Pac-Man is caught, and how far it moves at first
+const SHAKE_MS: u32 = 400;
+const SHAKE_PIXELS: f32 = 6.0;
+/// How long the white flash after an energizer takes to fade, and how bright it starts
+const FLASH_MS: u32 = 150;
+const FLASH_ALPHA: f32 = 160.0;
+
+/// Screen shake and flash, drawn by offsetting and covering the frame rather than by moving
+/// anything in the game.
+///
+/// Like particles they use their own noise, never the game's generator, so turning them off
+/// cannot change how a seeded game plays out.
+pub struct ScreenEffects {
+    enabled: bool,
+    shake_ms: u32,
+    flash_ms: u32,
+    noise: CosmeticNoise,
+}
+
+impl ScreenEffects {
+    pub fn new(enabled: bool) -> Self {
+        ScreenEffects {
+            enabled,
+            shake_ms: 0,
+            flash_ms: 0,
+            noise: CosmeticNoise::new(0x2545_f491),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning effects off also stops the ones under way
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.shake_ms = 0;
+            self.flash_ms = 0;
+        }
+    }
+
+    pub fn shake(&mut self) {
+        if self.enabled {
+            self.shake_ms = SHAKE_MS;
+        }
+    }
+
+    pub fn flash(&mut self) {
+        if self.enabled {
+            self.flash_ms = FLASH_MS;
+        }
+    }
+
+    pub fn update(&mut self, elapsed_ms: u32) {
+        self.shake_ms = self.shake_ms.saturating_sub(elapsed_ms);
+        self.flash_ms = self.flash_ms.saturating_sub(elapsed_ms);
+    }
+
+    /// How far to move this frame, in board pixels; the shake dies down as it runs out
+    pub fn offset(&mut self) -> (i32, i32) {
+        if self.shake_ms == 0 {
+            return (0, 0);
+        }
+        let reach = SHAKE_PIXELS * self.shake_ms as f32 / SHAKE_MS as f32;
+        let x = (self.noise.unit() * 2.0 - 1.0) * reach;
+        let y = (self.noise.unit() * 2.0 - 1.0) * reach;
+        (x.round() as i32, y.round() as i32)
+    }
+
+    fn flash_alpha(&self) -> u8 {
+        (FLASH_ALPHA * self.flash_ms as f32 / FLASH_MS as f32) as u8
+    }
+
+    /// Cover `area` with what is left of the flash
+    pub fn draw_flash(
+        &self,
+        canvas: &mut WindowCanvas,
+        area: Rect,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let alpha = self.flash_alpha();
+        if alpha == 0 {
+            return Ok(());
+        }
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(255, 255, 255, alpha));
+        canvas.fill_rect(area)?;
+        canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shake_dies_down() {
+        let mut effects = ScreenEffects::new(true);
+        assert_eq!(effects.offset(), (0, 0));
+
+        effects.shake();
+        for _ in 0..10 {
+            let (x, y) = effects.offset();
+            assert!(x.abs() <= SHAKE_PIXELS as i32 && y.abs() <= SHAKE_PIXELS as i32);
+        }
+        effects.update(SHAKE_MS);
+        assert_eq!(effects.offset(), (0, 0));
+    }
+
+    #[test]
+    fn test_flash_fades_and_effects_toggle() {
+        let mut effects = ScreenEffects::new(true);
+        effects.flash();
+        let bright = effects.flash_alpha();
+        effects.update(FLASH_MS / 2);
+        assert!(effects.flash_alpha() < bright);
+        effects.update(FLASH_MS);
+        assert_eq!(effects.flash_alpha(), 0);
+
+        effects.set_enabled(false);
+        effects.shake();
+        effects.flash();
+        assert_eq!(effects.offset(), (0, 0));
+        assert_eq!(effects.flash_alpha(), 0);
+    }
+}
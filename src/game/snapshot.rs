@@ -0,0 +1,483 @@
+use super::ghost_mode::GhostMode;
+use super::timers::TimerState;
+use crate::board::{BlockType, Direction};
+use crate::error::PacmanError;
+use crate::position::Position;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+
+/// Written when the window closes mid-game and read back by `--resume`
+pub const SAVE_GAME_PATH: &str = "savegame.txt";
+/// Slot used by the quick-save and quick-load debug keys
+pub const QUICK_SAVE_PATH: &str = "quicksave.txt";
+
+const GHOST_NAMES: [&str; 4] = ["blinky", "inky", "pinky", "clyde"];
+const RESUME_KEYS: [&str; 8] = [
+    "state",
+    "seed",
+    "rng_position",
+    "tick",
+    "direction",
+    "ghost_states",
+    "power",
+    "timers",
+];
+
+/// A saved game state, enough to redraw the frame it was taken from.
+///
+/// Stored as `key = value` lines followed by `map:` and one line per board row,
+/// using the same characters as `Board::CHAR_BOARD`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub level: u16,
+    pub score: u32,
+    pub lives: i8,
+    pub pacman: Position,
+    /// Blinky, Inky, Pinky, Clyde, then any extra ghosts
+    pub ghosts: Vec<Position>,
+    pub map: Vec<BlockType>,
+    /// Present in saved games; plain snapshots only describe the frame
+    pub resume: Option<ResumeState>,
+}
+
+/// Everything besides the frame needed to carry on playing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumeState {
+    /// Saved during the ready countdown rather than mid-play
+    pub ready: bool,
+    pub seed: u64,
+    /// Where the random draws had got to in the seed's stream
+    pub rng_position: u128,
+    pub tick: u32,
+    pub pacman_direction: Direction,
+    /// Blinky, Inky, Pinky, Clyde, then any extra ghosts
+    pub ghosts: Vec<GhostState>,
+    pub power_ms: u32,
+    pub timers: TimerState,
+}
+
+/// The parts of a ghost's mode that are not recomputed every update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GhostState {
+    pub direction: Direction,
+    pub mode: GhostMode,
+}
+
+impl Snapshot {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str(&format!("level = {}\n", self.level));
+        text.push_str(&format!("score = {}\n", self.score));
+        text.push_str(&format!("lives = {}\n", self.lives));
+        text.push_str(&format!("pacman = {}\n", format_position(self.pacman)));
+        for (index, position) in self.ghosts.iter().enumerate() {
+            text.push_str(&format!(
+                "{} = {}\n",
+                ghost_key(index),
+                format_position(*position)
+            ));
+        }
+        if let Some(ref resume) = self.resume {
+            text.push_str(&resume.to_text());
+        }
+
+        text.push_str("map:\n");
+        for row in self.map.chunks(BOARD_WIDTH) {
+            text.extend(row.iter().map(|block| block.to_char()));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (header, map_text) = text
+            .split_once("map:\n")
+            .ok_or("Snapshot is missing its map section")?;
+
+        let mut level = None;
+        let mut score = None;
+        let mut lives = None;
+        let mut pacman = None;
+        let mut ghosts: Vec<Option<Position>> = vec![None; GHOST_NAMES.len()];
+        let mut resume_fields: Vec<(&str, &str, usize)> = Vec::new();
+
+        for (index, raw_line) in header.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Snapshot line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let bad_value =
+                || format!("Snapshot line {}: invalid {} '{}'", line_number, key, value);
+
+            match key {
+                "level" => level = Some(value.parse().map_err(|_| bad_value())?),
+                "score" => score = Some(value.parse().map_err(|_| bad_value())?),
+                "lives" => lives = Some(value.parse().map_err(|_| bad_value())?),
+                "pacman" => pacman = Some(parse_position(value).ok_or_else(bad_value)?),
+                _ if RESUME_KEYS.contains(&key) => resume_fields.push((key, value, line_number)),
+                _ => match ghost_index(key) {
+                    Some(ghost) => {
+                        if ghosts.len() <= ghost {
+                            ghosts.resize(ghost + 1, None);
+                        }
+                        ghosts[ghost] = Some(parse_position(value).ok_or_else(bad_value)?)
+                    }
+                    None => {
+                        return Err(
+                            format!("Snapshot line {}: unknown key '{}'", line_number, key).into(),
+                        )
+                    }
+                },
+            }
+        }
+
+        let rows: Vec<&str> = map_text.lines().collect();
+        if rows.len() != BOARD_HEIGHT || rows.iter().any(|row| row.chars().count() != BOARD_WIDTH) {
+            return Err(format!("Snapshot map must be {}x{}", BOARD_WIDTH, BOARD_HEIGHT).into());
+        }
+        let map = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(BlockType::from_char)
+            .collect();
+
+        let missing = |key: &str| format!("Snapshot is missing '{}'", key);
+        let ghost_positions = ghosts
+            .iter()
+            .enumerate()
+            .map(|(index, position)| position.ok_or_else(|| missing(&ghost_key(index))))
+            .collect::<Result<Vec<Position>, String>>()?;
+
+        Ok(Snapshot {
+            level: level.ok_or_else(|| missing("level"))?,
+            score: score.ok_or_else(|| missing("score"))?,
+            lives: lives.ok_or_else(|| missing("lives"))?,
+            pacman: pacman.ok_or_else(|| missing("pacman"))?,
+            ghosts: ghost_positions,
+            map,
+            resume: match resume_fields.is_empty() {
+                true => None,
+                false => Some(ResumeState::parse(&resume_fields)?),
+            },
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        Snapshot::parse(&text).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), PacmanError> {
+        std::fs::write(path, self.to_text()).map_err(|source| PacmanError::SaveIo {
+            path: path.to_string(),
+            source,
+        })
+    }
+}
+
+impl ResumeState {
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        let state = if self.ready { "ready" } else { "playing" };
+        text.push_str(&format!("state = {}\n", state));
+        text.push_str(&format!("seed = {}\n", self.seed));
+        text.push_str(&format!("rng_position = {}\n", self.rng_position));
+        text.push_str(&format!("tick = {}\n", self.tick));
+        text.push_str(&format!(
+            "direction = {}\n",
+            self.pacman_direction.to_char()
+        ));
+        let ghosts: Vec<String> = self
+            .ghosts
+            .iter()
+            .map(|ghost| format!("{} {}", ghost.direction.to_char(), ghost.mode.name()))
+            .collect();
+        text.push_str(&format!("ghost_states = {}\n", ghosts.join(", ")));
+        text.push_str(&format!("power = {}\n", self.power_ms));
+        let timers = &self.timers;
+        text.push_str(&format!(
+            "timers = {},{},{},wave {}\n",
+            timers.game_ms, timers.start_ms, timers.ghost_ms, timers.wave
+        ));
+        text
+    }
+
+    /// Build from `(key, value, line number)` triples; every resume key must be present,
+    /// apart from `rng_position`, which older saves did not keep
+    fn parse(fields: &[(&str, &str, usize)]) -> Result<Self, Box<dyn std::error::Error>> {
+        let ready = match find_field(fields, "state")? {
+            ("ready", _) => true,
+            ("playing", _) => false,
+            entry => return Err(invalid_field("state", entry).into()),
+        };
+
+        let entry = find_field(fields, "direction")?;
+        let pacman_direction =
+            parse_direction(entry.0).ok_or_else(|| invalid_field("direction", entry))?;
+
+        let entry = find_field(fields, "ghost_states")?;
+        let ghosts: Vec<GhostState> = entry
+            .0
+            .split(',')
+            .map(parse_ghost_state)
+            .collect::<Option<_>>()
+            .filter(|ghosts: &Vec<GhostState>| ghosts.len() >= GHOST_NAMES.len())
+            .ok_or_else(|| invalid_field("ghost_states", entry))?;
+
+        let entry = find_field(fields, "timers")?;
+        let timers = parse_timers(entry.0).ok_or_else(|| invalid_field("timers", entry))?;
+
+        Ok(ResumeState {
+            ready,
+            seed: parse_field(fields, "seed")?,
+            rng_position: match find_field(fields, "rng_position") {
+                Ok(_) => parse_field(fields, "rng_position")?,
+                Err(_) => 0,
+            },
+            tick: parse_field(fields, "tick")?,
+            pacman_direction,
+            ghosts,
+            power_ms: parse_field(fields, "power")?,
+            timers,
+        })
+    }
+}
+
+fn find_field<'t>(
+    fields: &[(&str, &'t str, usize)],
+    key: &str,
+) -> Result<(&'t str, usize), String> {
+    fields
+        .iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|&(_, value, line_number)| (value, line_number))
+        .ok_or_else(|| format!("Saved game is missing '{}'", key))
+}
+
+fn invalid_field(key: &str, (value, line_number): (&str, usize)) -> String {
+    format!("Snapshot line {}: invalid {} '{}'", line_number, key, value)
+}
+
+fn parse_field<T: std::str::FromStr>(
+    fields: &[(&str, &str, usize)],
+    key: &str,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let entry = find_field(fields, key)?;
+    entry
+        .0
+        .parse()
+        .map_err(|_| invalid_field(key, entry).into())
+}
+
+fn parse_ghost_state(value: &str) -> Option<GhostState> {
+    let (direction, mode) = value.trim().split_once(' ')?;
+    Some(GhostState {
+        direction: parse_direction(direction)?,
+        // Older saves only said whether the ghost was alive; it finds its way from there
+        mode: match mode {
+            "alive" => GhostMode::LeavingHouse,
+            _ => GhostMode::from_name(mode)?,
+        },
+    })
+}
+
+fn parse_direction(value: &str) -> Option<Direction> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Direction::from_char(c),
+        _ => None,
+    }
+}
+
+fn parse_timers(value: &str) -> Option<TimerState> {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+    let wave = match fields[..] {
+        [_, _, _, wave] => wave.strip_prefix("wave ")?.parse().ok()?,
+        // Older saves alternated scatter and chase; carry on in a wave of the same kind
+        [_, _, _, _, "scatter"] => 0,
+        [_, _, _, _, "chase"] => 1,
+        _ => return None,
+    };
+    Some(TimerState {
+        game_ms: fields[0].parse().ok()?,
+        start_ms: fields[1].parse().ok()?,
+        ghost_ms: fields[2].parse().ok()?,
+        wave,
+    })
+}
+
+/// Key a ghost's position is saved under: its name, or `ghost5` onwards for extra ghosts
+fn ghost_key(index: usize) -> String {
+    match GHOST_NAMES.get(index) {
+        Some(name) => name.to_string(),
+        None => format!("ghost{}", index + 1),
+    }
+}
+
+fn ghost_index(key: &str) -> Option<usize> {
+    if let Some(index) = GHOST_NAMES.iter().position(|name| *name == key) {
+        return Some(index);
+    }
+    let number: usize = key.strip_prefix("ghost")?.parse().ok()?;
+    (number > GHOST_NAMES.len()).then_some(number - 1)
+}
+
+fn format_position(position: Position) -> String {
+    format!("{},{}", position.get_x(), position.get_y())
+}
+
+fn parse_position(value: &str) -> Option<Position> {
+    let (x, y) = value.split_once(',')?;
+    Some(Position::new(
+        x.trim().parse().ok()?,
+        y.trim().parse().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    fn sample() -> Snapshot {
+        Snapshot {
+            level: 4,
+            score: 12340,
+            lives: 2,
+            pacman: Position::new(312, 624),
+            ghosts: vec![
+                Position::new(312, 336),
+                Position::new(264, 408),
+                Position::new(312, 408),
+                Position::new(-12, 408),
+            ],
+            map: Board::CHAR_BOARD
+                .chars()
+                .map(BlockType::from_char)
+                .collect(),
+            resume: None,
+        }
+    }
+
+    fn saved_game() -> Snapshot {
+        let frightened = GhostState {
+            direction: Direction::Left,
+            mode: GhostMode::Frightened,
+        };
+        Snapshot {
+            resume: Some(ResumeState {
+                ready: false,
+                seed: 99,
+                rng_position: 1 << 70,
+                tick: 4321,
+                pacman_direction: Direction::Up,
+                ghosts: vec![
+                    frightened,
+                    GhostState {
+                        direction: Direction::Down,
+                        mode: GhostMode::Eaten,
+                    },
+                    frightened,
+                    frightened,
+                ],
+                power_ms: 2500,
+                timers: TimerState {
+                    game_ms: 70000,
+                    start_ms: 2500,
+                    ghost_ms: 3000,
+                    wave: 2,
+                },
+            }),
+            ..sample()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let snapshot = sample();
+        assert_eq!(Snapshot::parse(&snapshot.to_text()).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_extra_ghosts_round_trip() {
+        let mut snapshot = sample();
+        snapshot.ghosts.push(Position::new(336, 408));
+        let text = snapshot.to_text();
+        assert!(text.contains("ghost5 = 336,408\n"));
+        assert_eq!(Snapshot::parse(&text).unwrap(), snapshot);
+
+        // The usual four are still required, and extras cannot skip a number
+        assert!(Snapshot::parse(&text.replace("clyde", "ghost4")).is_err());
+        assert!(Snapshot::parse(&text.replace("ghost5", "ghost6")).is_err());
+    }
+
+    #[test]
+    fn test_saved_game_round_trip() {
+        let saved = saved_game();
+        let text = saved.to_text();
+        assert!(text.contains("ghost_states = L frightened, D eaten, L frightened, L frightened\n"));
+        assert_eq!(Snapshot::parse(&text).unwrap(), saved);
+
+        // Saves from before ghosts kept a mode just said they were alive
+        let old = text
+            .replace("L frightened", "L alive")
+            .replace("wave 2", "7000,chase");
+        let old = Snapshot::parse(&old).unwrap().resume.unwrap();
+        assert_eq!(old.ghosts[0].mode, GhostMode::LeavingHouse);
+        assert_eq!(old.timers.wave, 1);
+
+        // Saves from before the random draws were tracked start the seed's stream over
+        let old = text.replace("rng_position = 1180591620717411303424\n", "");
+        assert_eq!(
+            Snapshot::parse(&old).unwrap().resume.unwrap().rng_position,
+            0
+        );
+    }
+
+    #[test]
+    fn test_saved_game_needs_every_resume_field() {
+        let text = saved_game().to_text().replace("power = 2500\n", "");
+        let error = Snapshot::parse(&text).unwrap_err();
+        assert!(error.to_string().contains("power"));
+
+        let text = saved_game().to_text().replace("wave 2\n", "sideways\n");
+        assert!(Snapshot::parse(&text).is_err());
+
+        let text = saved_game()
+            .to_text()
+            .replace("L frightened, D eaten", "L frightened");
+        assert!(Snapshot::parse(&text).is_err());
+    }
+
+    #[test]
+    fn test_missing_fields() {
+        let text = sample().to_text().replace("lives = 2\n", "");
+        let error = Snapshot::parse(&text).unwrap_err();
+        assert!(error.to_string().contains("lives"));
+
+        assert!(Snapshot::parse("level = 1\n").is_err());
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let text = sample()
+            .to_text()
+            .replace("pacman = 312,624", "pacman = 312");
+        assert!(Snapshot::parse(&text).is_err());
+
+        let text = sample().to_text().replace("level = 4", "speed = 4");
+        assert!(Snapshot::parse(&text).is_err());
+    }
+
+    #[test]
+    fn test_map_must_match_board() {
+        let mut text = sample().to_text();
+        text.push_str("#\n");
+        assert!(Snapshot::parse(&text).is_err());
+    }
+}
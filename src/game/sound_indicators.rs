@@ -0,0 +1,147 @@
+/// How long an indicator stays up after the sound it stands for
+const SHOW_MS: u32 = 2000;
+/// The indicator blinks on and off at this period, so it catches the eye like a sound would
+const BLINK_MS: u32 = 250;
+/// Quarters of the maze eaten between steps of the siren, as it speeds up in the arcade
+const SIREN_STEPS: usize = 4;
+
+/// A sound worth knowing about, shown for players who cannot hear it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indicator {
+    /// The siren stepped up to this tier, counting from 1
+    Siren(u8),
+    PowerEnding,
+    FruitSpawned,
+    ExtraLife,
+}
+
+impl Indicator {
+    pub fn label(&self) -> String {
+        match self {
+            Indicator::Siren(tier) => format!("SIREN {}", tier),
+            Indicator::PowerEnding => "POWER ENDING".to_string(),
+            Indicator::FruitSpawned => "FRUIT".to_string(),
+            Indicator::ExtraLife => "1UP".to_string(),
+        }
+    }
+}
+
+/// The siren's tier for a maze with `eaten` of its `total` dots gone, 0 at the start
+pub fn siren_tier(eaten: usize, total: usize) -> u8 {
+    if total == 0 {
+        return 0;
+    }
+    (eaten * SIREN_STEPS / total).min(SIREN_STEPS - 1) as u8
+}
+
+/// The one indicator up at a time, newest first, for the accessibility option.
+///
+/// Siren and energizer changes are watched for here, since they are states rather than
+/// events; fruit and extra lives are handed in as they happen.
+pub struct SoundIndicators {
+    enabled: bool,
+    current: Option<Indicator>,
+    shown_ms: u32,
+    siren_tier: u8,
+    power_ending: bool,
+}
+
+impl SoundIndicators {
+    pub fn new(enabled: bool) -> Self {
+        SoundIndicators {
+            enabled,
+            current: None,
+            shown_ms: 0,
+            siren_tier: 0,
+            power_ending: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.current = None;
+    }
+
+    pub fn show(&mut self, indicator: Indicator) {
+        if self.enabled {
+            self.current = Some(indicator);
+            self.shown_ms = 0;
+        }
+    }
+
+    /// Show the siren stepping up; it drops back silently when a new maze starts
+    pub fn siren(&mut self, tier: u8) {
+        if tier > self.siren_tier {
+            self.show(Indicator::Siren(tier + 1));
+        }
+        self.siren_tier = tier;
+    }
+
+    /// Show the energizer's warning once, as it starts
+    pub fn power(&mut self, ending: bool) {
+        if ending && !self.power_ending {
+            self.show(Indicator::PowerEnding);
+        }
+        self.power_ending = ending;
+    }
+
+    pub fn update(&mut self, elapsed_ms: u32) {
+        self.shown_ms += elapsed_ms;
+        if self.shown_ms >= SHOW_MS {
+            self.current = None;
+        }
+    }
+
+    /// The indicator to draw this frame, if any, blinking while it is up
+    pub fn visible(&self) -> Option<Indicator> {
+        self.current
+            .filter(|_| (self.shown_ms / BLINK_MS).is_multiple_of(2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_siren_steps_up_as_the_maze_empties() {
+        assert_eq!(siren_tier(0, 240), 0);
+        assert_eq!(siren_tier(59, 240), 0);
+        assert_eq!(siren_tier(60, 240), 1);
+        assert_eq!(siren_tier(240, 240), 3);
+        assert_eq!(siren_tier(0, 0), 0);
+
+        let mut indicators = SoundIndicators::new(true);
+        indicators.siren(0);
+        assert_eq!(indicators.visible(), None);
+        indicators.siren(1);
+        assert_eq!(indicators.visible(), Some(Indicator::Siren(2)));
+        indicators.update(SHOW_MS);
+        indicators.siren(0);
+        assert_eq!(indicators.visible(), None);
+    }
+
+    #[test]
+    fn test_indicators_blink_then_clear() {
+        let mut indicators = SoundIndicators::new(true);
+        indicators.power(true);
+        assert_eq!(indicators.visible(), Some(Indicator::PowerEnding));
+        indicators.update(BLINK_MS);
+        assert_eq!(indicators.visible(), None);
+        indicators.update(BLINK_MS);
+        assert_eq!(indicators.visible(), Some(Indicator::PowerEnding));
+
+        // Still ending is not a new warning
+        indicators.update(SHOW_MS);
+        indicators.power(true);
+        assert_eq!(indicators.visible(), None);
+
+        let mut off = SoundIndicators::new(false);
+        off.show(Indicator::ExtraLife);
+        assert_eq!(off.visible(), None);
+    }
+}
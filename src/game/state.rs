@@ -1,5 +1,3 @@
-use std::time::Instant;
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameState {
     Ready,
@@ -8,80 +6,100 @@ pub enum GameState {
     GameOver,
     LevelComplete,
     Paused,
+    Milestone,
+    /// Coin mode's opening screen, until a credit is spent on a game
+    InsertCoin,
+    /// The title menu, where the next run is set up
+    Menu,
 }
 
+/// Stopwatch over simulated time; it only moves when `advance` is called
 #[derive(Debug, Clone)]
 pub struct GameTimer {
-    start_time: Option<Instant>,
+    is_started: bool,
     is_paused: bool,
-    pause_time: Option<Instant>,
-    accumulated_time: u128,
+    elapsed: u128,
+}
+
+impl Default for GameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameTimer {
     pub fn new() -> Self {
         GameTimer {
-            start_time: None,
+            is_started: false,
             is_paused: false,
-            pause_time: None,
-            accumulated_time: 0,
+            elapsed: 0,
         }
     }
 
     pub fn start(&mut self) {
-        self.start_time = Some(Instant::now());
+        self.is_started = true;
         self.is_paused = false;
-        self.pause_time = None;
     }
 
     pub fn restart(&mut self) {
-        self.start_time = Some(Instant::now());
-        self.accumulated_time = 0;
+        self.is_started = true;
         self.is_paused = false;
-        self.pause_time = None;
+        self.elapsed = 0;
     }
 
-    #[allow(dead_code)]
     pub fn reset(&mut self) {
-        self.start_time = None;
+        self.is_started = false;
         self.is_paused = false;
-        self.pause_time = None;
-        self.accumulated_time = 0;
+        self.elapsed = 0;
     }
 
     pub fn pause(&mut self) {
-        if !self.is_paused && self.start_time.is_some() {
-            self.pause_time = Some(Instant::now());
+        if self.is_started {
             self.is_paused = true;
         }
     }
 
     pub fn unpause(&mut self) {
-        if self.is_paused {
-            if let Some(pause_time) = self.pause_time {
-                self.accumulated_time += pause_time.elapsed().as_millis();
-            }
-            self.is_paused = false;
-            self.pause_time = None;
-            self.start_time = Some(Instant::now());
+        self.is_paused = false;
+    }
+
+    /// Add `ms` of simulated time if the timer is running
+    pub fn advance(&mut self, ms: u32) {
+        if self.is_started && !self.is_paused {
+            self.elapsed += ms as u128;
         }
     }
 
     pub fn get_ticks(&self) -> u128 {
-        if let Some(start) = self.start_time {
-            if self.is_paused {
-                if let Some(pause_time) = self.pause_time {
-                    return self.accumulated_time + start.elapsed().as_millis()
-                        - pause_time.elapsed().as_millis();
-                }
-            }
-            return self.accumulated_time + start.elapsed().as_millis();
-        }
-        0
+        self.elapsed
     }
 
-    #[allow(dead_code)]
     pub fn is_started(&self) -> bool {
-        self.start_time.is_some()
+        self.is_started
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_only_moves_when_running() {
+        let mut timer = GameTimer::new();
+        timer.advance(100);
+        assert_eq!(timer.get_ticks(), 0);
+
+        timer.start();
+        timer.advance(100);
+        timer.pause();
+        timer.advance(50);
+        assert_eq!(timer.get_ticks(), 100);
+
+        timer.unpause();
+        timer.advance(16);
+        assert_eq!(timer.get_ticks(), 116);
+
+        timer.restart();
+        assert_eq!(timer.get_ticks(), 0);
     }
 }
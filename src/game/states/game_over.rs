@@ -0,0 +1,71 @@
+use super::StateHooks;
+use crate::config::Config;
+use crate::font::GameFont;
+use crate::game::rewind::{RewindBuffer, REWIND_CAPACITY};
+use crate::game::state::GameState;
+use crate::game::Game;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// The end of a run, with GAME OVER below the ghost house and a prompt to play again
+pub(super) struct GameOver;
+
+impl StateHooks for GameOver {
+    fn on_enter(&self, game: &mut Game, _previous: &GameState) {
+        println!("Game Over!");
+        if let Some(ref survival) = game.survival {
+            println!("Survived {}", survival.clock());
+            game.show_toast(&format!("SURVIVED {}", survival.clock()));
+        }
+        game.finish_recording();
+    }
+
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        _texture_creator: &'a TextureCreator<WindowContext>,
+        _font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        Game::draw_banner(canvas, &game.board, &game.game_over_texture)?;
+        if !game.networked {
+            game.draw_start_prompt(canvas)?;
+        }
+        Ok(false)
+    }
+
+    /// Back to the menu, or without one a new game. In coin mode a credit continues the
+    /// level that was lost instead; nothing happens without one.
+    fn press_start(&self, game: &mut Game, config: &Config) {
+        match game.credits {
+            None if game.menu.is_some() => game.open_menu(),
+            None => game.reset(config),
+            Some(credits) if credits > 0 => {
+                game.credits = Some(credits - 1);
+                game.continue_game();
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+impl Game<'_> {
+    /// Carry on from the level that was lost with a full set of lives and the score back at zero.
+    ///
+    /// Eaten dots stay eaten. The continued run is not recorded, since the replay of the game
+    /// that just ended has already been stored.
+    fn continue_game(&mut self) {
+        self.board.restore(0, self.difficulty.starting_lives(), 0);
+        // Kids mode may have been switched on or off at the game over
+        self.update_difficulty();
+        let pacman_start = self.board.reset_position(crate::board::EntityType::PacMan);
+        self.pacman.set_position(pacman_start);
+        self.ghosts_manager.reset_all_ghost_positions(&self.board);
+        self.reset_for_next_life();
+        if self.rewind.is_some() {
+            self.rewind = Some(RewindBuffer::new(REWIND_CAPACITY));
+        }
+        println!("Continuing level {}", self.level);
+        self.set_state(GameState::Ready);
+    }
+}
@@ -0,0 +1,30 @@
+use super::StateHooks;
+use crate::config::Config;
+use crate::font::GameFont;
+use crate::game::Game;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// Coin mode waiting for a credit before the first game
+pub(super) struct InsertCoin;
+
+impl StateHooks for InsertCoin {
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        _texture_creator: &'a TextureCreator<WindowContext>,
+        _font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        game.draw_start_prompt(canvas)?;
+        Ok(false)
+    }
+
+    /// Spend a credit on a new game, if there is one
+    fn press_start(&self, game: &mut Game, config: &Config) {
+        if let Some(credits @ 1..) = game.credits {
+            game.credits = Some(credits - 1);
+            game.reset(config);
+        }
+    }
+}
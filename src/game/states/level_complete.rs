@@ -0,0 +1,133 @@
+use super::StateHooks;
+use crate::font::GameFont;
+use crate::game::core::LEVEL_FLASH_MS;
+use crate::game::events::GameEvent;
+use crate::game::levels::LevelParams;
+use crate::game::scoring::BonusTally;
+use crate::game::state::GameState;
+use crate::game::stats::Achievement;
+use crate::game::Game;
+use crate::{BLACK, TICK_MS, WHITE};
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// A cleared maze: the clear bonus is tallied and the maze flashes before the next level
+pub(super) struct LevelComplete;
+
+impl StateHooks for LevelComplete {
+    fn on_enter(&self, game: &mut Game, _previous: &GameState) {
+        println!("Level {} completed!", game.level);
+        game.events
+            .emit(GameEvent::LevelCompleted { level: game.level });
+        game.start_bonus_tally();
+    }
+
+    fn on_exit(&self, game: &mut Game) {
+        game.bonus_tally = None;
+        game.level_flash_ms = 0;
+    }
+
+    /// Tally the clear bonus, flash the maze, then set up the next level
+    fn update(&self, game: &mut Game) {
+        if game.tally_bonus() {
+            return;
+        }
+        if game.level_flash_ms < LEVEL_FLASH_MS {
+            game.level_flash_ms += TICK_MS;
+            return;
+        }
+        game.level += 1;
+        game.update_difficulty();
+        game.fruit.remove();
+        game.fruits_shown = 0;
+
+        let pacman_start = game.board.reset_position(crate::board::EntityType::PacMan);
+        game.pacman.set_position(pacman_start);
+        game.ghosts_manager.reset_all_ghost_positions(&game.board);
+
+        if LevelParams::is_max_difficulty(game.level)
+            && game.session_stats.unlock(Achievement::MaxDifficulty)
+        {
+            game.set_state(GameState::Milestone);
+        } else {
+            game.set_state(GameState::Ready);
+        }
+    }
+
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        game.draw_bonus_tally(canvas, texture_creator, font)?;
+        Ok(true)
+    }
+}
+
+impl<'a> Game<'a> {
+    /// Work out the clear bonus for the level just finished, if the rules give one
+    fn start_bonus_tally(&mut self) {
+        let spare_lives = self.board.get_lives().max(0) as u32;
+        let bonus = self
+            .scoring_system
+            .get_rules()
+            .level_bonus(self.level_ms, spare_lives);
+        self.level_ms = 0;
+        if bonus.total() > 0 {
+            println!(
+                "Clear bonus: {} for time, {} for lives",
+                bonus.time, bonus.lives
+            );
+            self.bonus_tally = Some(BonusTally::new(bonus));
+        }
+    }
+
+    /// Count the clear bonus into the score, returning true until it is done
+    fn tally_bonus(&mut self) -> bool {
+        let tally = match self.bonus_tally {
+            Some(ref mut tally) => tally,
+            None => return false,
+        };
+        self.board.score_increase(tally.advance(TICK_MS));
+        if tally.is_finished() {
+            self.bonus_tally = None;
+            return false;
+        }
+        true
+    }
+
+    /// The clear bonus counting up over the finished maze
+    fn draw_bonus_tally(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tally = match self.bonus_tally {
+            Some(ref tally) => tally,
+            None => return Ok(()),
+        };
+        let bonus = tally.get_bonus();
+        let locale = self.board.get_locale();
+        let texts = [
+            format!("TIME  {}", locale.format_number(bonus.time)),
+            format!("LIVES {}", locale.format_number(bonus.lives)),
+            format!("BONUS {}", locale.format_number(tally.get_paid())),
+        ];
+        for (texture, text) in self.bonus_textures.iter_mut().zip(&texts) {
+            texture.load_from_rendered_text(texture_creator, text, font, WHITE)?;
+        }
+
+        let hud = self.board.hud();
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(hud.panel(16, 6, 4))?;
+
+        for (i, texture) in self.bonus_textures.iter().enumerate() {
+            let (x, y) = hud.panel_line(16, i, texture.get_width());
+            texture.render(canvas, x, y, None)?;
+        }
+        Ok(())
+    }
+}
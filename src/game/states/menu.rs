@@ -0,0 +1,289 @@
+use super::StateHooks;
+use crate::config::Config;
+use crate::font::GameFont;
+use crate::game::levels::Difficulty;
+use crate::game::menu::{self, MenuItem};
+use crate::game::rewind::{RewindBuffer, REWIND_CAPACITY};
+use crate::game::scoring::ScoringSystem;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::texture::GameTexture;
+use crate::{BLACK, BLOCK_SIZE_24, WHITE, YELLOW};
+use sdl2::keyboard::Keycode;
+use sdl2::rect::Rect;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// Board row the title menu's panel starts on
+const MENU_TOP_ROW: i32 = 11;
+
+/// The title menu, where the next run is set up
+pub(super) struct Menu;
+
+impl StateHooks for Menu {
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        game.draw_menu(canvas, texture_creator, font)?;
+        Ok(false)
+    }
+
+    /// Arrows, WASD or IJKL (a co-op pad's d-pad) move around the menu, and Space (A)
+    /// plays or steps the focused line on. Returns false for keys the menu has no use for.
+    fn handle_input(&self, game: &mut Game, keycode: Keycode) -> bool {
+        let menu = match game.menu {
+            Some(ref mut menu) => menu,
+            None => return false,
+        };
+        match keycode {
+            Keycode::Up | Keycode::W | Keycode::I => menu.move_focus(false),
+            Keycode::Down | Keycode::S | Keycode::K => menu.move_focus(true),
+            Keycode::Left | Keycode::A | Keycode::J => game.change_menu_item(false),
+            Keycode::Right | Keycode::D | Keycode::L => game.change_menu_item(true),
+            Keycode::Space if menu.focused() == MenuItem::Play => game.start_from_menu(),
+            Keycode::Space => game.change_menu_item(true),
+            _ => return false,
+        }
+        true
+    }
+
+    fn press_start(&self, game: &mut Game, _config: &Config) {
+        game.start_from_menu();
+    }
+}
+
+impl<'a> Game<'a> {
+    /// Show the title menu with the focus on Play; games without one stay where they are
+    pub fn open_menu(&mut self) {
+        if let Some(ref mut menu) = self.menu {
+            menu.focus_play();
+            self.set_state(GameState::Menu);
+        }
+    }
+
+    /// Step the focused menu line on to its next choice, or back to the one before
+    fn change_menu_item(&mut self, forward: bool) {
+        let focused = match self.menu {
+            Some(ref mut menu) => {
+                if menu.change(forward) {
+                    return;
+                }
+                menu.focused()
+            }
+            None => return,
+        };
+        match focused {
+            MenuItem::Difficulty => {
+                let difficulty = menu::cycle(&Difficulty::ALL, self.difficulty, forward);
+                if difficulty != Difficulty::Kids {
+                    self.grown_up_difficulty = difficulty;
+                }
+                self.difficulty = difficulty;
+                println!("Difficulty: {}", difficulty.name());
+            }
+            MenuItem::Music => {
+                self.music = !self.music;
+                println!("Music: {}", self.music);
+            }
+            MenuItem::CpuDemo => {
+                self.set_autoplay(!self.autoplay);
+                println!("CPU demo: {}", self.autoplay);
+            }
+            MenuItem::Play | MenuItem::Mode | MenuItem::Level | MenuItem::Maze => {}
+        }
+    }
+
+    /// Begin a run set up the way the menu shows
+    fn start_from_menu(&mut self) {
+        let config = match self.menu {
+            Some(ref menu) => menu.run_config(),
+            None => return,
+        };
+        self.practice = config.practice;
+        self.ghost_rush = config.ghost_rush;
+        self.scoring_system = ScoringSystem::with_rules(Game::run_scoring_rules(&config));
+        match config.custom_maze {
+            Some(ref sketch) => self.board.set_custom_maze(sketch.clone()),
+            None => self.board.clear_custom_maze(),
+        }
+        // Rewinding could not take back ghosts that joined a survival run
+        self.rewind = (!config.survival).then(|| RewindBuffer::new(REWIND_CAPACITY));
+        self.reset(&config);
+    }
+
+    /// The menu's lines as shown, each with whether it has the focus
+    fn menu_lines(&self) -> Vec<(String, bool)> {
+        let menu = match self.menu {
+            Some(ref menu) => menu,
+            None => return Vec::new(),
+        };
+        let on_off = |on: bool| if on { "ON" } else { "OFF" };
+        MenuItem::ALL
+            .iter()
+            .map(|&item| {
+                let text = match item {
+                    MenuItem::Play => "PLAY".to_string(),
+                    MenuItem::Mode => format!("MODE  {}", menu.mode().name()),
+                    MenuItem::Level => format!("PRACTICE LEVEL  {}", menu.level()),
+                    MenuItem::Difficulty => {
+                        format!("DIFFICULTY  {}", self.difficulty.name().to_uppercase())
+                    }
+                    MenuItem::Maze => format!("MAZE  {}", menu.maze_name()),
+                    MenuItem::Music => format!("MUSIC  {}", on_off(self.music)),
+                    MenuItem::CpuDemo => format!("CPU DEMO  {}", on_off(self.autoplay)),
+                };
+                (text, item == menu.focused())
+            })
+            .collect()
+    }
+
+    /// The title menu in a panel over the maze, with the focused line in yellow and marked
+    fn draw_menu(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let lines = self.menu_lines();
+        if lines != self.menu_text {
+            self.menu_textures
+                .resize_with(lines.len(), GameTexture::new);
+            for ((text, focused), texture) in lines.iter().zip(&mut self.menu_textures) {
+                let color = if *focused { YELLOW } else { WHITE };
+                texture.load_from_rendered_text(texture_creator, text, font, color)?;
+            }
+            self.menu_text = lines;
+        }
+
+        let hud = self.board.hud();
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(hud.panel(MENU_TOP_ROW, 2 * MenuItem::ALL.len() as u32, 2))?;
+        let marker = BLOCK_SIZE_24 / 2;
+        let lines = self.menu_text.iter().zip(&self.menu_textures);
+        for (i, ((_, focused), texture)) in lines.enumerate() {
+            let (x, y) = hud.panel_line(MENU_TOP_ROW, i, texture.get_width());
+            texture.render(canvas, x, y, None)?;
+            if *focused {
+                let middle = y + (texture.get_height() as i32 - marker as i32) / 2;
+                canvas.set_draw_color(YELLOW);
+                canvas.fill_rect(Rect::new(x - BLOCK_SIZE_24 as i32, middle, marker, marker))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BlockType, Board};
+    use crate::game::menu::Menu;
+    use crate::theme::Theme;
+
+    fn game_on_menu(config: &Config) -> Game<'static> {
+        let mut game = Game::headless(config, &Theme::new());
+        game.menu = Some(Menu::new(config));
+        game.open_menu();
+        game
+    }
+
+    #[test]
+    fn test_menu_sets_up_the_run() {
+        let config = Config::new();
+        let mut game = game_on_menu(&config);
+        assert_eq!(game.get_game_state(), GameState::Menu);
+
+        // The d-pad's keys move around the menu instead of steering Pac-Man
+        for key in [
+            Keycode::Down,
+            Keycode::Right,
+            Keycode::K,
+            Keycode::L,
+            Keycode::L,
+        ] {
+            game.handle_input(key);
+        }
+        game.handle_input(Keycode::Down);
+        game.handle_input(Keycode::Left);
+        assert_eq!(game.difficulty, Difficulty::Easy);
+        // Kept for the next launch like the display toggles
+        assert_eq!(game.settings().difficulty, Difficulty::Easy);
+        assert_eq!(game.mover.queued(), None);
+        assert_eq!(
+            game.menu_lines()[..4],
+            [
+                ("PLAY".to_string(), false),
+                ("MODE  PRACTICE".to_string(), false),
+                ("PRACTICE LEVEL  3".to_string(), false),
+                ("DIFFICULTY  EASY".to_string(), true),
+            ]
+        );
+
+        game.press_start(&config);
+        assert_eq!(game.get_game_state(), GameState::Ready);
+        assert_eq!(game.get_level(), 3);
+        assert_eq!(game.get_lives(), Difficulty::Easy.starting_lives());
+        assert!(game.practice.is_some() && game.recording.is_none());
+
+        // A game over goes back to the menu on Play, so Enter or A plays again
+        game.set_state(GameState::GameOver);
+        game.press_start(&config);
+        assert_eq!(game.get_game_state(), GameState::Menu);
+        game.handle_input(Keycode::Space);
+        assert_eq!(game.get_game_state(), GameState::Ready);
+        assert_eq!(game.get_level(), 3);
+    }
+
+    #[test]
+    fn test_menu_modes_replace_the_last_run() {
+        let mut config = Config::new();
+        config.seed = Some(3);
+        let mut game = game_on_menu(&config);
+        let pellet = game.scoring_system.get_rules().pellet;
+        let choose = |game: &mut Game, item: MenuItem, steps: usize| {
+            while game.menu.as_ref().unwrap().focused() != item {
+                game.handle_input(Keycode::Down);
+            }
+            for _ in 0..steps {
+                game.handle_input(Keycode::Right);
+            }
+            game.open_menu();
+        };
+
+        choose(&mut game, MenuItem::Mode, 3);
+        choose(&mut game, MenuItem::Maze, 1);
+        game.handle_input(Keycode::Space);
+        assert!(game.ghost_rush);
+        assert_eq!(game.scoring_system.get_rules().pellet, pellet * 2);
+        assert!(game
+            .ghosts_manager
+            .iter()
+            .all(|ghost| ghost.get_ghost().direct_chase));
+        let walls = |map: &[BlockType]| -> Vec<bool> {
+            map.iter().map(|&block| block == BlockType::Wall).collect()
+        };
+        let random: Vec<BlockType> = crate::maze_gen::generate(3)
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        assert_eq!(walls(&game.actual_map), walls(&random));
+
+        // Back to an arcade run on the usual maze
+        game.open_menu();
+        choose(&mut game, MenuItem::Mode, 1);
+        choose(&mut game, MenuItem::Maze, 1);
+        game.press_start(&config);
+        assert!(!game.ghost_rush && game.survival.is_none());
+        assert_eq!(game.scoring_system.get_rules().pellet, pellet);
+        assert!(game.recording.is_some());
+        let classic: Vec<BlockType> = Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        assert_eq!(walls(&game.actual_map), walls(&classic));
+    }
+}
@@ -0,0 +1,86 @@
+use super::StateHooks;
+use crate::font::GameFont;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::locale::Date;
+use crate::{BLACK, WHITE};
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// A stop after reaching the top difficulty, with a summary of the run so far
+pub(super) struct Milestone;
+
+impl StateHooks for Milestone {
+    fn on_enter(&self, game: &mut Game, _previous: &GameState) {
+        println!(
+            "Max difficulty reached at level {} with score {}",
+            game.level,
+            game.board.get_score()
+        );
+    }
+
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        game.draw_milestone_summary(canvas, texture_creator, font)?;
+        Ok(false)
+    }
+}
+
+impl<'a> Game<'a> {
+    fn draw_milestone_summary(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &GameFont,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let level_text = format!("LEVEL {}", self.level);
+        self.summary_level_texture.load_from_rendered_text(
+            texture_creator,
+            &level_text,
+            font,
+            WHITE,
+        )?;
+        let locale = self.board.get_locale();
+        let score_text = format!("SCORE {}", locale.format_number(self.board.get_score()));
+        self.summary_score_texture.load_from_rendered_text(
+            texture_creator,
+            &score_text,
+            font,
+            WHITE,
+        )?;
+        let date_text = locale.format_date(Date::today());
+        self.summary_date_texture.load_from_rendered_text(
+            texture_creator,
+            &date_text,
+            font,
+            WHITE,
+        )?;
+
+        let hud = self.board.hud();
+        canvas.set_draw_color(BLACK);
+        canvas.fill_rect(hud.panel(14, 10, 2))?;
+
+        let lines = [
+            &self.milestone_texture,
+            &self.summary_level_texture,
+            &self.summary_score_texture,
+            &self.summary_date_texture,
+            if self.gamepad_prompts {
+                &self.continue_pad_texture
+            } else {
+                &self.continue_texture
+            },
+        ];
+        for (i, texture) in lines.iter().enumerate() {
+            let (x, y) = hud.panel_line(14, i, texture.get_width());
+            texture.render(canvas, x, y, None)?;
+        }
+
+        Ok(())
+    }
+}
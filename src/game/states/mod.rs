@@ -0,0 +1,99 @@
+//! What each `GameState` does, one file per state.
+//!
+//! A state can set itself up when it is entered and clean up when it is left, do its part of
+//! each update and draw over the maze. The screens between games also take Enter, and the
+//! menu the keys that move around it. `Game` runs the hooks of whichever state it is in.
+
+use crate::board::Board;
+use crate::config::Config;
+use crate::font::GameFont;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::texture::GameTexture;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+mod game_over;
+mod insert_coin;
+mod level_complete;
+mod menu;
+mod milestone;
+mod pacman_death;
+mod paused;
+mod playing;
+mod ready;
+
+/// What a state does; each hook does nothing unless the state needs it
+pub(super) trait StateHooks {
+    /// Setup for the state just entered from `previous`
+    fn on_enter(&self, _game: &mut Game, _previous: &GameState) {}
+
+    /// Cleanup for the state about to be left
+    fn on_exit(&self, _game: &mut Game) {}
+
+    /// One update of whatever the state does on its own
+    fn update(&self, _game: &mut Game) {}
+
+    /// Draw what belongs to the state, returning whether the actors are drawn over it
+    fn draw<'a>(
+        &self,
+        _game: &mut Game<'a>,
+        _canvas: &mut WindowCanvas,
+        _texture_creator: &'a TextureCreator<WindowContext>,
+        _font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(true)
+    }
+
+    /// A key for the state before the usual controls get it; returns whether it was used
+    fn handle_input(&self, _game: &mut Game, _keycode: Keycode) -> bool {
+        false
+    }
+
+    /// Enter or Start, which only does something on the screens between games
+    fn press_start(&self, _game: &mut Game, _config: &Config) {}
+}
+
+/// The hooks for `state`
+pub(super) fn hooks(state: &GameState) -> &'static dyn StateHooks {
+    match state {
+        GameState::Ready => &ready::Ready,
+        GameState::Playing => &playing::Playing,
+        GameState::Paused => &paused::Paused,
+        GameState::PacmanDeath => &pacman_death::PacmanDeath,
+        GameState::LevelComplete => &level_complete::LevelComplete,
+        GameState::GameOver => &game_over::GameOver,
+        GameState::Milestone => &milestone::Milestone,
+        GameState::InsertCoin => &insert_coin::InsertCoin,
+        GameState::Menu => &menu::Menu,
+    }
+}
+
+impl Game<'_> {
+    /// What Enter does on the game-over and coin screens: play, or ask for a coin first
+    fn draw_start_prompt(
+        &self,
+        canvas: &mut WindowCanvas,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let prompt = match self.credits {
+            Some(0) => &self.insert_coin_texture,
+            _ if self.gamepad_prompts => &self.play_again_pad_texture,
+            _ => &self.play_again_texture,
+        };
+        let (x, y) = self.board.hud().prompt(prompt.get_width());
+        prompt.render(canvas, x, y, None)?;
+        Ok(())
+    }
+
+    /// READY!, PAUSED or GAME OVER, centered below the ghost house
+    fn draw_banner(
+        canvas: &mut WindowCanvas,
+        board: &Board,
+        texture: &GameTexture,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (x, y) = board.hud().banner(texture.get_width());
+        texture.render(canvas, x, y, None)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use super::StateHooks;
+use crate::game::core::DEATH_FREEZE_MS;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::TICK_MS;
+
+/// Pac-Man caught by a ghost, held still for a moment before the death animation
+pub(super) struct PacmanDeath;
+
+impl StateHooks for PacmanDeath {
+    fn on_enter(&self, game: &mut Game, _previous: &GameState) {
+        game.death_freeze_ms = DEATH_FREEZE_MS;
+        println!("Pacman died!");
+    }
+
+    fn update(&self, game: &mut Game) {
+        // Freeze frame first, then the ghosts vanish and the animation plays
+        if game.death_freeze_ms > 0 {
+            game.death_freeze_ms = game.death_freeze_ms.saturating_sub(TICK_MS);
+            return;
+        }
+        game.pacman.update_death_animation();
+        if !game.pacman.is_dead_animation_ended() {
+            return;
+        }
+        if game.board.get_lives() > 0 {
+            let pacman_start = game.board.reset_position(crate::board::EntityType::PacMan);
+            game.pacman.set_position(pacman_start);
+            game.ghosts_manager.reset_all_ghost_positions(&game.board);
+
+            game.reset_game_for_death();
+            game.set_state(GameState::Ready);
+        } else {
+            game.set_state(GameState::GameOver);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::StateHooks;
+use crate::font::GameFont;
+use crate::game::state::GameState;
+use crate::game::Game;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// Play held still, with PAUSED below the ghost house
+pub(super) struct Paused;
+
+impl StateHooks for Paused {
+    fn on_enter(&self, game: &mut Game, _previous: &GameState) {
+        game.timer_system.pause_all();
+        println!("Game paused");
+    }
+
+    fn on_exit(&self, game: &mut Game) {
+        game.timer_system.unpause_all();
+    }
+
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        _texture_creator: &'a TextureCreator<WindowContext>,
+        _font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        Game::draw_banner(canvas, &game.board, &game.paused_texture)?;
+        Ok(true)
+    }
+}
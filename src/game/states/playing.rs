@@ -0,0 +1,45 @@
+use super::StateHooks;
+use crate::game::state::GameState;
+use crate::game::Game;
+
+/// Pac-Man and the ghosts on the move
+pub(super) struct Playing;
+
+impl StateHooks for Playing {
+    fn on_enter(&self, game: &mut Game, previous: &GameState) {
+        if *previous == GameState::Paused {
+            println!("Game resumed");
+        } else {
+            game.begin_play();
+        }
+    }
+
+    fn update(&self, game: &mut Game) {
+        if !game.pacman.is_alive() {
+            game.set_state(GameState::PacmanDeath);
+        } else if game.is_level_completed() {
+            game.set_state(GameState::LevelComplete);
+        } else {
+            game.update_game_logic();
+        }
+    }
+}
+
+impl Game<'_> {
+    /// Play starts once the ready countdown is over
+    fn begin_play(&mut self) {
+        if self.is_level_completed() {
+            self.reload_maze();
+        }
+
+        self.power_state.deactivate();
+        self.ghost_freeze_ms = 0;
+        self.ghosts_manager.reset_all_ghost_modes();
+        self.ghosts_manager.reset_all_ghost_facing();
+        self.pacman.reset_current_living_frame();
+
+        self.timer_system.restart_ghost_timer();
+        self.timer_system.start_ghost_timing();
+        println!("Game started!");
+    }
+}
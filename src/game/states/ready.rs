@@ -0,0 +1,34 @@
+use super::StateHooks;
+use crate::font::GameFont;
+use crate::game::state::GameState;
+use crate::game::Game;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// The countdown before play, with READY! below the ghost house
+pub(super) struct Ready;
+
+impl StateHooks for Ready {
+    fn on_enter(&self, game: &mut Game, _previous: &GameState) {
+        game.timer_system.set_start_ticks(2500);
+        game.timer_system.start_game();
+        println!("Starting level {}", game.level);
+    }
+
+    fn update(&self, game: &mut Game) {
+        if game.timer_system.get_game_ticks() >= game.timer_system.get_start_ticks() as u128 {
+            game.set_state(GameState::Playing);
+        }
+    }
+
+    fn draw<'a>(
+        &self,
+        game: &mut Game<'a>,
+        canvas: &mut WindowCanvas,
+        _texture_creator: &'a TextureCreator<WindowContext>,
+        _font: &GameFont,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        Game::draw_banner(canvas, &game.board, &game.ready_texture)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Achievement {
+    MaxDifficulty,
+}
+
+/// Running statistics for the current play session
+#[derive(Debug, Default)]
+pub struct SessionStats {
+    levels_completed: u16,
+    deaths: u16,
+    ghosts_eaten: u32,
+    /// Deaths keyed by the name of the ghost responsible
+    killed_by: BTreeMap<&'static str, u32>,
+    achievements: Vec<Achievement>,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        SessionStats::default()
+    }
+
+    pub fn record_level_completed(&mut self) {
+        self.levels_completed += 1;
+    }
+
+    pub fn record_death(&mut self) {
+        self.deaths += 1;
+    }
+
+    pub fn record_killed_by(&mut self, ghost: &'static str) {
+        *self.killed_by.entry(ghost).or_insert(0) += 1;
+    }
+
+    pub fn record_ghost_eaten(&mut self) {
+        self.ghosts_eaten += 1;
+    }
+
+    /// Unlock an achievement, returning true only the first time
+    pub fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.has_achievement(achievement) {
+            return false;
+        }
+        self.achievements.push(achievement);
+        true
+    }
+
+    pub fn has_achievement(&self, achievement: Achievement) -> bool {
+        self.achievements.contains(&achievement)
+    }
+
+    pub fn get_levels_completed(&self) -> u16 {
+        self.levels_completed
+    }
+
+    pub fn get_deaths(&self) -> u16 {
+        self.deaths
+    }
+
+    pub fn get_ghosts_eaten(&self) -> u32 {
+        self.ghosts_eaten
+    }
+
+    pub fn get_killed_by(&self) -> &BTreeMap<&'static str, u32> {
+        &self.killed_by
+    }
+}
@@ -0,0 +1,117 @@
+use crate::entity::GhostType;
+use crate::game::ghost_manager::ExtraGhost;
+
+/// Time between pellet waves; each wave puts back every dot eaten since the last
+pub const WAVE_MS: u32 = 60_000;
+/// Time between extra ghosts
+pub const GHOST_MS: u32 = 120_000;
+/// Points for every whole second Pac-Man stays alive
+pub const POINTS_PER_SECOND: u32 = 10;
+
+/// Kinds of the extra ghosts in the order they join, starting over after Clyde
+const GHOST_ORDER: [GhostType; 4] = [
+    GhostType::Blinky,
+    GhostType::Pinky,
+    GhostType::Inky,
+    GhostType::Clyde,
+];
+
+/// What fell due during one update of a survival run
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurvivalTick {
+    pub points: u32,
+    /// Time for a pellet wave
+    pub refill: bool,
+    /// The ghost to add, if one is due
+    pub ghost: Option<ExtraGhost>,
+}
+
+/// The clock of an endless survival run.
+///
+/// It only runs while Pac-Man is playing, so the countdown after a death does not count as
+/// time survived.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Survival {
+    elapsed_ms: u32,
+    ghosts_added: usize,
+}
+
+impl Default for Survival {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Survival {
+    pub fn new() -> Self {
+        Survival {
+            elapsed_ms: 0,
+            ghosts_added: 0,
+        }
+    }
+
+    pub fn get_elapsed_ms(&self) -> u32 {
+        self.elapsed_ms
+    }
+
+    /// Run the clock on by `elapsed_ms`
+    pub fn advance(&mut self, elapsed_ms: u32) -> SurvivalTick {
+        let before = self.elapsed_ms;
+        self.elapsed_ms += elapsed_ms;
+        let crossed = |every: u32| self.elapsed_ms / every - before / every;
+
+        let ghost = (crossed(GHOST_MS) > 0).then(|| {
+            let kind = GHOST_ORDER[self.ghosts_added % GHOST_ORDER.len()];
+            self.ghosts_added += 1;
+            ExtraGhost {
+                kind,
+                color: None,
+                corner: None,
+            }
+        });
+        SurvivalTick {
+            points: crossed(1000) * POINTS_PER_SECOND,
+            refill: crossed(WAVE_MS) > 0,
+            ghost,
+        }
+    }
+
+    /// Time survived as minutes and seconds, e.g. `3:07`
+    pub fn clock(&self) -> String {
+        let seconds = self.elapsed_ms / 1000;
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waves_and_ghosts_fall_due_on_time() {
+        let mut survival = Survival::new();
+        let mut points = 0;
+        let mut refills = 0;
+        let mut ghosts = Vec::new();
+        // Ten minutes in steps that do not divide a second evenly
+        while survival.get_elapsed_ms() < 600_000 {
+            let tick = survival.advance(16);
+            points += tick.points;
+            refills += tick.refill as u32;
+            ghosts.extend(tick.ghost.map(|ghost| ghost.kind));
+        }
+        assert_eq!(points, 600 * POINTS_PER_SECOND);
+        assert_eq!(refills, 10);
+        assert_eq!(
+            ghosts,
+            vec![
+                GhostType::Blinky,
+                GhostType::Pinky,
+                GhostType::Inky,
+                GhostType::Clyde,
+                GhostType::Blinky,
+            ]
+        );
+        assert_eq!(survival.clock(), "10:00");
+    }
+}
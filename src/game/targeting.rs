@@ -0,0 +1,86 @@
+use crate::board::Direction;
+
+/// Order the arcade breaks ties in when two ways lead equally close to the target
+pub const TIE_ORDER: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+
+/// The arcade's choice at a junction: the open way, other than straight back, that leads
+/// closest to the target, with ties going Up, then Left, then Down.
+///
+/// `options` pairs each open way with the squared distance to the target from where it leads.
+/// Backing up is only chosen when it is the one way out. `None` when there are no options.
+pub fn choose_direction(options: &[(Direction, i32)], current: Direction) -> Option<Direction> {
+    let reverse = current.opposite();
+    let rank = |direction: Direction| {
+        TIE_ORDER
+            .iter()
+            .position(|&other| other == direction)
+            .unwrap_or(TIE_ORDER.len())
+    };
+    options
+        .iter()
+        .filter(|&&(direction, _)| direction != reverse || options.len() == 1)
+        .min_by_key(|&&(direction, distance)| (distance, rank(direction)))
+        .map(|&(direction, _)| direction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_way_wins() {
+        let options = [(Direction::Right, 100), (Direction::Down, 81)];
+        assert_eq!(
+            choose_direction(&options, Direction::Right),
+            Some(Direction::Down)
+        );
+    }
+
+    #[test]
+    fn test_ties_go_up_then_left_then_down() {
+        let all = [
+            (Direction::Right, 25),
+            (Direction::Down, 25),
+            (Direction::Left, 25),
+            (Direction::Up, 25),
+        ];
+        assert_eq!(
+            choose_direction(&all, Direction::Nowhere),
+            Some(Direction::Up)
+        );
+        assert_eq!(
+            choose_direction(&all, Direction::Down),
+            Some(Direction::Left)
+        );
+        assert_eq!(
+            choose_direction(&all[..2], Direction::Left),
+            Some(Direction::Down)
+        );
+
+        // 3-4-5 against 5-0: the same squared distance, so still a tie
+        let options = [(Direction::Right, 3 * 3 + 4 * 4), (Direction::Down, 5 * 5)];
+        assert_eq!(
+            choose_direction(&options, Direction::Right),
+            Some(Direction::Down)
+        );
+    }
+
+    #[test]
+    fn test_never_reverses_unless_cornered() {
+        let options = [(Direction::Left, 1), (Direction::Up, 400)];
+        assert_eq!(
+            choose_direction(&options, Direction::Right),
+            Some(Direction::Up)
+        );
+        assert_eq!(
+            choose_direction(&options[..1], Direction::Right),
+            Some(Direction::Left)
+        );
+        assert_eq!(choose_direction(&[], Direction::Right), None);
+    }
+}
@@ -0,0 +1,233 @@
+use super::collision::CollisionEvent;
+use crate::position::Tile;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+pub const TELEMETRY_LOG_PATH: &str = "telemetry.log";
+pub const DEATH_HEATMAP_PATH: &str = "death_heatmap.txt";
+
+/// Number of deaths per board cell, accumulated across sessions
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeathHeatmap {
+    counts: Vec<u32>,
+}
+
+impl Default for DeathHeatmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeathHeatmap {
+    pub fn new() -> Self {
+        DeathHeatmap {
+            counts: vec![0; BOARD_WIDTH * BOARD_HEIGHT],
+        }
+    }
+
+    /// Count a death; tunnel tiles past the board edge are folded onto the edge column
+    pub fn record(&mut self, tile: Tile) {
+        if tile.y < 0 || tile.y >= BOARD_HEIGHT as i16 {
+            return;
+        }
+        let x = tile.x.clamp(0, BOARD_WIDTH as i16 - 1) as usize;
+        self.counts[tile.y as usize * BOARD_WIDTH + x] += 1;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u32 {
+        self.counts[y * BOARD_WIDTH + x]
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    /// One line per board row with comma separated counts
+    pub fn serialize(&self) -> String {
+        let mut text = String::new();
+        for row in self.counts.chunks(BOARD_WIDTH) {
+            let line: Vec<String> = row.iter().map(|count| count.to_string()).collect();
+            text.push_str(&line.join(","));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut heatmap = DeathHeatmap::new();
+        let rows: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if rows.len() != BOARD_HEIGHT {
+            return Err(
+                format!("Heatmap has {} rows, expected {}", rows.len(), BOARD_HEIGHT).into(),
+            );
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            let cells: Vec<&str> = row.split(',').collect();
+            if cells.len() != BOARD_WIDTH {
+                return Err(format!(
+                    "Heatmap row {} has {} columns, expected {}",
+                    y + 1,
+                    cells.len(),
+                    BOARD_WIDTH
+                )
+                .into());
+            }
+            for (x, cell) in cells.iter().enumerate() {
+                heatmap.counts[y * BOARD_WIDTH + x] = cell
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Heatmap row {} column {}: bad count", y + 1, x + 1))?;
+            }
+        }
+
+        Ok(heatmap)
+    }
+
+    /// Load a saved heatmap, starting fresh if none exists yet
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => DeathHeatmap::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DeathHeatmap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.serialize())?;
+        Ok(())
+    }
+}
+
+/// Optional event log and death heatmap for offline analysis
+pub struct Telemetry {
+    log: Option<File>,
+    heatmap: Option<DeathHeatmap>,
+}
+
+impl Telemetry {
+    pub fn disabled() -> Self {
+        Telemetry {
+            log: None,
+            heatmap: None,
+        }
+    }
+
+    pub fn new(enabled: bool) -> Self {
+        if !enabled {
+            return Telemetry::disabled();
+        }
+
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(TELEMETRY_LOG_PATH)
+            .map_err(|e| println!("Telemetry log unavailable: {}", e))
+            .ok();
+        let heatmap = DeathHeatmap::load(DEATH_HEATMAP_PATH)
+            .map_err(|e| println!("Death heatmap unavailable: {}", e))
+            .ok();
+
+        Telemetry { log, heatmap }
+    }
+
+    /// Append a ghost collision to the log, and count it on the heatmap if Pacman died
+    pub fn record_collision(&mut self, level: u16, event: &CollisionEvent) {
+        let line = match event {
+            CollisionEvent::PacmanEatsGhost {
+                ghost_type,
+                tile,
+                mode,
+                ..
+            } => format!(
+                "level={} event=ghost_eaten ghost={:?} tile={},{} mode={}",
+                level,
+                ghost_type,
+                tile.x,
+                tile.y,
+                mode.name()
+            ),
+            CollisionEvent::GhostKillsPacman {
+                ghost_type,
+                tile,
+                mode,
+            } => {
+                if let Some(ref mut heatmap) = self.heatmap {
+                    heatmap.record(*tile);
+                }
+                format!(
+                    "level={} event=pacman_death ghost={:?} tile={},{} mode={}",
+                    level,
+                    ghost_type,
+                    tile.x,
+                    tile.y,
+                    mode.name()
+                )
+            }
+            CollisionEvent::NoCollision => return,
+        };
+
+        if let Some(ref mut log) = self.log {
+            if let Err(e) = writeln!(log, "{}", line) {
+                println!("Failed to write telemetry: {}", e);
+                self.log = None;
+            }
+        }
+    }
+
+    /// Write the heatmap back to disk
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(ref heatmap) = self.heatmap {
+            heatmap.save(DEATH_HEATMAP_PATH)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_records_deaths() {
+        let mut heatmap = DeathHeatmap::new();
+        heatmap.record(Tile { x: 5, y: 8 });
+        heatmap.record(Tile { x: 5, y: 8 });
+        assert_eq!(heatmap.get(5, 8), 2);
+        assert_eq!(heatmap.total(), 2);
+    }
+
+    #[test]
+    fn test_heatmap_folds_tunnel_tiles_onto_edges() {
+        let mut heatmap = DeathHeatmap::new();
+        heatmap.record(Tile { x: -1, y: 17 });
+        heatmap.record(Tile { x: 29, y: 17 });
+        heatmap.record(Tile { x: 3, y: -2 });
+        assert_eq!(heatmap.get(0, 17), 1);
+        assert_eq!(heatmap.get(BOARD_WIDTH - 1, 17), 1);
+        assert_eq!(heatmap.total(), 2);
+    }
+
+    #[test]
+    fn test_heatmap_round_trip() {
+        let mut heatmap = DeathHeatmap::new();
+        heatmap.record(Tile { x: 0, y: 0 });
+        heatmap.record(Tile { x: 27, y: 35 });
+
+        let parsed = DeathHeatmap::parse(&heatmap.serialize()).unwrap();
+        assert_eq!(parsed, heatmap);
+    }
+
+    #[test]
+    fn test_heatmap_rejects_wrong_shape() {
+        assert!(DeathHeatmap::parse("1,2,3\n").is_err());
+
+        let mut text = DeathHeatmap::new().serialize();
+        text = text.replacen('0', "x", 1);
+        assert!(DeathHeatmap::parse(&text).is_err());
+    }
+}
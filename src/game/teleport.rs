@@ -0,0 +1,71 @@
+use crate::board::BlockType;
+use crate::entity::{BaseEntity, Entity};
+use crate::position::{Position, Tile};
+use crate::{BLOCK_SIZE_24, BOARD_HEIGHT, BOARD_WIDTH};
+
+/// How long an entity ignores teleporters after using one, long enough to step off the far end
+pub const TELEPORT_COOLDOWN_MS: u32 = 500;
+
+/// The other tile of the teleporter pair at `index`, if it has one
+pub fn twin(map: &[BlockType], index: usize) -> Option<usize> {
+    let BlockType::Teleport(pair) = map[index] else {
+        return None;
+    };
+    map.iter()
+        .enumerate()
+        .position(|(other, &block)| other != index && block == BlockType::Teleport(pair))
+}
+
+/// Move `entity` to the twin of the teleporter it has stepped onto, keeping its heading.
+///
+/// Counts down the cooldown by `elapsed_ms` first; returns whether the entity was moved.
+pub fn update(entity: &mut BaseEntity, map: &[BlockType], elapsed_ms: u32) -> bool {
+    if entity.teleport_cooldown_ms > 0 {
+        entity.teleport_cooldown_ms = entity.teleport_cooldown_ms.saturating_sub(elapsed_ms);
+        return false;
+    }
+
+    let tile = Tile::from_position(entity.get_position());
+    if tile.x < 0 || tile.y < 0 || tile.x >= BOARD_WIDTH as i16 || tile.y >= BOARD_HEIGHT as i16 {
+        return false;
+    }
+    let Some(target) = twin(map, tile.y as usize * BOARD_WIDTH + tile.x as usize) else {
+        return false;
+    };
+
+    let x = (target % BOARD_WIDTH) as u32 * BLOCK_SIZE_24;
+    let y = (target / BOARD_WIDTH) as u32 * BLOCK_SIZE_24;
+    entity.set_position(Position::new(x as i16, y as i16));
+    entity.teleport_cooldown_ms = TELEPORT_COOLDOWN_MS;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Direction, EntityType};
+    use crate::TICK_MS;
+
+    #[test]
+    fn test_teleporter_sends_entity_to_twin_once() {
+        let mut map = vec![BlockType::Nothing; BOARD_WIDTH * BOARD_HEIGHT];
+        map[5 * BOARD_WIDTH + 2] = BlockType::Teleport(0);
+        map[20 * BOARD_WIDTH + 9] = BlockType::Teleport(0);
+        map[8 * BOARD_WIDTH + 4] = BlockType::Teleport(1);
+        assert_eq!(twin(&map, 5 * BOARD_WIDTH + 2), Some(20 * BOARD_WIDTH + 9));
+        assert_eq!(twin(&map, 8 * BOARD_WIDTH + 4), None);
+
+        let mut entity = BaseEntity::new(EntityType::PacMan);
+        entity.mod_direction(Direction::Left);
+        entity.set_position(Position::new(2 * 24 + 5, 5 * 24));
+        assert!(update(&mut entity, &map, TICK_MS));
+        assert_eq!(entity.get_position(), Position::new(9 * 24, 20 * 24));
+        assert_eq!(entity.get_direction(), Direction::Left);
+
+        // Standing on the twin does not send it straight back
+        for _ in 0..TELEPORT_COOLDOWN_MS.div_ceil(TICK_MS) {
+            assert!(!update(&mut entity, &map, TICK_MS));
+        }
+        assert!(update(&mut entity, &map, TICK_MS));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::board::{BlockType, Direction};
+use crate::game::bot::{is_open, step, DIRECTIONS};
+use crate::position::Tile;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+
+/// Which ways lead out of each tile of the maze, worked out once per level.
+///
+/// Walls and the ghost house door are closed, as they are for ghosts out in the maze. Only
+/// the layout matters, so the graph stays valid while pellets are eaten.
+pub struct TileGraph {
+    /// One bit per direction, in `DIRECTIONS` order; closed tiles have none
+    exits: Vec<u8>,
+    /// The map the graph was built from, for moves the graph does not cover
+    layout: Vec<BlockType>,
+}
+
+impl TileGraph {
+    pub fn new(map: &[BlockType]) -> Self {
+        let exits = (0..BOARD_WIDTH * BOARD_HEIGHT)
+            .map(|index| {
+                let tile = Tile {
+                    x: (index % BOARD_WIDTH) as i16,
+                    y: (index / BOARD_WIDTH) as i16,
+                };
+                if !is_open(map, tile) {
+                    return 0;
+                }
+                DIRECTIONS
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &direction)| {
+                        step(tile, direction).is_some_and(|next| is_open(map, next))
+                    })
+                    .fold(0, |bits, (bit, _)| bits | 1 << bit)
+            })
+            .collect();
+        TileGraph {
+            exits,
+            layout: map.to_vec(),
+        }
+    }
+
+    pub fn layout(&self) -> &[BlockType] {
+        &self.layout
+    }
+
+    /// Whether the tile is on the board and can be stood on
+    pub fn is_open(&self, tile: Tile) -> bool {
+        self.bits(tile) != 0
+    }
+
+    pub fn has_exit(&self, tile: Tile, direction: Direction) -> bool {
+        match DIRECTIONS.iter().position(|&other| other == direction) {
+            Some(bit) => self.bits(tile) & 1 << bit != 0,
+            None => false,
+        }
+    }
+
+    /// More than one way on for something arriving from any side, so a choice has to be made
+    pub fn is_intersection(&self, tile: Tile) -> bool {
+        self.bits(tile).count_ones() > 2
+    }
+
+    fn bits(&self, tile: Tile) -> u8 {
+        if tile.x < 0
+            || tile.y < 0
+            || tile.x as usize >= BOARD_WIDTH
+            || tile.y as usize >= BOARD_HEIGHT
+        {
+            return 0;
+        }
+        self.exits[tile.y as usize * BOARD_WIDTH + tile.x as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_exits_follow_the_maze() {
+        let map: Vec<BlockType> = Board::CHAR_BOARD
+            .chars()
+            .map(BlockType::from_char)
+            .collect();
+        let graph = TileGraph::new(&map);
+
+        // Top left corner: right and down only
+        let corner = Tile { x: 1, y: 4 };
+        assert!(graph.has_exit(corner, Direction::Right));
+        assert!(graph.has_exit(corner, Direction::Down));
+        assert!(!graph.has_exit(corner, Direction::Up));
+        assert!(!graph.has_exit(corner, Direction::Left));
+        assert!(!graph.is_intersection(corner));
+
+        // Where the top corridor meets the first column down
+        assert!(graph.is_intersection(Tile { x: 6, y: 8 }));
+
+        // The side tunnel wraps round, and the door stays shut
+        assert!(graph.has_exit(Tile { x: 0, y: 17 }, Direction::Left));
+        assert!(!graph.has_exit(Tile { x: 13, y: 14 }, Direction::Down));
+        assert!(!graph.is_open(Tile { x: 0, y: 3 }));
+        assert!(!graph.is_open(Tile { x: -1, y: 17 }));
+    }
+}
@@ -1,17 +1,33 @@
+use super::levels::{LevelParams, WAVES};
 use super::state::GameTimer;
 
+/// The parts of a `TimerSystem` that change during play, as stored in save files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimerState {
+    pub game_ms: u32,
+    pub start_ms: u32,
+    pub ghost_ms: u32,
+    /// Index into the level's scatter/chase waves
+    pub wave: usize,
+}
+
 /// Manages all game timing behavior including ghost AI state transitions
 pub struct TimerSystem {
     // Core game timing
     game_timer: GameTimer,
     start_ticks: u32,
 
-    // Ghost AI timing
+    // Ghost AI timing: time spent in the current wave, which only runs while ghosts are not
+    // frightened
     ghost_timer: GameTimer,
-    scatter_time: u32,
-    chasing_time: u32,
-    ghost_timer_target: u32,
-    timed_status: bool, // true = scatter mode, false = chase mode
+    waves: [u32; WAVES],
+    wave: usize,
+}
+
+impl Default for TimerSystem {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TimerSystem {
@@ -20,10 +36,8 @@ impl TimerSystem {
             game_timer: GameTimer::new(),
             start_ticks: 0,
             ghost_timer: GameTimer::new(),
-            scatter_time: 7000,        // 7 seconds scatter
-            chasing_time: 20000,       // 20 seconds chase
-            ghost_timer_target: 20000, // Start with chasing
-            timed_status: false,       // Start in chase mode
+            waves: LevelParams::for_level(1).waves,
+            wave: 0,
         }
     }
 
@@ -37,51 +51,46 @@ impl TimerSystem {
         self.ghost_timer.start();
     }
 
-    /// Restart ghost timer for new cycle
+    /// Go back to the first scatter wave, as every life starts
     pub fn restart_ghost_timer(&mut self) {
         self.ghost_timer.restart();
+        self.wave = 0;
+        self.skip_empty_waves();
     }
 
-    /// Update ghost AI timing and return true if mode should change
-    pub fn update_ghost_timing(&mut self) -> bool {
-        if self.ghost_timer.get_ticks() >= self.ghost_timer_target as u128 {
-            // Time to switch modes
-            if self.timed_status {
-                // Currently scattering, switch to chasing
-                self.ghost_timer_target = self.chasing_time;
-                self.timed_status = false;
-            } else {
-                // Currently chasing, switch to scattering
-                self.ghost_timer_target = self.scatter_time;
-                self.timed_status = true;
-            }
-            self.ghost_timer.restart();
-            return true; // Mode changed
+    /// Waves of no length are passed over at once, so a schedule of nothing but empty waves
+    /// chases for good from the start
+    fn skip_empty_waves(&mut self) {
+        while self.waves.get(self.wave) == Some(&0) {
+            self.wave += 1;
         }
-        false // No mode change
     }
 
-    /// Set ghost timer to scatter mode (for energizer)
-    pub fn set_scatter_mode(&mut self) {
-        self.ghost_timer_target = self.scatter_time;
-        self.timed_status = true;
-        self.ghost_timer.restart();
+    /// Run the wave clock for `ms` and return true if the ghosts' mode should change.
+    ///
+    /// The clock is only run while ghosts are not frightened, so an energizer holds the
+    /// schedule where it was rather than resetting it.
+    pub fn update_ghost_timing(&mut self, ms: u32) -> bool {
+        self.ghost_timer.advance(ms);
+        match self.waves.get(self.wave) {
+            Some(&length) if self.ghost_timer.get_ticks() >= length as u128 => {
+                self.wave += 1;
+                self.skip_empty_waves();
+                self.ghost_timer.restart();
+                true
+            }
+            _ => false,
+        }
     }
 
-    /// Check if ghosts should be in scatter mode
+    /// Check if ghosts should be in scatter mode; after the last wave they chase for good
     pub fn is_scatter_mode(&self) -> bool {
-        self.timed_status
-    }
-
-    /// Get current ghost timer target
-    pub fn get_ghost_timer_target(&self) -> u32 {
-        self.ghost_timer_target
+        self.wave < WAVES && self.wave.is_multiple_of(2)
     }
 
-    /// Set custom ghost timer target
-    #[allow(dead_code)]
-    pub fn set_ghost_timer_target(&mut self, target: u32) {
-        self.ghost_timer_target = target;
+    /// Which wave of the level is running, from 0
+    pub fn wave(&self) -> usize {
+        self.wave
     }
 
     /// Get game timer ticks
@@ -104,6 +113,12 @@ impl TimerSystem {
         self.start_ticks = ticks;
     }
 
+    /// Move the game timer forward by one update's worth of time; the wave clock runs in
+    /// `update_ghost_timing`
+    pub fn advance(&mut self, ms: u32) {
+        self.game_timer.advance(ms);
+    }
+
     /// Pause all timers
     pub fn pause_all(&mut self) {
         self.game_timer.pause();
@@ -116,11 +131,69 @@ impl TimerSystem {
         self.ghost_timer.unpause();
     }
 
-    /// Update difficulty by increasing chase time and decreasing scatter time
-    pub fn update_difficulty(&mut self) {
-        self.chasing_time += 1000; // Increase chase time by 1 second
-        if self.scatter_time > 2000 {
-            self.scatter_time -= 1000; // Decrease scatter time by 1 second
+    pub fn get_state(&self) -> TimerState {
+        TimerState {
+            game_ms: self.game_timer.get_ticks() as u32,
+            start_ms: self.start_ticks,
+            ghost_ms: self.ghost_timer.get_ticks() as u32,
+            wave: self.wave,
         }
     }
+
+    /// Pick up timing where a saved game left off; both timers are left running
+    pub fn restore_state(&mut self, state: TimerState) {
+        self.game_timer.restart();
+        self.game_timer.advance(state.game_ms);
+        self.start_ticks = state.start_ms;
+        self.ghost_timer.restart();
+        self.ghost_timer.advance(state.ghost_ms);
+        self.wave = state.wave.min(WAVES);
+    }
+
+    /// Apply the scatter and chase waves for a level
+    pub fn apply_level_params(&mut self, params: LevelParams) {
+        self.waves = params.waves;
+        if self.wave == 0 {
+            self.skip_empty_waves();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waves_run_in_order_then_chase_for_good() {
+        let mut timers = TimerSystem::new();
+        timers.restart_ghost_timer();
+        assert!(timers.is_scatter_mode());
+
+        let waves = LevelParams::for_level(1).waves;
+        assert!(!timers.update_ghost_timing(waves[0] - 1));
+        assert!(timers.update_ghost_timing(1));
+        assert!(!timers.is_scatter_mode());
+
+        for (wave, &length) in waves.iter().enumerate().skip(1) {
+            assert!(timers.update_ghost_timing(length));
+            assert_eq!(timers.wave(), wave + 1);
+        }
+        assert!(!timers.is_scatter_mode());
+        assert!(!timers.update_ghost_timing(u32::MAX));
+
+        timers.restart_ghost_timer();
+        assert_eq!(timers.wave(), 0);
+    }
+
+    #[test]
+    fn test_empty_waves_chase_from_the_start() {
+        let mut timers = TimerSystem::new();
+        timers.apply_level_params(LevelParams::for_level(1).rushed());
+        assert!(!timers.is_scatter_mode());
+        assert_eq!(timers.wave(), WAVES);
+
+        timers.restart_ghost_timer();
+        assert!(!timers.is_scatter_mode());
+        assert!(!timers.update_ghost_timing(u32::MAX));
+    }
 }
@@ -0,0 +1,39 @@
+use sdl2::controller::Button;
+use sdl2::keyboard::Keycode;
+
+/// The key a gamepad button stands in for, so a pad can do everything the keyboard does.
+///
+/// The d-pad moves Pac-Man, or Blinky when the pad is player 2 in co-op. A pauses and
+/// carries on from the ready and milestone screens like Space, Start begins a game like
+/// Enter and Back drops a coin like C. On the menu the d-pad moves the focus either way and
+/// A or Start picks.
+pub fn key_for(button: Button, coop: bool) -> Option<Keycode> {
+    let keycode = match (button, coop) {
+        (Button::DPadRight, false) => Keycode::Right,
+        (Button::DPadUp, false) => Keycode::Up,
+        (Button::DPadLeft, false) => Keycode::Left,
+        (Button::DPadDown, false) => Keycode::Down,
+        (Button::DPadRight, true) => Keycode::L,
+        (Button::DPadUp, true) => Keycode::I,
+        (Button::DPadLeft, true) => Keycode::J,
+        (Button::DPadDown, true) => Keycode::K,
+        (Button::A, _) => Keycode::Space,
+        (Button::Start, _) => Keycode::Return,
+        (Button::Back, _) => Keycode::C,
+        _ => return None,
+    };
+    Some(keycode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dpad_moves_pacman_or_blinky() {
+        assert_eq!(key_for(Button::DPadUp, false), Some(Keycode::Up));
+        assert_eq!(key_for(Button::DPadUp, true), Some(Keycode::I));
+        assert_eq!(key_for(Button::Start, true), Some(Keycode::Return));
+        assert_eq!(key_for(Button::Guide, false), None);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::assets::{Sprite, MAP_IMAGE};
+use crate::theme::Theme;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the watched files are looked at
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What an edited file means for the running game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssetKind {
+    /// Sprite sheets and the board image; the textures are rebuilt in place
+    Image,
+    /// The font every label is drawn with
+    Font,
+    /// The custom maze file; it starts a fresh game on the new layout
+    Maze,
+}
+
+struct WatchedFile {
+    path: PathBuf,
+    kind: AssetKind,
+    modified: Option<SystemTime>,
+}
+
+/// Watches the files a game was loaded from so artists can edit them while it runs.
+///
+/// Modification times are polled rather than asking the OS for change events, which keeps
+/// the game free of a file-watching dependency.
+pub struct AssetWatcher {
+    files: Vec<WatchedFile>,
+    last_poll: Instant,
+}
+
+impl Default for AssetWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AssetWatcher {
+    pub fn new() -> Self {
+        AssetWatcher {
+            files: Vec::new(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Watch every image and the font the theme resolves to, plus the custom maze if any
+    pub fn for_game(theme: &Theme, maze_path: Option<&str>) -> Self {
+        let mut watcher = AssetWatcher::new();
+        for sprite in Sprite::ALL {
+            watcher.watch(theme.asset_path(sprite.file()), AssetKind::Image);
+        }
+        watcher.watch(theme.asset_path(MAP_IMAGE), AssetKind::Image);
+        watcher.watch(theme.font_path(), AssetKind::Font);
+        if let Some(path) = maze_path {
+            watcher.watch(path, AssetKind::Maze);
+        }
+        watcher
+    }
+
+    pub fn watch(&mut self, path: impl Into<PathBuf>, kind: AssetKind) {
+        let path = path.into();
+        if self.files.iter().any(|file| file.path == path) {
+            return;
+        }
+        let modified = modified(&path);
+        self.files.push(WatchedFile {
+            path,
+            kind,
+            modified,
+        });
+    }
+
+    /// Kinds of asset edited since last time, checking the disk at most every half second
+    pub fn poll(&mut self) -> Vec<AssetKind> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+        self.check()
+    }
+
+    /// Kinds of asset edited since the last check, each listed once
+    fn check(&mut self) -> Vec<AssetKind> {
+        let mut changed = Vec::new();
+        for file in self.files.iter_mut() {
+            let modified = modified(&file.path);
+            if modified != file.modified {
+                file.modified = modified;
+                println!("Asset changed: {}", file.path.display());
+                if !changed.contains(&file.kind) {
+                    changed.push(file.kind);
+                }
+            }
+        }
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_edits_are_reported_once() {
+        let dir = std::env::temp_dir();
+        let image = dir.join(format!("hot_reload_{}.png", std::process::id()));
+        let maze = dir.join(format!("hot_reload_{}.txt", std::process::id()));
+        std::fs::write(&image, "before").unwrap();
+        std::fs::write(&maze, "before").unwrap();
+
+        let mut watcher = AssetWatcher::new();
+        watcher.watch(&image, AssetKind::Image);
+        watcher.watch(&image, AssetKind::Image);
+        watcher.watch(&maze, AssetKind::Maze);
+        assert!(watcher.check().is_empty());
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(&image)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(watcher.check(), vec![AssetKind::Image]);
+        assert!(watcher.check().is_empty());
+
+        // A deleted file counts as changed; reloading it then fails and says why
+        std::fs::remove_file(&maze).unwrap();
+        assert_eq!(watcher.check(), vec![AssetKind::Maze]);
+        std::fs::remove_file(&image).unwrap();
+    }
+}
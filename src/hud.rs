@@ -0,0 +1,169 @@
+use crate::{BLOCK_SIZE_24, BLOCK_SIZE_32};
+use sdl2::rect::Rect;
+
+/// Row the READY!, PAUSED and GAME OVER banners sit on, below the ghost house
+const BANNER_ROW: i32 = 20;
+/// Row of the play again and insert coin prompts
+const PROMPT_ROW: i32 = 23;
+/// Text is lifted a few pixels off its row so it clears the walls underneath
+const TEXT_RAISE: i32 = 5;
+/// Top of the line drawn first in a panel, below the panel's top edge
+const PANEL_PADDING: i32 = 12;
+
+/// Where the scores, lives and text go on screen, in board pixels.
+///
+/// Positions are worked out from the board's size, and text is centered or right-aligned
+/// by the width of its rendered texture, so nothing drifts when the font or maze changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hud {
+    width: i32,
+    height: i32,
+}
+
+impl Hud {
+    pub fn for_board(columns: usize, rows: usize) -> Self {
+        Hud {
+            width: columns as i32 * BLOCK_SIZE_24 as i32,
+            height: rows as i32 * BLOCK_SIZE_24 as i32,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    /// The whole screen, HUD included
+    pub fn screen(&self) -> Rect {
+        Rect::new(0, 0, self.width as u32, self.height as u32)
+    }
+
+    pub fn score_label(&self) -> (i32, i32) {
+        (0, 0)
+    }
+
+    pub fn score(&self) -> (i32, i32) {
+        (0, BLOCK_SIZE_32 as i32)
+    }
+
+    /// The high score starts halfway across, above the score's right half of the maze
+    pub fn high_score_label(&self) -> (i32, i32) {
+        (self.width / 2, 0)
+    }
+
+    pub fn high_score(&self) -> (i32, i32) {
+        (self.width / 2, BLOCK_SIZE_32 as i32)
+    }
+
+    /// Text in the top right corner, on the label row
+    pub fn top_right(&self, text_width: u32) -> (i32, i32) {
+        (self.width - text_width as i32, 0)
+    }
+
+    /// Text against the right edge on the score row
+    pub fn score_row_right(&self, text_width: u32) -> (i32, i32) {
+        (self.width - text_width as i32, BLOCK_SIZE_32 as i32)
+    }
+
+    /// The `index`th spare life, counting from 0, along the bottom left
+    pub fn life(&self, index: u8) -> (i32, i32) {
+        let size = BLOCK_SIZE_32 as i32;
+        ((index as i32 + 1) * size, self.height - size - size / 4)
+    }
+
+    pub fn banner(&self, text_width: u32) -> (i32, i32) {
+        (self.centered_x(text_width), text_row(BANNER_ROW))
+    }
+
+    pub fn prompt(&self, text_width: u32) -> (i32, i32) {
+        (self.centered_x(text_width), text_row(PROMPT_ROW))
+    }
+
+    /// Centered on the bottom row, for captions and the credit count
+    pub fn bottom(&self, text_width: u32) -> (i32, i32) {
+        (self.centered_x(text_width), self.bottom_row(1))
+    }
+
+    /// Against the right edge, `lines_up` rows from the bottom
+    pub fn bottom_right(&self, text_width: u32, lines_up: i32) -> (i32, i32) {
+        (self.width - text_width as i32, self.bottom_row(lines_up))
+    }
+
+    /// Centered on the whole screen
+    pub fn centered(&self, text_width: u32, text_height: u32) -> (i32, i32) {
+        (
+            self.centered_x(text_width),
+            (self.height - text_height as i32) / 2,
+        )
+    }
+
+    /// A box against the bottom left corner
+    pub fn bottom_left(&self, width: u32, height: u32) -> Rect {
+        Rect::new(0, self.height - height as i32, width, height)
+    }
+
+    /// The strip across the bottom `rows` rows, full width
+    pub fn bottom_band(&self, rows: u32) -> Rect {
+        let height = rows * BLOCK_SIZE_24;
+        Rect::new(0, self.height - height as i32, self.width(), height)
+    }
+
+    /// A box `rows` rows tall from `top_row`, leaving `margin` cells free on either side
+    pub fn panel(&self, top_row: i32, rows: u32, margin: u32) -> Rect {
+        let block = BLOCK_SIZE_24 as i32;
+        Rect::new(
+            margin as i32 * block,
+            top_row * block,
+            self.width() - 2 * margin * BLOCK_SIZE_24,
+            rows * BLOCK_SIZE_24,
+        )
+    }
+
+    /// The `line`th of the centered lines in a panel from `top_row`, two rows apart
+    pub fn panel_line(&self, top_row: i32, line: usize, text_width: u32) -> (i32, i32) {
+        let y = (top_row + 2 * line as i32) * BLOCK_SIZE_24 as i32 + PANEL_PADDING;
+        (self.centered_x(text_width), y)
+    }
+
+    fn centered_x(&self, text_width: u32) -> i32 {
+        (self.width - text_width as i32) / 2
+    }
+
+    fn bottom_row(&self, lines_up: i32) -> i32 {
+        self.height - lines_up * BLOCK_SIZE_24 as i32 - TEXT_RAISE
+    }
+}
+
+fn text_row(row: i32) -> i32 {
+    row * BLOCK_SIZE_24 as i32 - TEXT_RAISE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+
+    #[test]
+    fn test_positions_follow_the_board_size() {
+        let hud = Hud::for_board(BOARD_WIDTH, BOARD_HEIGHT);
+        assert_eq!(hud.high_score_label(), (336, 0));
+        assert_eq!(hud.life(0), (32, 824));
+        assert_eq!(hud.banner(100), (286, 475));
+        assert_eq!(hud.bottom_right(72, 1), (600, 835));
+
+        // A wider board moves the right half and the centre with it
+        let wide = Hud::for_board(32, BOARD_HEIGHT);
+        assert_eq!(wide.high_score_label(), (384, 0));
+        assert_eq!(wide.banner(100).0, 334);
+        assert_eq!(wide.panel(16, 6, 4), Rect::new(96, 384, 576, 144));
+    }
+
+    #[test]
+    fn test_text_is_centered_by_its_width() {
+        let hud = Hud::for_board(BOARD_WIDTH, BOARD_HEIGHT);
+        let (narrow, _) = hud.prompt(40);
+        let (wide, _) = hud.prompt(200);
+        assert_eq!(narrow + 20, wide + 100);
+        assert_eq!(hud.centered(72, 24), (300, 420));
+        assert_eq!(hud.panel_line(14, 2, 72), (300, 444));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::error::PacmanError;
+use crate::{BLOCK_SIZE_24, SCREEN_HEIGHT, SCREEN_WIDTH};
+use sdl2::render::WindowCanvas;
+
+/// Smallest and largest board cell the window can be laid out with, in window pixels
+pub const TILE_SIZES: (u32, u32) = (8, 64);
+pub const MAX_SCALE: u32 = 4;
+
+/// How big the board is drawn in the window, picked at startup.
+///
+/// Positions, speeds and the HUD are all worked out in board pixels, 24 to a cell, so a
+/// replay or versus game plays out the same whatever the window size; the layout only
+/// scales that picture when it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    /// Window pixels per board cell before scaling
+    pub tile_size: u32,
+    /// Whole-number zoom on top of the tile size, for 2x and 3x pixel scaling
+    pub scale: u32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Layout {
+            tile_size: BLOCK_SIZE_24,
+            scale: 1,
+        }
+    }
+
+    /// Window pixels per board pixel
+    pub fn zoom(&self) -> f32 {
+        (self.tile_size * self.scale) as f32 / BLOCK_SIZE_24 as f32
+    }
+
+    pub fn window_size(&self) -> (u32, u32) {
+        let scaled = |length: u32| length * self.tile_size * self.scale / BLOCK_SIZE_24;
+        (scaled(SCREEN_WIDTH), scaled(SCREEN_HEIGHT))
+    }
+
+    /// Make everything drawn on `canvas` from here on land at this layout's size
+    pub fn apply(&self, canvas: &mut WindowCanvas) -> Result<(), PacmanError> {
+        canvas
+            .set_scale(self.zoom(), self.zoom())
+            .map_err(PacmanError::Render)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_size_follows_tile_size_and_scale() {
+        assert_eq!(Layout::new().window_size(), (SCREEN_WIDTH, SCREEN_HEIGHT));
+        assert_eq!(Layout::new().zoom(), 1.0);
+
+        let doubled = Layout {
+            tile_size: BLOCK_SIZE_24,
+            scale: 2,
+        };
+        assert_eq!(doubled.window_size(), (1344, 1728));
+
+        let small = Layout {
+            tile_size: 16,
+            scale: 3,
+        };
+        assert_eq!(small.window_size(), (1344, 1728));
+        assert_eq!(small.zoom(), 2.0);
+    }
+}
@@ -0,0 +1,62 @@
+//! The game itself: board, entities, simulation and the SDL and terminal frontends.
+//!
+//! The binary in `main.rs` only parses the command line and runs the window loop, so the
+//! simulation can also be driven from the benches.
+
+use sdl2::pixels::Color;
+
+pub mod assets;
+pub mod audio;
+pub mod bench_sim;
+pub mod board;
+pub mod config;
+pub mod entity;
+pub mod error;
+pub mod font;
+pub mod game;
+pub mod gamepad;
+pub mod hot_reload;
+pub mod hud;
+pub mod layout;
+pub mod locale;
+pub mod maze_file;
+pub mod maze_gen;
+pub mod mouse_steer;
+pub mod netplay;
+pub mod pacing;
+pub mod palette;
+pub mod position;
+pub mod profiling;
+pub mod renderer;
+pub mod settings;
+pub mod shapes;
+pub mod simulate;
+pub mod snapshot_diff;
+pub mod texture;
+pub mod theme;
+pub mod touch;
+#[cfg(not(target_os = "emscripten"))]
+pub mod tui;
+pub mod wall_tiles;
+#[cfg(target_os = "emscripten")]
+pub mod web;
+
+pub const BOARD_WIDTH: usize = 28;
+pub const BOARD_HEIGHT: usize = 36;
+pub const BLOCK_SIZE_24: u32 = 24;
+pub const BLOCK_SIZE_32: u32 = 32;
+/// Size of the playfield in board pixels; the layout scales it to the window
+pub const SCREEN_WIDTH: u32 = BOARD_WIDTH as u32 * BLOCK_SIZE_24;
+pub const SCREEN_HEIGHT: u32 = BOARD_HEIGHT as u32 * BLOCK_SIZE_24;
+pub const TARGET_FPS: u32 = 60;
+/// Simulated time covered by a single `Game::update` call
+pub const TICK_MS: u32 = 1000 / TARGET_FPS;
+
+pub const BLACK: Color = Color::RGB(0, 0, 0);
+pub const WHITE: Color = Color::RGB(255, 255, 255);
+pub const YELLOW: Color = Color::RGB(255, 255, 0);
+pub const RED: Color = Color::RGB(255, 0, 0);
+pub const CYAN: Color = Color::RGB(0, 192, 255);
+pub const PINK: Color = Color::RGB(255, 192, 203);
+pub const ORANGE: Color = Color::RGB(255, 128, 0);
+pub const BLUE: Color = Color::RGB(0, 0, 255);
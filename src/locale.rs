@@ -0,0 +1,245 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Order of the fields in a short date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// Number and date conventions for on-screen text.
+///
+/// Picked from a POSIX locale tag such as `de_DE.UTF-8`; unknown tags and the `C`
+/// locale print plain digits and ISO dates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Locale {
+    pub thousands_separator: Option<char>,
+    pub date_order: DateOrder,
+    pub date_separator: char,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Locale {
+    /// The `C` locale
+    pub fn new() -> Self {
+        Locale {
+            thousands_separator: None,
+            date_order: DateOrder::YearMonthDay,
+            date_separator: '-',
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> Self {
+        // Strip encoding and modifier, e.g. "de_DE.UTF-8@euro" -> "de_DE"
+        let tag = tag.split(['.', '@']).next().unwrap_or("");
+        let (language, region) = match tag.split_once(['_', '-']) {
+            Some((language, region)) => (language, region),
+            None => (tag, ""),
+        };
+
+        let (thousands_separator, date_order, date_separator) = match (language, region) {
+            ("en", "US") | ("en", "") => (Some(','), DateOrder::MonthDayYear, '/'),
+            ("en", _) => (Some(','), DateOrder::DayMonthYear, '/'),
+            ("de" | "da" | "nl" | "it" | "es" | "pt" | "tr" | "id", _) => {
+                (Some('.'), DateOrder::DayMonthYear, '.')
+            }
+            ("fr" | "ru" | "pl" | "cs" | "uk" | "fi" | "nb" | "no", _) => {
+                (Some(' '), DateOrder::DayMonthYear, '.')
+            }
+            ("sv", _) => (Some(' '), DateOrder::YearMonthDay, '-'),
+            ("ja" | "zh" | "ko", _) => (Some(','), DateOrder::YearMonthDay, '/'),
+            _ => return Locale::new(),
+        };
+
+        Locale {
+            thousands_separator,
+            date_order,
+            date_separator,
+        }
+    }
+
+    /// Read the locale from the environment the way libc does for numbers
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map(|tag| Locale::from_tag(&tag))
+            .unwrap_or_else(Locale::new)
+    }
+
+    pub fn format_number(&self, value: u32) -> String {
+        let digits = value.to_string();
+        let separator = match self.thousands_separator {
+            Some(separator) => separator,
+            None => return digits,
+        };
+
+        let mut text = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                text.push(separator);
+            }
+            text.push(digit);
+        }
+        text
+    }
+
+    pub fn format_date(&self, date: Date) -> String {
+        let day = format!("{:02}", date.day);
+        let month = format!("{:02}", date.month);
+        let year = date.year.to_string();
+        let fields = match self.date_order {
+            DateOrder::DayMonthYear => [day, month, year],
+            DateOrder::MonthDayYear => [month, day, year],
+            DateOrder::YearMonthDay => [year, month, day],
+        };
+        fields.join(&self.date_separator.to_string())
+    }
+}
+
+/// A calendar date in UTC
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Date::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    /// `YYYY-MM-DD`, independent of locale
+    pub fn to_iso(self) -> String {
+        Locale::new().format_date(self)
+    }
+
+    pub fn parse_iso(text: &str) -> Option<Self> {
+        let mut fields = text.splitn(3, '-');
+        let year = fields.next()?.parse().ok()?;
+        let month = fields.next()?.parse().ok()?;
+        let day = fields.next()?.parse().ok()?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Civil date for a day count since 1970-01-01 (Howard Hinnant's algorithm)
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+        Date { year, month, day }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_grouping() {
+        assert_eq!(Locale::new().format_number(1234567), "1234567");
+        assert_eq!(
+            Locale::from_tag("en_US.UTF-8").format_number(1234567),
+            "1,234,567"
+        );
+        assert_eq!(
+            Locale::from_tag("de_DE").format_number(1234567),
+            "1.234.567"
+        );
+        assert_eq!(Locale::from_tag("fr_FR").format_number(12345), "12 345");
+        assert_eq!(Locale::from_tag("en_GB").format_number(999), "999");
+        assert_eq!(Locale::from_tag("en_GB").format_number(0), "0");
+    }
+
+    #[test]
+    fn test_date_formats() {
+        let date = Date {
+            year: 2024,
+            month: 3,
+            day: 9,
+        };
+        assert_eq!(Locale::new().format_date(date), "2024-03-09");
+        assert_eq!(Locale::from_tag("en_US").format_date(date), "03/09/2024");
+        assert_eq!(Locale::from_tag("en_GB").format_date(date), "09/03/2024");
+        assert_eq!(
+            Locale::from_tag("de_DE.UTF-8@euro").format_date(date),
+            "09.03.2024"
+        );
+        assert_eq!(Locale::from_tag("ja_JP").format_date(date), "2024/03/09");
+    }
+
+    #[test]
+    fn test_unknown_tags_fall_back_to_c() {
+        assert_eq!(Locale::from_tag("C"), Locale::new());
+        assert_eq!(Locale::from_tag("POSIX"), Locale::new());
+        assert_eq!(Locale::from_tag(""), Locale::new());
+    }
+
+    #[test]
+    fn test_iso_dates() {
+        let date = Date {
+            year: 2024,
+            month: 3,
+            day: 9,
+        };
+        assert_eq!(date.to_iso(), "2024-03-09");
+        assert_eq!(Date::parse_iso("2024-03-09"), Some(date));
+        assert_eq!(Date::parse_iso("2024-13-01"), None);
+        assert_eq!(Date::parse_iso("yesterday"), None);
+    }
+
+    #[test]
+    fn test_civil_dates() {
+        assert_eq!(
+            Date::from_days_since_epoch(0),
+            Date {
+                year: 1970,
+                month: 1,
+                day: 1
+            }
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(19_782),
+            Date {
+                year: 2024,
+                month: 2,
+                day: 29
+            }
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(-1),
+            Date {
+                year: 1969,
+                month: 12,
+                day: 31
+            }
+        );
+    }
+}
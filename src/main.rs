@@ -1,94 +1,556 @@
-use sdl2::event::Event;
+use sdl2::controller::GameController;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
-use std::time::{Duration, Instant};
-
-mod board;
-mod entity;
-mod game;
-mod position;
-mod texture;
-
-use game::Game;
-
-pub const BOARD_WIDTH: usize = 28;
-pub const BOARD_HEIGHT: usize = 36;
-pub const BLOCK_SIZE_24: u32 = 24;
-pub const BLOCK_SIZE_32: u32 = 32;
-pub const WINDOW_WIDTH: u32 = BOARD_WIDTH as u32 * BLOCK_SIZE_24;
-pub const WINDOW_HEIGHT: u32 = BOARD_HEIGHT as u32 * BLOCK_SIZE_24;
-
-pub const BLACK: Color = Color::RGB(0, 0, 0);
-pub const WHITE: Color = Color::RGB(255, 255, 255);
-pub const YELLOW: Color = Color::RGB(255, 255, 0);
-pub const RED: Color = Color::RGB(255, 0, 0);
-pub const CYAN: Color = Color::RGB(0, 192, 255);
-pub const PINK: Color = Color::RGB(255, 192, 203);
-pub const ORANGE: Color = Color::RGB(255, 128, 0);
-pub const BLUE: Color = Color::RGB(0, 0, 255);
+use std::time::Instant;
+
+use rust_pacman::config::{Command, Config, Netplay};
+use rust_pacman::error::PacmanError;
+use rust_pacman::font::Fonts;
+use rust_pacman::game::replay::Replay;
+use rust_pacman::game::rng::GameRng;
+use rust_pacman::game::snapshot::{Snapshot, SAVE_GAME_PATH};
+use rust_pacman::game::state::GameState;
+use rust_pacman::game::Game;
+use rust_pacman::hot_reload::{AssetKind, AssetWatcher};
+use rust_pacman::mouse_steer::MouseSteering;
+use rust_pacman::netplay::NetSession;
+use rust_pacman::pacing::Pacing;
+use rust_pacman::profiling::{profile_scope, Profiler};
+use rust_pacman::settings::{Settings, SETTINGS_PATH};
+use rust_pacman::theme::Theme;
+use rust_pacman::touch::{Gesture, TouchControls};
+#[cfg(not(target_os = "emscripten"))]
+use rust_pacman::tui;
+#[cfg(target_os = "emscripten")]
+use rust_pacman::web;
+use rust_pacman::{
+    audio, bench_sim, gamepad, maze_file, pacing, simulate, snapshot_diff, touch, BLACK,
+};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
+    // Flags on the command line win over the saved settings. A file that cannot be read is
+    // left as it is rather than overwritten with defaults on the way out.
+    let saved_settings = match Settings::load(SETTINGS_PATH) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            println!(
+                "Ignoring saved settings, which will be left untouched: {}",
+                e
+            );
+            None
+        }
+    };
+    let mut base = Config::new();
+    if let Some(ref settings) = saved_settings {
+        settings.apply(&mut base);
+    }
+    let mut config = Config::from_args_over(std::env::args(), base)?;
+    // Replays override the seed; a maze dropped on the window goes back to the one asked for
+    let requested_seed = config.seed;
+    let theme = match config.theme {
+        Some(ref name) => Theme::load(name)?,
+        None => Theme::new(),
+    };
+
+    if let Command::SnapshotDiff {
+        ref before,
+        ref after,
+        ref output,
+    } = config.command
+    {
+        return snapshot_diff::run(before, after, output, &config, &theme);
+    }
+    if let Command::Simulate(ref options) = config.command {
+        return simulate::run(options, &config, &theme);
+    }
+    if let Command::BenchSim(ticks) = config.command {
+        return bench_sim::run(ticks, &config, &theme);
+    }
+    #[cfg(not(target_os = "emscripten"))]
+    if config.command == Command::Tui {
+        return tui::run(&config, &theme);
+    }
+
+    // A replay only reproduces the run when played with the seed it was recorded with
+    let replay = match config.replay {
+        Some(ref path) => {
+            let replay = Replay::load(path)?;
+            replay.check_compatible(&config)?;
+            config.seed = Some(replay.metadata.seed);
+            Some(replay)
+        }
+        None => None,
+    };
+
+    // Both sides of a versus game have to play with the seed the host picked
+    let mut netplay = match config.netplay.clone() {
+        Some(Netplay::Host(port)) => {
+            let seed = config
+                .seed
+                .unwrap_or_else(|| GameRng::from_entropy().get_seed());
+            config.seed = Some(seed);
+            Some(NetSession::host(port, &config, seed)?)
+        }
+        Some(Netplay::Join(address)) => {
+            let (session, seed) = NetSession::join(&address, &config)?;
+            config.seed = Some(seed);
+            Some(session)
+        }
+        None => None,
+    };
+
+    let sdl_context = sdl2::init().map_err(PacmanError::SdlInit)?;
+    let video_subsystem = sdl_context.video().map_err(PacmanError::SdlInit)?;
 
-    let _image_context = sdl2::image::init(sdl2::image::InitFlag::PNG)?;
+    let _image_context =
+        sdl2::image::init(sdl2::image::InitFlag::PNG).map_err(PacmanError::SdlInit)?;
 
-    let ttf_context =
-        sdl2::ttf::init().map_err(|e| format!("SDL2_TTF initialization failed: {}", e))?;
+    let fonts = Fonts::new(config.bitmap_font)?;
 
+    let (window_width, window_height) = config.layout.window_size();
     let window = video_subsystem
-        .window("Pacman", WINDOW_WIDTH, WINDOW_HEIGHT)
+        .window("Pacman", window_width, window_height)
         .position_centered()
         .build()?;
 
-    let mut canvas = window.into_canvas().present_vsync().build()?;
+    let mut canvas_builder = window.into_canvas();
+    if config.vsync {
+        canvas_builder = canvas_builder.present_vsync();
+    }
+    let mut canvas = canvas_builder.build()?;
+    config.layout.apply(&mut canvas)?;
     let texture_creator = canvas.texture_creator();
 
-    let font = ttf_context.load_font("assets/emulogic.ttf", 24)?;
+    let mut font = fonts.load(&theme)?;
+
+    let mut game = Game::new(&texture_creator, &fonts, &config, &theme)?;
+    // What the run started with, so only what is toggled in game gets saved
+    let started_settings = game.settings();
+    println!("Seed: {}", game.get_seed());
+    if let Some(replay) = replay {
+        game.start_playback(replay);
+    } else if let Some(ref session) = netplay {
+        game.start_netplay();
+        println!("Versus game: you are {}", session.get_role().character());
+    } else if config.resume {
+        game.resume_game(&Snapshot::load(SAVE_GAME_PATH)?)?;
+        println!("Resumed game from {}", SAVE_GAME_PATH);
+    } else if !config.bot {
+        // The bot's soak tests play straight away; everyone else picks a run first
+        game.open_menu();
+    }
 
-    let mut game = Game::new(&texture_creator, &ttf_context)?;
+    // The first connected gamepad plays Pac-Man, Blinky in co-op, or our own side in versus
+    let mut gamepad = open_gamepad(&sdl_context, config.coop)?;
+    let mut touch = config.touch.then(|| TouchControls::new(&config.layout));
+    let mut steering = config
+        .mouse_steer
+        .then(|| MouseSteering::new(&config.layout));
 
+    // Music and effects are optional; the game plays the same without an audio device
+    let mut audio = match audio::Audio::open(&sdl_context, &theme) {
+        Ok(audio) => Some(audio),
+        Err(e) => {
+            println!("Playing without sound: {}", e);
+            None
+        }
+    };
+
+    let _profiler = Profiler::start();
     let mut event_pump = sdl_context.event_pump()?;
-    let mut running = true;
-    let target_fps = 60;
-    let frame_duration = Duration::from_millis(1000 / target_fps);
+    let mut pacing = Pacing::new(config.debug, config.frame_rate);
+    let frame_duration = pacing::frame_duration(config.game_speed, config.frame_rate);
+    let precise_wait = !config.vsync;
+    if config.game_speed < 100 {
+        println!("Game speed: {}%", config.game_speed);
+    }
+    let mut previous_frame_start = Instant::now();
+    // Debug builds pick up edited images, fonts and mazes without a restart
+    let mut watcher = if cfg!(debug_assertions) {
+        Some(AssetWatcher::for_game(&theme, config.maze_path.as_deref()))
+    } else {
+        None
+    };
 
-    while running {
-        let frame_start = Instant::now();
+    // One frame of input, updates and drawing, returning false once the player has quit
+    let mut frame = |frame_start: Instant| -> Result<bool, Box<dyn std::error::Error>> {
+        profile_scope!("frame");
+        let mut running = true;
 
         for event in event_pump.poll_iter() {
+            if let Some(gesture) = touch.as_mut().and_then(|touch| touch.handle_event(&event)) {
+                let keycode = touch::key_for(gesture, game.get_game_state());
+                press_button(keycode, &mut game, &mut netplay, &config);
+                continue;
+            }
+            if let Some(ref mut steering) = steering {
+                steering.handle_event(&event, game.get_map());
+            }
+            // On-screen prompts name the keys or buttons of whatever was pressed last
+            match event {
+                Event::KeyDown { .. } => game.show_gamepad_prompts(false),
+                Event::ControllerButtonDown { .. } => game.show_gamepad_prompts(true),
+                _ => {}
+            }
             match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
+                Event::Quit { .. } => running = false,
+                Event::Window {
+                    win_event: WindowEvent::FocusLost,
+                    ..
+                } if config.pause_on_focus_loss => game.pause(),
+                // Escape closes the debug console first, and asks before abandoning a run
+                Event::KeyDown {
                     keycode: Some(Keycode::Escape),
                     ..
-                } => {
-                    running = false;
+                } if !game.is_console_open() => {
+                    running = !game.request_quit();
                 }
+                // Both sides would have to agree to a rematch, so versus games end here
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return | Keycode::KpEnter),
+                    ..
+                } if netplay.is_none() && !game.is_console_open() => {
+                    game.press_start(&config);
+                }
+                // Debug keys are consumed before the game sees them
                 Event::KeyDown {
                     keycode: Some(keycode),
                     ..
+                } if !pacing.handle_input(keycode) => match netplay {
+                    Some(ref mut session) => match session.action_for(keycode) {
+                        Some(action) => session.queue_local(action),
+                        None => game.handle_input(keycode),
+                    },
+                    None => game.handle_input(keycode),
+                },
+                Event::TextInput { text, .. } => game.type_text(&text),
+                Event::KeyUp {
+                    keycode: Some(keycode),
+                    ..
                 } => {
-                    game.handle_input(keycode);
+                    game.handle_key_up(keycode);
+                }
+                Event::DropFile { filename, .. } => {
+                    if netplay.is_some() {
+                        println!("Ignoring {}: nothing can be loaded mid-versus", filename);
+                        continue;
+                    }
+                    match open_dropped_file(&filename, &mut config, requested_seed) {
+                        Ok(replay) => {
+                            game.settings().apply(&mut config);
+                            game.shutdown()?;
+                            game = Game::new(&texture_creator, &fonts, &config, &theme)?;
+                            println!("Loaded {}; seed: {}", filename, game.get_seed());
+                            if watcher.is_some() {
+                                watcher = Some(AssetWatcher::for_game(
+                                    &theme,
+                                    config.maze_path.as_deref(),
+                                ));
+                            }
+                            match replay {
+                                Some(replay) => {
+                                    game.start_playback(replay);
+                                    game.show_toast("REPLAY LOADED");
+                                }
+                                None => game.show_toast("MAZE LOADED"),
+                            }
+                        }
+                        Err(e) => {
+                            println!("Could not load {}: {}", filename, e);
+                            game.show_toast("LOAD FAILED");
+                        }
+                    }
+                }
+                Event::ControllerButtonDown { button, .. } => {
+                    if let Some(keycode) = gamepad::key_for(button, config.coop) {
+                        press_button(keycode, &mut game, &mut netplay, &config);
+                    }
+                }
+                Event::ControllerDeviceAdded { .. } if gamepad.is_none() => {
+                    gamepad = open_gamepad(&sdl_context, config.coop)?;
+                    if gamepad.is_some() {
+                        game.controller_reconnected();
+                    }
+                }
+                Event::ControllerDeviceRemoved { which, .. }
+                    if gamepad.as_ref().map(GameController::instance_id) == Some(which) =>
+                {
+                    println!("Gamepad disconnected");
+                    gamepad = None;
+                    game.controller_disconnected();
                 }
                 _ => {}
             }
         }
 
-        game.update();
+        let changed = watcher.as_mut().map(AssetWatcher::poll).unwrap_or_default();
+        if changed.contains(&AssetKind::Font) {
+            match fonts.load(&theme) {
+                Ok(reloaded) => font = reloaded,
+                Err(e) => println!("Could not reload the font: {}", e),
+            }
+        }
+        if changed.contains(&AssetKind::Maze) && netplay.is_some() {
+            println!("Ignoring the maze edit: nothing can be loaded mid-versus");
+        } else if changed.contains(&AssetKind::Maze) {
+            let path = config.maze_path.clone().unwrap_or_default();
+            match maze_file::load(&path) {
+                Ok(sketch) => {
+                    config.custom_maze = Some(sketch);
+                    config.seed = requested_seed;
+                    game.settings().apply(&mut config);
+                    game.shutdown()?;
+                    game = Game::new(&texture_creator, &fonts, &config, &theme)?;
+                    println!("Reloaded {}; seed: {}", path, game.get_seed());
+                    game.show_toast("MAZE RELOADED");
+                }
+                Err(e) => {
+                    println!("Could not reload {}: {}", path, e);
+                    game.show_toast("RELOAD FAILED");
+                }
+            }
+        } else if !changed.is_empty() {
+            match game.reload_textures(&texture_creator, &fonts) {
+                Ok(()) => game.show_toast("ASSETS RELOADED"),
+                Err(e) => {
+                    println!("Could not reload assets: {}", e);
+                    game.show_toast("RELOAD FAILED");
+                }
+            }
+        }
+
+        // Mouse steering turns like the arrow keys would, so its turns are recorded as usual
+        if game.get_game_state() == GameState::Playing {
+            let turn = steering
+                .as_mut()
+                .and_then(|steering| steering.next_turn(&game.bot_view()));
+            if let Some(direction) = turn {
+                let keycode = touch::key_for(Gesture::Turn(direction), GameState::Playing);
+                press_button(keycode, &mut game, &mut netplay, &config);
+            }
+        }
+
+        // Slow motion and freezing run fewer updates per drawn frame, but a game speed below
+        // 100 stretches the whole frame instead, drawing included
+        let update_start = Instant::now();
+        for _ in 0..pacing.updates_for_frame() {
+            if let Some(ref mut session) = netplay {
+                match session.exchange(game.get_tick()) {
+                    Ok(actions) => game.apply_inputs(&actions),
+                    Err(e) => {
+                        println!("Versus game ended: {}", e);
+                        running = false;
+                        break;
+                    }
+                }
+            }
+            game.update();
+        }
+        let update_time = update_start.elapsed();
+
+        if let Some(ref mut audio) = audio {
+            for sound in game.take_sounds() {
+                audio.play(sound);
+            }
+            audio.play_music(game.music_track());
+            audio.update();
+        }
 
+        let draw_start = Instant::now();
         canvas.set_draw_color(BLACK);
         canvas.clear();
 
         game.draw(&mut canvas, &texture_creator, &font)?;
+        if let Some(ref touch) = touch {
+            touch.draw(&mut canvas)?;
+        }
+        if let Some(ref steering) = steering {
+            steering.draw(&mut canvas)?;
+        }
 
         canvas.present();
+        game.record_frame_timing(
+            frame_start - previous_frame_start,
+            update_time,
+            draw_start.elapsed(),
+        );
+        previous_frame_start = frame_start;
+        Ok(running)
+    };
 
-        let frame_time = frame_start.elapsed();
-        if frame_time < frame_duration {
-            std::thread::sleep(frame_duration - frame_time);
+    // In a browser the page's animation frames set the pace instead
+    #[cfg(target_os = "emscripten")]
+    let looped = {
+        web::run_frames(|| match frame(Instant::now()) {
+            Ok(running) => running,
+            Err(e) => {
+                println!("Stopped: {}", e);
+                false
+            }
+        });
+        Ok(())
+    };
+    #[cfg(not(target_os = "emscripten"))]
+    let looped = loop {
+        let frame_start = Instant::now();
+        match frame(frame_start) {
+            Ok(true) => {}
+            Ok(false) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+        // Vsync already holds each present to the display, so a plain sleep is close enough
+        pacing::wait_until(frame_start + frame_duration, precise_wait);
+    };
+
+    finish(
+        &mut game,
+        looped,
+        saved_settings,
+        &started_settings,
+        SETTINGS_PATH,
+    )
+}
+
+/// Save everything on the way out, however the window loop ended.
+///
+/// The game shuts down and the toggled settings are written even when the loop failed or
+/// the other save did; the first error is returned, the loop's before either save's.
+fn finish(
+    game: &mut Game,
+    looped: Result<(), Box<dyn std::error::Error>>,
+    saved_settings: Option<Settings>,
+    started_settings: &Settings,
+    settings_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let shutdown = game.shutdown();
+    let settings = match saved_settings {
+        Some(settings) => {
+            let toggled = settings.with_changes(started_settings, &game.settings());
+            if toggled != settings {
+                toggled.save(settings_path).map_err(Into::into)
+            } else {
+                Ok(())
+            }
         }
+        None => Ok(()),
+    };
+    looped.and(shutdown).and(settings)
+}
+
+/// Set `config` up for a file dropped on the window: a replay to play back, or a maze to play.
+///
+/// Returns the replay, if it was one. A maze starts a fresh game with the seed asked for on the
+/// command line.
+fn open_dropped_file(
+    path: &str,
+    config: &mut Config,
+    requested_seed: Option<u64>,
+) -> Result<Option<Replay>, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)?;
+    if Replay::is_replay(&text) {
+        let replay = Replay::parse(&text)?;
+        replay.check_compatible(config)?;
+        config.seed = Some(replay.metadata.seed);
+        return Ok(Some(replay));
     }
 
-    Ok(())
+    config.custom_maze = Some(maze_file::load(path)?);
+    config.maze_path = Some(path.to_string());
+    config.seed = requested_seed;
+    Ok(None)
+}
+
+/// Pass on the key a gamepad button or touch gesture stands in for
+fn press_button(
+    keycode: Keycode,
+    game: &mut Game,
+    netplay: &mut Option<NetSession>,
+    config: &Config,
+) {
+    match netplay {
+        Some(ref mut session) => {
+            if let Some(action) = session.action_for(keycode) {
+                session.queue_local(action);
+            }
+        }
+        None if keycode == Keycode::Return && !game.is_console_open() => {
+            game.press_start(config);
+        }
+        None => game.handle_input(keycode),
+    }
+}
+
+/// Open the first attached gamepad, if there is one
+fn open_gamepad(
+    sdl_context: &sdl2::Sdl,
+    coop: bool,
+) -> Result<Option<GameController>, Box<dyn std::error::Error>> {
+    let subsystem = sdl_context.game_controller()?;
+    for index in 0..subsystem.num_joysticks()? {
+        if subsystem.is_game_controller(index) {
+            let controller = subsystem.open(index)?;
+            let player = if coop { "Player 2 gamepad" } else { "Gamepad" };
+            println!("{}: {}", player, controller.name());
+            return Ok(Some(controller));
+        }
+    }
+    if coop {
+        println!("No gamepad found; player 2 can use IJKL");
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_failed_frame_still_saves_everything() {
+        let temp = |name: &str| {
+            let path = std::env::temp_dir().join(format!("{}_{}.txt", name, std::process::id()));
+            path.to_str().unwrap().to_string()
+        };
+        let save_path = temp("finish_save");
+        let replay_path = temp("finish_replay");
+        let settings_path = temp("finish_settings");
+
+        let mut config = Config::new();
+        config.seed = Some(3);
+        let mut game = Game::headless(&config, &Theme::new());
+        game.reset(&config);
+        game.set_save_game_path(&save_path);
+        game.set_best_replay_path(&replay_path);
+        // Out of the countdown and into the first pellets, so the run has a score to keep
+        for _ in 0..300 {
+            game.update();
+        }
+        assert!(game.get_score() > 0);
+        // Music was switched off in game
+        let saved = Settings {
+            music: !game.settings().music,
+            ..game.settings()
+        };
+
+        let failed = Err("the frame failed".into());
+        let finished = finish(
+            &mut game,
+            failed,
+            Some(saved.clone()),
+            &saved,
+            &settings_path,
+        );
+        assert_eq!(finished.unwrap_err().to_string(), "the frame failed");
+        assert!(Snapshot::load(&save_path).is_ok());
+        assert_eq!(
+            Replay::load(&replay_path).unwrap().metadata.final_score,
+            game.get_score()
+        );
+        assert_eq!(
+            Settings::load(&settings_path).unwrap().music,
+            game.settings().music
+        );
+        for path in [save_path, replay_path, settings_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
 }
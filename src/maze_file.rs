@@ -0,0 +1,394 @@
+use crate::board::TUNNEL_ROWS;
+use crate::error::PacmanError;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+use std::path::Path;
+
+/// Characters a maze may use, as in `Board::CHAR_BOARD`
+const MAZE_CHARS: &str = " #.o=0123456789|";
+/// Start markers every maze needs: Pac-Man, then Blinky, Inky, Pinky and Clyde
+const REQUIRED_MARKERS: &str = "01234";
+/// Unreachable pellets listed one by one before the rest are just counted
+const MAX_LISTED_PELLETS: usize = 5;
+
+/// Read a custom maze for `Board::set_custom_maze`.
+///
+/// A `.txt` maze has one line per board row. A `.ron` maze lists the rows as quoted strings,
+/// e.g. `Maze(rows: ["#####", ...])`; anything outside the quotes is ignored. Either way there
+/// are 36 rows of up to 28 characters, short rows being padded with spaces. Ghosts still head
+/// for the standard ghost house, so custom mazes should keep it where `CHAR_BOARD` has it.
+/// A `|` in the outer wall on rows 4 and 34 of the same column opens a vertical tunnel, and
+/// the two tiles of each capital letter are a pair of teleporters.
+pub fn load(path: &str) -> Result<String, PacmanError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| PacmanError::MapParse(format!("{}: {}", path, e)))?;
+    let quoted = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ron"));
+    parse(&text, quoted).map_err(|e| PacmanError::MapParse(format!("{}: {}", path, e)))
+}
+
+/// Turn maze rows into a sketch of `BOARD_WIDTH * BOARD_HEIGHT` characters.
+///
+/// Every problem found is reported with the row and column it is at, counting from 1.
+pub fn parse(text: &str, quoted: bool) -> Result<String, PacmanError> {
+    let rows: Vec<&str> = if quoted {
+        text.split('"').skip(1).step_by(2).collect()
+    } else {
+        text.lines().collect()
+    };
+    if rows.len() != BOARD_HEIGHT {
+        return Err(PacmanError::MapParse(format!(
+            "expected {} rows, found {}",
+            BOARD_HEIGHT,
+            rows.len()
+        )));
+    }
+
+    let mut sketch = String::with_capacity(BOARD_WIDTH * BOARD_HEIGHT);
+    for (index, row) in rows.iter().enumerate() {
+        let row = row.trim_end_matches('\r');
+        if row.chars().count() > BOARD_WIDTH {
+            return Err(PacmanError::MapParse(format!(
+                "row {} is wider than {} tiles",
+                index + 1,
+                BOARD_WIDTH
+            )));
+        }
+        if let Some((column, c)) = row
+            .chars()
+            .enumerate()
+            .find(|&(_, c)| !MAZE_CHARS.contains(c) && !c.is_ascii_uppercase())
+        {
+            return Err(PacmanError::MapParse(format!(
+                "row {}, column {}: unknown tile '{}'",
+                index + 1,
+                column + 1,
+                c
+            )));
+        }
+        sketch.push_str(&format!("{:width$}", row, width = BOARD_WIDTH));
+    }
+
+    let problems = validate(&sketch);
+    if !problems.is_empty() {
+        return Err(PacmanError::MapParse(problems.join("; ")));
+    }
+    Ok(sketch)
+}
+
+/// `row R, column C`, counting from 1, for a tile index into the sketch
+fn tile_name(index: usize) -> String {
+    format!(
+        "row {}, column {}",
+        index / BOARD_WIDTH + 1,
+        index % BOARD_WIDTH + 1
+    )
+}
+
+/// Everything that would stop the sketch from playing properly
+fn validate(sketch: &str) -> Vec<String> {
+    let tiles: Vec<char> = sketch.chars().collect();
+    let mut problems = Vec::new();
+
+    // Markers '5' to '9' place any extra ghosts, which start in the house without one
+    for marker in '0'..='9' {
+        let found: Vec<String> = (0..tiles.len())
+            .filter(|&index| tiles[index] == marker)
+            .map(tile_name)
+            .collect();
+        match found.len() {
+            0 if REQUIRED_MARKERS.contains(marker) => {
+                problems.push(format!("start marker '{}' is missing", marker))
+            }
+            0 | 1 => {}
+            _ => problems.push(format!(
+                "start marker '{}' appears more than once, at {}",
+                marker,
+                found.join(" and ")
+            )),
+        }
+    }
+
+    let (top, bottom) = TUNNEL_ROWS;
+    for index in (0..tiles.len()).filter(|&index| tiles[index] == '|') {
+        let (row, column) = (index / BOARD_WIDTH, index % BOARD_WIDTH);
+        let other = if row == top { bottom } else { top };
+        if row != top && row != bottom {
+            problems.push(format!(
+                "{}: vertical tunnels ('|') belong in row {} or {}",
+                tile_name(index),
+                top + 1,
+                bottom + 1
+            ));
+        } else if tiles[other * BOARD_WIDTH + column] != '|' {
+            problems.push(format!(
+                "{}: vertical tunnel has no other end at {}",
+                tile_name(index),
+                tile_name(other * BOARD_WIDTH + column)
+            ));
+        }
+    }
+
+    for pair in 'A'..='Z' {
+        let found: Vec<String> = (0..tiles.len())
+            .filter(|&index| tiles[index] == pair)
+            .map(tile_name)
+            .collect();
+        if !found.is_empty() && found.len() != 2 {
+            problems.push(format!(
+                "teleporter '{}' needs exactly two tiles, found {} at {}",
+                pair,
+                found.len(),
+                found.join(" and ")
+            ));
+        }
+    }
+
+    let doors = regions(&tiles, |tile| tile == '=');
+    match doors.len() {
+        0 => problems.push("the ghost house needs a door ('=')".to_string()),
+        1 => {}
+        _ => {
+            let starts: Vec<String> = doors.iter().map(|door| tile_name(door[0])).collect();
+            problems.push(format!(
+                "the ghost house needs exactly one door, found {} at {}",
+                doors.len(),
+                starts.join(" and ")
+            ));
+        }
+    }
+
+    let pellets: Vec<usize> = (0..tiles.len())
+        .filter(|&index| matches!(tiles[index], '.' | 'o'))
+        .collect();
+    if pellets.is_empty() {
+        problems.push("the maze has nothing to eat".to_string());
+    } else if let Some(start) = tiles.iter().position(|&tile| tile == '0') {
+        let reached = regions(&tiles, |tile| !matches!(tile, '#' | '='))
+            .into_iter()
+            .find(|region| region.contains(&start))
+            .unwrap_or_default();
+        let stranded: Vec<usize> = pellets
+            .into_iter()
+            .filter(|index| !reached.contains(index))
+            .collect();
+        for &index in stranded.iter().take(MAX_LISTED_PELLETS) {
+            problems.push(format!(
+                "{}: pellet cannot be reached from Pac-Man's start",
+                tile_name(index)
+            ));
+        }
+        if stranded.len() > MAX_LISTED_PELLETS {
+            problems.push(format!(
+                "{} more unreachable pellets",
+                stranded.len() - MAX_LISTED_PELLETS
+            ));
+        }
+    }
+    problems
+}
+
+/// Groups of neighbouring tiles that pass `keep`, each listed from its first tile.
+///
+/// Rows wrap from one side to the other, as they do through the tunnels, and a vertical tunnel
+/// leads from the top of the maze to the bottom instead of into the rows around it. Each
+/// teleporter leads to its twin.
+fn regions(tiles: &[char], keep: impl Fn(char) -> bool) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; tiles.len()];
+    let mut found = Vec::new();
+    for first in 0..tiles.len() {
+        if seen[first] || !keep(tiles[first]) {
+            continue;
+        }
+        seen[first] = true;
+        let mut region = vec![first];
+        let mut next = 0;
+        while next < region.len() {
+            let index = region[next];
+            next += 1;
+            let (row, column) = (index / BOARD_WIDTH, index % BOARD_WIDTH);
+            let mut neighbours = vec![
+                row * BOARD_WIDTH + (column + BOARD_WIDTH - 1) % BOARD_WIDTH,
+                row * BOARD_WIDTH + (column + 1) % BOARD_WIDTH,
+            ];
+            let (top, bottom) = TUNNEL_ROWS;
+            if tiles[index] == '|' {
+                let other = if row == top { bottom } else { top };
+                neighbours.push(other * BOARD_WIDTH + column);
+            }
+            if tiles[index].is_ascii_uppercase() {
+                neighbours.extend(
+                    (0..tiles.len())
+                        .filter(|&other| other != index && tiles[other] == tiles[index]),
+                );
+            }
+            if row > 0 && row != top {
+                neighbours.push(index - BOARD_WIDTH);
+            }
+            if row + 1 < BOARD_HEIGHT && row != bottom {
+                neighbours.push(index + BOARD_WIDTH);
+            }
+            for neighbour in neighbours {
+                if !seen[neighbour] && keep(tiles[neighbour]) {
+                    seen[neighbour] = true;
+                    region.push(neighbour);
+                }
+            }
+        }
+        found.push(region);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BlockType, Board};
+
+    fn standard_rows() -> Vec<String> {
+        let chars: Vec<char> = Board::CHAR_BOARD.chars().collect();
+        chars
+            .chunks(BOARD_WIDTH)
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    /// A maze file as an editor would save it, with a newline after every row
+    fn text_file(rows: &[String], newline: &str) -> String {
+        rows.iter()
+            .map(|row| format!("{}{}", row, newline))
+            .collect()
+    }
+
+    #[test]
+    fn test_text_and_ron_mazes() {
+        let rows = standard_rows();
+        let text = text_file(&rows, "\r\n");
+        assert_eq!(parse(&text, false).unwrap(), Board::CHAR_BOARD);
+
+        let quoted: Vec<String> = rows.iter().map(|row| format!("\"{}\"", row)).collect();
+        let ron = format!(
+            "Maze(\n    rows: [\n        {},\n    ],\n)\n",
+            quoted.join(",\n        ")
+        );
+        assert_eq!(parse(&ron, true).unwrap(), Board::CHAR_BOARD);
+    }
+
+    #[test]
+    fn test_rejects_broken_mazes() {
+        let rows = standard_rows();
+        assert!(parse(&text_file(&rows[..35], "\n"), false).is_err());
+
+        let mut wide = rows.clone();
+        wide[4].push_str("###");
+        assert!(parse(&text_file(&wide, "\n"), false).is_err());
+
+        let mut unknown = rows.clone();
+        unknown[4] = unknown[4].replace('.', "*");
+        assert!(parse(&text_file(&unknown, "\n"), false).is_err());
+
+        let mut twins = rows.clone();
+        twins[4] = twins[4].replacen('.', "0", 1);
+        assert!(matches!(
+            parse(&text_file(&twins, "\n"), false),
+            Err(PacmanError::MapParse(_))
+        ));
+        assert!(matches!(
+            load("no_such_maze.txt"),
+            Err(PacmanError::MapParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_problems_say_where_they_are() {
+        let rows = standard_rows();
+        let error = |rows: &[String]| {
+            parse(&text_file(rows, "\n"), false)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let mut unknown = rows.clone();
+        unknown[4] = unknown[4].replacen('.', "*", 1);
+        assert_eq!(error(&unknown), "row 5, column 2: unknown tile '*'");
+
+        // Wall off the top left corner, stranding its pellets
+        let mut walled = rows.clone();
+        walled[4].replace_range(6..7, "#");
+        walled[5].replace_range(1..2, "#");
+        walled[5].replace_range(6..7, "#");
+        assert_eq!(
+            error(&walled),
+            "row 5, column 2: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 3: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 4: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 5: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 6: pellet cannot be reached from Pac-Man's start"
+        );
+
+        let mut doors = rows.clone();
+        doors[4].replace_range(1..2, "=");
+        let message = error(&doors);
+        assert!(message.contains("exactly one door, found 2 at row 5, column 2 and row 16"));
+
+        let mut missing = rows.clone();
+        missing[17] = missing[17].replace('2', " ");
+        missing[4] = missing[4].replacen('.', "1", 1);
+        let message = error(&missing);
+        assert!(message.contains("start marker '1' appears more than once, at row 5, column 2"));
+        assert!(message.contains("start marker '2' is missing"));
+
+        // Extra ghost markers are optional, but still only one of each
+        let mut extra = rows.clone();
+        extra[4] = extra[4].replacen('.', "5", 1);
+        assert!(parse(&text_file(&extra, "\n"), false).is_ok());
+        extra[5] = extra[5].replacen('.', "5", 1);
+        assert!(error(&extra).contains("start marker '5' appears more than once"));
+    }
+
+    #[test]
+    fn test_vertical_tunnels_need_both_ends() {
+        let mut rows = standard_rows();
+        rows[3].replace_range(1..2, "|");
+        assert_eq!(
+            parse(&text_file(&rows, "\n"), false)
+                .unwrap_err()
+                .to_string(),
+            "row 4, column 2: vertical tunnel has no other end at row 34, column 2"
+        );
+
+        rows[33].replace_range(1..2, "|");
+        let sketch = parse(&text_file(&rows, "\n"), false).unwrap();
+        assert_eq!(sketch.matches('|').count(), 2);
+
+        rows[10].replace_range(0..1, "|");
+        assert!(parse(&text_file(&rows, "\n"), false).is_err());
+    }
+
+    #[test]
+    fn test_teleporters_come_in_pairs() {
+        // Wall off the top left corner with a teleporter inside and another outside
+        let mut rows = standard_rows();
+        rows[4].replace_range(6..7, "#");
+        rows[5].replace_range(1..2, "#");
+        rows[5].replace_range(6..7, "#");
+        rows[4].replace_range(1..2, "A");
+        assert_eq!(
+            parse(&text_file(&rows, "\n"), false)
+                .unwrap_err()
+                .to_string(),
+            "teleporter 'A' needs exactly two tiles, found 1 at row 5, column 2; \
+             row 5, column 3: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 4: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 5: pellet cannot be reached from Pac-Man's start; \
+             row 5, column 6: pellet cannot be reached from Pac-Man's start"
+        );
+
+        rows[8].replace_range(1..2, "A");
+        let sketch = parse(&text_file(&rows, "\n"), false).unwrap();
+        assert_eq!(
+            BlockType::from_char(sketch.chars().nth(4 * BOARD_WIDTH + 1).unwrap()),
+            BlockType::Teleport(0)
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::board::Board;
+use crate::game::rng::GameRng;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH};
+
+/// Rows copied from the standard maze: the ghost house, its approaches and the side tunnels
+const FIXED_ROWS: std::ops::RangeInclusive<usize> = 12..=22;
+/// Corridor junctions of the left half, above and below the fixed rows
+const TOP_NODE_ROWS: [usize; 3] = [4, 8, 11];
+const BOTTOM_NODE_ROWS: [usize; 4] = [23, 26, 29, 32];
+const NODE_COLUMNS: [usize; 4] = [1, 6, 9, 12];
+/// Pac-Man starts between the two middle columns of this row
+const PACMAN_ROW: usize = 26;
+/// Chance out of 10 of each remaining corridor being opened as an extra loop
+const LOOP_CHANCE: usize = 3;
+
+/// A (row, column) tile
+type Tile = (usize, usize);
+/// A straight corridor between two junctions
+type Corridor = (Tile, Tile);
+
+/// Corridors between junctions, left half only; the right half mirrors it
+struct Region {
+    rows: &'static [usize],
+    /// Junctions already joined to the fixed rows above or below
+    exits: &'static [Tile],
+    /// Corridors that must be open
+    required: &'static [Corridor],
+}
+
+const TOP: Region = Region {
+    rows: &TOP_NODE_ROWS,
+    exits: &[(11, 6), (11, 12)],
+    required: &[],
+};
+
+const BOTTOM: Region = Region {
+    rows: &BOTTOM_NODE_ROWS,
+    exits: &[(23, 6), (23, 9)],
+    required: &[((PACMAN_ROW, 12), (PACMAN_ROW, 13))],
+};
+
+/// A random maze in the `CHAR_BOARD` format, the same one for the same seed.
+///
+/// The layout is left-right symmetric with no dead ends. The ghost house, the rows around
+/// it and the side tunnels are those of the standard maze, so the ghosts find their way home;
+/// the corridors above and below are carved afresh, with an energizer in each quarter.
+pub fn generate(seed: u64) -> String {
+    let mut rng = GameRng::new(seed);
+    let standard: Vec<char> = Board::CHAR_BOARD.chars().collect();
+    let mut tiles = vec![' '; BOARD_WIDTH * BOARD_HEIGHT];
+
+    for row in 3..=33 {
+        for column in 0..BOARD_WIDTH {
+            let index = row * BOARD_WIDTH + column;
+            tiles[index] = if FIXED_ROWS.contains(&row) {
+                standard[index]
+            } else {
+                '#'
+            };
+        }
+    }
+
+    for region in [&TOP, &BOTTOM] {
+        let corridors = pick_corridors(region, &mut rng);
+        for (from, to) in corridors {
+            carve(&mut tiles, from, to);
+        }
+        place_energizer(&mut tiles, region, &mut rng);
+    }
+    set_tile(&mut tiles, PACMAN_ROW, BOARD_WIDTH / 2 - 1, '0');
+
+    tiles.into_iter().collect()
+}
+
+/// Every corridor the region could have, between neighbouring junctions of the left half.
+///
+/// A corridor from the last column to `BOARD_WIDTH / 2 - 1` crosses the middle to its mirror.
+fn candidate_corridors(region: &Region) -> Vec<Corridor> {
+    let middle = BOARD_WIDTH / 2 - 1;
+    let mut corridors = Vec::new();
+    for (row_index, &row) in region.rows.iter().enumerate() {
+        for (column_index, &column) in NODE_COLUMNS.iter().enumerate() {
+            match NODE_COLUMNS.get(column_index + 1) {
+                Some(&next) => corridors.push(((row, column), (row, next))),
+                None => corridors.push(((row, column), (row, middle))),
+            }
+            if let Some(&below) = region.rows.get(row_index + 1) {
+                corridors.push(((row, column), (below, column)));
+            }
+        }
+    }
+    corridors
+}
+
+/// A random spanning tree of the junctions, plus enough extra corridors to leave no dead end
+fn pick_corridors(region: &Region, rng: &mut GameRng) -> Vec<Corridor> {
+    let middle = BOARD_WIDTH / 2 - 1;
+    let mut candidates = candidate_corridors(region);
+    let mut chosen: Vec<Corridor> = region.required.to_vec();
+    candidates.retain(|corridor| !chosen.contains(corridor));
+
+    // Grow the tree from the junctions that already lead to the fixed rows
+    let mut reached: Vec<Tile> = region.exits.to_vec();
+    loop {
+        let frontier: Vec<usize> = (0..candidates.len())
+            .filter(|&index| {
+                let (from, to) = candidates[index];
+                to.1 != middle && reached.contains(&from) != reached.contains(&to)
+            })
+            .collect();
+        if frontier.is_empty() {
+            break;
+        }
+        let corridor = candidates.remove(frontier[rng.next_index(frontier.len(), "maze tree")]);
+        for end in [corridor.0, corridor.1] {
+            if !reached.contains(&end) {
+                reached.push(end);
+            }
+        }
+        chosen.push(corridor);
+    }
+
+    let degree = |chosen: &[Corridor], node: Tile| {
+        let exits = region.exits.iter().filter(|&&exit| exit == node).count();
+        exits
+            + chosen
+                .iter()
+                .filter(|&&(from, to)| from == node || to == node)
+                .count()
+    };
+
+    // Open a few loops, then make sure every junction has a way out other than back
+    let mut index = 0;
+    while index < candidates.len() {
+        if rng.next_index(10, "maze loop") < LOOP_CHANCE {
+            chosen.push(candidates.remove(index));
+        } else {
+            index += 1;
+        }
+    }
+    for node in reached {
+        while degree(&chosen, node) < 2 {
+            let options: Vec<usize> = (0..candidates.len())
+                .filter(|&index| candidates[index].0 == node || candidates[index].1 == node)
+                .collect();
+            if options.is_empty() {
+                break;
+            }
+            let pick = options[rng.next_index(options.len(), "maze dead end")];
+            chosen.push(candidates.remove(pick));
+        }
+    }
+    chosen
+}
+
+/// Open a straight corridor of pellets and its mirror image
+fn carve(tiles: &mut [char], from: Tile, to: Tile) {
+    for row in from.0.min(to.0)..=from.0.max(to.0) {
+        for column in from.1.min(to.1)..=from.1.max(to.1) {
+            set_tile(tiles, row, column, '.');
+        }
+    }
+}
+
+/// Swap one pellet near the outer edge of the region for an energizer, on both sides
+fn place_energizer(tiles: &mut [char], region: &Region, rng: &mut GameRng) {
+    let first = region.rows[0];
+    let last = region.rows[region.rows.len() - 1];
+    let spots: Vec<Tile> = (first..=last)
+        .flat_map(|row| (1..=NODE_COLUMNS[1]).map(move |column| (row, column)))
+        .filter(|&(row, column)| tiles[row * BOARD_WIDTH + column] == '.')
+        .collect();
+    let (row, column) = spots[rng.next_index(spots.len(), "maze energizer")];
+    set_tile(tiles, row, column, 'o');
+}
+
+/// Set a tile in the left half and its mirror in the right
+fn set_tile(tiles: &mut [char], row: usize, column: usize, tile: char) {
+    tiles[row * BOARD_WIDTH + column] = tile;
+    let mirrored = if tile == '0' { ' ' } else { tile };
+    tiles[row * BOARD_WIDTH + BOARD_WIDTH - 1 - column] = mirrored;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze_file;
+
+    fn rows(sketch: &str) -> Vec<Vec<char>> {
+        let chars: Vec<char> = sketch.chars().collect();
+        chars.chunks(BOARD_WIDTH).map(|row| row.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_maze() {
+        assert_eq!(generate(42), generate(42));
+        assert_ne!(generate(42), generate(43));
+    }
+
+    #[test]
+    fn test_generated_mazes_are_playable() {
+        for seed in 0..50 {
+            let sketch = generate(seed);
+            let text: String = rows(&sketch)
+                .iter()
+                .map(|row| row.iter().collect::<String>() + "\n")
+                .collect();
+            assert_eq!(maze_file::parse(&text, false).unwrap(), sketch);
+            assert_eq!(sketch.matches('o').count(), 4, "seed {}", seed);
+
+            let grid = rows(&sketch);
+            let open = |row: usize, column: usize| !matches!(grid[row][column], '#' | '=');
+            // Mirrored apart from the standard rows, whose ghost markers are not
+            for (row, tiles) in grid.iter().enumerate() {
+                let mut mirrored: Vec<char> = tiles.iter().rev().cloned().collect();
+                if row == PACMAN_ROW {
+                    mirrored.swap(BOARD_WIDTH / 2 - 1, BOARD_WIDTH / 2);
+                }
+                if !FIXED_ROWS.contains(&row) {
+                    assert_eq!(*tiles, mirrored, "seed {} row {}", seed, row);
+                }
+            }
+
+            // Every corridor tile outside the fixed rows has two ways out
+            for row in (4..=32).filter(|row| !FIXED_ROWS.contains(row)) {
+                for column in 1..BOARD_WIDTH - 1 {
+                    if !open(row, column) {
+                        continue;
+                    }
+                    let exits = [
+                        open(row - 1, column),
+                        open(row + 1, column),
+                        open(row, column - 1),
+                        open(row, column + 1),
+                    ];
+                    let exits = exits.iter().filter(|&&exit| exit).count();
+                    assert!(exits >= 2, "seed {} dead end at {},{}", seed, row, column);
+                }
+            }
+
+            // All the food can be reached from Pac-Man's start, wrapping through the tunnel
+            let start = (PACMAN_ROW, BOARD_WIDTH / 2 - 1);
+            let mut seen = vec![start];
+            let mut queue = vec![start];
+            while let Some((row, column)) = queue.pop() {
+                let neighbours = [
+                    (row - 1, column),
+                    (row + 1, column),
+                    (row, (column + BOARD_WIDTH - 1) % BOARD_WIDTH),
+                    (row, (column + 1) % BOARD_WIDTH),
+                ];
+                for (next_row, next_column) in neighbours {
+                    if open(next_row, next_column) && !seen.contains(&(next_row, next_column)) {
+                        seen.push((next_row, next_column));
+                        queue.push((next_row, next_column));
+                    }
+                }
+            }
+            for (row, tiles) in grid.iter().enumerate() {
+                for (column, &tile) in tiles.iter().enumerate() {
+                    if tile == '.' || tile == 'o' {
+                        assert!(seen.contains(&(row, column)), "seed {} cut off", seed);
+                    }
+                }
+            }
+        }
+    }
+}
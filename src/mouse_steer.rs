@@ -0,0 +1,181 @@
+use crate::board::{BlockType, Direction};
+use crate::game::bot::{is_open, BotView};
+use crate::game::pathfind::first_step;
+use crate::layout::Layout;
+use crate::position::Tile;
+use crate::{BLOCK_SIZE_24, YELLOW};
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+/// Turns a clicked tile into the turns that take Pac-Man there.
+///
+/// Clicking an open tile makes it the target, and holding the button down lets the target
+/// follow the pointer. Every frame the first step of the shortest route from Pac-Man's
+/// tile is worked out, and a turn is pushed whenever that step changes, so he takes each
+/// junction on the way like a held key would. The target is dropped once he reaches it, or
+/// with a right click.
+pub struct MouseSteering {
+    /// Window pixels per board pixel, to bring mouse positions onto the board
+    zoom: f32,
+    target: Option<Tile>,
+    /// The last turn pushed, so the same one is not queued again every frame
+    pushed: Option<Direction>,
+}
+
+impl MouseSteering {
+    pub fn new(layout: &Layout) -> Self {
+        MouseSteering {
+            zoom: layout.zoom(),
+            target: None,
+            pushed: None,
+        }
+    }
+
+    /// Pick up clicks and drags; the map says which tiles can be aimed at
+    pub fn handle_event(&mut self, event: &Event, map: &[BlockType]) {
+        match *event {
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => self.aim(self.tile_at(x, y), map),
+            Event::MouseMotion {
+                mousestate, x, y, ..
+            } if mousestate.left() => self.aim(self.tile_at(x, y), map),
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Right,
+                ..
+            } => self.target = None,
+            _ => {}
+        }
+    }
+
+    fn tile_at(&self, x: i32, y: i32) -> Tile {
+        let block_size = BLOCK_SIZE_24 as f32;
+        Tile {
+            x: (x as f32 / self.zoom / block_size).floor() as i16,
+            y: (y as f32 / self.zoom / block_size).floor() as i16,
+        }
+    }
+
+    /// Walls and the ghost house cannot be aimed at; clicking one leaves the target as it was
+    fn aim(&mut self, tile: Tile, map: &[BlockType]) {
+        if is_open(map, tile) && self.target != Some(tile) {
+            self.target = Some(tile);
+            self.pushed = None;
+        }
+    }
+
+    /// The turn to push into the input queue this frame, if it changed since the last one
+    pub fn next_turn(&mut self, view: &BotView) -> Option<Direction> {
+        let target = self.target?;
+        if view.pacman == target {
+            self.target = None;
+            return None;
+        }
+        let direction = first_step(view.map, view.pacman, target)?;
+        if self.pushed == Some(direction) {
+            return None;
+        }
+        self.pushed = Some(direction);
+        Some(direction)
+    }
+
+    /// Outline the tile Pac-Man is heading for
+    pub fn draw(&self, canvas: &mut WindowCanvas) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(target) = self.target {
+            let block_size = BLOCK_SIZE_24 as i32;
+            canvas.set_draw_color(YELLOW);
+            canvas.draw_rect(Rect::new(
+                target.x as i32 * block_size,
+                target.y as i32 * block_size,
+                BLOCK_SIZE_24,
+                BLOCK_SIZE_24,
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::stock_map;
+
+    fn click(x: i32, y: i32) -> Event {
+        Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 1,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        }
+    }
+
+    fn view(map: &[BlockType], pacman: Tile) -> BotView<'_> {
+        BotView {
+            map,
+            pacman,
+            ghosts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_clicked_tile_is_steered_to() {
+        let map = stock_map();
+        let mut steering = MouseSteering::new(&Layout::new());
+        // The bottom-left energizer, seen from Pac-Man's start along the same corridor
+        steering.handle_event(&click(24 + 5, 26 * 24 + 20), &map);
+        assert_eq!(steering.target, Some(Tile { x: 1, y: 26 }));
+
+        let start = Tile { x: 13, y: 26 };
+        assert_eq!(
+            steering.next_turn(&view(&map, start)),
+            Some(Direction::Left)
+        );
+        // Nothing new to push until the route changes
+        assert_eq!(steering.next_turn(&view(&map, start)), None);
+
+        assert_eq!(steering.next_turn(&view(&map, Tile { x: 1, y: 26 })), None);
+        assert_eq!(steering.target, None);
+    }
+
+    #[test]
+    fn test_walls_cannot_be_aimed_at() {
+        let map = stock_map();
+        let layout = Layout {
+            scale: 2,
+            ..Layout::new()
+        };
+        let mut steering = MouseSteering::new(&layout);
+        steering.handle_event(&click(10, 2 * 3 * 24 + 10), &map);
+        assert_eq!(steering.target, None);
+
+        // Clicks are scaled back onto the board
+        steering.handle_event(&click(2 * (6 * 24 + 12), 2 * (8 * 24 + 12)), &map);
+        assert_eq!(steering.target, Some(Tile { x: 6, y: 8 }));
+        assert_eq!(
+            steering.next_turn(&view(&map, Tile { x: 6, y: 11 })),
+            Some(Direction::Up)
+        );
+
+        steering.handle_event(
+            &Event::MouseButtonDown {
+                timestamp: 0,
+                window_id: 1,
+                which: 0,
+                mouse_btn: MouseButton::Right,
+                clicks: 1,
+                x: 0,
+                y: 0,
+            },
+            &map,
+        );
+        assert_eq!(steering.target, None);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::board::Direction;
+use crate::config::Config;
+use crate::game::replay::{fnv1a, InputAction};
+use sdl2::keyboard::Keycode;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+const PROTOCOL: &str = "pacman-versus v1";
+
+/// Local input is scheduled this many updates ahead, hiding the round trip to the peer
+pub const INPUT_DELAY: u32 = 3;
+
+/// Give up on a peer that has sent nothing for this long
+const PEER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Plays Pac-Man and picks the seed
+    Host,
+    /// Plays Blinky
+    Guest,
+}
+
+impl Role {
+    pub fn character(self) -> &'static str {
+        match self {
+            Role::Host => "Pac-Man",
+            Role::Guest => "Blinky",
+        }
+    }
+}
+
+/// One end of a lockstep versus game.
+///
+/// Both instances run the same deterministic simulation. Every update each side sends the
+/// actions it wants applied `INPUT_DELAY` updates later, then waits for the other side's
+/// actions for the current update, so both apply exactly the same inputs on the same tick.
+///
+/// Each line on the wire is `<tick> <actions>`, with actions written as in replay files.
+pub struct NetSession {
+    role: Role,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// Pressed since the last line was sent
+    pending: Vec<InputAction>,
+    /// Sent but not yet applied, oldest first
+    in_flight: VecDeque<(u32, Vec<InputAction>)>,
+    next_send_tick: u32,
+}
+
+impl NetSession {
+    /// Wait for a guest on `port`, then tell it which seed and options to play with
+    pub fn host(port: u16, config: &Config, seed: u64) -> Result<Self, Box<dyn std::error::Error>> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        println!("Waiting for the other player on port {}...", port);
+        let (stream, peer) = listener.accept()?;
+        println!("Player 2 connected from {}", peer);
+
+        let mut session = NetSession::new(stream, Role::Host)?;
+        session.send_line(&format!("{} {} {:x}", PROTOCOL, seed, config_hash(config)))?;
+        let reply = session.read_line()?;
+        match reply.strip_prefix("reject ") {
+            Some(reason) => Err(format!("Player 2 refused the game: {}", reason).into()),
+            None if reply == "ready" => Ok(session),
+            None => Err(format!("Unexpected reply from player 2: {}", reply).into()),
+        }
+    }
+
+    /// Connect to a host, returning the session and the seed the host chose
+    pub fn join(address: &str, config: &Config) -> Result<(Self, u64), Box<dyn std::error::Error>> {
+        let stream = TcpStream::connect(address)?;
+        println!("Connected to {}", address);
+
+        let mut session = NetSession::new(stream, Role::Guest)?;
+        let hello = session.read_line()?;
+        let fields: Vec<&str> = hello
+            .strip_prefix(PROTOCOL)
+            .ok_or_else(|| format!("Not a versus host: {}", hello))?
+            .split_whitespace()
+            .collect();
+        let (seed, hash) = match fields[..] {
+            [seed, hash] => (seed.parse::<u64>(), u64::from_str_radix(hash, 16)),
+            _ => return Err(format!("Malformed greeting: {}", hello).into()),
+        };
+        let (seed, hash) = (seed?, hash?);
+
+        if hash != config_hash(config) {
+            session.send_line("reject different game options")?;
+            return Err(format!(
+                "The host is playing with different options than ours ({})",
+                config.fingerprint()
+            )
+            .into());
+        }
+        session.send_line("ready")?;
+        Ok((session, seed))
+    }
+
+    fn new(stream: TcpStream, role: Role) -> Result<Self, Box<dyn std::error::Error>> {
+        // Lines are tiny and sent once per update; waiting to batch them only adds lag
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(PEER_TIMEOUT))?;
+        Ok(NetSession {
+            role,
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            pending: Vec::new(),
+            in_flight: VecDeque::new(),
+            next_send_tick: 0,
+        })
+    }
+
+    pub fn get_role(&self) -> Role {
+        self.role
+    }
+
+    /// The action a key stands for when pressed by this player, if any
+    pub fn action_for(&self, keycode: Keycode) -> Option<InputAction> {
+        let direction = match keycode {
+            Keycode::Right | Keycode::D | Keycode::L => Direction::Right,
+            Keycode::Up | Keycode::W | Keycode::I => Direction::Up,
+            Keycode::Left | Keycode::A | Keycode::J => Direction::Left,
+            Keycode::Down | Keycode::S | Keycode::K => Direction::Down,
+            Keycode::Space => return Some(InputAction::Continue),
+            _ => return None,
+        };
+        Some(self.steer(direction))
+    }
+
+    /// Move this player's character, whichever one that is
+    pub fn steer(&self, direction: Direction) -> InputAction {
+        match self.role {
+            Role::Host => InputAction::Turn(direction),
+            Role::Guest => InputAction::Steer(direction),
+        }
+    }
+
+    /// Queue a local action; it takes effect `INPUT_DELAY` updates from now
+    pub fn queue_local(&mut self, action: InputAction) {
+        self.pending.push(action);
+    }
+
+    /// Trade inputs for update `tick`, blocking until the peer's arrive.
+    ///
+    /// Returns both players' actions, the host's first, so the two sides apply them in the
+    /// same order.
+    pub fn exchange(&mut self, tick: u32) -> Result<Vec<InputAction>, Box<dyn std::error::Error>> {
+        while self.next_send_tick <= tick + INPUT_DELAY {
+            let actions = if self.next_send_tick == tick + INPUT_DELAY {
+                std::mem::take(&mut self.pending)
+            } else {
+                Vec::new()
+            };
+            let chars: String = actions.iter().map(|action| action.to_char()).collect();
+            self.send_line(&format!("{} {}", self.next_send_tick, chars))?;
+            self.in_flight.push_back((self.next_send_tick, actions));
+            self.next_send_tick += 1;
+        }
+
+        let local = match self.in_flight.pop_front() {
+            Some((sent_tick, actions)) if sent_tick == tick => actions,
+            _ => return Err(format!("Local input for update {} went missing", tick).into()),
+        };
+        let remote = self.read_actions(tick)?;
+        Ok(match self.role {
+            Role::Host => local.into_iter().chain(remote).collect(),
+            Role::Guest => remote.into_iter().chain(local).collect(),
+        })
+    }
+
+    fn read_actions(&mut self, tick: u32) -> Result<Vec<InputAction>, Box<dyn std::error::Error>> {
+        let line = self.read_line()?;
+        let malformed = || format!("Malformed input from the other player: {:?}", line);
+        let (line_tick, chars) = line.split_once(' ').ok_or_else(malformed)?;
+        if line_tick.parse::<u32>().map_err(|_| malformed())? != tick {
+            return Err(format!("Out of sync with the other player at update {}", tick).into());
+        }
+
+        let mut actions = Vec::new();
+        for c in chars.chars() {
+            let action = InputAction::from_char(c).ok_or_else(malformed)?;
+            // Each player may only move their own character
+            let allowed = matches!(
+                (self.role, action),
+                (_, InputAction::Continue)
+                    | (Role::Host, InputAction::Steer(_))
+                    | (Role::Guest, InputAction::Turn(_))
+            );
+            if !allowed {
+                return Err(malformed().into());
+            }
+            actions.push(action);
+        }
+        Ok(actions)
+    }
+
+    fn send_line(&mut self, line: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err("The other player disconnected".into());
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+fn config_hash(config: &Config) -> u64 {
+    fnv1a(config.fingerprint().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Netplay;
+
+    fn versus_config() -> Config {
+        let mut config = Config::new();
+        config.coop = true;
+        config.netplay = Some(Netplay::Host(0));
+        config
+    }
+
+    /// A connected host and guest on the loopback interface
+    fn pair(guest_config: Config) -> Result<(NetSession, NetSession, u64), String> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let host = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut session = NetSession::new(stream, Role::Host).unwrap();
+            let hash = config_hash(&versus_config());
+            session
+                .send_line(&format!("{} 1234 {:x}", PROTOCOL, hash))
+                .unwrap();
+            let reply = session.read_line().unwrap();
+            (session, reply)
+        });
+
+        let joined = NetSession::join(&address, &guest_config).map_err(|e| e.to_string());
+        let (host, reply) = host.join().unwrap();
+        let (guest, seed) = joined?;
+        assert_eq!(reply, "ready");
+        Ok((host, guest, seed))
+    }
+
+    #[test]
+    fn test_guest_receives_seed() {
+        let (host, guest, seed) = pair(versus_config()).unwrap();
+        assert_eq!(seed, 1234);
+        assert_eq!(host.get_role(), Role::Host);
+        assert_eq!(guest.get_role(), Role::Guest);
+    }
+
+    #[test]
+    fn test_guest_rejects_different_options() {
+        let mut config = versus_config();
+        config.theme = Some("neon".to_string());
+        assert!(pair(config).is_err());
+    }
+
+    #[test]
+    fn test_both_sides_apply_the_same_inputs() {
+        let (mut host, mut guest, _) = pair(versus_config()).unwrap();
+        let guest = std::thread::spawn(move || {
+            guest.queue_local(guest.steer(Direction::Left));
+            (0..6)
+                .map(|tick| guest.exchange(tick).unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        host.queue_local(InputAction::Continue);
+        host.queue_local(host.steer(Direction::Up));
+        let host_inputs: Vec<_> = (0..6).map(|tick| host.exchange(tick).unwrap()).collect();
+        let guest_inputs = guest.join().unwrap();
+
+        assert_eq!(host_inputs, guest_inputs);
+        for (tick, actions) in host_inputs.iter().enumerate() {
+            if tick as u32 == INPUT_DELAY {
+                assert_eq!(
+                    actions,
+                    &vec![
+                        InputAction::Continue,
+                        InputAction::Turn(Direction::Up),
+                        InputAction::Steer(Direction::Left)
+                    ]
+                );
+            } else {
+                assert!(actions.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_keys_map_to_own_character() {
+        let (host, guest, _) = pair(versus_config()).unwrap();
+        assert_eq!(
+            host.action_for(Keycode::Left),
+            Some(InputAction::Turn(Direction::Left))
+        );
+        assert_eq!(
+            guest.action_for(Keycode::J),
+            Some(InputAction::Steer(Direction::Left))
+        );
+        assert_eq!(
+            guest.action_for(Keycode::Space),
+            Some(InputAction::Continue)
+        );
+        assert_eq!(guest.action_for(Keycode::F1), None);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{TARGET_FPS, TICK_MS};
+use sdl2::keyboard::Keycode;
+use std::time::{Duration, Instant};
+
+/// Simulation speeds offered by the debug controls, in updates per rendered frame
+const SPEEDS: [f32; 3] = [1.0, 0.5, 0.25];
+/// Slowest the game speed setting goes, in percent
+pub const MIN_GAME_SPEED: u32 = 50;
+/// Frame rates the frame cap can be set to
+pub const FRAME_RATES: (u32, u32) = (30, 240);
+/// Without vsync the last stretch before a frame is spun rather than slept, since sleeps
+/// overshoot by up to a millisecond or two
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+/// Real time between frames drawn at `frame_rate` per second, at `game_speed` percent.
+///
+/// Each update still covers `TICK_MS` of game time, so a slowed game plays out exactly as it
+/// would at full speed, only stretched.
+pub fn frame_duration(game_speed: u32, frame_rate: u32) -> Duration {
+    let micros = TICK_MS as u64 * 1000 * TARGET_FPS as u64 * 100;
+    Duration::from_micros(micros / (frame_rate as u64 * game_speed as u64))
+}
+
+/// Hold until `deadline`; `precise` spins through the last moment instead of sleeping past it
+pub fn wait_until(deadline: Instant, precise: bool) {
+    let now = Instant::now();
+    if deadline <= now {
+        return;
+    }
+    let remaining = deadline - now;
+    if !precise {
+        std::thread::sleep(remaining);
+        return;
+    }
+    if remaining > SPIN_MARGIN {
+        std::thread::sleep(remaining - SPIN_MARGIN);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+/// Decides how many game updates each rendered frame runs.
+///
+/// Normally that is exactly one, or a steady share of updates at other frame rates. With
+/// `--debug`, F5 freezes the simulation, F6 steps a single update while frozen, and F7 cycles
+/// between full, half and quarter speed.
+pub struct Pacing {
+    debug: bool,
+    /// Updates per frame at full speed
+    rate: f32,
+    speed_index: usize,
+    frozen: bool,
+    pending_steps: u32,
+    owed: f32,
+}
+
+impl Pacing {
+    pub fn new(debug: bool, frame_rate: u32) -> Self {
+        Pacing {
+            debug,
+            rate: TARGET_FPS as f32 / frame_rate as f32,
+            speed_index: 0,
+            frozen: false,
+            pending_steps: 0,
+            owed: 0.0,
+        }
+    }
+
+    /// Handle a debug key, returning true if the game should not see it
+    pub fn handle_input(&mut self, keycode: Keycode) -> bool {
+        if !self.debug {
+            return false;
+        }
+        match keycode {
+            Keycode::F5 => {
+                self.frozen = !self.frozen;
+                self.owed = 0.0;
+                println!(
+                    "Simulation {}",
+                    if self.frozen { "frozen" } else { "running" }
+                );
+            }
+            Keycode::F6 => {
+                if self.frozen {
+                    self.pending_steps += 1;
+                } else {
+                    println!("Freeze the simulation with F5 before stepping");
+                }
+            }
+            Keycode::F7 => {
+                self.speed_index = (self.speed_index + 1) % SPEEDS.len();
+                self.owed = 0.0;
+                println!("Simulation speed: {}%", (self.get_speed() * 100.0) as u32);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        SPEEDS[self.speed_index]
+    }
+
+    /// Updates to run for the frame about to be drawn
+    pub fn updates_for_frame(&mut self) -> u32 {
+        if self.frozen {
+            return std::mem::take(&mut self.pending_steps);
+        }
+        self.owed += self.get_speed() * self.rate;
+        let updates = self.owed.floor();
+        self.owed -= updates;
+        updates as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn updates_over(pacing: &mut Pacing, frames: u32) -> u32 {
+        (0..frames).map(|_| pacing.updates_for_frame()).sum()
+    }
+
+    #[test]
+    fn test_one_update_per_frame_by_default() {
+        let mut pacing = Pacing::new(false, TARGET_FPS);
+        assert_eq!(updates_over(&mut pacing, 60), 60);
+        assert!(!pacing.handle_input(Keycode::F5));
+        assert_eq!(updates_over(&mut pacing, 60), 60);
+    }
+
+    #[test]
+    fn test_frame_cap_keeps_the_update_rate() {
+        let mut fast = Pacing::new(false, 120);
+        assert_eq!(updates_over(&mut fast, 120), 60);
+        let mut slow = Pacing::new(false, 30);
+        assert_eq!(updates_over(&mut slow, 30), 60);
+
+        assert_eq!(
+            frame_duration(100, 120) * 2,
+            frame_duration(100, TARGET_FPS)
+        );
+    }
+
+    #[test]
+    fn test_slow_motion() {
+        let mut pacing = Pacing::new(true, TARGET_FPS);
+        assert!(pacing.handle_input(Keycode::F7));
+        assert_eq!(updates_over(&mut pacing, 60), 30);
+        pacing.handle_input(Keycode::F7);
+        assert_eq!(updates_over(&mut pacing, 60), 15);
+        pacing.handle_input(Keycode::F7);
+        assert_eq!(pacing.get_speed(), 1.0);
+    }
+
+    #[test]
+    fn test_frame_advance() {
+        let mut pacing = Pacing::new(true, TARGET_FPS);
+        pacing.handle_input(Keycode::F6);
+        assert_eq!(pacing.updates_for_frame(), 1);
+
+        pacing.handle_input(Keycode::F5);
+        assert_eq!(updates_over(&mut pacing, 10), 0);
+        pacing.handle_input(Keycode::F6);
+        pacing.handle_input(Keycode::F6);
+        assert_eq!(pacing.updates_for_frame(), 2);
+        assert_eq!(pacing.updates_for_frame(), 0);
+
+        pacing.handle_input(Keycode::F5);
+        assert_eq!(updates_over(&mut pacing, 10), 10);
+    }
+
+    #[test]
+    fn test_game_speed_stretches_frames() {
+        let full = frame_duration(100, TARGET_FPS);
+        assert_eq!(full, Duration::from_millis(TICK_MS as u64));
+        assert_eq!(frame_duration(50, TARGET_FPS), full * 2);
+        assert!(frame_duration(75, TARGET_FPS) > full);
+    }
+
+    #[test]
+    fn test_other_keys_pass_through() {
+        let mut pacing = Pacing::new(true, TARGET_FPS);
+        assert!(!pacing.handle_input(Keycode::Space));
+        assert!(!pacing.handle_input(Keycode::Left));
+    }
+}
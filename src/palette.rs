@@ -0,0 +1,94 @@
+use crate::board::EntityType;
+use crate::{BLUE, WHITE};
+use sdl2::pixels::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Palette {
+    Standard,
+    Colorblind,
+    HighContrast,
+}
+
+/// Marking drawn over a ghost body so ghosts can be told apart without color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GhostPattern {
+    Plain,
+    Dots,
+    Stripes,
+    Checker,
+}
+
+impl Palette {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Palette::Standard),
+            "colorblind" => Some(Palette::Colorblind),
+            "high-contrast" => Some(Palette::HighContrast),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Standard => "standard",
+            Palette::Colorblind => "colorblind",
+            Palette::HighContrast => "high-contrast",
+        }
+    }
+
+    /// Cycle to the next palette
+    pub fn next(self) -> Self {
+        match self {
+            Palette::Standard => Palette::Colorblind,
+            Palette::Colorblind => Palette::HighContrast,
+            Palette::HighContrast => Palette::Standard,
+        }
+    }
+
+    /// Body color for a ghost, falling back to its own color on the standard palette
+    pub fn ghost_color(self, identity: EntityType, standard: Color) -> Color {
+        match self {
+            Palette::Standard => standard,
+            // Okabe-Ito colors, distinguishable with deuteranopia and protanopia
+            Palette::Colorblind => match identity {
+                EntityType::Blinky => Color::RGB(213, 94, 0),
+                EntityType::Pinky => Color::RGB(204, 121, 167),
+                EntityType::Inky => Color::RGB(86, 180, 233),
+                EntityType::Clyde => Color::RGB(0, 158, 115),
+                _ => standard,
+            },
+            Palette::HighContrast => match identity {
+                EntityType::Blinky => Color::RGB(255, 0, 0),
+                EntityType::Pinky => Color::RGB(255, 0, 255),
+                EntityType::Inky => Color::RGB(0, 255, 255),
+                EntityType::Clyde => Color::RGB(255, 160, 0),
+                _ => standard,
+            },
+        }
+    }
+
+    pub fn frightened_color(self) -> Color {
+        match self {
+            Palette::Standard => BLUE,
+            Palette::Colorblind => Color::RGB(0, 114, 178),
+            Palette::HighContrast => Color::RGB(64, 64, 255),
+        }
+    }
+
+    pub fn maze_color(self) -> Color {
+        match self {
+            Palette::Standard => BLUE,
+            Palette::Colorblind => Color::RGB(0, 114, 178),
+            Palette::HighContrast => WHITE,
+        }
+    }
+
+    pub fn ghost_pattern(identity: EntityType) -> GhostPattern {
+        match identity {
+            EntityType::Pinky => GhostPattern::Dots,
+            EntityType::Inky => GhostPattern::Stripes,
+            EntityType::Clyde => GhostPattern::Checker,
+            _ => GhostPattern::Plain,
+        }
+    }
+}
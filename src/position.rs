@@ -1,3 +1,5 @@
+use crate::BLOCK_SIZE_24;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Position {
     pub x: i16,
@@ -17,7 +19,6 @@ impl Position {
         self.y
     }
 
-    #[allow(dead_code)]
     pub fn get_pos(&self) -> Position {
         *self
     }
@@ -30,15 +31,31 @@ impl Position {
         self.y = new_y;
     }
 
-    #[allow(dead_code)]
     pub fn mod_coords(&mut self, new_x: i16, new_y: i16) {
         self.x = new_x;
         self.y = new_y;
     }
 
-    #[allow(dead_code)]
     pub fn mod_pos(&mut self, new_pos: Position) {
         self.x = new_pos.x;
         self.y = new_pos.y;
     }
 }
+
+/// Board cell coordinates, which may fall outside the board inside the side tunnels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile {
+    pub x: i16,
+    pub y: i16,
+}
+
+impl Tile {
+    /// The cell an entity mostly overlaps
+    pub fn from_position(position: Position) -> Self {
+        let block_size = BLOCK_SIZE_24 as i16;
+        Tile {
+            x: (position.x + block_size / 2).div_euclid(block_size),
+            y: (position.y + block_size / 2).div_euclid(block_size),
+        }
+    }
+}
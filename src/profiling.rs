@@ -0,0 +1,47 @@
+/// Where spans are written when built with the `profiling` feature
+#[cfg(feature = "profiling")]
+pub const PROFILE_PATH: &str = "trace.json";
+
+/// Time the rest of the enclosing block as a span named `name`.
+///
+/// Expands to nothing unless the game is built with `--features profiling`, so the spans
+/// can stay in hot code for free.
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        let _span = tracing::info_span!($name).entered();
+    };
+}
+pub use profile_scope;
+
+/// Collects spans for the whole run and writes them out when dropped
+#[cfg(feature = "profiling")]
+pub struct Profiler {
+    _guard: tracing_chrome::FlushGuard,
+}
+
+#[cfg(not(feature = "profiling"))]
+pub struct Profiler;
+
+impl Profiler {
+    #[cfg(feature = "profiling")]
+    pub fn start() -> Self {
+        use tracing_subscriber::prelude::*;
+
+        let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+            .file(PROFILE_PATH)
+            .build();
+        tracing_subscriber::registry().with(layer).init();
+        println!(
+            "Profiling to {}; open it in chrome://tracing or Perfetto",
+            PROFILE_PATH
+        );
+        Profiler { _guard: guard }
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    pub fn start() -> Self {
+        Profiler
+    }
+}
@@ -0,0 +1,173 @@
+use crate::error::PacmanError;
+use crate::texture::GameTexture;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+/// A single sprite copy queued for a batch
+pub struct SpriteCmd<'t, 'a> {
+    pub texture: &'t GameTexture<'a>,
+    pub x: i32,
+    pub y: i32,
+    pub clip: Option<Rect>,
+}
+
+impl<'t, 'a> SpriteCmd<'t, 'a> {
+    pub fn new(texture: &'t GameTexture<'a>, x: i32, y: i32, clip: Option<Rect>) -> Self {
+        SpriteCmd {
+            texture,
+            x,
+            y,
+            clip,
+        }
+    }
+
+    fn texture_key(&self) -> usize {
+        self.texture as *const GameTexture as usize
+    }
+}
+
+/// Draw call counters for the current frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    pub draw_calls: u32,
+    /// Texture switches the sprites would have caused in submission order
+    pub unbatched_switches: u32,
+    /// Texture switches actually issued after grouping
+    pub batched_switches: u32,
+}
+
+impl FrameStats {
+    fn add(&mut self, other: FrameStats) {
+        self.draw_calls += other.draw_calls;
+        self.unbatched_switches += other.unbatched_switches;
+        self.batched_switches += other.batched_switches;
+    }
+}
+
+pub struct Renderer {
+    frame: FrameStats,
+    total: FrameStats,
+    frames: u32,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Renderer {
+            frame: FrameStats::default(),
+            total: FrameStats::default(),
+            frames: 0,
+        }
+    }
+
+    /// Fold the previous frame into the running totals and start counting afresh
+    pub fn begin_frame(&mut self) {
+        self.total.add(self.frame);
+        self.frames += 1;
+        self.frame = FrameStats::default();
+    }
+
+    /// Copy a layer of sprites grouped by texture.
+    ///
+    /// Sprites sharing a texture keep their relative order, but the layer as a whole
+    /// is reordered, so only batch sprites that never overlap one another.
+    pub fn draw_batch(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        commands: &[SpriteCmd],
+    ) -> Result<(), PacmanError> {
+        let keys: Vec<usize> = commands.iter().map(SpriteCmd::texture_key).collect();
+        let order = batch_order(&keys);
+
+        self.frame.unbatched_switches += count_switches(keys.iter().copied());
+        self.frame.batched_switches += count_switches(order.iter().map(|&i| keys[i]));
+
+        for index in order {
+            let command = &commands[index];
+            command
+                .texture
+                .render(canvas, command.x, command.y, command.clip)?;
+            self.frame.draw_calls += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame
+    }
+
+    /// Per-frame averages over every completed frame
+    pub fn average_stats(&self) -> FrameStats {
+        if self.frames == 0 {
+            return FrameStats::default();
+        }
+        FrameStats {
+            draw_calls: self.total.draw_calls / self.frames,
+            unbatched_switches: self.total.unbatched_switches / self.frames,
+            batched_switches: self.total.batched_switches / self.frames,
+        }
+    }
+}
+
+/// Indices of `keys` stably sorted so equal keys are adjacent
+fn batch_order(keys: &[usize]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by_key(|&index| keys[index]);
+    order
+}
+
+/// Number of times the bound texture changes, counting the first bind
+fn count_switches(keys: impl Iterator<Item = usize>) -> u32 {
+    let mut switches = 0;
+    let mut bound = None;
+    for key in keys {
+        if bound != Some(key) {
+            switches += 1;
+            bound = Some(key);
+        }
+    }
+    switches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_order_groups_keys_stably() {
+        let keys = [2, 1, 2, 1, 3];
+        assert_eq!(batch_order(&keys), vec![1, 3, 0, 2, 4]);
+    }
+
+    #[test]
+    fn test_count_switches() {
+        assert_eq!(count_switches([].into_iter()), 0);
+        assert_eq!(count_switches([1, 2, 1, 2].into_iter()), 4);
+        assert_eq!(count_switches([1, 1, 2, 2].into_iter()), 2);
+    }
+
+    #[test]
+    fn test_average_stats() {
+        let mut renderer = Renderer::new();
+        assert_eq!(renderer.average_stats(), FrameStats::default());
+
+        renderer.frame = FrameStats {
+            draw_calls: 10,
+            unbatched_switches: 6,
+            batched_switches: 2,
+        };
+        renderer.begin_frame();
+        renderer.begin_frame();
+
+        let average = renderer.average_stats();
+        assert_eq!(average.draw_calls, 5);
+        assert_eq!(average.unbatched_switches, 3);
+        assert_eq!(average.batched_switches, 1);
+    }
+}
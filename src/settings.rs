@@ -0,0 +1,261 @@
+use crate::config::Config;
+use crate::error::PacmanError;
+use crate::game::levels::Difficulty;
+use crate::palette::Palette;
+
+pub const SETTINGS_PATH: &str = "settings.txt";
+
+/// Format version written by this build; files from older versions are migrated on load
+const VERSION: u32 = 2;
+
+/// Options the player can change while playing, kept between runs.
+///
+/// They are read before the command line, so a flag still wins for that run, and written
+/// back on exit with whatever was changed in game. Flags are not written back. The window
+/// scale is only ever set by a flag, and there is no volume control or key rebinding in
+/// game, so none of those are kept here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub palette: Palette,
+    pub ghost_patterns: bool,
+    pub dots_hud: bool,
+    pub particles: bool,
+    pub screen_effects: bool,
+    pub music: bool,
+    pub sound_indicators: bool,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings::from_config(&Config::new())
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Settings {
+            palette: config.palette,
+            ghost_patterns: config.ghost_patterns,
+            dots_hud: config.dots_hud,
+            particles: config.particles,
+            screen_effects: config.screen_effects,
+            music: config.music,
+            sound_indicators: config.sound_indicators,
+            difficulty: config.difficulty,
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        config.palette = self.palette;
+        config.ghost_patterns = self.ghost_patterns;
+        config.dots_hud = self.dots_hud;
+        config.particles = self.particles;
+        config.screen_effects = self.screen_effects;
+        config.music = self.music;
+        config.sound_indicators = self.sound_indicators;
+        config.difficulty = self.difficulty;
+    }
+
+    /// These settings with whatever changed from `before` to `after` carried over, and
+    /// everything else left as saved
+    pub fn with_changes(&self, before: &Settings, after: &Settings) -> Settings {
+        fn pick<T: PartialEq + Copy>(saved: T, before: T, after: T) -> T {
+            if before == after {
+                saved
+            } else {
+                after
+            }
+        }
+        Settings {
+            palette: pick(self.palette, before.palette, after.palette),
+            ghost_patterns: pick(
+                self.ghost_patterns,
+                before.ghost_patterns,
+                after.ghost_patterns,
+            ),
+            dots_hud: pick(self.dots_hud, before.dots_hud, after.dots_hud),
+            particles: pick(self.particles, before.particles, after.particles),
+            screen_effects: pick(
+                self.screen_effects,
+                before.screen_effects,
+                after.screen_effects,
+            ),
+            music: pick(self.music, before.music, after.music),
+            sound_indicators: pick(
+                self.sound_indicators,
+                before.sound_indicators,
+                after.sound_indicators,
+            ),
+            difficulty: pick(self.difficulty, before.difficulty, after.difficulty),
+        }
+    }
+
+    /// Saved settings, or the defaults when none have been saved yet
+    pub fn load(path: &str) -> Result<Self, PacmanError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Settings::parse(&text)
+                .map_err(|e| PacmanError::ConfigParse(format!("{}: {}", path, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::new()),
+            Err(e) => Err(PacmanError::ConfigParse(format!("{}: {}", path, e))),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), PacmanError> {
+        std::fs::write(path, self.to_text()).map_err(|source| PacmanError::SaveIo {
+            path: path.to_string(),
+            source,
+        })
+    }
+
+    /// Read `key = value` lines; settings left out keep their defaults
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut settings = Settings::new();
+        let mut version = 0;
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            if key == "version" {
+                version = value
+                    .parse()
+                    .map_err(|_| format!("line {}: invalid version '{}'", line_number, value))?;
+                if version > VERSION {
+                    return Err(format!("saved by a newer version ({})", version));
+                }
+                continue;
+            }
+
+            let (key, value) = migrate(version, key, value);
+            let flag = || match value {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(format!(
+                    "line {}: expected true or false for {}",
+                    line_number, key
+                )),
+            };
+            match key {
+                "palette" => {
+                    settings.palette = Palette::from_name(value).ok_or_else(|| {
+                        format!("line {}: unknown palette '{}'", line_number, value)
+                    })?
+                }
+                "ghost_patterns" => settings.ghost_patterns = flag()?,
+                "dots_hud" => settings.dots_hud = flag()?,
+                "particles" => settings.particles = flag()?,
+                "screen_effects" => settings.screen_effects = flag()?,
+                "music" => settings.music = flag()?,
+                "sound_indicators" => settings.sound_indicators = flag()?,
+                "difficulty" => {
+                    settings.difficulty = Difficulty::from_name(value).ok_or_else(|| {
+                        format!("line {}: unknown difficulty '{}'", line_number, value)
+                    })?
+                }
+                _ => return Err(format!("line {}: unknown setting '{}'", line_number, key)),
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str(&format!("version = {}\n", VERSION));
+        text.push_str(&format!("palette = {}\n", self.palette.name()));
+        text.push_str(&format!("ghost_patterns = {}\n", self.ghost_patterns));
+        text.push_str(&format!("dots_hud = {}\n", self.dots_hud));
+        text.push_str(&format!("particles = {}\n", self.particles));
+        text.push_str(&format!("screen_effects = {}\n", self.screen_effects));
+        text.push_str(&format!("music = {}\n", self.music));
+        text.push_str(&format!("sound_indicators = {}\n", self.sound_indicators));
+        text.push_str(&format!("difficulty = {}\n", self.difficulty.name()));
+        text
+    }
+}
+
+/// Bring a setting written by an older version up to date.
+///
+/// A file without a version line counts as version 0, which has the same keys as version 1.
+/// Version 2 added the difficulty, which older files leave at its default. When a setting is
+/// renamed or changes meaning, bump `VERSION` and translate it here.
+fn migrate<'t>(_version: u32, key: &'t str, value: &'t str) -> (&'t str, &'t str) {
+    (key, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = Settings {
+            palette: Palette::Colorblind,
+            ghost_patterns: true,
+            dots_hud: true,
+            particles: false,
+            screen_effects: false,
+            music: false,
+            sound_indicators: true,
+            difficulty: Difficulty::Hard,
+        };
+        assert_eq!(Settings::parse(&settings.to_text()), Ok(settings));
+
+        // Missing settings keep their defaults
+        let partial = Settings::parse("palette = high-contrast\n").unwrap();
+        assert_eq!(partial.palette, Palette::HighContrast);
+        assert!(partial.particles);
+
+        // Files from before the difficulty was kept still load
+        let old = Settings::parse("version = 1\nmusic = false\n").unwrap();
+        assert_eq!(old.difficulty, Difficulty::Normal);
+    }
+
+    #[test]
+    fn test_settings_errors() {
+        assert!(Settings::parse("version = 99\n").is_err());
+        assert!(Settings::parse("volume = 3\n").is_err());
+        assert!(Settings::parse("particles = maybe\n").is_err());
+        assert!(Settings::parse("palette = sepia\n").is_err());
+        assert!(Settings::parse("difficulty = insane\n").is_err());
+    }
+
+    #[test]
+    fn test_only_toggled_settings_are_saved() {
+        let saved = Settings::new();
+        // A one-off flag turned the particles off and picked a palette for this run
+        let before = Settings {
+            particles: false,
+            palette: Palette::Colorblind,
+            ..Settings::new()
+        };
+        let after = Settings {
+            music: false,
+            ..before.clone()
+        };
+        let kept = saved.with_changes(&before, &after);
+        assert!(!kept.music);
+        assert!(kept.particles);
+        assert_eq!(kept.palette, Palette::Standard);
+        assert_eq!(saved.with_changes(&before, &before), saved);
+    }
+
+    #[test]
+    fn test_settings_override_config_defaults() {
+        let mut config = Config::new();
+        Settings::parse("ghost_patterns = true\n")
+            .unwrap()
+            .apply(&mut config);
+        assert!(config.ghost_patterns);
+        assert_eq!(Settings::from_config(&config).palette, Palette::Standard);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::assets::Sprite;
+use crate::error::PacmanError;
+use crate::{BLUE, PINK, RED, WHITE, YELLOW};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::WindowCanvas;
+use std::f32::consts::PI;
+
+const FOOD_COLOR: Color = Color::RGB(255, 185, 175);
+const STEM_COLOR: Color = Color::RGB(0, 160, 0);
+
+/// How far Pac-Man's mouth opens, as half the angle of the gap, for each chomping frame
+const CHOMP: [f32; 3] = [0.1, 0.45, 0.8];
+
+/// Draw a sprite frame from filled shapes instead of its image, for `--no-assets`.
+///
+/// `frame` is given relative to the sheet, as for `AssetManager::render`, and `x` and `y`
+/// are where its top left corner would be drawn. `color` tints the shape the same way.
+pub fn draw(
+    canvas: &mut WindowCanvas,
+    sprite: Sprite,
+    frame: Rect,
+    x: i32,
+    y: i32,
+    facing: u8,
+    color: Color,
+) -> Result<(), PacmanError> {
+    // Sheets lay their frames out left to right
+    let index = (frame.x() / frame.width().max(1) as i32) as usize;
+    let tinted = |default: Color| if color == WHITE { default } else { color };
+    match sprite {
+        Sprite::PacMan => pacman(
+            canvas,
+            x + 16,
+            y + 16,
+            14,
+            facing,
+            CHOMP[index % CHOMP.len()],
+        ),
+        // The mouth opens wider and wider until nothing is left
+        Sprite::PacManDeath => {
+            let mouth = (0.3 * (index + 1) as f32).min(PI);
+            pacman(canvas, x + 16, y + 16, 14, facing, mouth)
+        }
+        Sprite::Lives => pacman(canvas, x + 16, y + 16, 11, 2, CHOMP[1]),
+        Sprite::GhostBody => {
+            canvas.set_draw_color(color);
+            canvas
+                .fill_rects(&[
+                    Rect::new(x + 8, y + 2, 16, 4),
+                    Rect::new(x + 4, y + 6, 24, 22),
+                ])
+                .map_err(PacmanError::Render)
+        }
+        Sprite::GhostEyes => ghost_eyes(canvas, x, y, index, color),
+        Sprite::Pellet => {
+            canvas.set_draw_color(tinted(FOOD_COLOR));
+            canvas
+                .fill_rect(Rect::new(x + 10, y + 10, 4, 4))
+                .map_err(PacmanError::Render)
+        }
+        Sprite::Energizer => disc(canvas, x + 12, y + 12, 8, tinted(FOOD_COLOR)),
+        Sprite::Door => {
+            canvas.set_draw_color(tinted(PINK));
+            canvas
+                .fill_rect(Rect::new(x, y, frame.width(), frame.height()))
+                .map_err(PacmanError::Render)
+        }
+        Sprite::Fruit => {
+            disc(canvas, x + 16, y + 19, 9, tinted(RED))?;
+            canvas.set_draw_color(STEM_COLOR);
+            canvas
+                .fill_rect(Rect::new(x + 15, y + 5, 3, 7))
+                .map_err(PacmanError::Render)
+        }
+    }
+}
+
+/// Two eyes looking the way the ghost faces; the fifth frame, frightened, has no pupils
+fn ghost_eyes(
+    canvas: &mut WindowCanvas,
+    x: i32,
+    y: i32,
+    frame: usize,
+    color: Color,
+) -> Result<(), PacmanError> {
+    canvas.set_draw_color(color);
+    canvas
+        .fill_rects(&[
+            Rect::new(x + 8, y + 10, 7, 8),
+            Rect::new(x + 18, y + 10, 7, 8),
+        ])
+        .map_err(PacmanError::Render)?;
+
+    let (dx, dy) = match frame {
+        0 => (3, 2),
+        1 => (1, 0),
+        2 => (0, 2),
+        3 => (1, 4),
+        _ => return Ok(()),
+    };
+    canvas.set_draw_color(BLUE);
+    canvas
+        .fill_rects(&[
+            Rect::new(x + 8 + dx, y + 10 + dy, 4, 4),
+            Rect::new(x + 18 + dx, y + 10 + dy, 4, 4),
+        ])
+        .map_err(PacmanError::Render)
+}
+
+fn disc(
+    canvas: &mut WindowCanvas,
+    center_x: i32,
+    center_y: i32,
+    radius: i32,
+    color: Color,
+) -> Result<(), PacmanError> {
+    pacman_in(canvas, center_x, center_y, radius, 0, 0.0, color)
+}
+
+fn pacman(
+    canvas: &mut WindowCanvas,
+    center_x: i32,
+    center_y: i32,
+    radius: i32,
+    facing: u8,
+    mouth: f32,
+) -> Result<(), PacmanError> {
+    pacman_in(canvas, center_x, center_y, radius, facing, mouth, YELLOW)
+}
+
+fn pacman_in(
+    canvas: &mut WindowCanvas,
+    center_x: i32,
+    center_y: i32,
+    radius: i32,
+    facing: u8,
+    mouth: f32,
+    color: Color,
+) -> Result<(), PacmanError> {
+    let mut points = Vec::new();
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if in_pacman(dx, dy, radius, facing, mouth) {
+                points.push(Point::new(center_x + dx, center_y + dy));
+            }
+        }
+    }
+    canvas.set_draw_color(color);
+    canvas
+        .draw_points(points.as_slice())
+        .map_err(PacmanError::Render)
+}
+
+/// Whether a pixel `dx`, `dy` from the center is inside a disc with a wedge cut out of it.
+///
+/// The wedge is `mouth` radians either side of `facing` (0 right, 1 up, 2 left, 3 down).
+fn in_pacman(dx: i32, dy: i32, radius: i32, facing: u8, mouth: f32) -> bool {
+    if dx * dx + dy * dy > radius * radius {
+        return false;
+    }
+    if mouth <= 0.0 || (dx == 0 && dy == 0) {
+        return true;
+    }
+    // Screen y grows downwards, so flip it to measure angles the usual way round
+    let angle = (-dy as f32).atan2(dx as f32);
+    let heading = facing as f32 * PI / 2.0;
+    let mut apart = (angle - heading).abs() % (2.0 * PI);
+    if apart > PI {
+        apart = 2.0 * PI - apart;
+    }
+    apart > mouth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mouth_opens_the_way_pacman_faces() {
+        // Facing right, the pixel straight ahead is in the mouth and the one behind is not
+        assert!(!in_pacman(10, 0, 14, 0, 0.5));
+        assert!(in_pacman(-10, 0, 14, 0, 0.5));
+        // Facing up, the mouth is above the center, where screen y is smaller
+        assert!(!in_pacman(0, -10, 14, 1, 0.5));
+        assert!(in_pacman(0, 10, 14, 1, 0.5));
+        assert!(!in_pacman(-10, 0, 14, 2, 0.5));
+        assert!(!in_pacman(0, 10, 14, 3, 0.5));
+
+        // A closed mouth leaves a full disc, and nothing lies outside the radius
+        assert!(in_pacman(10, 0, 14, 0, 0.0));
+        assert!(!in_pacman(14, 14, 14, 0, 0.0));
+    }
+}
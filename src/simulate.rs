@@ -0,0 +1,279 @@
+use crate::config::{Config, ReportFormat, SimulateOptions};
+use crate::game::rng::GameRng;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::theme::Theme;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Give up on a game after an hour of simulated play
+const MAX_UPDATES: u32 = 216_000;
+
+/// Report columns for deaths, in the order ghosts are listed everywhere else
+const GHOST_NAMES: [&str; 4] = ["blinky", "inky", "pinky", "clyde"];
+
+/// Outcome of one bot-played game
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    pub level: u16,
+    pub score: u32,
+    pub killed_by: [u32; 4],
+    pub timed_out: bool,
+}
+
+/// Aggregated results for every game that started on the same level
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileSummary {
+    pub start_level: u16,
+    pub games: usize,
+    pub average_level: f64,
+    /// Minimum, 25th percentile, median, 75th percentile and maximum
+    pub score_quartiles: [u32; 5],
+    pub killed_by: [u32; 4],
+    pub timeouts: usize,
+}
+
+/// Play every requested game on a pool of worker threads and write the report
+pub fn run(
+    options: &SimulateOptions,
+    config: &Config,
+    theme: &Theme,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let base_seed = config
+        .seed
+        .unwrap_or_else(|| GameRng::from_entropy().get_seed());
+    let mut game_config = config.clone();
+    game_config.telemetry = false;
+
+    let jobs: Vec<(usize, u64)> = (0..options.levels.len())
+        .flat_map(|profile| (0..options.games as u64).map(move |game| (profile, game)))
+        .collect();
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .min(jobs.len());
+
+    let next_job = AtomicUsize::new(0);
+    let mut finished: Vec<(usize, GameResult)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next_job.fetch_add(1, Ordering::Relaxed);
+                        let Some(&(profile, _)) = jobs.get(index) else {
+                            break;
+                        };
+                        let mut game_config = game_config.clone();
+                        game_config.seed = Some(base_seed.wrapping_add(index as u64));
+                        let result = play_one(&game_config, theme, options.levels[profile]);
+                        results.push((index, result));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("simulation worker panicked"))
+            .collect()
+    });
+    finished.sort_by_key(|(index, _)| *index);
+
+    let summaries: Vec<ProfileSummary> = options
+        .levels
+        .iter()
+        .enumerate()
+        .map(|(profile, &start_level)| {
+            let results: Vec<GameResult> = finished
+                .iter()
+                .filter(|(index, _)| jobs[*index].0 == profile)
+                .map(|(_, result)| result.clone())
+                .collect();
+            summarize(start_level, &results)
+        })
+        .collect();
+
+    let report = match options.format {
+        ReportFormat::Csv => to_csv(&summaries),
+        ReportFormat::Json => to_json(&summaries),
+    };
+    std::fs::write(&options.output, report)?;
+    println!(
+        "Simulated {} games with base seed {}; report written to {}",
+        jobs.len(),
+        base_seed,
+        options.output
+    );
+    Ok(())
+}
+
+/// Let the bot play one game from `start_level` until it runs out of lives
+pub fn play_one(config: &Config, theme: &Theme, start_level: u16) -> GameResult {
+    let mut game = Game::headless(config, theme);
+    game.start_at_level(start_level);
+    game.set_autoplay(true);
+
+    let mut updates = 0;
+    while game.get_game_state() != GameState::GameOver && updates < MAX_UPDATES {
+        game.update();
+        updates += 1;
+    }
+
+    let killed_by = game.get_session_stats().get_killed_by();
+    GameResult {
+        level: game.get_level(),
+        score: game.get_score(),
+        killed_by: GHOST_NAMES.map(|name| killed_by.get(name).copied().unwrap_or(0)),
+        timed_out: updates == MAX_UPDATES,
+    }
+}
+
+pub fn summarize(start_level: u16, results: &[GameResult]) -> ProfileSummary {
+    let mut scores: Vec<u32> = results.iter().map(|result| result.score).collect();
+    scores.sort_unstable();
+    let quantile = |q: usize| match scores.len() {
+        0 => 0,
+        len => scores[(len - 1) * q / 4],
+    };
+
+    let mut killed_by = [0; 4];
+    for result in results {
+        for (total, deaths) in killed_by.iter_mut().zip(result.killed_by) {
+            *total += deaths;
+        }
+    }
+
+    let level_sum: u32 = results.iter().map(|result| result.level as u32).sum();
+    ProfileSummary {
+        start_level,
+        games: results.len(),
+        average_level: level_sum as f64 / results.len().max(1) as f64,
+        score_quartiles: [0, 1, 2, 3, 4].map(quantile),
+        killed_by,
+        timeouts: results.iter().filter(|result| result.timed_out).count(),
+    }
+}
+
+pub fn to_csv(summaries: &[ProfileSummary]) -> String {
+    let mut text = String::from(
+        "start_level,games,average_level,score_min,score_p25,score_median,score_p75,score_max",
+    );
+    for name in GHOST_NAMES {
+        text.push_str(&format!(",deaths_{}", name));
+    }
+    text.push_str(",timeouts\n");
+
+    for summary in summaries {
+        text.push_str(&format!(
+            "{},{},{:.2}",
+            summary.start_level, summary.games, summary.average_level
+        ));
+        for score in summary.score_quartiles {
+            text.push_str(&format!(",{}", score));
+        }
+        for deaths in summary.killed_by {
+            text.push_str(&format!(",{}", deaths));
+        }
+        text.push_str(&format!(",{}\n", summary.timeouts));
+    }
+    text
+}
+
+pub fn to_json(summaries: &[ProfileSummary]) -> String {
+    let profiles: Vec<String> = summaries
+        .iter()
+        .map(|summary| {
+            let [min, p25, median, p75, max] = summary.score_quartiles;
+            let deaths: Vec<String> = GHOST_NAMES
+                .iter()
+                .zip(summary.killed_by)
+                .map(|(name, count)| format!("\"{}\": {}", name, count))
+                .collect();
+            format!(
+                concat!(
+                    "  {{\"start_level\": {}, \"games\": {}, \"average_level\": {:.2}, ",
+                    "\"score\": {{\"min\": {}, \"p25\": {}, \"median\": {}, \"p75\": {}, \"max\": {}}}, ",
+                    "\"deaths\": {{{}}}, \"timeouts\": {}}}"
+                ),
+                summary.start_level,
+                summary.games,
+                summary.average_level,
+                min,
+                p25,
+                median,
+                p75,
+                max,
+                deaths.join(", "),
+                summary.timeouts
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", profiles.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(level: u16, score: u32, killed_by: [u32; 4]) -> GameResult {
+        GameResult {
+            level,
+            score,
+            killed_by,
+            timed_out: false,
+        }
+    }
+
+    fn sample() -> ProfileSummary {
+        summarize(
+            1,
+            &[
+                result(2, 4000, [1, 1, 0, 0]),
+                result(1, 1000, [2, 0, 0, 1]),
+                result(3, 9000, [0, 1, 1, 1]),
+                result(2, 5000, [1, 0, 2, 0]),
+                result(4, 12000, [3, 0, 0, 0]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_summary_statistics() {
+        let summary = sample();
+        assert_eq!(summary.games, 5);
+        assert!((summary.average_level - 2.4).abs() < 1e-9);
+        assert_eq!(summary.score_quartiles, [1000, 4000, 5000, 9000, 12000]);
+        assert_eq!(summary.killed_by, [7, 2, 3, 2]);
+        assert_eq!(summary.timeouts, 0);
+    }
+
+    #[test]
+    fn test_empty_profile() {
+        let summary = summarize(5, &[]);
+        assert_eq!(summary.games, 0);
+        assert_eq!(summary.average_level, 0.0);
+        assert_eq!(summary.score_quartiles, [0; 5]);
+    }
+
+    #[test]
+    fn test_csv_report() {
+        let csv = to_csv(&[sample()]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("start_level,games,average_level"));
+        assert_eq!(lines[1], "1,5,2.40,1000,4000,5000,9000,12000,7,2,3,2,0");
+        assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+    }
+
+    #[test]
+    fn test_json_report() {
+        let json = to_json(&[sample()]);
+        assert!(json.starts_with("[\n  {\"start_level\": 1, \"games\": 5"));
+        assert!(json.contains("\"median\": 5000"));
+        assert!(
+            json.contains("\"deaths\": {\"blinky\": 7, \"inky\": 2, \"pinky\": 3, \"clyde\": 2}")
+        );
+        assert!(json.trim_end().ends_with(']'));
+    }
+}
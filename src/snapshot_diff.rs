@@ -0,0 +1,142 @@
+use crate::config::Config;
+use crate::error::PacmanError;
+use crate::font::{Fonts, GameFont};
+use crate::game::snapshot::Snapshot;
+use crate::game::Game;
+use crate::theme::Theme;
+use crate::BLACK;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::surface::Surface;
+use sdl2::video::WindowContext;
+
+const BYTES_PER_PIXEL: usize = 3;
+
+/// Render two saved snapshots in a hidden window and write a BMP marking every changed pixel
+pub fn run(
+    before_path: &str,
+    after_path: &str,
+    output_path: &str,
+    config: &Config,
+    theme: &Theme,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let before = Snapshot::load(before_path)?;
+    let after = Snapshot::load(after_path)?;
+
+    let sdl_context = sdl2::init().map_err(PacmanError::SdlInit)?;
+    let video_subsystem = sdl_context.video().map_err(PacmanError::SdlInit)?;
+    let _image_context =
+        sdl2::image::init(sdl2::image::InitFlag::PNG).map_err(PacmanError::SdlInit)?;
+    let fonts = Fonts::new(config.bitmap_font)?;
+
+    // Diffs come out at the size the game is played at
+    let (width, height) = config.layout.window_size();
+    let window = video_subsystem
+        .window("Pacman snapshot diff", width, height)
+        .hidden()
+        .build()?;
+    let mut canvas = window.into_canvas().software().build()?;
+    config.layout.apply(&mut canvas)?;
+    let texture_creator = canvas.texture_creator();
+    let font = fonts.load(theme)?;
+
+    let before_pixels = render_snapshot(
+        &mut canvas,
+        &texture_creator,
+        &fonts,
+        &font,
+        config,
+        theme,
+        &before,
+    )?;
+    let after_pixels = render_snapshot(
+        &mut canvas,
+        &texture_creator,
+        &fonts,
+        &font,
+        config,
+        theme,
+        &after,
+    )?;
+
+    let (mut diff, changed) = diff_frames(&before_pixels, &after_pixels);
+    let surface = Surface::from_data(
+        &mut diff,
+        width,
+        height,
+        width * BYTES_PER_PIXEL as u32,
+        PixelFormatEnum::RGB24,
+    )?;
+    surface.save_bmp(output_path)?;
+
+    println!(
+        "{} of {} pixels changed, diff written to {}",
+        changed,
+        width * height,
+        output_path
+    );
+    Ok(())
+}
+
+fn render_snapshot(
+    canvas: &mut WindowCanvas,
+    texture_creator: &TextureCreator<WindowContext>,
+    fonts: &Fonts,
+    font: &GameFont,
+    config: &Config,
+    theme: &Theme,
+    snapshot: &Snapshot,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut game = Game::new(texture_creator, fonts, config, theme)?;
+    game.restore_snapshot(snapshot);
+
+    canvas.set_draw_color(BLACK);
+    canvas.clear();
+    game.draw(canvas, texture_creator, font)?;
+
+    Ok(canvas.read_pixels(None, PixelFormatEnum::RGB24)?)
+}
+
+/// Compare two RGB24 frames of equal size.
+///
+/// Changed pixels come out solid red over a dimmed copy of `after`; also returns how many changed.
+pub fn diff_frames(before: &[u8], after: &[u8]) -> (Vec<u8>, usize) {
+    let mut diff = Vec::with_capacity(after.len());
+    let mut changed = 0;
+
+    for (old, new) in before
+        .chunks(BYTES_PER_PIXEL)
+        .zip(after.chunks(BYTES_PER_PIXEL))
+    {
+        if old == new {
+            diff.extend(new.iter().map(|channel| channel / 4));
+        } else {
+            diff.extend_from_slice(&[255, 0, 0]);
+            changed += 1;
+        }
+    }
+
+    (diff, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_frames() {
+        let frame = [200, 100, 40, 0, 0, 0];
+        let (diff, changed) = diff_frames(&frame, &frame);
+        assert_eq!(changed, 0);
+        assert_eq!(diff, vec![50, 25, 10, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_changed_pixels_are_red() {
+        let before = [0, 0, 0, 8, 8, 8, 1, 2, 3];
+        let after = [0, 0, 0, 8, 8, 9, 4, 4, 4];
+        let (diff, changed) = diff_frames(&before, &after);
+        assert_eq!(changed, 2);
+        assert_eq!(diff, vec![0, 0, 0, 255, 0, 0, 255, 0, 0]);
+    }
+}
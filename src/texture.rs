@@ -1,10 +1,10 @@
+use crate::error::PacmanError;
+use crate::font::GameFont;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::{Texture, TextureCreator, WindowCanvas};
 use sdl2::surface::Surface;
-use sdl2::ttf::Font;
 use sdl2::video::WindowContext;
-use std::path::Path;
 
 pub struct GameTexture<'a> {
     texture: Option<Texture<'a>>,
@@ -12,6 +12,12 @@ pub struct GameTexture<'a> {
     height: u32,
 }
 
+impl Default for GameTexture<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> GameTexture<'a> {
     pub fn new() -> Self {
         GameTexture {
@@ -21,17 +27,16 @@ impl<'a> GameTexture<'a> {
         }
     }
 
-    pub fn load_from_file(
+    pub fn load_from_surface(
         &mut self,
         texture_creator: &'a TextureCreator<WindowContext>,
-        path: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        surface: &Surface,
+    ) -> Result<(), PacmanError> {
         self.reset();
 
-        let surface: Surface = sdl2::image::LoadSurface::from_file(Path::new(path))
-            .map_err(|e| format!("Unable to load image {}: {}", path, e))?;
-
-        let texture = texture_creator.create_texture_from_surface(&surface)?;
+        let texture = texture_creator
+            .create_texture_from_surface(surface)
+            .map_err(|e| PacmanError::AssetLoad(format!("Unable to create texture: {}", e)))?;
 
         self.width = surface.width();
         self.height = surface.height();
@@ -44,17 +49,16 @@ impl<'a> GameTexture<'a> {
         &mut self,
         texture_creator: &'a TextureCreator<WindowContext>,
         text: &str,
-        font: &Font,
+        font: &GameFont,
         color: Color,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), PacmanError> {
         self.reset();
 
-        let text_surface = font
-            .render(text)
-            .solid(color)
-            .map_err(|e| format!("Unable to render text surface: {}", e))?;
+        let text_surface = font.render(text, color)?;
 
-        let texture = texture_creator.create_texture_from_surface(&text_surface)?;
+        let texture = texture_creator
+            .create_texture_from_surface(&text_surface)
+            .map_err(|e| PacmanError::Render(format!("Unable to create text texture: {}", e)))?;
 
         self.width = text_surface.width();
         self.height = text_surface.height();
@@ -63,26 +67,24 @@ impl<'a> GameTexture<'a> {
         Ok(())
     }
 
+    pub fn is_loaded(&self) -> bool {
+        self.texture.is_some()
+    }
+
     pub fn reset(&mut self) {
         self.texture = None;
         self.width = 0;
         self.height = 0;
     }
 
-    pub fn set_color(
-        &mut self,
-        red: u8,
-        green: u8,
-        blue: u8,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn set_color(&mut self, red: u8, green: u8, blue: u8) -> Result<(), PacmanError> {
         if let Some(ref mut texture) = self.texture {
             texture.set_color_mod(red, green, blue);
         }
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn set_alpha(&mut self, alpha: u8) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn set_alpha(&mut self, alpha: u8) -> Result<(), PacmanError> {
         if let Some(ref mut texture) = self.texture {
             texture.set_alpha_mod(alpha);
         }
@@ -95,10 +97,11 @@ impl<'a> GameTexture<'a> {
         x: i32,
         y: i32,
         clip: Option<Rect>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.render_with_facing(canvas, x, y, 0, clip)
+    ) -> Result<(), PacmanError> {
+        self.render_with_facing(canvas, x, y, 0, clip, 1)
     }
 
+    /// Draw turned a quarter turn per `facing`, stretched to `scale` times the clip's size
     pub fn render_with_facing(
         &self,
         canvas: &mut WindowCanvas,
@@ -106,7 +109,8 @@ impl<'a> GameTexture<'a> {
         y: i32,
         facing: u8,
         clip: Option<Rect>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        scale: u32,
+    ) -> Result<(), PacmanError> {
         if let Some(ref texture) = self.texture {
             let mut render_quad = Rect::new(x, y, self.width, self.height);
 
@@ -114,6 +118,8 @@ impl<'a> GameTexture<'a> {
                 render_quad.set_width(clip_rect.width());
                 render_quad.set_height(clip_rect.height());
             }
+            render_quad.set_width(render_quad.width() * scale);
+            render_quad.set_height(render_quad.height() * scale);
 
             let angle = match facing {
                 0 => 0.0,
@@ -123,17 +129,17 @@ impl<'a> GameTexture<'a> {
                 _ => 0.0,
             };
 
-            canvas.copy_ex(texture, clip, Some(render_quad), angle, None, false, false)?;
+            canvas
+                .copy_ex(texture, clip, Some(render_quad), angle, None, false, false)
+                .map_err(PacmanError::Render)?;
         }
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn get_width(&self) -> u32 {
         self.width
     }
 
-    #[allow(dead_code)]
     pub fn get_height(&self) -> u32 {
         self.height
     }
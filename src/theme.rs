@@ -0,0 +1,331 @@
+use crate::palette::Palette;
+use sdl2::pixels::Color;
+use std::path::{Path, PathBuf};
+
+const ASSETS_DIR: &str = "assets";
+const THEMES_DIR: &str = "themes";
+const MANIFEST_FILE: &str = "manifest.txt";
+
+/// Sprite layout and colors for a skin, read from `themes/<name>/manifest.txt`.
+///
+/// The manifest holds `key = value` lines; `#` starts a comment. Recognized keys:
+/// `pacman_frames`, `death_frames`, `ghost_body_frames`, `maze_color` (`r,g,b`), `maze_colors`
+/// (`r,g,b; r,g,b; ...`, the rotation used by `--maze-colors`) and `font`.
+/// Any image or font missing from the theme directory falls back to `assets/`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeManifest {
+    pub pacman_frames: usize,
+    pub death_frames: usize,
+    pub ghost_body_frames: usize,
+    pub maze_color: Option<Color>,
+    pub maze_colors: Vec<Color>,
+    pub font: String,
+}
+
+impl Default for ThemeManifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThemeManifest {
+    pub fn new() -> Self {
+        ThemeManifest {
+            pacman_frames: 3,
+            death_frames: 10,
+            ghost_body_frames: 2,
+            maze_color: None,
+            maze_colors: Vec::new(),
+            font: "emulogic.ttf".to_string(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut manifest = ThemeManifest::new();
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Manifest line {}: expected key = value", line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "pacman_frames" => manifest.pacman_frames = parse_frames(value, line_number)?,
+                "death_frames" => manifest.death_frames = parse_frames(value, line_number)?,
+                "ghost_body_frames" => {
+                    manifest.ghost_body_frames = parse_frames(value, line_number)?
+                }
+                "maze_color" => manifest.maze_color = Some(parse_color(value, line_number)?),
+                "maze_colors" => {
+                    manifest.maze_colors = value
+                        .split(';')
+                        .map(|color| parse_color(color, line_number))
+                        .collect::<Result<_, _>>()?
+                }
+                "font" => manifest.font = value.to_string(),
+                _ => {
+                    return Err(
+                        format!("Manifest line {}: unknown key '{}'", line_number, key).into(),
+                    )
+                }
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+fn parse_frames(value: &str, line_number: usize) -> Result<usize, Box<dyn std::error::Error>> {
+    match value.parse::<usize>() {
+        // Frame counters are stored in u8, which caps the animation length
+        Ok(frames) if (1..=15).contains(&frames) => Ok(frames),
+        _ => Err(format!(
+            "Manifest line {}: frame count must be between 1 and 15, got '{}'",
+            line_number, value
+        )
+        .into()),
+    }
+}
+
+fn parse_color(value: &str, line_number: usize) -> Result<Color, Box<dyn std::error::Error>> {
+    let channels: Vec<u8> = value
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Manifest line {}: invalid color '{}'", line_number, value))?;
+
+    match channels.as_slice() {
+        [r, g, b] => Ok(Color::RGB(*r, *g, *b)),
+        _ => Err(format!("Manifest line {}: color needs three channels", line_number).into()),
+    }
+}
+
+/// Maze colors a theme without its own `maze_colors` cycles through
+const DEFAULT_MAZE_COLORS: [Color; 6] = [
+    Color::RGB(33, 33, 255),
+    Color::RGB(255, 184, 174),
+    Color::RGB(222, 151, 81),
+    Color::RGB(255, 0, 0),
+    Color::RGB(0, 222, 222),
+    Color::RGB(150, 90, 255),
+];
+
+/// What moves the maze on to the next color of the rotation, picked with `--maze-colors`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MazeColors {
+    /// One color for the whole game, as in the arcade
+    Fixed,
+    PerLevel,
+    /// Each maze layout keeps a color of its own
+    PerMaze,
+}
+
+impl MazeColors {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fixed" => Some(MazeColors::Fixed),
+            "level" => Some(MazeColors::PerLevel),
+            "maze" => Some(MazeColors::PerMaze),
+            _ => None,
+        }
+    }
+}
+
+/// Asset directory override selected with `--theme`
+#[derive(Debug, Clone)]
+pub struct Theme {
+    dir: Option<PathBuf>,
+    pub manifest: ThemeManifest,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Theme {
+    /// The stock look, loaded straight from `assets/`
+    pub fn new() -> Self {
+        Theme {
+            dir: None,
+            manifest: ThemeManifest::new(),
+        }
+    }
+
+    pub fn load(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = Path::new(THEMES_DIR).join(name);
+        if !dir.is_dir() {
+            return Err(format!("Theme directory not found: {}", dir.display()).into());
+        }
+
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest = if manifest_path.is_file() {
+            let text = std::fs::read_to_string(&manifest_path)?;
+            ThemeManifest::parse(&text)
+                .map_err(|e| format!("{}: {}", manifest_path.display(), e))?
+        } else {
+            ThemeManifest::new()
+        };
+
+        println!("Loaded theme '{}'", name);
+        Ok(Theme {
+            dir: Some(dir),
+            manifest,
+        })
+    }
+
+    /// Resolve an asset file, preferring the theme's copy when it has one
+    pub fn asset_path(&self, file: &str) -> String {
+        if let Some(ref dir) = self.dir {
+            let themed = dir.join(file);
+            if themed.is_file() {
+                return themed.to_string_lossy().into_owned();
+            }
+        }
+        Path::new(ASSETS_DIR)
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Every place `asset_path` looks for a file, in the order it looks
+    pub fn search_paths(&self, file: &str) -> Vec<String> {
+        let mut paths = Vec::new();
+        if let Some(ref dir) = self.dir {
+            paths.push(dir.join(file).to_string_lossy().into_owned());
+        }
+        paths.push(
+            Path::new(ASSETS_DIR)
+                .join(file)
+                .to_string_lossy()
+                .into_owned(),
+        );
+        paths
+    }
+
+    pub fn font_path(&self) -> String {
+        self.asset_path(&self.manifest.font)
+    }
+
+    /// Maze color for a palette on a level and maze layout.
+    ///
+    /// The theme's own colors only replace the standard one; the accessible palettes keep
+    /// theirs whatever the rotation.
+    pub fn maze_color(
+        &self,
+        palette: Palette,
+        rotation: MazeColors,
+        level: u16,
+        maze: usize,
+    ) -> Color {
+        let colors: &[Color] = if self.manifest.maze_colors.is_empty() {
+            &DEFAULT_MAZE_COLORS
+        } else {
+            &self.manifest.maze_colors
+        };
+        let step = match rotation {
+            MazeColors::Fixed => None,
+            MazeColors::PerLevel => Some(level.saturating_sub(1) as usize),
+            MazeColors::PerMaze => Some(maze),
+        };
+        match (palette, step, self.manifest.maze_color) {
+            (Palette::Standard, Some(step), _) => colors[step % colors.len()],
+            (Palette::Standard, None, Some(color)) => color,
+            _ => palette.maze_color(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_manifest_uses_defaults() {
+        let manifest = ThemeManifest::parse("# nothing here\n\n").unwrap();
+        assert_eq!(manifest, ThemeManifest::new());
+    }
+
+    #[test]
+    fn test_manifest_values() {
+        let manifest = ThemeManifest::parse(
+            "pacman_frames = 4\nghost_body_frames=3 # wobble\nmaze_color = 200, 0, 40\nfont = retro.ttf",
+        )
+        .unwrap();
+        assert_eq!(manifest.pacman_frames, 4);
+        assert_eq!(manifest.death_frames, 10);
+        assert_eq!(manifest.ghost_body_frames, 3);
+        assert_eq!(manifest.maze_color, Some(Color::RGB(200, 0, 40)));
+        assert_eq!(manifest.font, "retro.ttf");
+    }
+
+    #[test]
+    fn test_manifest_errors_report_line() {
+        let error = ThemeManifest::parse("pacman_frames = 3\nspeed = 9").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+
+        assert!(ThemeManifest::parse("pacman_frames = 0").is_err());
+        assert!(ThemeManifest::parse("maze_color = 1,2").is_err());
+        assert!(ThemeManifest::parse("just text").is_err());
+    }
+
+    #[test]
+    fn test_maze_color_rotation() {
+        let mut theme = Theme::new();
+        let fixed = theme.maze_color(Palette::Standard, MazeColors::Fixed, 5, 1);
+        assert_eq!(fixed, Palette::Standard.maze_color());
+
+        let first = theme.maze_color(Palette::Standard, MazeColors::PerLevel, 1, 0);
+        let second = theme.maze_color(Palette::Standard, MazeColors::PerLevel, 2, 0);
+        assert_ne!(first, second);
+        assert_eq!(
+            theme.maze_color(Palette::Standard, MazeColors::PerMaze, 7, 1),
+            theme.maze_color(Palette::Standard, MazeColors::PerMaze, 2, 1)
+        );
+        assert_eq!(
+            theme.maze_color(Palette::HighContrast, MazeColors::PerLevel, 2, 0),
+            Palette::HighContrast.maze_color()
+        );
+
+        theme.manifest = ThemeManifest::parse("maze_colors = 1,2,3; 4,5,6").unwrap();
+        assert_eq!(
+            theme.maze_color(Palette::Standard, MazeColors::PerLevel, 3, 0),
+            Color::RGB(1, 2, 3)
+        );
+        assert!(ThemeManifest::parse("maze_colors = 1,2,3;").is_err());
+    }
+
+    #[test]
+    fn test_default_theme_paths() {
+        let theme = Theme::new();
+        assert_eq!(
+            theme.asset_path("Map24.png"),
+            Path::new("assets").join("Map24.png").to_string_lossy()
+        );
+        assert!(theme.font_path().ends_with("emulogic.ttf"));
+    }
+
+    #[test]
+    fn test_search_paths_try_the_theme_first() {
+        assert_eq!(
+            Theme::new().search_paths("Door.png"),
+            vec![Path::new("assets").join("Door.png").to_string_lossy()]
+        );
+
+        let themed = Theme {
+            dir: Some(Path::new("themes").join("neon")),
+            manifest: ThemeManifest::new(),
+        };
+        let paths = themed.search_paths("Door.png");
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].starts_with("themes"));
+        assert_eq!(paths[1], themed.asset_path("Door.png"));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::board::Direction;
+use crate::game::state::GameState;
+use crate::layout::Layout;
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, WindowCanvas};
+
+/// How far, in board pixels, a finger has to travel before the move counts as a swipe
+const SWIPE_DISTANCE: f32 = 24.0;
+/// Centre of the d-pad and the distance from it to the end of each arm, in board pixels.
+/// It sits over the bottom-right corner of the maze, out of the way of the lives and level
+/// counters.
+const DPAD_CENTER: (f32, f32) = (SCREEN_WIDTH as f32 - 90.0, SCREEN_HEIGHT as f32 - 150.0);
+const DPAD_REACH: f32 = 72.0;
+/// Width of each arm
+const DPAD_ARM: u32 = 48;
+const DPAD_COLOR: Color = Color::RGBA(255, 255, 255, 48);
+const DPAD_PRESSED_COLOR: Color = Color::RGBA(255, 255, 255, 112);
+/// SDL's mouse id for the mouse events it makes up from touches, which are handled as touches
+const TOUCH_MOUSE_ID: u32 = u32::MAX;
+
+/// What a finger did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A swipe, or a press on one of the d-pad's arms
+    Turn(Direction),
+    /// A touch that let go without moving far, anywhere off the d-pad
+    Tap,
+}
+
+/// The key a gesture stands in for.
+///
+/// A tap starts a game from the game over and insert coin screens like Enter, and otherwise
+/// pauses and carries on like Space.
+pub fn key_for(gesture: Gesture, state: GameState) -> Keycode {
+    match gesture {
+        Gesture::Turn(Direction::Right) => Keycode::Right,
+        Gesture::Turn(Direction::Up) => Keycode::Up,
+        Gesture::Turn(Direction::Left) => Keycode::Left,
+        Gesture::Turn(Direction::Down | Direction::Nowhere) => Keycode::Down,
+        Gesture::Tap => match state {
+            GameState::GameOver | GameState::InsertCoin => Keycode::Return,
+            _ => Keycode::Space,
+        },
+    }
+}
+
+/// What the finger currently down is doing
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Touch {
+    /// Holding the d-pad, on the arm pointing this way
+    Dpad(Option<Direction>),
+    /// Moving freely; `from` is where the last swipe ended or the finger landed
+    Swipe {
+        from: (f32, f32),
+        last: Option<Direction>,
+    },
+}
+
+/// Swipes anywhere, plus an on-screen d-pad, for touch screens.
+///
+/// Dragging with the left mouse button does the same, so it can be tried without one. Only
+/// the first finger down is followed; others are ignored until it lifts.
+pub struct TouchControls {
+    /// Window pixels per board pixel, to bring mouse positions onto the board
+    zoom: f32,
+    touch: Option<Touch>,
+    finger: Option<i64>,
+}
+
+impl TouchControls {
+    pub fn new(layout: &Layout) -> Self {
+        TouchControls {
+            zoom: layout.zoom(),
+            touch: None,
+            finger: None,
+        }
+    }
+
+    /// Follow touch and mouse events, returning a gesture when one is finished
+    pub fn handle_event(&mut self, event: &Event) -> Option<Gesture> {
+        let board = |x: f32, y: f32| (x * SCREEN_WIDTH as f32, y * SCREEN_HEIGHT as f32);
+        match *event {
+            Event::FingerDown {
+                finger_id, x, y, ..
+            } if self.finger.is_none() => {
+                self.finger = Some(finger_id);
+                self.press(board(x, y))
+            }
+            Event::FingerMotion {
+                finger_id, x, y, ..
+            } if self.finger == Some(finger_id) => self.drag(board(x, y)),
+            Event::FingerUp { finger_id, .. } if self.finger == Some(finger_id) => {
+                self.finger = None;
+                self.release()
+            }
+            Event::MouseButtonDown {
+                which,
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } if which != TOUCH_MOUSE_ID && self.finger.is_none() => {
+                self.press(self.board_point(x, y))
+            }
+            Event::MouseMotion {
+                which,
+                mousestate,
+                x,
+                y,
+                ..
+            } if which != TOUCH_MOUSE_ID && mousestate.left() && self.finger.is_none() => {
+                self.drag(self.board_point(x, y))
+            }
+            Event::MouseButtonUp {
+                which,
+                mouse_btn: MouseButton::Left,
+                ..
+            } if which != TOUCH_MOUSE_ID && self.finger.is_none() => self.release(),
+            _ => None,
+        }
+    }
+
+    fn board_point(&self, x: i32, y: i32) -> (f32, f32) {
+        (x as f32 / self.zoom, y as f32 / self.zoom)
+    }
+
+    fn press(&mut self, at: (f32, f32)) -> Option<Gesture> {
+        if let Some(arm) = dpad_arm(at) {
+            self.touch = Some(Touch::Dpad(Some(arm)));
+            return Some(Gesture::Turn(arm));
+        }
+        if dpad_contains(at) {
+            self.touch = Some(Touch::Dpad(None));
+            return None;
+        }
+        self.touch = Some(Touch::Swipe {
+            from: at,
+            last: None,
+        });
+        None
+    }
+
+    /// A swipe turns as soon as it is long enough, and a finger that keeps going the other way
+    /// without lifting turns again
+    fn drag(&mut self, at: (f32, f32)) -> Option<Gesture> {
+        match self.touch? {
+            Touch::Dpad(held) => {
+                let arm = dpad_arm(at);
+                self.touch = Some(Touch::Dpad(arm.or(held)));
+                arm.filter(|&arm| Some(arm) != held).map(Gesture::Turn)
+            }
+            Touch::Swipe { from, last } => {
+                let (dx, dy) = (at.0 - from.0, at.1 - from.1);
+                if dx.hypot(dy) < SWIPE_DISTANCE {
+                    return None;
+                }
+                let direction = dominant_direction(dx, dy);
+                self.touch = Some(Touch::Swipe {
+                    from: at,
+                    last: Some(direction),
+                });
+                (last != Some(direction)).then_some(Gesture::Turn(direction))
+            }
+        }
+    }
+
+    fn release(&mut self) -> Option<Gesture> {
+        match self.touch.take()? {
+            Touch::Swipe { last: None, .. } => Some(Gesture::Tap),
+            _ => None,
+        }
+    }
+
+    /// Draw the d-pad over the maze, lighting up the arm being held
+    pub fn draw(&self, canvas: &mut WindowCanvas) -> Result<(), Box<dyn std::error::Error>> {
+        let held = match self.touch {
+            Some(Touch::Dpad(held)) => held,
+            _ => None,
+        };
+        canvas.set_blend_mode(BlendMode::Blend);
+        for arm in [
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+        ] {
+            let color = if held == Some(arm) {
+                DPAD_PRESSED_COLOR
+            } else {
+                DPAD_COLOR
+            };
+            canvas.set_draw_color(color);
+            canvas.fill_rect(arm_rect(arm))?;
+        }
+        canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+}
+
+/// Whichever way the larger part of a move points
+fn dominant_direction(dx: f32, dy: f32) -> Direction {
+    if dx.abs() >= dy.abs() {
+        if dx > 0.0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        }
+    } else if dy > 0.0 {
+        Direction::Down
+    } else {
+        Direction::Up
+    }
+}
+
+fn dpad_contains(at: (f32, f32)) -> bool {
+    let (dx, dy) = (at.0 - DPAD_CENTER.0, at.1 - DPAD_CENTER.1);
+    dx.abs() <= DPAD_REACH && dy.abs() <= DPAD_REACH
+}
+
+/// The arm under a point on the d-pad; the middle belongs to no arm
+fn dpad_arm(at: (f32, f32)) -> Option<Direction> {
+    let (dx, dy) = (at.0 - DPAD_CENTER.0, at.1 - DPAD_CENTER.1);
+    let hub = DPAD_ARM as f32 / 2.0;
+    if !dpad_contains(at) || (dx.abs() <= hub && dy.abs() <= hub) {
+        return None;
+    }
+    Some(dominant_direction(dx, dy))
+}
+
+fn arm_rect(arm: Direction) -> Rect {
+    let (x, y) = (DPAD_CENTER.0 as i32, DPAD_CENTER.1 as i32);
+    let (reach, half) = (DPAD_REACH as i32, DPAD_ARM as i32 / 2);
+    let length = (reach - half) as u32;
+    match arm {
+        Direction::Right => Rect::new(x + half, y - half, length, DPAD_ARM),
+        Direction::Left => Rect::new(x - reach, y - half, length, DPAD_ARM),
+        Direction::Down => Rect::new(x - half, y + half, DPAD_ARM, length),
+        _ => Rect::new(x - half, y - reach, DPAD_ARM, length),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controls() -> TouchControls {
+        TouchControls::new(&Layout::new())
+    }
+
+    #[test]
+    fn test_swipes_turn_and_taps_tap() {
+        let mut touch = controls();
+        assert_eq!(touch.press((100.0, 100.0)), None);
+        assert_eq!(touch.drag((110.0, 104.0)), None);
+        assert_eq!(
+            touch.drag((130.0, 110.0)),
+            Some(Gesture::Turn(Direction::Right))
+        );
+        // Carrying on the same way does not turn again, but bending round does
+        assert_eq!(touch.drag((160.0, 112.0)), None);
+        assert_eq!(
+            touch.drag((162.0, 80.0)),
+            Some(Gesture::Turn(Direction::Up))
+        );
+        assert_eq!(touch.release(), None);
+
+        assert_eq!(touch.press((300.0, 300.0)), None);
+        assert_eq!(touch.drag((305.0, 302.0)), None);
+        assert_eq!(touch.release(), Some(Gesture::Tap));
+        assert_eq!(touch.release(), None);
+    }
+
+    #[test]
+    fn test_dpad_arms() {
+        let (x, y) = DPAD_CENTER;
+        let mut touch = controls();
+        assert_eq!(
+            touch.press((x, y + 50.0)),
+            Some(Gesture::Turn(Direction::Down))
+        );
+        assert_eq!(touch.drag((x, y)), None);
+        assert_eq!(
+            touch.drag((x - 60.0, y + 5.0)),
+            Some(Gesture::Turn(Direction::Left))
+        );
+        // Letting go of the d-pad is not a tap
+        assert_eq!(touch.release(), None);
+
+        assert_eq!(touch.press((x, y)), None);
+        assert_eq!(touch.release(), None);
+        assert_eq!(dpad_arm((x + DPAD_REACH + 1.0, y)), None);
+    }
+
+    #[test]
+    fn test_mouse_drags_like_a_finger_but_emulated_touches_are_ignored() {
+        let layout = Layout {
+            scale: 2,
+            ..Layout::new()
+        };
+        let mut touch = TouchControls::new(&layout);
+        let button = |down: bool, which: u32, x: i32| {
+            let (timestamp, window_id, clicks, y) = (0, 1, 1, 400);
+            let mouse_btn = MouseButton::Left;
+            if down {
+                Event::MouseButtonDown {
+                    timestamp,
+                    window_id,
+                    which,
+                    mouse_btn,
+                    clicks,
+                    x,
+                    y,
+                }
+            } else {
+                Event::MouseButtonUp {
+                    timestamp,
+                    window_id,
+                    which,
+                    mouse_btn,
+                    clicks,
+                    x,
+                    y,
+                }
+            }
+        };
+        assert_eq!(touch.handle_event(&button(true, 0, 200)), None);
+        assert_eq!(
+            touch.handle_event(&button(false, 0, 204)),
+            Some(Gesture::Tap)
+        );
+        assert_eq!(touch.handle_event(&button(true, TOUCH_MOUSE_ID, 200)), None);
+        assert_eq!(
+            touch.handle_event(&button(false, TOUCH_MOUSE_ID, 204)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_taps_start_games_or_pause() {
+        assert_eq!(key_for(Gesture::Tap, GameState::GameOver), Keycode::Return);
+        assert_eq!(key_for(Gesture::Tap, GameState::Playing), Keycode::Space);
+        assert_eq!(
+            key_for(Gesture::Turn(Direction::Left), GameState::Playing),
+            Keycode::Left
+        );
+    }
+}
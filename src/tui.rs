@@ -0,0 +1,254 @@
+use crate::board::BlockType;
+use crate::config::Config;
+use crate::game::ghost_mode::GhostMode;
+use crate::game::replay::BEST_REPLAY_PATH;
+use crate::game::state::GameState;
+use crate::game::Game;
+use crate::position::{Position, Tile};
+use crate::theme::Theme;
+use crate::{BOARD_HEIGHT, BOARD_WIDTH, TICK_MS};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use sdl2::keyboard::Keycode;
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+/// Ghost letters in manager order; extra ghosts are all `G`
+const GHOST_LETTERS: [char; 4] = ['B', 'I', 'P', 'C'];
+const PACMAN: char = '@';
+/// Eyes on their way home
+const EYES: char = '"';
+
+/// Puts the terminal back how it was found, even if the game loop bails out with an error
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter(out: &mut Stdout) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Play in the terminal, drawing the maze and everyone in it as characters.
+///
+/// The game runs headless with the same keys as the window. The screen is redrawn in full
+/// every update, so log lines printed by the game never stay up for long.
+pub fn run(config: &Config, theme: &Theme) -> Result<(), Box<dyn std::error::Error>> {
+    let mut game = Game::headless(config, theme);
+    game.set_best_replay_path(BEST_REPLAY_PATH);
+    game.set_autoplay(config.bot);
+    let mut out = io::stdout();
+    let _terminal = RawTerminal::enter(&mut out)?;
+
+    let tick = Duration::from_millis(TICK_MS as u64);
+    let mut next_tick = Instant::now();
+    loop {
+        // Take every key waiting, but never hold up the next update
+        while event::poll(next_tick.saturating_duration_since(Instant::now()))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && handle_key(&mut game, key, config) {
+                    return Ok(());
+                }
+            }
+        }
+        game.update();
+        draw(&mut out, &game)?;
+        next_tick += tick;
+    }
+}
+
+/// Pass a key on to the game, returning true once quitting is confirmed
+fn handle_key(game: &mut Game, key: KeyEvent, config: &Config) -> bool {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        return true;
+    }
+    match key.code {
+        KeyCode::Esc if !game.is_console_open() => return game.request_quit(),
+        KeyCode::Enter if !game.is_console_open() => game.press_start(config),
+        KeyCode::Char(c) if game.is_console_open() && c != '`' => {
+            game.type_text(&c.to_string());
+        }
+        code => {
+            if let Some(keycode) = keycode_for(code) {
+                game.handle_input(keycode);
+            }
+        }
+    }
+    false
+}
+
+/// The window's key for a terminal key, for the ones the game listens to
+fn keycode_for(code: KeyCode) -> Option<Keycode> {
+    let keycode = match code {
+        KeyCode::Left => Keycode::Left,
+        KeyCode::Right => Keycode::Right,
+        KeyCode::Up => Keycode::Up,
+        KeyCode::Down => Keycode::Down,
+        KeyCode::Enter => Keycode::Return,
+        KeyCode::Esc => Keycode::Escape,
+        KeyCode::Backspace => Keycode::Backspace,
+        KeyCode::F(n) => match n {
+            1 => Keycode::F1,
+            2 => Keycode::F2,
+            3 => Keycode::F3,
+            4 => Keycode::F4,
+            8 => Keycode::F8,
+            9 => Keycode::F9,
+            10 => Keycode::F10,
+            11 => Keycode::F11,
+            12 => Keycode::F12,
+            _ => return None,
+        },
+        KeyCode::Char(c) => match c.to_ascii_lowercase() {
+            ' ' => Keycode::Space,
+            '`' => Keycode::Backquote,
+            'w' => Keycode::W,
+            'a' => Keycode::A,
+            's' => Keycode::S,
+            'd' => Keycode::D,
+            'i' => Keycode::I,
+            'j' => Keycode::J,
+            'k' => Keycode::K,
+            'l' => Keycode::L,
+            'c' => Keycode::C,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(keycode)
+}
+
+/// The maze as one character per tile, with the ghosts and then Pac-Man drawn over it.
+///
+/// Frightened ghosts show as lowercase letters and eaten ones as eyes.
+fn frame_rows(game: &Game) -> Vec<String> {
+    let mut cells: Vec<char> = game
+        .get_map()
+        .iter()
+        .map(|&block| match block {
+            // Tunnel ends are just floor to the player
+            BlockType::Teleport(_) => ' ',
+            block => block.to_char(),
+        })
+        .collect();
+    let mut put = |position: Position, c: char| {
+        let tile = Tile::from_position(position);
+        if (0..BOARD_WIDTH as i16).contains(&tile.x) && (0..BOARD_HEIGHT as i16).contains(&tile.y) {
+            cells[tile.y as usize * BOARD_WIDTH + tile.x as usize] = c;
+        }
+    };
+
+    let ghosts = game.get_ghost_positions();
+    for (index, (position, state)) in ghosts.into_iter().zip(game.get_ghost_states()).enumerate() {
+        let letter = GHOST_LETTERS.get(index).copied().unwrap_or('G');
+        let c = match state.mode {
+            GhostMode::Frightened => letter.to_ascii_lowercase(),
+            GhostMode::Eaten => EYES,
+            _ => letter,
+        };
+        put(position, c);
+    }
+    put(game.get_pacman_position(), PACMAN);
+
+    cells
+        .chunks(BOARD_WIDTH)
+        .map(|row| row.iter().collect())
+        .collect()
+}
+
+fn status_line(game: &Game) -> String {
+    let prompt = match game.get_game_state() {
+        GameState::Ready => "READY!",
+        GameState::Paused => "PAUSED - Space to resume, Esc to quit",
+        GameState::LevelComplete => "LEVEL CLEAR",
+        GameState::GameOver => "GAME OVER - Enter to play again",
+        GameState::Milestone => "MAX DIFFICULTY - Space to continue",
+        GameState::InsertCoin => "C to insert a coin, Enter to start",
+        GameState::Menu => "Enter to play",
+        GameState::Playing | GameState::PacmanDeath => "",
+    };
+    format!(
+        "SCORE {}  LEVEL {}  LIVES {}  {}",
+        game.get_score(),
+        game.get_level(),
+        game.get_lives().max(0),
+        prompt
+    )
+}
+
+fn color_for(c: char) -> Color {
+    match c {
+        '#' => Color::Blue,
+        '=' => Color::Magenta,
+        PACMAN => Color::Yellow,
+        'B' => Color::Red,
+        'P' => Color::Magenta,
+        'I' => Color::Cyan,
+        'C' => Color::DarkYellow,
+        'G' => Color::Green,
+        'b' | 'p' | 'i' | 'c' | 'g' => Color::DarkBlue,
+        _ => Color::White,
+    }
+}
+
+/// Redraw the whole screen, tiles spaced out so the maze keeps roughly its shape
+fn draw(out: &mut Stdout, game: &Game) -> io::Result<()> {
+    queue!(
+        out,
+        MoveTo(0, 0),
+        ResetColor,
+        Print(status_line(game)),
+        Clear(ClearType::UntilNewLine)
+    )?;
+    for (y, row) in frame_rows(game).iter().enumerate() {
+        queue!(out, MoveTo(0, y as u16 + 1))?;
+        for c in row.chars() {
+            queue!(out, SetForegroundColor(color_for(c)), Print(c), Print(' '))?;
+        }
+    }
+    queue!(out, ResetColor, Clear(ClearType::FromCursorDown))?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_shows_the_maze_and_everyone_in_it() {
+        let mut config = Config::new();
+        config.seed = Some(1);
+        let game = Game::headless(&config, &Theme::new());
+        let rows = frame_rows(&game);
+        assert_eq!(rows.len(), BOARD_HEIGHT);
+        assert!(rows.iter().all(|row| row.chars().count() == BOARD_WIDTH));
+
+        let text = rows.concat();
+        assert_eq!(text.matches(PACMAN).count(), 1);
+        assert!(text.contains('#') && text.contains('.') && text.contains('o'));
+        for letter in GHOST_LETTERS {
+            assert!(text.contains(letter), "no {} in the maze", letter);
+        }
+        assert!(status_line(&game).starts_with("SCORE 0  LEVEL 1"));
+    }
+
+    #[test]
+    fn test_terminal_keys_map_to_game_keys() {
+        assert_eq!(keycode_for(KeyCode::Left), Some(Keycode::Left));
+        assert_eq!(keycode_for(KeyCode::Char('W')), Some(Keycode::W));
+        assert_eq!(keycode_for(KeyCode::Char(' ')), Some(Keycode::Space));
+        assert_eq!(keycode_for(KeyCode::F(10)), Some(Keycode::F10));
+        assert_eq!(keycode_for(KeyCode::Char('q')), None);
+    }
+}
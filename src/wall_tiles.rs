@@ -0,0 +1,172 @@
+/// Side of a wall tile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Corner of a wall tile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub const ALL: [Corner; 4] = [
+        Corner::TopLeft,
+        Corner::TopRight,
+        Corner::BottomLeft,
+        Corner::BottomRight,
+    ];
+
+    /// The two sides that meet at the corner, the horizontal one first
+    pub fn sides(self) -> (Side, Side) {
+        match self {
+            Corner::TopLeft => (Side::Top, Side::Left),
+            Corner::TopRight => (Side::Top, Side::Right),
+            Corner::BottomLeft => (Side::Bottom, Side::Left),
+            Corner::BottomRight => (Side::Bottom, Side::Right),
+        }
+    }
+
+    fn offset(self) -> (i32, i32) {
+        match self {
+            Corner::TopLeft => (-1, -1),
+            Corner::TopRight => (1, -1),
+            Corner::BottomLeft => (-1, 1),
+            Corner::BottomRight => (1, 1),
+        }
+    }
+}
+
+impl Side {
+    fn offset(self) -> (i32, i32) {
+        match self {
+            Side::Top => (0, -1),
+            Side::Bottom => (0, 1),
+            Side::Left => (-1, 0),
+            Side::Right => (1, 0),
+        }
+    }
+}
+
+/// A piece of wall outline drawn inside one tile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WallPiece {
+    /// A straight line along a side that faces open floor.
+    ///
+    /// The flags say whether the line stops short at its first and second end (left or top
+    /// end first) to leave room for a rounded corner.
+    Edge(Side, bool, bool),
+    /// A rounded corner where both sides face floor
+    OuterCorner(Corner),
+    /// The joint where the edges of the two neighbouring tiles meet, when both sides are
+    /// wall but the tile across the corner is floor
+    InnerCorner(Corner),
+}
+
+/// Pick the pieces that outline the wall tile at `x`, `y`.
+///
+/// `solid` tells whether a tile is wall; it is asked about all eight neighbours, and
+/// should treat tiles off the board as wall.
+pub fn pieces(solid: impl Fn(i32, i32) -> bool, x: i32, y: i32) -> Vec<WallPiece> {
+    let open = |side: Side| {
+        let (dx, dy) = side.offset();
+        !solid(x + dx, y + dy)
+    };
+    let rounded = |corner: Corner| {
+        let (horizontal, vertical) = corner.sides();
+        open(horizontal) && open(vertical)
+    };
+
+    let mut pieces = Vec::new();
+    let edges = [
+        (Side::Top, Corner::TopLeft, Corner::TopRight),
+        (Side::Bottom, Corner::BottomLeft, Corner::BottomRight),
+        (Side::Left, Corner::TopLeft, Corner::BottomLeft),
+        (Side::Right, Corner::TopRight, Corner::BottomRight),
+    ];
+    for (side, first, second) in edges {
+        if open(side) {
+            pieces.push(WallPiece::Edge(side, rounded(first), rounded(second)));
+        }
+    }
+
+    for corner in Corner::ALL {
+        let (horizontal, vertical) = corner.sides();
+        let (dx, dy) = corner.offset();
+        if rounded(corner) {
+            pieces.push(WallPiece::OuterCorner(corner));
+        } else if !open(horizontal) && !open(vertical) && !solid(x + dx, y + dy) {
+            pieces.push(WallPiece::InnerCorner(corner));
+        }
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A solid lookup over rows of `#` and `.`, with everything off the grid solid
+    fn grid(rows: &'static [&'static str]) -> impl Fn(i32, i32) -> bool {
+        move |x, y| {
+            if x < 0 || y < 0 {
+                return true;
+            }
+            rows.get(y as usize)
+                .and_then(|row| row.as_bytes().get(x as usize))
+                .is_none_or(|&tile| tile == b'#')
+        }
+    }
+
+    #[test]
+    fn test_lone_wall_is_rounded_all_round() {
+        let solid = grid(&["...", ".#.", "..."]);
+        let pieces = pieces(&solid, 1, 1);
+        assert!(pieces.contains(&WallPiece::Edge(Side::Top, true, true)));
+        assert!(pieces.contains(&WallPiece::Edge(Side::Left, true, true)));
+        for corner in Corner::ALL {
+            assert!(pieces.contains(&WallPiece::OuterCorner(corner)));
+        }
+        assert_eq!(pieces.len(), 8);
+    }
+
+    #[test]
+    fn test_wall_inside_a_block_draws_nothing() {
+        let solid = grid(&["###", "###", "###"]);
+        assert!(pieces(&solid, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn test_straight_wall_and_its_ends() {
+        let solid = grid(&[".....", ".###.", "....."]);
+        // The middle of a bar is two plain edges
+        assert_eq!(
+            pieces(&solid, 2, 1),
+            vec![
+                WallPiece::Edge(Side::Top, false, false),
+                WallPiece::Edge(Side::Bottom, false, false),
+            ]
+        );
+        // The left end rounds off on its open side only
+        let end = pieces(&solid, 1, 1);
+        assert!(end.contains(&WallPiece::Edge(Side::Top, true, false)));
+        assert!(end.contains(&WallPiece::OuterCorner(Corner::TopLeft)));
+        assert!(!end.contains(&WallPiece::OuterCorner(Corner::TopRight)));
+    }
+
+    #[test]
+    fn test_concave_corner_gets_a_joint() {
+        let solid = grid(&["#.", "##"]);
+        assert_eq!(
+            pieces(&solid, 0, 1),
+            vec![WallPiece::InnerCorner(Corner::TopRight)]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::os::raw::{c_int, c_void};
+
+extern "C" {
+    fn emscripten_set_main_loop_arg(
+        func: extern "C" fn(*mut c_void),
+        arg: *mut c_void,
+        fps: c_int,
+        simulate_infinite_loop: c_int,
+    );
+    fn emscripten_cancel_main_loop();
+}
+
+/// Hand the frame loop to the browser, which calls `frame` from `requestAnimationFrame`
+/// until it returns false.
+///
+/// Browsers cannot block in a loop with sleeps between frames, so this never returns: the
+/// call unwinds out of `main` while keeping its stack, and everything `frame` borrows from
+/// it, alive. Each callback is one display refresh, which is one update at the usual 60 Hz.
+///
+/// Build with `cargo build --release --target wasm32-unknown-emscripten`; the link flags in
+/// `.cargo/config.toml` pull in Emscripten's SDL2 ports and preload `assets/` into the
+/// virtual file system, so assets load from the same paths as on the desktop. Then serve
+/// `web/index.html` next to the `rust_pacman.js`, `.wasm` and `.data` files from
+/// `target/wasm32-unknown-emscripten/release/deps`.
+pub fn run_frames<F: FnMut() -> bool>(mut frame: F) {
+    extern "C" fn call_frame<F: FnMut() -> bool>(arg: *mut c_void) {
+        // The frame outlives the loop, since `emscripten_set_main_loop_arg` never returns
+        let frame = unsafe { &mut *(arg as *mut F) };
+        if !frame() {
+            unsafe { emscripten_cancel_main_loop() };
+        }
+    }
+
+    let arg = &mut frame as *mut F as *mut c_void;
+    unsafe { emscripten_set_main_loop_arg(call_frame::<F>, arg, 0, 1) };
+}
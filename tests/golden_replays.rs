@@ -0,0 +1,56 @@
+//! Recorded bot runs played back against the checkpoints they reached when recorded, so any
+//! change in how the game plays out shows up as a failure.
+
+use rust_pacman::config::Config;
+use rust_pacman::game::replay::{Checkpoint, Replay};
+use rust_pacman::game::Game;
+use rust_pacman::theme::Theme;
+
+/// A replay in `tests/replays` and the checkpoint it should reach
+macro_rules! golden_replay {
+    ($name:literal) => {
+        (
+            $name,
+            include_str!(concat!("replays/", $name, ".txt")),
+            include_str!(concat!("replays/", $name, ".checkpoint")),
+        )
+    };
+}
+
+#[test]
+fn test_golden_replays_play_out_the_same() {
+    let goldens = [
+        golden_replay!("bot_level_one"),
+        golden_replay!("bot_level_two"),
+        golden_replay!("bot_level_three"),
+    ];
+    // Set after a deliberate change in behaviour to rewrite the checkpoints
+    let bless = std::env::var_os("BLESS_GOLDEN").is_some();
+    for (name, replay, checkpoint) in goldens {
+        let replay = Replay::parse(replay).unwrap();
+        let expected = Checkpoint::parse(checkpoint).unwrap();
+        let mut config = Config::new();
+        config.seed = Some(replay.metadata.seed);
+        let mut game = Game::headless(&config, &Theme::new());
+        game.start_playback(replay);
+        for _ in 0..expected.tick {
+            game.update();
+        }
+
+        let actual = Checkpoint::of(&game.snapshot(), game.get_tick());
+        if bless {
+            let path = format!(
+                "{}/tests/replays/{}.checkpoint",
+                env!("CARGO_MANIFEST_DIR"),
+                name
+            );
+            std::fs::write(path, actual.to_text()).unwrap();
+            continue;
+        }
+        assert_eq!(
+            actual, expected,
+            "{} played out differently; rerun with BLESS_GOLDEN=1 if that was intended",
+            name
+        );
+    }
+}
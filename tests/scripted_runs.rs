@@ -0,0 +1,193 @@
+//! Hand-written input scripts played through a headless game, checking what happens on the
+//! way through the `GameEvent`s it reports.
+
+use rust_pacman::board::Direction;
+use rust_pacman::config::Config;
+use rust_pacman::game::collision::GhostType;
+use rust_pacman::game::events::GameEvent;
+use rust_pacman::game::replay::{InputAction, InputEvent};
+use rust_pacman::game::state::GameState;
+use rust_pacman::game::Game;
+use rust_pacman::theme::Theme;
+
+/// Inputs to feed a headless game, written by hand rather than recorded.
+///
+/// One `<tick> <action>` per line, where the tick counts updates from the start of the run
+/// and the action is `left`, `right`, `up`, `down`, `steer-<direction>` or `continue`.
+/// Blank lines and lines starting with `#` are skipped, and ticks may not go backwards.
+#[derive(Debug, Clone, PartialEq)]
+struct InputScript {
+    inputs: Vec<InputEvent>,
+}
+
+impl InputScript {
+    fn parse(text: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut inputs: Vec<InputEvent> = Vec::new();
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let input = parse_line(line).ok_or_else(|| {
+                format!(
+                    "Script line {}: expected '<tick> <left|right|up|down|steer-...|continue>'",
+                    line_number
+                )
+            })?;
+            if inputs.last().is_some_and(|last| last.tick > input.tick) {
+                return Err(format!("Script line {}: tick goes backwards", line_number).into());
+            }
+            inputs.push(input);
+        }
+        Ok(InputScript { inputs })
+    }
+
+    /// Step `game` through `ticks` updates, feeding each input on its tick, and collect
+    /// everything that happened along with the tick it happened on
+    fn run(&self, game: &mut Game, ticks: u32) -> Vec<(u32, GameEvent)> {
+        let mut pending = self.inputs.iter().peekable();
+        let mut events = Vec::new();
+        for tick in 0..ticks {
+            let mut actions = Vec::new();
+            while let Some(input) = pending.next_if(|input| input.tick == tick) {
+                actions.push(input.action);
+            }
+            events.extend(game.step(&actions).into_iter().map(|event| (tick, event)));
+        }
+        events
+    }
+}
+
+fn parse_line(line: &str) -> Option<InputEvent> {
+    let (tick, action) = line.split_once(char::is_whitespace)?;
+    let tick = tick.parse().ok()?;
+    let action = match action.trim() {
+        "continue" => InputAction::Continue,
+        name => match name.strip_prefix("steer-") {
+            Some(direction) => InputAction::Steer(parse_direction(direction)?),
+            None => InputAction::Turn(parse_direction(name)?),
+        },
+    };
+    Some(InputEvent { tick, action })
+}
+
+fn parse_direction(name: &str) -> Option<Direction> {
+    match name {
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        _ => None,
+    }
+}
+
+fn seeded_game() -> Game<'static> {
+    let mut config = Config::new();
+    config.seed = Some(7);
+    config.telemetry = false;
+    Game::headless(&config, &Theme::new())
+}
+
+#[test]
+fn test_parse_script() {
+    let script = InputScript::parse("# warm up\n0 continue\n\n12 left\n12 steer-up\n").unwrap();
+    assert_eq!(
+        script.inputs,
+        vec![
+            InputEvent {
+                tick: 0,
+                action: InputAction::Continue
+            },
+            InputEvent {
+                tick: 12,
+                action: InputAction::Turn(Direction::Left)
+            },
+            InputEvent {
+                tick: 12,
+                action: InputAction::Steer(Direction::Up)
+            },
+        ]
+    );
+
+    assert!(InputScript::parse("3 jump").is_err());
+    assert!(InputScript::parse("left").is_err());
+    assert!(InputScript::parse("5 left\n4 up").is_err());
+}
+
+#[test]
+fn test_energizer_then_ghosts() {
+    let script = InputScript::parse(include_str!("scripts/energizer_then_ghosts.txt"));
+    let mut game = seeded_game();
+    let events = script.unwrap().run(&mut game, 403);
+
+    let energizer = events
+        .iter()
+        .position(|(_, event)| matches!(event, GameEvent::EnergizerEaten { .. }))
+        .expect("no energizer eaten");
+    let ghost = events
+        .iter()
+        .position(|(_, event)| matches!(event, GameEvent::GhostEaten { .. }))
+        .expect("no ghost eaten");
+    assert!(energizer < ghost);
+    assert!(matches!(
+        events[ghost].1,
+        GameEvent::GhostEaten {
+            ghost: GhostType::Inky,
+            points: 200,
+            ..
+        }
+    ));
+    // 21 pellets, the energizer and the first ghost
+    assert_eq!(game.get_score(), 450);
+
+    let more = InputScript { inputs: Vec::new() }.run(&mut game, 100);
+    assert!(more.iter().any(|(_, event)| matches!(
+        event,
+        GameEvent::GhostEaten {
+            ghost: GhostType::Clyde,
+            points: 400,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_ghost_contact_costs_a_life() {
+    let script = InputScript::parse(include_str!("scripts/caught_at_the_wall.txt"));
+    let mut game = seeded_game();
+    let lives = game.get_lives();
+    let events = script.unwrap().run(&mut game, 450);
+
+    let deaths: Vec<_> = events
+        .iter()
+        .filter(|(_, event)| matches!(event, GameEvent::PacmanDied { .. }))
+        .collect();
+    assert_eq!(deaths.len(), 1);
+    assert!(matches!(
+        deaths[0].1,
+        GameEvent::PacmanDied {
+            killed_by: GhostType::Clyde
+        }
+    ));
+    assert_eq!(game.get_lives(), lives - 1);
+    assert_eq!(game.get_score(), 70);
+}
+
+#[test]
+fn test_scripted_run_clears_level_one() {
+    let script = InputScript::parse(include_str!("scripts/clear_level_one.txt"));
+    let mut game = seeded_game();
+    let events = script.unwrap().run(&mut game, 5100);
+
+    let died = events
+        .iter()
+        .filter(|(_, event)| matches!(event, GameEvent::PacmanDied { .. }))
+        .count();
+    assert_eq!(died, 1);
+    assert!(events
+        .iter()
+        .any(|(_, event)| *event == GameEvent::LevelCompleted { level: 1 }));
+    assert_eq!(game.get_game_state(), GameState::LevelComplete);
+}